@@ -0,0 +1,67 @@
+//! Benchmarks of rendering the prompt in this repository, which has a bit of everything:
+//! a git repository with changes, and a Rust project, like a typical project does.
+//! The goal is for the prompt to render in about 50ms here, also on Windows, where
+//! starting processes is slower. `prompt_budget` reports how it compares, and fails if
+//! `STARSHIP_BENCH_ASSERT_BUDGET` is set and the prompt is slower.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use starship::context::Context;
+use starship::print;
+use std::env;
+use std::time::{Duration, Instant};
+
+/// How long the prompt should take to render in this repository
+const PROMPT_BUDGET: Duration = Duration::from_millis(50);
+
+/// How many prompts the budget is checked against
+const BUDGET_RUNS: u32 = 20;
+
+fn context() -> Context<'static> {
+    Context::new_with_dir(clap::ArgMatches::default(), env!("CARGO_MANIFEST_DIR"))
+}
+
+fn render_prompt(c: &mut Criterion) {
+    // Benchmark the default configuration, regardless of the one of the user
+    env::set_var("STARSHIP_CONFIG", "");
+    c.bench_function("prompt", |b| b.iter(|| print::get_prompt(context())));
+}
+
+fn prompt_budget(_: &mut Criterion) {
+    env::set_var("STARSHIP_CONFIG", "");
+    // The first prompt fills the caches, like the one of a shell that just started
+    print::get_prompt(context());
+    let start = Instant::now();
+    for _ in 0..BUDGET_RUNS {
+        print::get_prompt(context());
+    }
+    let mean = start.elapsed() / BUDGET_RUNS;
+
+    let within = mean <= PROMPT_BUDGET;
+    println!(
+        "prompt budget: mean {:.1}ms over {} runs, {} the budget of {}ms",
+        mean.as_secs_f64() * 1000.0,
+        BUDGET_RUNS,
+        if within { "within" } else { "over" },
+        PROMPT_BUDGET.as_millis()
+    );
+    if env::var_os("STARSHIP_BENCH_ASSERT_BUDGET").is_some() {
+        assert!(within, "The prompt rendered slower than its budget");
+    }
+}
+
+fn render_modules(c: &mut Criterion) {
+    env::set_var("STARSHIP_CONFIG", "");
+    let mut group = c.benchmark_group("module");
+    for module in &["directory", "git_branch", "git_status", "package", "rust"] {
+        group.bench_function(*module, |b| {
+            b.iter(|| {
+                let context = context();
+                starship::modules::handle(module, &context).map(|module| module.to_string())
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, render_prompt, prompt_budget, render_modules);
+criterion_main!(benches);
@@ -1,12 +1,14 @@
 use crate::utils::exec_cmd;
 
+use once_cell::sync::Lazy;
+use regex::Regex;
 use std::fs;
 use std::path::PathBuf;
 
 #[cfg(feature = "http")]
 const GIT_IO_BASE_URL: &str = "https://git.io/";
 
-pub fn create() {
+pub fn create(open_browser: bool) {
     let os_info = os_info::get();
 
     let environment = Environment {
@@ -14,17 +16,19 @@ pub fn create() {
         os_version: os_info.version().to_owned(),
         shell_info: get_shell_info(),
         terminal_info: get_terminal_info(),
-        starship_config: get_starship_config(),
+        starship_config: redact_config(&get_starship_config()),
     };
 
-    let link = make_github_issue_link(crate_version!(), environment);
+    let body = make_issue_body(crate_version!(), environment);
+    let link = make_github_issue_link(&body);
 
-    if open::that(&link).is_ok() {
+    if open_browser && open::that(&link).is_ok() {
         print!("Take a look at your browser. A GitHub issue has been populated with your configuration")
     } else {
+        println!("{}\n", body);
         let link = shorten_link(&link).unwrap_or(link);
         println!(
-            "Click this link to create a GitHub issue populated with your configuration:\n\n  {}",
+            "Check the report above for anything private, then click this link to create a GitHub issue populated with it:\n\n  {}",
             link
         );
     }
@@ -59,8 +63,8 @@ struct Environment {
     starship_config: String,
 }
 
-fn make_github_issue_link(starship_version: &str, environment: Environment) -> String {
-    let body = urlencoding::encode(&format!("#### Current Behavior
+fn make_issue_body(starship_version: &str, environment: Environment) -> String {
+    format!("#### Current Behavior
 <!-- A clear and concise description of the behavior. -->
 
 #### Expected Behavior
@@ -98,8 +102,11 @@ fn make_github_issue_link(starship_version: &str, environment: Environment) -> S
         os_version = environment.os_version,
         shell_config = environment.shell_info.config,
         starship_config = environment.starship_config,
-    ))
-        .replace("%20", "+");
+    )
+}
+
+fn make_github_issue_link(body: &str) -> String {
+    let body = urlencoding::encode(body).replace("%20", "+");
 
     format!(
         "https://github.com/starship/starship/issues/new?template={}&body={}",
@@ -130,13 +137,13 @@ fn get_shell_info() -> ShellInfo {
 
     let shell = shell.unwrap();
 
-    let version = exec_cmd(&shell, &["--version"])
+    let version = exec_cmd(&shell, &["--version"], None)
         .map(|output| output.stdout.trim().to_string())
-        .unwrap_or_else(|| UNKNOWN_VERSION.to_string());
+        .unwrap_or_else(|_| UNKNOWN_VERSION.to_string());
 
     let config = get_config_path(&shell)
         .and_then(|config_path| fs::read_to_string(config_path).ok())
-        .map(|config| config.trim().to_string())
+        .map(|config| redact_config(config.trim()))
         .unwrap_or_else(|| UNKNOWN_CONFIG.to_string());
 
     ShellInfo {
@@ -201,6 +208,25 @@ fn get_starship_config() -> String {
         .unwrap_or_else(|| UNKNOWN_CONFIG.to_string())
 }
 
+/// Hide the parts of a config file that may be private: the path of the home directory,
+/// and the values of options named like they hold a secret
+fn redact_config(config: &str) -> String {
+    static SECRET_OPTION: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r#"(?im)^([\w \t"'$-]*(?:token|password|secret|api_key)[\w"'-]*[ \t]*=[ \t]*).+$"#,
+        )
+        .unwrap()
+    });
+
+    let config = match dirs::home_dir() {
+        Some(home_dir) => config.replace(&*home_dir.to_string_lossy(), "~"),
+        None => config.to_owned(),
+    };
+    SECRET_OPTION
+        .replace_all(&config, r#"${1}"<redacted>""#)
+        .into_owned()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -224,7 +250,7 @@ mod tests {
             starship_config: "No Starship config".to_string(),
         };
 
-        let link = make_github_issue_link(starship_version, environment);
+        let link = make_github_issue_link(&make_issue_body(starship_version, environment));
 
         assert!(link.contains(starship_version));
         assert!(link.contains("Linux"));
@@ -235,6 +261,18 @@ mod tests {
         assert!(link.contains("No+Starship+config"));
     }
 
+    #[test]
+    fn test_redact_config() {
+        let config = "[custom.weather]\ncommand = \"curl example.com\"\napi_token = \"1234\"\n";
+        let expected =
+            "[custom.weather]\ncommand = \"curl example.com\"\napi_token = \"<redacted>\"\n";
+        assert_eq!(redact_config(config), expected);
+
+        let config = "export GITHUB_TOKEN=1234\neval \"$(starship init bash)\"";
+        let expected = "export GITHUB_TOKEN=\"<redacted>\"\neval \"$(starship init bash)\"";
+        assert_eq!(redact_config(config), expected);
+    }
+
     #[test]
     fn test_get_shell_info() {
         env::remove_var("STARSHIP_SHELL");
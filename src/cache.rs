@@ -0,0 +1,214 @@
+use crate::utils::CommandOutput;
+use once_cell::sync::Lazy;
+use serde_json::json;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// Outputs read from or written to the cache by this process, with the time they were
+/// stored, which spares `starship session` from reading them again for every prompt
+static LOADED: Lazy<Mutex<HashMap<String, (SystemTime, CommandOutput)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The directory starship caches command outputs in, from `$STARSHIP_CACHE` or else
+/// the `starship` directory in the cache directory of the platform
+pub fn cache_dir() -> Option<PathBuf> {
+    match env::var_os("STARSHIP_CACHE") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => dirs::cache_dir().map(|dir| dir.join("starship")),
+    }
+}
+
+/// Get the cached output of the command identified by `key`, if it was stored less
+/// than `ttl` ago
+pub fn get(dir: &Path, key: &str, ttl: Duration) -> Option<CommandOutput> {
+    let path = entry_path(dir, "commands", key);
+    if let Some((stored, output)) = LOADED.lock().unwrap().get(&path_key(&path, key)) {
+        if is_fresh(*stored, ttl) {
+            return Some(output.clone());
+        }
+    }
+
+    let (stored, entry) = read_entry(&path, key, ttl)?;
+    let output = CommandOutput {
+        stdout: entry["stdout"].as_str()?.to_owned(),
+        stderr: entry["stderr"].as_str()?.to_owned(),
+    };
+    LOADED
+        .lock()
+        .unwrap()
+        .insert(path_key(&path, key), (stored, output.clone()));
+    Some(output)
+}
+
+/// Store the output of the command identified by `key`
+pub fn set(dir: &Path, key: &str, output: &CommandOutput) {
+    let path = entry_path(dir, "commands", key);
+    let entry = json!({ "key": key, "stdout": output.stdout, "stderr": output.stderr });
+
+    LOADED
+        .lock()
+        .unwrap()
+        .insert(path_key(&path, key), (SystemTime::now(), output.clone()));
+
+    if let Err(error) = write_entry(&path, &entry) {
+        log::debug!("Unable to cache the output of {:?}: {}", key, error);
+    }
+}
+
+/// Get the prompt last rendered for `key`, if it was stored less than `ttl` ago
+pub fn get_prompt(dir: &Path, key: &str, ttl: Duration) -> Option<String> {
+    let (_, entry) = read_entry(&entry_path(dir, "prompts", key), key, ttl)?;
+    Some(entry["prompt"].as_str()?.to_owned())
+}
+
+/// Store the prompt rendered for `key`
+pub fn set_prompt(dir: &Path, key: &str, prompt: &str) {
+    let entry = json!({ "key": key, "prompt": prompt });
+    if let Err(error) = write_entry(&entry_path(dir, "prompts", key), &entry) {
+        log::debug!("Unable to cache the prompt: {}", error);
+    }
+}
+
+/// Read the entry for `key` from a file, with the time it was stored, if that was less
+/// than `ttl` ago
+fn read_entry(path: &Path, key: &str, ttl: Duration) -> Option<(SystemTime, serde_json::Value)> {
+    let stored = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()?;
+    if !is_fresh(stored, ttl) {
+        return None;
+    }
+
+    let entry: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    // Different keys may share a file if their hashes collide
+    if entry["key"].as_str()? != key {
+        return None;
+    }
+    Some((stored, entry))
+}
+
+fn write_entry(path: &Path, entry: &serde_json::Value) -> std::io::Result<()> {
+    // Modules run in parallel, and prompts are rendered in the background, so the entry
+    // is written to a file of its own first, to never leave a partially written entry
+    let temp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&temp_path, entry.to_string()))
+        .and_then(|_| fs::rename(&temp_path, path));
+    if result.is_err() {
+        fs::remove_file(&temp_path).ok();
+    }
+    result
+}
+
+/// Remove all cached command outputs and prompts
+pub fn clear() {
+    let dir = match cache_dir() {
+        Some(dir) => dir,
+        None => return,
+    };
+    LOADED.lock().unwrap().clear();
+    let mut is_cleared = false;
+    for kind in &["commands", "prompts"] {
+        match fs::remove_dir_all(dir.join(kind)) {
+            Ok(()) => is_cleared = true,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+            Err(error) => {
+                eprintln!(
+                    "Error: Unable to clear the cache in {}: {}",
+                    dir.to_string_lossy(),
+                    error
+                );
+                std::process::exit(1);
+            }
+        }
+    }
+    if is_cleared {
+        println!("Cleared the cache in {}", dir.to_string_lossy());
+    } else {
+        println!("The cache is empty");
+    }
+}
+
+fn is_fresh(stored: SystemTime, ttl: Duration) -> bool {
+    match SystemTime::now().duration_since(stored) {
+        Ok(age) => age <= ttl,
+        Err(_) => false,
+    }
+}
+
+/// Identifies an output among the loaded outputs, which may come from different
+/// cache directories
+fn path_key(path: &Path, key: &str) -> String {
+    format!("{}\n{}", path.to_string_lossy(), key)
+}
+
+fn entry_path(dir: &Path, kind: &str, key: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    dir.join(kind)
+        .join(format!("{:016x}.json", hasher.finish()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(stdout: &str) -> CommandOutput {
+        CommandOutput {
+            stdout: String::from(stdout),
+            stderr: String::new(),
+        }
+    }
+
+    #[test]
+    fn get_stored_output() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        set(dir.path(), "node --version", &output("v14.4.0\n"));
+
+        let actual = get(dir.path(), "node --version", Duration::from_secs(60));
+        assert_eq!(actual, Some(output("v14.4.0\n")));
+        dir.close()
+    }
+
+    #[test]
+    fn get_missing_output() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        set(dir.path(), "node --version", &output("v14.4.0\n"));
+
+        let actual = get(dir.path(), "dotnet --version", Duration::from_secs(60));
+        assert_eq!(actual, None);
+        dir.close()
+    }
+
+    #[test]
+    fn get_stored_prompt() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        set_prompt(dir.path(), "/home/user\n", "~ ❯ ");
+        set(dir.path(), "/home/user\n", &output("v14.4.0\n"));
+
+        let actual = get_prompt(dir.path(), "/home/user\n", Duration::from_secs(60));
+        assert_eq!(actual.as_deref(), Some("~ ❯ "));
+        let actual = get_prompt(dir.path(), "/tmp\n", Duration::from_secs(60));
+        assert_eq!(actual, None);
+        dir.close()
+    }
+
+    #[test]
+    fn get_expired_output() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        set(dir.path(), "node --version", &output("v14.4.0\n"));
+        std::thread::sleep(Duration::from_millis(20));
+
+        let actual = get(dir.path(), "node --version", Duration::from_millis(10));
+        assert_eq!(actual, None);
+        dir.close()
+    }
+}
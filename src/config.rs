@@ -1,13 +1,27 @@
+use crate::configs;
+use crate::configs::custom::CustomConfig;
+use crate::configs::symbol_sets::get_symbol_set;
 use crate::configs::StarshipRootConfig;
+use crate::module::ALL_MODULES;
+use crate::profile;
 use crate::utils;
 use ansi_term::{Color, Style};
 
 use std::clone::Clone;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::marker::Sized;
 
 use dirs::home_dir;
+use once_cell::sync::{Lazy, OnceCell};
+use serde_json::{self as json, json};
+use std::cell::RefCell;
 use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 use toml::Value;
 
 /// Root config of a module.
@@ -33,6 +47,12 @@ where
             Self::new()
         }
     }
+
+    /// Check the values of `self` that have the right type but can't be used, like a
+    /// negative length.
+    fn check(&self) -> Vec<ConfigWarning> {
+        Vec::new()
+    }
 }
 
 /// Parsable config.
@@ -49,6 +69,129 @@ where
     fn load_config(&self, config: &'a Value) -> Self {
         Self::from_config(config).unwrap_or_else(|| self.clone())
     }
+
+    /// Convert `self` back to a toml value, or `None` if it has no value.
+    fn to_config(&self) -> Option<Value> {
+        None
+    }
+
+    /// Describe the toml values that can be loaded, as a JSON Schema.
+    fn schema() -> json::Value {
+        json!({})
+    }
+
+    /// Check a toml value that would be merged with `self`, and describe every part of it
+    /// that would be ignored.
+    fn check_config(&self, config: &'a Value) -> Vec<ConfigWarning> {
+        if Self::from_config(config).is_some() {
+            return Vec::new();
+        }
+        match self.to_config() {
+            Some(ref default) if default.type_str() != config.type_str() => {
+                vec![ConfigWarning::expected(describe_type(default), config)]
+            }
+            _ => vec![ConfigWarning::invalid_value(config)],
+        }
+    }
+}
+
+/// A problem found in the configuration, which makes starship ignore part of it
+#[derive(Debug, PartialEq)]
+pub struct ConfigWarning {
+    /// The dotted path of the option, relative to the table that was checked
+    pub key: String,
+    pub message: String,
+}
+
+impl ConfigWarning {
+    pub fn new(message: String) -> Self {
+        Self {
+            key: String::new(),
+            message,
+        }
+    }
+
+    /// A warning for a value of the wrong type, `expected` being a description like "a string"
+    pub fn expected(expected: &str, found: &Value) -> Self {
+        Self::new(format!(
+            "expected {}, found {}",
+            expected,
+            describe_value(found)
+        ))
+    }
+
+    /// A warning for a value of the right type that can't be used
+    pub fn invalid_value(value: &Value) -> Self {
+        let message = match value {
+            Value::Array(_) => String::from("invalid array"),
+            Value::Table(_) => String::from("invalid table"),
+            _ => format!("invalid value {}", value),
+        };
+        Self::new(message)
+    }
+
+    /// Make the key of the warning relative to the parent table of `table`
+    pub fn in_table(self, table: &str) -> Self {
+        let key = if self.key.is_empty() {
+            table.to_owned()
+        } else {
+            format!("{}.{}", table, self.key)
+        };
+        Self { key, ..self }
+    }
+}
+
+impl fmt::Display for ConfigWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.key.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.key, self.message)
+        }
+    }
+}
+
+fn describe_type(value: &Value) -> &'static str {
+    match value {
+        Value::String(_) => "a string",
+        Value::Integer(_) => "an integer",
+        Value::Float(_) => "a float",
+        Value::Boolean(_) => "a boolean",
+        Value::Datetime(_) => "a datetime",
+        Value::Array(_) => "an array",
+        Value::Table(_) => "a table",
+    }
+}
+
+/// Describe a value in a warning, without printing whole arrays and tables
+fn describe_value(value: &Value) -> String {
+    match value {
+        Value::Array(_) | Value::Table(_) => describe_type(value).to_owned(),
+        _ => value.to_string(),
+    }
+}
+
+/// Add the values of a config to the schema of its type as the defaults of its options
+pub fn schema_with_defaults(mut schema: json::Value, defaults: &Value) -> json::Value {
+    if let (Some(properties), Some(defaults)) = (
+        schema
+            .get_mut("properties")
+            .and_then(json::Value::as_object_mut),
+        defaults.as_table(),
+    ) {
+        for (key, default) in defaults {
+            if let Some(property) = properties.get_mut(key) {
+                *property = if property.get("properties").is_some() {
+                    schema_with_defaults(property.take(), default)
+                } else {
+                    let mut property = property.take();
+                    property["default"] = json::to_value(default).unwrap_or_default();
+                    property
+                };
+            }
+        }
+    }
+    schema
 }
 
 // TODO: Add logging to default implementations
@@ -56,24 +199,74 @@ impl<'a> ModuleConfig<'a> for &'a str {
     fn from_config(config: &'a Value) -> Option<Self> {
         config.as_str()
     }
+
+    fn to_config(&self) -> Option<Value> {
+        Some(Value::String((*self).to_owned()))
+    }
+
+    fn schema() -> json::Value {
+        json!({ "type": "string" })
+    }
 }
 
 impl<'a> ModuleConfig<'a> for Style {
     fn from_config(config: &Value) -> Option<Self> {
         parse_style_string(config.as_str()?)
     }
+
+    fn to_config(&self) -> Option<Value> {
+        Some(Value::String(style_to_string(*self)))
+    }
+
+    fn schema() -> json::Value {
+        json!({ "type": "string" })
+    }
+
+    fn check_config(&self, config: &Value) -> Vec<ConfigWarning> {
+        // A style containing `none` is valid, even though it is ignored
+        let is_none = match config.as_str() {
+            Some(style) => style
+                .split_whitespace()
+                .any(|token| token.eq_ignore_ascii_case("none")),
+            None => false,
+        };
+        if Self::from_config(config).is_some() || is_none {
+            Vec::new()
+        } else {
+            vec![ConfigWarning::new(format!(
+                "invalid style string {}",
+                config
+            ))]
+        }
+    }
 }
 
 impl<'a> ModuleConfig<'a> for bool {
     fn from_config(config: &Value) -> Option<Self> {
         config.as_bool()
     }
+
+    fn to_config(&self) -> Option<Value> {
+        Some(Value::Boolean(*self))
+    }
+
+    fn schema() -> json::Value {
+        json!({ "type": "boolean" })
+    }
 }
 
 impl<'a> ModuleConfig<'a> for i64 {
     fn from_config(config: &Value) -> Option<Self> {
         config.as_integer()
     }
+
+    fn to_config(&self) -> Option<Value> {
+        Some(Value::Integer(*self))
+    }
+
+    fn schema() -> json::Value {
+        json!({ "type": "integer" })
+    }
 }
 
 impl<'a> ModuleConfig<'a> for u64 {
@@ -91,12 +284,28 @@ impl<'a> ModuleConfig<'a> for u64 {
             _ => None,
         }
     }
+
+    fn to_config(&self) -> Option<Value> {
+        Some(Value::Integer(*self as i64))
+    }
+
+    fn schema() -> json::Value {
+        json!({ "type": "integer", "minimum": 1 })
+    }
 }
 
 impl<'a> ModuleConfig<'a> for f64 {
     fn from_config(config: &Value) -> Option<Self> {
         config.as_float()
     }
+
+    fn to_config(&self) -> Option<Value> {
+        Some(Value::Float(*self))
+    }
+
+    fn schema() -> json::Value {
+        json!({ "type": "number" })
+    }
 }
 
 impl<'a> ModuleConfig<'a> for usize {
@@ -113,6 +322,14 @@ impl<'a> ModuleConfig<'a> for usize {
             _ => None,
         }
     }
+
+    fn to_config(&self) -> Option<Value> {
+        Some(Value::Integer(*self as i64))
+    }
+
+    fn schema() -> json::Value {
+        json!({ "type": "integer", "minimum": 1 })
+    }
 }
 
 impl<'a, T> ModuleConfig<'a> for Vec<T>
@@ -126,6 +343,16 @@ where
             .map(|value| T::from_config(value))
             .collect()
     }
+
+    fn to_config(&self) -> Option<Value> {
+        Some(Value::Array(
+            self.iter().filter_map(ModuleConfig::to_config).collect(),
+        ))
+    }
+
+    fn schema() -> json::Value {
+        json!({ "type": "array", "items": T::schema() })
+    }
 }
 
 impl<'a, T, S: ::std::hash::BuildHasher + Default> ModuleConfig<'a> for HashMap<String, T, S>
@@ -142,6 +369,43 @@ where
 
         Some(hm)
     }
+
+    /// Merge every entry into the one with the same key, so that setting an entry keeps
+    /// the other default ones
+    fn load_config(&self, config: &'a Value) -> Self {
+        let table = match config.as_table() {
+            Some(table) => table,
+            None => return self.clone(),
+        };
+        let mut hm = self.clone();
+        for (x, y) in table.iter() {
+            let value = match self.get(x) {
+                Some(value) => Some(value.load_config(y)),
+                None => T::from_config(y),
+            };
+            if let Some(value) = value {
+                hm.insert(x.clone(), value);
+            }
+        }
+        hm
+    }
+
+    fn to_config(&self) -> Option<Value> {
+        let mut keys = self.keys().collect::<Vec<&String>>();
+        keys.sort();
+
+        let mut table = toml::value::Table::new();
+        for key in keys {
+            if let Some(value) = self[key].to_config() {
+                table.insert(key.clone(), value);
+            }
+        }
+        Some(Value::Table(table))
+    }
+
+    fn schema() -> json::Value {
+        json!({ "type": "object", "additionalProperties": T::schema() })
+    }
 }
 
 impl<'a, T> ModuleConfig<'a> for Option<T>
@@ -151,43 +415,672 @@ where
     fn from_config(config: &'a Value) -> Option<Self> {
         Some(T::from_config(config))
     }
+
+    fn to_config(&self) -> Option<Value> {
+        self.as_ref()?.to_config()
+    }
+
+    fn schema() -> json::Value {
+        T::schema()
+    }
+
+    fn check_config(&self, config: &'a Value) -> Vec<ConfigWarning> {
+        match self {
+            Some(value) => value.check_config(config),
+            None if T::from_config(config).is_none() => {
+                vec![ConfigWarning::invalid_value(config)]
+            }
+            None => Vec::new(),
+        }
+    }
+}
+
+/// An option that has been replaced by another one, with how to carry its value over
+struct DeprecatedOption {
+    /// The table the option is in, empty for top-level options
+    table: &'static str,
+    name: &'static str,
+    replacement: &'static str,
+    /// Converts the value of the option to a value of its replacement
+    migrate: fn(&Value) -> Option<Value>,
+}
+
+const DEPRECATED_OPTIONS: &[DeprecatedOption] = &[
+    DeprecatedOption {
+        table: "",
+        name: "prompt_order",
+        replacement: "format",
+        migrate: migrate_prompt_order,
+    },
+    DeprecatedOption {
+        table: "character",
+        name: "symbol",
+        replacement: "success_symbol",
+        migrate: migrate_unchanged,
+    },
+];
+
+fn migrate_unchanged(value: &Value) -> Option<Value> {
+    Some(value.clone())
+}
+
+fn migrate_prompt_order(value: &Value) -> Option<Value> {
+    let format = value
+        .as_array()?
+        .iter()
+        .map(|module| Some(format!("${{{}}}", module.as_str()?)))
+        .collect::<Option<String>>()?;
+    Some(Value::String(format))
+}
+
+/// A deprecated option that was found in a config, and replaced
+#[derive(Debug, PartialEq)]
+pub struct Migration {
+    /// The table the option is in, empty for top-level options
+    pub table: &'static str,
+    pub name: &'static str,
+    pub replacement: &'static str,
+    /// The value of the replacement, or `None` if the value of the option couldn't be
+    /// converted, or the replacement was already set
+    pub value: Option<Value>,
+}
+
+impl fmt::Display for Migration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key = |name: &str| {
+            if self.table.is_empty() {
+                name.to_owned()
+            } else {
+                format!("{}.{}", self.table, name)
+            }
+        };
+        match &self.value {
+            Some(value) => write!(
+                f,
+                "`{}` is deprecated, use `{} = {}` instead",
+                key(self.name),
+                key(self.replacement),
+                value
+            ),
+            None => write!(
+                f,
+                "`{}` is deprecated and ignored, use `{}` instead",
+                key(self.name),
+                key(self.replacement)
+            ),
+        }
+    }
+}
+
+/// Replace the deprecated options in a config with the options that replaced them
+pub fn migrate_options(table: &mut toml::value::Table) -> Vec<Migration> {
+    let mut migrations = Vec::new();
+    for option in DEPRECATED_OPTIONS {
+        let option_table = if option.table.is_empty() {
+            Some(&mut *table)
+        } else {
+            table.get_mut(option.table).and_then(Value::as_table_mut)
+        };
+        let option_table = match option_table {
+            Some(option_table) => option_table,
+            None => continue,
+        };
+        let old_value = match option_table.remove(option.name) {
+            Some(old_value) => old_value,
+            None => continue,
+        };
+
+        let value = if option_table.contains_key(option.replacement) {
+            None
+        } else {
+            (option.migrate)(&old_value)
+        };
+        if let Some(value) = &value {
+            option_table.insert(option.replacement.to_owned(), value.clone());
+        }
+        migrations.push(Migration {
+            table: option.table,
+            name: option.name,
+            replacement: option.replacement,
+            value,
+        });
+    }
+    migrations
+}
+
+/// The deprecations that have been warned about, so that every one is only logged once,
+/// even by `starship session`
+static WARNED_MIGRATIONS: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// The configs last loaded from a file, by the project config merged into them and the
+/// selected profile, so that a file is only parsed and checked again once it or the
+/// overrides from the environment change, e.g. by `starship session`.
+static LOADED_CONFIGS: Lazy<Mutex<HashMap<LoadedConfigKey, LoadedConfig>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The project config and the profile of a loaded config
+type LoadedConfigKey = (Option<PathBuf>, Option<String>);
+
+struct LoadedConfig {
+    path: Option<String>,
+    /// The files the config was read from, with their versions at the time
+    files: Vec<(PathBuf, Option<FileVersion>)>,
+    overrides: Vec<(String, String)>,
+    config: Value,
+    palette: Arc<Palette>,
+}
+
+/// The colors of a palette, keyed by their lowercased name
+type Palette = HashMap<String, Color>;
+
+thread_local! {
+    /// The palette of the config whose styles are parsed on this thread, see
+    /// `StarshipConfig::use_palette`
+    static PALETTE: RefCell<Option<Arc<Palette>>> = const { RefCell::new(None) };
+}
+
+/// The modification time and size of a file
+type FileVersion = (SystemTime, u64);
+
+fn file_version<P: AsRef<Path>>(path: P) -> Option<FileVersion> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Merge the options of `overlay` into `base`, the ones of tables key by key
+fn merge_tables(base: &mut toml::value::Table, overlay: toml::value::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base_table)), Value::Table(overlay_table)) => {
+                merge_tables(base_table, overlay_table)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Puts back the palette style strings were parsed with before `use_palette` when dropped
+pub struct PaletteGuard {
+    previous: Option<Arc<Palette>>,
+}
+
+impl Drop for PaletteGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        PALETTE.with(|palette| *palette.borrow_mut() = previous);
+    }
 }
 
 /// Root config of starship.
 pub struct StarshipConfig {
     pub config: Option<Value>,
+    /// The palette selected by the top-level `palette` option, which the style strings of
+    /// this config are parsed with
+    palette: OnceCell<Arc<Palette>>,
 }
 
 impl StarshipConfig {
+    /// Create a config from its TOML value, like the one of a config file
+    pub fn new(config: Option<Value>) -> Self {
+        StarshipConfig {
+            config,
+            palette: OnceCell::new(),
+        }
+    }
+
+    /// Parse the style strings on this thread with the palette of this config, until the
+    /// returned guard is dropped. Modules are rendered like this, e.g. by
+    /// `starship::render_prompt`, so that they use the palette of their context.
+    pub fn use_palette(&self) -> PaletteGuard {
+        let palette = self
+            .palette
+            .get_or_init(|| Arc::new(self.get_palette()))
+            .clone();
+        PaletteGuard {
+            previous: PALETTE.with(|current| current.replace(Some(palette))),
+        }
+    }
+
     /// Initialize the Config struct
     pub fn initialize() -> Self {
-        if let Some(file_data) = Self::config_from_file() {
-            StarshipConfig {
-                config: Some(file_data),
+        Self::initialize_with(None, None)
+    }
+
+    /// Initialize the Config struct, with the options of a project config file, like the
+    /// `.starship.toml` of a repository, taking precedence over the ones of the user, and
+    /// then the ones of a profile from `profiles`, or else the one named by
+    /// `$STARSHIP_PROFILE_NAME`
+    pub fn initialize_with(project_config: Option<&Path>, profile: Option<&str>) -> Self {
+        Self::initialize_with_env(project_config, profile, None)
+    }
+
+    /// Initialize the Config struct like `initialize_with`, with `$STARSHIP_CONFIG` and
+    /// the other variables the config is read from taken from `env` if it's set, instead
+    /// of from the environment of the process
+    pub fn initialize_with_env(
+        project_config: Option<&Path>,
+        profile: Option<&str>,
+        env: Option<&HashMap<String, String>>,
+    ) -> Self {
+        let profile = profile
+            .map(str::to_owned)
+            .or_else(|| utils::get_env(env, "STARSHIP_PROFILE_NAME"))
+            .filter(|profile| !profile.is_empty());
+        Self::load(project_config, profile, env)
+    }
+
+    fn load(
+        project_config: Option<&Path>,
+        profile: Option<String>,
+        env: Option<&HashMap<String, String>>,
+    ) -> Self {
+        let _span = profile::span("config", "load config");
+        let file_path = Self::config_path(env);
+        let overrides = Self::env_overrides(env);
+
+        let cache_key = (project_config.map(Path::to_path_buf), profile.clone());
+        if let (Some(file_path), Some(loaded)) =
+            (&file_path, LOADED_CONFIGS.lock().unwrap().get(&cache_key))
+        {
+            if Some(file_path) == loaded.path.as_ref()
+                && overrides == loaded.overrides
+                && loaded
+                    .files
+                    .iter()
+                    .all(|(path, version)| file_version(path) == *version)
+            {
+                log::debug!("Config file is unchanged since it was loaded");
+                return StarshipConfig {
+                    config: Some(loaded.config.clone()),
+                    palette: OnceCell::from(loaded.palette.clone()),
+                };
             }
-        } else {
-            StarshipConfig {
-                config: Some(Value::Table(toml::value::Table::new())),
+        }
+
+        let mut files = Vec::new();
+        let file_data = file_path
+            .as_ref()
+            .and_then(|path| Self::config_from_file_with_includes(Path::new(path), &mut files));
+        let mut config = StarshipConfig::new(Some(
+            file_data.unwrap_or_else(|| Value::Table(toml::value::Table::new())),
+        ));
+
+        if let Some(project_config) = project_config {
+            log::debug!("Merging the project config {}", project_config.display());
+            let project_data = Self::config_from_file_with_includes(project_config, &mut files);
+            if let (Some(Value::Table(table)), Some(Value::Table(project_table))) =
+                (&mut config.config, project_data)
+            {
+                merge_tables(table, project_table);
+            }
+        }
+
+        config.apply_profile(profile.as_deref());
+        config.apply_overrides(&overrides);
+        if let Some(table) = config.config.as_mut().and_then(Value::as_table_mut) {
+            for migration in migrate_options(table) {
+                let warning = migration.to_string();
+                if WARNED_MIGRATIONS.lock().unwrap().insert(warning.clone()) {
+                    log::warn!("{}, or run `starship config migrate`", warning);
+                }
             }
         }
+        config.apply_symbol_sets();
+
+        // The palette is in place before the config is checked, which parses its styles
+        if let Some(value) = &config.config {
+            let loaded = LoadedConfig {
+                path: file_path,
+                files,
+                overrides,
+                config: value.clone(),
+                palette: Arc::new(config.get_palette()),
+            };
+            config.palette = OnceCell::from(loaded.palette.clone());
+            LOADED_CONFIGS.lock().unwrap().insert(cache_key, loaded);
+        }
+        let _palette = config.use_palette();
+        for warning in config.check() {
+            log::warn!("Invalid config: {}", warning);
+        }
+        config
     }
 
-    /// Create a config from a starship configuration file
-    fn config_from_file() -> Option<Value> {
-        let file_path = if let Ok(path) = env::var("STARSHIP_CONFIG") {
+    /// Check the configuration against the config structs of starship and its modules
+    pub fn check(&self) -> Vec<ConfigWarning> {
+        let table = match self.config.as_ref().and_then(Value::as_table) {
+            Some(table) => table,
+            None => return Vec::new(),
+        };
+
+        let mut warnings = Vec::new();
+        let mut root_config = toml::value::Table::new();
+        for (key, value) in table {
+            if key == "custom" {
+                let custom_modules = match value.as_table() {
+                    Some(custom_modules) => custom_modules,
+                    None => {
+                        warnings.push(ConfigWarning::expected("a table", value).in_table(key));
+                        continue;
+                    }
+                };
+                for (name, module_config) in custom_modules {
+                    // Custom modules have a `when` command of their own
+                    let mut custom_config = without_module_options(module_config);
+                    if let (Some(table), Some(when)) =
+                        (custom_config.as_table_mut(), module_config.get("when"))
+                    {
+                        table.insert(String::from("when"), when.clone());
+                    }
+                    let module_warnings = CustomConfig::new()
+                        .check_config(&custom_config)
+                        .into_iter()
+                        .map(|warning| warning.in_table(name).in_table(key));
+                    warnings.extend(module_warnings);
+                }
+            } else if key == "plugin" {
+                // The options of plugins are up to the plugins themselves
+                let plugins = match value.as_table() {
+                    Some(plugins) => plugins,
+                    None => {
+                        warnings.push(ConfigWarning::expected("a table", value).in_table(key));
+                        continue;
+                    }
+                };
+                for (name, plugin_config) in plugins {
+                    if !plugin_config.is_table() {
+                        warnings.push(
+                            ConfigWarning::expected("a table", plugin_config)
+                                .in_table(name)
+                                .in_table(key),
+                        );
+                    }
+                }
+            } else if key == "env_var" && value.is_table() {
+                // Besides its own options, `env_var` has a table for every other variable
+                let instances = configs::env_var::instances(value);
+                let mut module_config = value.clone();
+                for (name, instance_config) in &instances {
+                    if let Some(module_config) = module_config.as_table_mut() {
+                        module_config.remove(*name);
+                    }
+                    let instance_warnings =
+                        configs::check_module_config(key, &without_module_options(instance_config))
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|warning| warning.in_table(name).in_table(key));
+                    warnings.extend(instance_warnings);
+                }
+                let module_warnings =
+                    configs::check_module_config(key, &without_module_options(&module_config))
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|warning| warning.in_table(key));
+                warnings.extend(module_warnings);
+            } else if let Some(module_warnings) =
+                configs::check_module_config(key, &without_module_options(value))
+            {
+                warnings.extend(
+                    module_warnings
+                        .into_iter()
+                        .map(|warning| warning.in_table(key)),
+                );
+            } else if !(key == "format" && value.is_array()) {
+                // Multi-line formats can be given as an array, which isn't part of the root config
+                root_config.insert(key.clone(), value.clone());
+            }
+        }
+        warnings.extend(StarshipRootConfig::new().check_config(&Value::Table(root_config)));
+        warnings
+    }
+
+    /// Fill in the symbols of the selected `symbol_set` for every module option
+    /// that isn't explicitly configured
+    fn apply_symbol_sets(&mut self) {
+        let table = match self.config.as_mut().and_then(Value::as_table_mut) {
+            Some(table) => table,
+            None => return,
+        };
+
+        let default_set_name = table
+            .get("symbol_set")
+            .and_then(Value::as_str)
+            .unwrap_or("unicode")
+            .to_owned();
+        let default_set = get_symbol_set(&default_set_name).unwrap_or_else(|| {
+            log::warn!("Unknown symbol_set \"{}\"", default_set_name);
+            get_symbol_set("unicode").unwrap_or_default()
+        });
+
+        for module in ALL_MODULES {
+            let module_set_name = table
+                .get(*module)
+                .and_then(|config| config.get("symbol_set"))
+                .and_then(Value::as_str);
+            let symbol_set = match module_set_name {
+                Some(name) => match get_symbol_set(name) {
+                    Some(symbol_set) => symbol_set,
+                    None => {
+                        log::warn!("Unknown symbol_set \"{}\" for module \"{}\"", name, module);
+                        default_set
+                    }
+                },
+                None => default_set,
+            };
+
+            for (_, option, symbol) in symbol_set.iter().filter(|(name, _, _)| name == module) {
+                let module_config = table
+                    .entry(*module)
+                    .or_insert_with(|| Value::Table(toml::value::Table::new()));
+                if let Some(module_config) = module_config.as_table_mut() {
+                    module_config
+                        .entry(*option)
+                        .or_insert_with(|| Value::from(*symbol));
+                }
+            }
+        }
+    }
+
+    /// Merge the options of a profile from the `profiles` table into the config, which
+    /// take precedence over the other options of the file
+    fn apply_profile(&mut self, profile: Option<&str>) {
+        let table = match self.config.as_mut().and_then(Value::as_table_mut) {
+            Some(table) => table,
+            None => return,
+        };
+
+        let mut profiles = match table.remove("profiles") {
+            Some(Value::Table(profiles)) => profiles,
+            Some(value) => {
+                log::warn!(
+                    "Invalid config: {}",
+                    ConfigWarning::expected("a table", &value).in_table("profiles")
+                );
+                return;
+            }
+            None => toml::value::Table::new(),
+        };
+        let profile = match profile {
+            Some(profile) => profile,
+            None => return,
+        };
+        log::debug!("Using the profile {}", profile);
+        match profiles.remove(profile) {
+            Some(Value::Table(profile_table)) => merge_tables(table, profile_table),
+            Some(value) => log::warn!(
+                "Invalid config: {}",
+                ConfigWarning::expected("a table", &value)
+                    .in_table(profile)
+                    .in_table("profiles")
+            ),
+            None => log::warn!("Unknown profile \"{}\"", profile),
+        }
+    }
+
+    /// The options set with `STARSHIP_CONFIG_<MODULE>_<OPTION>` environment variables,
+    /// without the prefix
+    fn env_overrides(env: Option<&HashMap<String, String>>) -> Vec<(String, String)> {
+        let vars: Vec<(String, String)> = match env {
+            Some(env) => env
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            None => env::vars().collect(),
+        };
+        let mut overrides: Vec<(String, String)> = vars
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let name = key.strip_prefix("STARSHIP_CONFIG_")?;
+                Some((name.to_owned(), value))
+            })
+            .collect();
+        overrides.sort();
+        overrides
+    }
+
+    /// Set options from the environment, which take precedence over the config file.
+    /// A name like `GIT_BRANCH_SYMBOL` sets the `symbol` of the `git_branch` module,
+    /// `CUSTOM_FOO_COMMAND` the `command` of the custom module `foo`, and a name that
+    /// doesn't start with a module, like `ADD_NEWLINE`, a top-level option. Values are
+    /// read as TOML, or else as a string.
+    fn apply_overrides(&mut self, overrides: &[(String, String)]) {
+        let table = match self.config.as_mut().and_then(Value::as_table_mut) {
+            Some(table) => table,
+            None => return,
+        };
+
+        for (name, value) in overrides {
+            let name = name.to_lowercase();
+            let value = toml::from_str::<Value>(&format!("value = {}", value))
+                .ok()
+                .and_then(|table| table.get("value").cloned())
+                .unwrap_or_else(|| Value::String(value.clone()));
+
+            let custom_modules: Vec<String> = table
+                .get("custom")
+                .and_then(Value::as_table)
+                .map(|custom| custom.keys().cloned().collect())
+                .unwrap_or_default();
+            let custom_module = custom_modules.iter().find_map(|custom_module| {
+                let prefix = format!("custom_{}_", custom_module.to_lowercase().replace('-', "_"));
+                Some((custom_module, name.strip_prefix(&prefix)?))
+            });
+            let module = ALL_MODULES
+                .iter()
+                .filter_map(|module| Some((*module, name.strip_prefix(&format!("{}_", module))?)))
+                .max_by_key(|(module, _)| module.len());
+
+            let (module_table, option) = if let Some((custom_module, option)) = custom_module {
+                let custom = table
+                    .get_mut("custom")
+                    .and_then(Value::as_table_mut)
+                    .and_then(|custom| custom.get_mut(custom_module))
+                    .and_then(Value::as_table_mut);
+                (custom, option)
+            } else if let Some((module, option)) = module {
+                let module_table = table
+                    .entry(module)
+                    .or_insert_with(|| Value::Table(toml::value::Table::new()))
+                    .as_table_mut();
+                (module_table, option)
+            } else {
+                (Some(&mut *table), name.as_str())
+            };
+
+            match module_table {
+                Some(module_table) if !option.is_empty() => {
+                    log::debug!("Setting {} from the environment", name);
+                    module_table.insert(option.to_owned(), value);
+                }
+                _ => log::warn!("Unable to set {} from the environment", name),
+            }
+        }
+    }
+
+    /// The path of the configuration file, from `$STARSHIP_CONFIG` or else the default
+    fn config_path(env: Option<&HashMap<String, String>>) -> Option<String> {
+        if let Some(path) = utils::get_env(env, "STARSHIP_CONFIG") {
             // Use $STARSHIP_CONFIG as the config path if available
             log::debug!("STARSHIP_CONFIG is set: \n{}", &path);
-            path
+            Some(path)
         } else {
             // Default to using ~/.config/starship.toml
             log::debug!("STARSHIP_CONFIG is not set");
             let config_path = home_dir()?.join(".config/starship.toml");
             let config_path_str = config_path.to_str()?.to_owned();
             log::debug!("Using default config path: {}", config_path_str);
-            config_path_str
+            Some(config_path_str)
+        }
+    }
+
+    /// Create a config from a starship configuration file, and the files it lists in
+    /// `include`. Their options are merged into it in order, later files taking
+    /// precedence, and paths are relative to the file that includes them. Every file
+    /// read is added to `files`, and is only read once.
+    fn config_from_file_with_includes(
+        file_path: &Path,
+        files: &mut Vec<(PathBuf, Option<FileVersion>)>,
+    ) -> Option<Value> {
+        let canonical_path = fs::canonicalize(file_path).unwrap_or_else(|_| file_path.into());
+        files.push((canonical_path, file_version(file_path)));
+        let mut config = Self::config_from_file(file_path.to_str()?)?;
+
+        let includes = match config.as_table_mut()?.remove("include") {
+            Some(Value::Array(includes)) => includes,
+            Some(value) => {
+                log::warn!(
+                    "Invalid config: {}",
+                    ConfigWarning::expected("an array of paths", &value).in_table("include")
+                );
+                Vec::new()
+            }
+            None => Vec::new(),
         };
+        for include in includes {
+            let include = match include.as_str() {
+                Some(include) => include,
+                None => {
+                    log::warn!(
+                        "Invalid config: {}",
+                        ConfigWarning::expected("a path", &include).in_table("include")
+                    );
+                    continue;
+                }
+            };
+            let include_path = match (include.strip_prefix("~/"), home_dir()) {
+                (Some(path), Some(home_dir)) => home_dir.join(path),
+                _ => file_path
+                    .parent()
+                    .map_or_else(|| PathBuf::from(include), |dir| dir.join(include)),
+            };
+            let canonical_path =
+                fs::canonicalize(&include_path).unwrap_or_else(|_| include_path.clone());
+            if files.iter().any(|(path, _)| *path == canonical_path) {
+                log::debug!("{} is already included", include_path.display());
+                continue;
+            }
+            if !include_path.is_file() {
+                log::warn!(
+                    "Unable to find the included file {}",
+                    include_path.display()
+                );
+            }
 
-        let toml_content = match utils::read_file(&file_path) {
+            let included = Self::config_from_file_with_includes(&include_path, files);
+            if let (Some(table), Some(Value::Table(included_table))) =
+                (config.as_table_mut(), included)
+            {
+                merge_tables(table, included_table);
+            }
+        }
+        Some(config)
+    }
+
+    /// Create a config from a starship configuration file
+    fn config_from_file(file_path: &str) -> Option<Value> {
+        let toml_content = match utils::read_file(file_path) {
             Ok(content) => {
                 log::trace!("Config file content: \n{}", &content);
                 Some(content)
@@ -198,7 +1091,9 @@ impl StarshipConfig {
             }
         }?;
 
-        let config = toml::from_str(&toml_content).ok()?;
+        let config = toml::from_str(&toml_content)
+            .map_err(|error| log::warn!("Unable to parse the config file: {}", error))
+            .ok()?;
         log::debug!("Config parsed: \n{:?}", &config);
         Some(config)
     }
@@ -238,6 +1133,43 @@ impl StarshipConfig {
         self.config.as_ref()?.as_table()?.get("custom")?.as_table()
     }
 
+    /// Get the `[plugin.<name>]` table of a plugin, which is passed to the plugin as is
+    pub fn get_plugin_config(&self, name: &str) -> Option<&Value> {
+        self.config.as_ref()?.get("plugin")?.get(name)
+    }
+
+    /// Get the colors of the palette selected with the `palette` option
+    pub fn get_palette(&self) -> Palette {
+        let root_config = self.get_root_config();
+        let palette_name = match root_config.palette {
+            Some(palette_name) => palette_name,
+            None => return HashMap::new(),
+        };
+        let palette = match root_config.palettes.get(palette_name) {
+            Some(palette) => palette,
+            None => {
+                log::warn!("Could not find the palette \"{}\"", palette_name);
+                return HashMap::new();
+            }
+        };
+
+        palette
+            .iter()
+            .filter_map(|(name, color_string)| {
+                let color = parse_builtin_color_string(&color_string.to_lowercase());
+                if color.is_none() {
+                    log::warn!(
+                        "Invalid color \"{}\" for \"{}\" in palette \"{}\"",
+                        color_string,
+                        name,
+                        palette_name
+                    );
+                }
+                Some((name.to_lowercase(), color?))
+            })
+            .collect()
+    }
+
     pub fn get_root_config(&self) -> StarshipRootConfig {
         if let Some(root_config) = &self.config {
             StarshipRootConfig::load(root_config)
@@ -286,6 +1218,54 @@ impl<'a> ModuleConfig<'a> for SegmentConfig<'a> {
         };
         new_config
     }
+
+    fn to_config(&self) -> Option<Value> {
+        match self.style {
+            Some(style) => {
+                let mut table = toml::value::Table::new();
+                table.insert(String::from("value"), Value::String(self.value.to_owned()));
+                table.insert(String::from("style"), Value::String(style_to_string(style)));
+                Some(Value::Table(table))
+            }
+            None => Some(Value::String(self.value.to_owned())),
+        }
+    }
+
+    fn check_config(&self, config: &'a Value) -> Vec<ConfigWarning> {
+        match config {
+            Value::String(_) => Vec::new(),
+            Value::Table(config_table) => config_table
+                .iter()
+                .flat_map(|(key, value)| {
+                    let warnings = match key.as_str() {
+                        "value" => self.value.check_config(value),
+                        "style" => self.style.unwrap_or_default().check_config(value),
+                        _ => vec![ConfigWarning::new(String::from("unknown option"))],
+                    };
+                    warnings
+                        .into_iter()
+                        .map(move |warning| warning.in_table(key))
+                })
+                .collect(),
+            _ => vec![ConfigWarning::expected("a string or a table", config)],
+        }
+    }
+
+    fn schema() -> json::Value {
+        json!({
+            "anyOf": [
+                { "type": "string" },
+                {
+                    "type": "object",
+                    "properties": {
+                        "value": { "type": "string" },
+                        "style": { "type": "string" },
+                    },
+                    "additionalProperties": false,
+                },
+            ]
+        })
+    }
 }
 
 impl<'a> SegmentConfig<'a> {
@@ -316,6 +1296,22 @@ impl Default for SegmentConfig<'static> {
     }
 }
 
+/// Remove the options that every module accepts, which aren't part of module config structs
+fn without_module_options(config: &Value) -> Value {
+    let mut config = config.clone();
+    if let Some(table) = config.as_table_mut() {
+        table.remove("bg");
+        table.remove("symbol_set");
+        table.remove("timeout");
+        table.remove("cache_ttl");
+        table.remove("when");
+        table.remove("max_width");
+        table.remove("max_width_symbol");
+        table.remove("style_rules");
+    }
+    config
+}
+
 /** Parse a style string which represents an ansi style. Valid tokens in the style
  string include the following:
  - 'fg:<color>'    (specifies that the color read should be a foreground color)
@@ -347,6 +1343,10 @@ pub fn parse_style_string(style_string: &str) -> Option<ansi_term::Style> {
                     "bold" => Some(style.bold()),
                     "italic" => Some(style.italic()),
                     "dimmed" => Some(style.dimmed()),
+                    "blink" => Some(style.blink()),
+                    "inverted" => Some(style.reverse()),
+                    "hidden" => Some(style.hidden()),
+                    "strikethrough" => Some(style.strikethrough()),
                     "none" => None,
 
                     // Try to see if this token parses as a valid color string
@@ -362,29 +1362,104 @@ pub fn parse_style_string(style_string: &str) -> Option<ansi_term::Style> {
         })
 }
 
+/// Convert a style back to a style string, the inverse of `parse_style_string`
+pub fn style_to_string(style: Style) -> String {
+    let mut tokens = Vec::new();
+    let modifiers = [
+        (style.is_bold, "bold"),
+        (style.is_dimmed, "dimmed"),
+        (style.is_italic, "italic"),
+        (style.is_underline, "underline"),
+        (style.is_blink, "blink"),
+        (style.is_reverse, "inverted"),
+        (style.is_hidden, "hidden"),
+        (style.is_strikethrough, "strikethrough"),
+    ];
+    for (enabled, token) in &modifiers {
+        if *enabled {
+            tokens.push((*token).to_owned());
+        }
+    }
+    if let Some(color) = style.foreground {
+        tokens.push(format!("fg:{}", color_to_string(color)));
+    }
+    if let Some(color) = style.background {
+        tokens.push(format!("bg:{}", color_to_string(color)));
+    }
+    tokens.join(" ")
+}
+
+/// Convert a color back to a color string, the inverse of `parse_builtin_color_string`
+fn color_to_string(color: Color) -> String {
+    const BRIGHT_COLORS: [&str; 8] = [
+        "bright-black",
+        "bright-red",
+        "bright-green",
+        "bright-yellow",
+        "bright-blue",
+        "bright-purple",
+        "bright-cyan",
+        "bright-white",
+    ];
+
+    match color {
+        Color::Black => String::from("black"),
+        Color::Red => String::from("red"),
+        Color::Green => String::from("green"),
+        Color::Yellow => String::from("yellow"),
+        Color::Blue => String::from("blue"),
+        Color::Purple => String::from("purple"),
+        Color::Cyan => String::from("cyan"),
+        Color::White => String::from("white"),
+        Color::Fixed(index @ 8..=15) => BRIGHT_COLORS[usize::from(index - 8)].to_owned(),
+        Color::Fixed(index) => index.to_string(),
+        Color::RGB(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+    }
+}
+
+/// Parse a string that represents a color setting, looking it up in the palette of the
+/// config it's parsed for before falling back to the builtin color formats
+pub fn parse_color_string(color_string: &str) -> Option<ansi_term::Color> {
+    let palette_color = PALETTE.with(|palette| {
+        let palette = palette.borrow();
+        palette.as_ref()?.get(color_string).copied()
+    });
+    if let Some(color) = palette_color {
+        log::trace!("Read palette color: {}", color_string);
+        return Some(color);
+    }
+    parse_builtin_color_string(color_string)
+}
+
 /** Parse a string that represents a color setting, returning None if this fails
- There are three valid color formats:
+ There are four valid color formats:
   - #RRGGBB      (a hash followed by an RGB hex)
   - u8           (a number from 0-255, representing an ANSI color)
+  - colorNNN     (the same as u8, prefixed with "color")
   - colstring    (one of the 16 predefined color strings)
 */
-fn parse_color_string(color_string: &str) -> Option<ansi_term::Color> {
+fn parse_builtin_color_string(color_string: &str) -> Option<ansi_term::Color> {
     // Parse RGB hex values
     log::trace!("Parsing color_string: {}", color_string);
-    if color_string.starts_with('#') {
+    if let Some(hex) = color_string.strip_prefix('#') {
         log::trace!(
             "Attempting to read hexadecimal color string: {}",
             color_string
         );
-        let r: u8 = u8::from_str_radix(&color_string[1..3], 16).ok()?;
-        let g: u8 = u8::from_str_radix(&color_string[3..5], 16).ok()?;
-        let b: u8 = u8::from_str_radix(&color_string[5..7], 16).ok()?;
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            log::debug!("Invalid hexadecimal color string: {}", color_string);
+            return None;
+        }
+        let r: u8 = u8::from_str_radix(&hex[0..2], 16).ok()?;
+        let g: u8 = u8::from_str_radix(&hex[2..4], 16).ok()?;
+        let b: u8 = u8::from_str_radix(&hex[4..6], 16).ok()?;
         log::trace!("Read RGB color string: {},{},{}", r, g, b);
         return Some(Color::RGB(r, g, b));
     }
 
-    // Parse a u8 (ansi color)
-    if let Result::Ok(ansi_color_num) = color_string.parse::<u8>() {
+    // Parse a u8 (ansi color), optionally prefixed with "color"
+    let ansi_color_string = color_string.strip_prefix("color").unwrap_or(color_string);
+    if let Result::Ok(ansi_color_num) = ansi_color_string.parse::<u8>() {
         log::trace!("Read ANSI color string: {}", ansi_color_num);
         return Some(Color::Fixed(ansi_color_num));
     }
@@ -419,6 +1494,87 @@ fn parse_color_string(color_string: &str) -> Option<ansi_term::Color> {
     predefined_color
 }
 
+static COLOR: AtomicBool = AtomicBool::new(true);
+static TRUECOLOR: AtomicBool = AtomicBool::new(false);
+
+/// Turn the styles of the prompt on or off, for output that can't display them
+pub fn set_color(enabled: bool) {
+    COLOR.store(enabled, Ordering::Relaxed);
+}
+
+/// Show RGB colors as they are, or as their closest 256-color equivalent for terminals
+/// that can't display them
+pub fn set_truecolor(enabled: bool) {
+    TRUECOLOR.store(enabled, Ordering::Relaxed);
+}
+
+/// The style text is painted with: no style at all while colors are turned off, and
+/// without RGB colors unless truecolor is turned on
+pub fn display_style(style: Style) -> Style {
+    if !COLOR.load(Ordering::Relaxed) {
+        Style::default()
+    } else if TRUECOLOR.load(Ordering::Relaxed) {
+        style
+    } else {
+        downgrade_style(style)
+    }
+}
+
+/// Replace any RGB colors in a style with their closest 256-color equivalent,
+/// for terminals that can't display truecolor
+pub fn downgrade_style(style: Style) -> Style {
+    Style {
+        foreground: style.foreground.map(downgrade_color),
+        background: style.background.map(downgrade_color),
+        ..style
+    }
+}
+
+fn downgrade_color(color: Color) -> Color {
+    match color {
+        Color::RGB(r, g, b) => Color::Fixed(rgb_to_ansi256(r, g, b)),
+        color => color,
+    }
+}
+
+/// Find the closest color in the 256-color palette, looking at both the 6x6x6
+/// color cube (16-231) and the grayscale ramp (232-255)
+fn rgb_to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    let nearest_level = |value: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| (i32::from(CUBE_LEVELS[i]) - i32::from(value)).abs())
+            .unwrap_or(0)
+    };
+    let distance = |(r1, g1, b1): (u8, u8, u8)| {
+        let dr = i32::from(r1) - i32::from(r);
+        let dg = i32::from(g1) - i32::from(g);
+        let db = i32::from(b1) - i32::from(b);
+        dr * dr + dg * dg + db * db
+    };
+
+    let (ri, gi, bi) = (nearest_level(r), nearest_level(g), nearest_level(b));
+    let cube_index = 16 + 36 * ri + 6 * gi + bi;
+    let cube_color = (CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]);
+
+    // The grayscale ramp goes from 8 to 238 in steps of 10
+    let average = (u32::from(r) + u32::from(g) + u32::from(b)) / 3;
+    let gray_step = if average < 8 {
+        0
+    } else {
+        ((average - 8 + 5) / 10).min(23)
+    };
+    let gray_level = (8 + 10 * gray_step) as u8;
+    let gray_index = 232 + gray_step as usize;
+
+    if distance((gray_level, gray_level, gray_level)) < distance(cube_color) {
+        gray_index as u8
+    } else {
+        cube_index as u8
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -450,6 +1606,131 @@ mod tests {
         assert_eq!(rust_config.some_array, vec!["A"]);
     }
 
+    #[test]
+    fn test_config_to_config() {
+        #[derive(Clone, ModuleConfig)]
+        struct TestConfig<'a> {
+            pub symbol: SegmentConfig<'a>,
+            pub disabled: bool,
+            pub some_array: Vec<&'a str>,
+            pub style: Style,
+            pub suffix: Option<&'a str>,
+        }
+
+        let config = TestConfig {
+            symbol: SegmentConfig::new("S "),
+            disabled: false,
+            some_array: vec!["A", "B"],
+            style: Color::Red.bold(),
+            suffix: None,
+        };
+
+        let expected = toml::toml! {
+            symbol = "S "
+            disabled = false
+            some_array = ["A", "B"]
+            style = "bold fg:red"
+        };
+        assert_eq!(config.to_config(), Some(expected));
+    }
+
+    #[test]
+    fn test_check_config() {
+        #[derive(Clone, ModuleConfig)]
+        struct TestConfig<'a> {
+            pub symbol: SegmentConfig<'a>,
+            pub disabled: bool,
+            pub style: Style,
+            pub suffix: Option<&'a str>,
+        }
+
+        let config = TestConfig {
+            symbol: SegmentConfig::new("S "),
+            disabled: false,
+            style: Color::Red.bold(),
+            suffix: None,
+        };
+
+        let valid = toml::toml! {
+            symbol = { value = "S ", style = "none" }
+            disabled = true
+            suffix = " "
+        };
+        assert_eq!(config.check_config(&valid), Vec::new());
+
+        let invalid = toml::toml! {
+            symbol = { value = "S ", colour = "red" }
+            disabled = "yes"
+            style = "bold rde"
+            suffix = 1
+            prefix = " "
+        };
+        let warnings = config
+            .check_config(&invalid)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<String>>();
+        assert_eq!(
+            warnings,
+            vec![
+                "symbol.colour: unknown option",
+                "disabled: expected a boolean, found \"yes\"",
+                "style: invalid style string \"bold rde\"",
+                "suffix: invalid value 1",
+                "prefix: unknown option",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_schema() {
+        #[derive(Clone, ModuleConfig)]
+        struct TestConfig<'a> {
+            pub disabled: bool,
+            pub some_array: Vec<&'a str>,
+            pub suffix: Option<&'a str>,
+        }
+
+        let config = TestConfig {
+            disabled: false,
+            some_array: vec!["A"],
+            suffix: None,
+        };
+
+        let expected = json!({
+            "type": "object",
+            "properties": {
+                "disabled": { "type": "boolean", "default": false },
+                "some_array": { "type": "array", "items": { "type": "string" }, "default": ["A"] },
+                "suffix": { "type": "string" },
+            },
+            "additionalProperties": false,
+        });
+        let schema = schema_with_defaults(TestConfig::schema(), &config.to_config().unwrap());
+        assert_eq!(schema, expected);
+    }
+
+    #[test]
+    fn test_style_to_string() {
+        let styles = [
+            Style::new(),
+            Color::Fixed(10).on(Color::RGB(0x12, 0xab, 0xff)),
+            Style::new()
+                .fg(Color::Fixed(208))
+                .italic()
+                .underline()
+                .dimmed(),
+            Style::new().blink().reverse().hidden().strikethrough(),
+        ];
+        for style in &styles {
+            assert_eq!(parse_style_string(&style_to_string(*style)), Some(*style));
+        }
+        assert_eq!(
+            style_to_string(Color::Fixed(8).bold()),
+            "bold fg:bright-black"
+        );
+    }
+
     #[test]
     fn test_load_nested_config() {
         #[derive(Clone, ModuleConfig)]
@@ -655,4 +1936,327 @@ mod tests {
             Style::new().fg(Color::Fixed(125)).on(Color::Fixed(127))
         );
     }
+
+    #[test]
+    fn table_get_styles_extended_modifiers() {
+        let config = Value::from("blink inverted hidden strikethrough");
+        let mystyle = <Style>::from_config(&config).unwrap();
+        assert_eq!(
+            mystyle,
+            Style::new().blink().reverse().hidden().strikethrough()
+        );
+    }
+
+    #[test]
+    fn table_get_styles_color_index_and_hex() {
+        let config = Value::from("fg:color208 bg:#FF8800");
+        let mystyle = <Style>::from_config(&config).unwrap();
+        assert_eq!(
+            mystyle,
+            Style::new()
+                .fg(Color::Fixed(208))
+                .on(Color::RGB(255, 136, 0))
+        );
+
+        // Malformed color strings are rejected instead of panicking
+        assert!(<Style>::from_config(&Value::from("#ff88")).is_none());
+        assert!(<Style>::from_config(&Value::from("#ff88zz")).is_none());
+        assert!(<Style>::from_config(&Value::from("#ff8800ff")).is_none());
+        assert!(<Style>::from_config(&Value::from("color256")).is_none());
+    }
+
+    #[test]
+    fn rgb_to_ansi256_picks_nearest_color() {
+        assert_eq!(rgb_to_ansi256(0, 0, 0), 16);
+        assert_eq!(rgb_to_ansi256(255, 255, 255), 231);
+        assert_eq!(rgb_to_ansi256(255, 0, 0), 196);
+        assert_eq!(rgb_to_ansi256(255, 136, 0), 208);
+        assert_eq!(rgb_to_ansi256(128, 128, 128), 244);
+        assert_eq!(rgb_to_ansi256(5, 5, 5), 232);
+    }
+
+    #[test]
+    fn downgrade_style_only_changes_rgb_colors() {
+        let style = Style::new()
+            .bold()
+            .fg(Color::RGB(255, 0, 0))
+            .on(Color::Blue);
+        assert_eq!(
+            downgrade_style(style),
+            Style::new().bold().fg(Color::Fixed(196)).on(Color::Blue)
+        );
+    }
+
+    #[test]
+    fn get_palette_from_config() {
+        let config = StarshipConfig::new(Some(toml::toml! {
+            palette = "nord"
+
+            [palettes.nord]
+            Primary = "#a3be8c"
+            secondary = "bright-blue"
+            broken = "not-a-color"
+
+            [palettes.other]
+            primary = "red"
+        }));
+        let palette = config.get_palette();
+
+        assert_eq!(palette.len(), 2);
+        assert_eq!(palette.get("primary"), Some(&Color::RGB(163, 190, 140)));
+        assert_eq!(palette.get("secondary"), Some(&Color::Fixed(12)));
+    }
+
+    #[test]
+    fn get_palette_without_selected_palette() {
+        let config = StarshipConfig::new(Some(toml::toml! {
+            palette = "missing"
+
+            [palettes.nord]
+            primary = "#a3be8c"
+        }));
+        assert!(config.get_palette().is_empty());
+
+        let config = StarshipConfig::new(Some(toml::toml! {
+            [palettes.nord]
+            primary = "#a3be8c"
+        }));
+        assert!(config.get_palette().is_empty());
+    }
+
+    #[test]
+    fn parse_style_with_palette_of_config() {
+        let nord = StarshipConfig::new(Some(toml::toml! {
+            palette = "nord"
+
+            [palettes.nord]
+            primary = "#a3be8c"
+        }));
+        let other = StarshipConfig::new(Some(toml::toml! {
+            palette = "other"
+
+            [palettes.other]
+            primary = "red"
+        }));
+
+        {
+            let _palette = nord.use_palette();
+            assert_eq!(
+                parse_style_string("primary"),
+                Some(Color::RGB(163, 190, 140).normal())
+            );
+            {
+                let _palette = other.use_palette();
+                assert_eq!(parse_style_string("primary"), Some(Color::Red.normal()));
+            }
+            assert_eq!(
+                parse_style_string("primary"),
+                Some(Color::RGB(163, 190, 140).normal())
+            );
+        }
+        assert_eq!(parse_style_string("primary"), None);
+    }
+
+    #[test]
+    fn apply_symbol_sets_with_module_override() {
+        let mut config = StarshipConfig::new(Some(toml::toml! {
+            symbol_set = "ascii"
+
+            [rust]
+            symbol = "R "
+
+            [python]
+            symbol_set = "unicode"
+
+            [nodejs]
+            symbol_set = "nerd-font"
+        }));
+        config.apply_symbol_sets();
+
+        let get_symbol = |module: &str| {
+            config
+                .get_module_config(module)
+                .and_then(|config| config.get("symbol"))
+                .and_then(Value::as_str)
+                .map(str::to_owned)
+        };
+        assert_eq!(get_symbol("rust"), Some("R ".to_owned()));
+        assert_eq!(get_symbol("golang"), Some("go ".to_owned()));
+        assert_eq!(get_symbol("python"), None);
+        assert_eq!(get_symbol("nodejs"), Some("\u{e718} ".to_owned()));
+    }
+
+    #[test]
+    fn apply_overrides() {
+        let mut config = StarshipConfig::new(Some(toml::toml! {
+            add_newline = true
+
+            [git_branch]
+            symbol = "B "
+            style = "red"
+
+            [custom.my-tool]
+            command = "echo a"
+        }));
+        let overrides = |overrides: &[(&str, &str)]| -> Vec<(String, String)> {
+            overrides
+                .iter()
+                .map(|(name, value)| (String::from(*name), String::from(*value)))
+                .collect()
+        };
+        config.apply_overrides(&overrides(&[
+            ("ADD_NEWLINE", "false"),
+            ("GIT_BRANCH_SYMBOL", "on "),
+            ("GIT_BRANCH_TRUNCATION_LENGTH", "4"),
+            ("CMD_DURATION_MIN_TIME", "500"),
+            ("CUSTOM_MY_TOOL_COMMAND", "\"echo b\""),
+        ]));
+
+        assert_eq!(
+            config.config,
+            Some(toml::toml! {
+                add_newline = false
+
+                [git_branch]
+                symbol = "on "
+                style = "red"
+                truncation_length = 4
+
+                [cmd_duration]
+                min_time = 500
+
+                [custom.my-tool]
+                command = "echo b"
+            })
+        );
+    }
+
+    #[test]
+    fn merge_project_tables() {
+        let mut config = toml::toml! {
+            add_newline = false
+
+            [kubernetes]
+            symbol = "k8s "
+            style = "blue"
+        };
+        let project_config = toml::toml! {
+            [kubernetes]
+            disabled = false
+            style = "red"
+        };
+        match (&mut config, project_config) {
+            (Value::Table(table), Value::Table(project_table)) => {
+                merge_tables(table, project_table)
+            }
+            _ => unreachable!(),
+        }
+
+        assert_eq!(
+            config,
+            toml::toml! {
+                add_newline = false
+
+                [kubernetes]
+                symbol = "k8s "
+                style = "red"
+                disabled = false
+            }
+        );
+    }
+
+    #[test]
+    fn config_from_file_with_includes() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir(dir.path().join("starship"))?;
+        fs::write(
+            dir.path().join("starship.toml"),
+            r#"
+                include = ["starship/colors.toml", "starship/machine.toml"]
+                add_newline = false
+
+                [directory]
+                style = "cyan"
+                truncation_length = 5
+            "#,
+        )?;
+        fs::write(
+            dir.path().join("starship/colors.toml"),
+            r#"
+                include = ["../starship.toml"]
+
+                [directory]
+                style = "blue"
+            "#,
+        )?;
+        fs::write(
+            dir.path().join("starship/machine.toml"),
+            r#"
+                [directory]
+                style = "red"
+            "#,
+        )?;
+
+        let mut files = Vec::new();
+        let config = StarshipConfig::config_from_file_with_includes(
+            &dir.path().join("starship.toml"),
+            &mut files,
+        );
+        assert_eq!(
+            config,
+            Some(toml::toml! {
+                add_newline = false
+
+                [directory]
+                style = "red"
+                truncation_length = 5
+            })
+        );
+        assert_eq!(files.len(), 3);
+        dir.close()
+    }
+
+    #[test]
+    fn migrate_deprecated_options() {
+        let mut config = toml::toml! {
+            prompt_order = ["directory", "character"]
+        };
+        let migrations = migrate_options(config.as_table_mut().unwrap());
+        assert_eq!(
+            config,
+            toml::toml! {
+                format = "${directory}${character}"
+            }
+        );
+        assert_eq!(
+            migrations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "`prompt_order` is deprecated, use `format = \"${directory}${character}\"` instead"
+            ]
+        );
+
+        let mut config = toml::toml! {
+            format = "$all"
+            prompt_order = ["directory", "character"]
+        };
+        let migrations = migrate_options(config.as_table_mut().unwrap());
+        assert_eq!(config, toml::toml! { format = "$all" });
+        assert_eq!(migrations[0].value, None);
+
+        let mut config = toml::toml! {
+            [character]
+            symbol = "➜"
+        };
+        migrate_options(config.as_table_mut().unwrap());
+        assert_eq!(
+            config,
+            toml::toml! {
+                [character]
+                success_symbol = "➜"
+            }
+        );
+    }
 }
@@ -0,0 +1,26 @@
+use crate::config::{ModuleConfig, RootModuleConfig, SegmentConfig};
+
+use ansi_term::{Color, Style};
+use starship_module_config_derive::ModuleConfig;
+use std::collections::HashMap;
+
+#[derive(Clone, ModuleConfig)]
+pub struct AnsibleConfig<'a> {
+    pub symbol: SegmentConfig<'a>,
+    pub format: &'a str,
+    pub aliases: HashMap<String, &'a str>,
+    pub style: Style,
+    pub disabled: bool,
+}
+
+impl<'a> RootModuleConfig<'a> for AnsibleConfig<'a> {
+    fn new() -> Self {
+        AnsibleConfig {
+            symbol: SegmentConfig::new("🅰 "),
+            format: "$symbol$version( \\($environment\\))",
+            aliases: HashMap::new(),
+            style: Color::Red.bold(),
+            disabled: false,
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::config::{ModuleConfig, RootModuleConfig, SegmentConfig};
+use crate::configs::redact::Redact;
 use std::collections::HashMap;
 
 use ansi_term::{Color, Style};
@@ -16,10 +17,14 @@ pub struct AwsConfig<'a> {
     pub symbol: SegmentConfig<'a>,
     pub profile: SegmentConfig<'a>,
     pub region: SegmentConfig<'a>,
+    pub expiration: SegmentConfig<'a>,
+    pub expiration_threshold: i64,
+    pub expiration_style: Style,
     pub style: Style,
     pub disabled: bool,
     pub displayed_items: AwsItems,
     pub region_aliases: HashMap<String, &'a str>,
+    pub redact: Redact,
 }
 
 impl<'a> RootModuleConfig<'a> for AwsConfig<'a> {
@@ -28,10 +33,14 @@ impl<'a> RootModuleConfig<'a> for AwsConfig<'a> {
             symbol: SegmentConfig::new("☁️  "),
             profile: SegmentConfig::default(),
             region: SegmentConfig::default(),
+            expiration: SegmentConfig::default(),
+            expiration_threshold: 900,
+            expiration_style: Color::Red.bold(),
             style: Color::Yellow.bold(),
             disabled: false,
             displayed_items: AwsItems::All,
             region_aliases: HashMap::new(),
+            redact: Redact::None,
         }
     }
 }
@@ -45,4 +54,17 @@ impl<'a> ModuleConfig<'a> for AwsItems {
             _ => None,
         }
     }
+
+    fn to_config(&self) -> Option<toml::Value> {
+        let items = match self {
+            AwsItems::All => "all",
+            AwsItems::Region => "region",
+            AwsItems::Profile => "profile",
+        };
+        Some(toml::Value::String(items.to_owned()))
+    }
+
+    fn schema() -> serde_json::Value {
+        serde_json::json!({ "enum": ["all", "region", "profile"] })
+    }
 }
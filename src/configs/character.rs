@@ -1,29 +1,118 @@
-use crate::config::{ModuleConfig, RootModuleConfig, SegmentConfig};
+use crate::config::{ConfigWarning, ModuleConfig, RootModuleConfig, SegmentConfig};
 
 use ansi_term::{Color, Style};
 use starship_module_config_derive::ModuleConfig;
+use std::collections::HashMap;
 
 #[derive(Clone, ModuleConfig)]
 pub struct CharacterConfig<'a> {
-    pub symbol: SegmentConfig<'a>,
+    pub success_symbol: SegmentConfig<'a>,
     pub error_symbol: SegmentConfig<'a>,
     pub vicmd_symbol: SegmentConfig<'a>,
     pub use_symbol_for_status: bool,
     pub style_success: Style,
     pub style_failure: Style,
+    pub shells: HashMap<String, ShellSymbols<'a>>,
     pub disabled: bool,
 }
 
 impl<'a> RootModuleConfig<'a> for CharacterConfig<'a> {
     fn new() -> Self {
+        let mut shells = HashMap::new();
+        shells.insert(
+            String::from("nu"),
+            ShellSymbols {
+                success_symbol: Some(SegmentConfig::new(">")),
+                ..ShellSymbols::default()
+            },
+        );
+
         CharacterConfig {
-            symbol: SegmentConfig::new("❯"),
+            success_symbol: SegmentConfig::new("❯"),
             error_symbol: SegmentConfig::new("✖"),
             vicmd_symbol: SegmentConfig::new("❮"),
             use_symbol_for_status: false,
             style_success: Color::Green.bold(),
             style_failure: Color::Red.bold(),
+            shells,
             disabled: false,
         }
     }
 }
+
+/// The symbols used in a shell instead of the ones of every shell, for those that are set
+#[derive(Clone, Default)]
+pub struct ShellSymbols<'a> {
+    pub success_symbol: Option<SegmentConfig<'a>>,
+    pub error_symbol: Option<SegmentConfig<'a>>,
+    pub vicmd_symbol: Option<SegmentConfig<'a>>,
+}
+
+const SHELL_SYMBOLS: &[&str] = &["success_symbol", "error_symbol", "vicmd_symbol"];
+
+impl<'a> ModuleConfig<'a> for ShellSymbols<'a> {
+    fn from_config(config: &'a toml::Value) -> Option<Self> {
+        let config = config.as_table()?;
+        let symbol = |name| config.get(name).and_then(SegmentConfig::from_config);
+        Some(ShellSymbols {
+            success_symbol: symbol("success_symbol"),
+            error_symbol: symbol("error_symbol"),
+            vicmd_symbol: symbol("vicmd_symbol"),
+        })
+    }
+
+    fn load_config(&self, config: &'a toml::Value) -> Self {
+        match Self::from_config(config) {
+            Some(symbols) => ShellSymbols {
+                success_symbol: symbols
+                    .success_symbol
+                    .or_else(|| self.success_symbol.clone()),
+                error_symbol: symbols.error_symbol.or_else(|| self.error_symbol.clone()),
+                vicmd_symbol: symbols.vicmd_symbol.or_else(|| self.vicmd_symbol.clone()),
+            },
+            None => self.clone(),
+        }
+    }
+
+    fn to_config(&self) -> Option<toml::Value> {
+        let symbols = [&self.success_symbol, &self.error_symbol, &self.vicmd_symbol];
+        let table = SHELL_SYMBOLS
+            .iter()
+            .zip(symbols.iter())
+            .filter_map(|(name, symbol)| Some((name.to_string(), symbol.to_config()?)))
+            .collect();
+        Some(toml::Value::Table(table))
+    }
+
+    fn check_config(&self, config: &'a toml::Value) -> Vec<ConfigWarning> {
+        let config = match config.as_table() {
+            Some(config) => config,
+            None => return vec![ConfigWarning::expected("a table", config)],
+        };
+        config
+            .iter()
+            .flat_map(|(key, value)| {
+                let warnings = if SHELL_SYMBOLS.contains(&key.as_str()) {
+                    SegmentConfig::default().check_config(value)
+                } else {
+                    vec![ConfigWarning::new(String::from("unknown option"))]
+                };
+                warnings
+                    .into_iter()
+                    .map(move |warning| warning.in_table(key))
+            })
+            .collect()
+    }
+
+    fn schema() -> serde_json::Value {
+        let properties: serde_json::Map<String, serde_json::Value> = SHELL_SYMBOLS
+            .iter()
+            .map(|name| (name.to_string(), SegmentConfig::schema()))
+            .collect();
+        serde_json::json!({
+            "type": "object",
+            "properties": properties,
+            "additionalProperties": false,
+        })
+    }
+}
@@ -0,0 +1,29 @@
+use crate::config::{ModuleConfig, RootModuleConfig, SegmentConfig};
+
+use ansi_term::{Color, Style};
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig)]
+pub struct CiConfig<'a> {
+    pub symbol: SegmentConfig<'a>,
+    pub github_actions_symbol: SegmentConfig<'a>,
+    pub gitlab_ci_symbol: SegmentConfig<'a>,
+    pub buildkite_symbol: SegmentConfig<'a>,
+    pub show_provider: bool,
+    pub style: Style,
+    pub disabled: bool,
+}
+
+impl<'a> RootModuleConfig<'a> for CiConfig<'a> {
+    fn new() -> Self {
+        CiConfig {
+            symbol: SegmentConfig::new("⚙️  "),
+            github_actions_symbol: SegmentConfig::new("🐙 "),
+            gitlab_ci_symbol: SegmentConfig::new("🦊 "),
+            buildkite_symbol: SegmentConfig::new("🪁 "),
+            show_provider: true,
+            style: Color::Red.bold(),
+            disabled: false,
+        }
+    }
+}
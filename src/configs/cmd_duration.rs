@@ -9,6 +9,7 @@ pub struct CmdDurationConfig<'a> {
     pub prefix: &'a str,
     pub style: Style,
     pub show_milliseconds: bool,
+    pub use_locale: bool,
     pub disabled: bool,
 }
 
@@ -18,6 +19,7 @@ impl<'a> RootModuleConfig<'a> for CmdDurationConfig<'a> {
             min_time: 2_000,
             prefix: "took ",
             show_milliseconds: false,
+            use_locale: false,
             style: Color::Yellow.bold(),
             disabled: false,
         }
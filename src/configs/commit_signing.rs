@@ -0,0 +1,29 @@
+use crate::config::{ModuleConfig, RootModuleConfig, SegmentConfig};
+
+use ansi_term::{Color, Style};
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig)]
+pub struct CommitSigningConfig<'a> {
+    pub symbol: SegmentConfig<'a>,
+    pub no_key_symbol: SegmentConfig<'a>,
+    pub key: SegmentConfig<'a>,
+    pub show_key: bool,
+    pub style: Style,
+    pub no_key_style: Style,
+    pub disabled: bool,
+}
+
+impl<'a> RootModuleConfig<'a> for CommitSigningConfig<'a> {
+    fn new() -> Self {
+        CommitSigningConfig {
+            symbol: SegmentConfig::new("🔏 "),
+            no_key_symbol: SegmentConfig::new("🔓 "),
+            key: SegmentConfig::default(),
+            show_key: false,
+            style: Color::Green.bold(),
+            no_key_style: Color::Red.bold(),
+            disabled: true,
+        }
+    }
+}
@@ -26,6 +26,8 @@ pub struct CustomConfig<'a> {
     pub files: Files<'a>,
     pub extensions: Extensions<'a>,
     pub directories: Directories<'a>,
+    pub allowed_env: Vec<&'a str>,
+    pub max_output: usize,
 }
 
 impl<'a> RootModuleConfig<'a> for CustomConfig<'a> {
@@ -43,6 +45,8 @@ impl<'a> RootModuleConfig<'a> for CustomConfig<'a> {
             files: Files::default(),
             extensions: Extensions::default(),
             directories: Directories::default(),
+            allowed_env: Vec::new(),
+            max_output: 1024,
         }
     }
 }
@@ -61,6 +65,14 @@ impl<'a> ModuleConfig<'a> for Files<'a> {
 
         Some(Files(files))
     }
+
+    fn to_config(&self) -> Option<toml::Value> {
+        self.0.to_config()
+    }
+
+    fn schema() -> serde_json::Value {
+        <Vec<&str>>::schema()
+    }
 }
 
 impl<'a> ModuleConfig<'a> for Extensions<'a> {
@@ -77,6 +89,14 @@ impl<'a> ModuleConfig<'a> for Extensions<'a> {
 
         Some(Extensions(extensions))
     }
+
+    fn to_config(&self) -> Option<toml::Value> {
+        self.0.to_config()
+    }
+
+    fn schema() -> serde_json::Value {
+        <Vec<&str>>::schema()
+    }
 }
 
 impl<'a> ModuleConfig<'a> for Directories<'a> {
@@ -93,4 +113,12 @@ impl<'a> ModuleConfig<'a> for Directories<'a> {
 
         Some(Directories(directories))
     }
+
+    fn to_config(&self) -> Option<toml::Value> {
+        self.0.to_config()
+    }
+
+    fn schema() -> serde_json::Value {
+        <Vec<&str>>::schema()
+    }
 }
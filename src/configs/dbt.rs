@@ -0,0 +1,23 @@
+use crate::config::{ModuleConfig, RootModuleConfig, SegmentConfig};
+
+use ansi_term::{Color, Style};
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig)]
+pub struct DbtConfig<'a> {
+    pub symbol: SegmentConfig<'a>,
+    pub format: &'a str,
+    pub style: Style,
+    pub disabled: bool,
+}
+
+impl<'a> RootModuleConfig<'a> for DbtConfig<'a> {
+    fn new() -> Self {
+        DbtConfig {
+            symbol: SegmentConfig::new("🟧 "),
+            format: "$symbol$profile( \\($target\\))",
+            style: Color::Fixed(208).bold(),
+            disabled: false,
+        }
+    }
+}
@@ -1,7 +1,8 @@
-use crate::config::{ModuleConfig, RootModuleConfig};
+use crate::config::{ModuleConfig, RootModuleConfig, SegmentConfig};
 
 use ansi_term::{Color, Style};
 use starship_module_config_derive::ModuleConfig;
+use std::collections::HashMap;
 
 #[derive(Clone, ModuleConfig)]
 pub struct DirectoryConfig<'a> {
@@ -9,8 +10,14 @@ pub struct DirectoryConfig<'a> {
     pub truncate_to_repo: bool,
     pub fish_style_pwd_dir_length: i64,
     pub use_logical_path: bool,
+    pub substitutions: HashMap<String, &'a str>,
+    pub home_symbol: &'a str,
     pub prefix: &'a str,
+    pub read_only_symbol: SegmentConfig<'a>,
+    pub check_read_only: bool,
+    pub hyperlink: bool,
     pub style: Style,
+    pub repo_root_style: Option<Style>,
     pub disabled: bool,
 }
 
@@ -21,8 +28,17 @@ impl<'a> RootModuleConfig<'a> for DirectoryConfig<'a> {
             truncate_to_repo: true,
             fish_style_pwd_dir_length: 0,
             use_logical_path: true,
+            substitutions: HashMap::new(),
+            home_symbol: "~",
             prefix: "in ",
+            read_only_symbol: SegmentConfig {
+                value: "🔒",
+                style: Some(Color::Red.normal()),
+            },
+            check_read_only: true,
+            hyperlink: false,
             style: Color::Cyan.bold(),
+            repo_root_style: None,
             disabled: false,
         }
     }
@@ -7,6 +7,8 @@ use starship_module_config_derive::ModuleConfig;
 pub struct DotnetConfig<'a> {
     pub symbol: SegmentConfig<'a>,
     pub version: SegmentConfig<'a>,
+    pub target_framework: SegmentConfig<'a>,
+    pub show_target_framework: bool,
     pub style: Style,
     pub heuristic: bool,
     pub disabled: bool,
@@ -17,6 +19,8 @@ impl<'a> RootModuleConfig<'a> for DotnetConfig<'a> {
         DotnetConfig {
             symbol: SegmentConfig::new("•NET "),
             version: SegmentConfig::default(),
+            target_framework: SegmentConfig::default(),
+            show_target_framework: true,
             style: Color::Blue.bold(),
             heuristic: true,
             disabled: false,
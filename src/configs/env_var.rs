@@ -1,4 +1,5 @@
 use crate::config::{ModuleConfig, RootModuleConfig, SegmentConfig};
+use crate::configs::redact::Redact;
 
 use ansi_term::{Color, Style};
 use starship_module_config_derive::ModuleConfig;
@@ -10,6 +11,7 @@ pub struct EnvVarConfig<'a> {
     pub default: Option<&'a str>,
     pub prefix: &'a str,
     pub suffix: &'a str,
+    pub redact: Redact,
     pub style: Style,
     pub disabled: bool,
 }
@@ -22,8 +24,24 @@ impl<'a> RootModuleConfig<'a> for EnvVarConfig<'a> {
             default: None,
             prefix: "",
             suffix: "",
+            redact: Redact::None,
             style: Color::Black.bold().dimmed(),
             disabled: false,
         }
     }
 }
+
+/// The `[env_var.<name>]` tables of the config of `env_var`, which each show another
+/// variable, with the same options as `env_var`
+pub fn instances(config: &toml::Value) -> Vec<(&str, &toml::Value)> {
+    let table = match config.as_table() {
+        Some(table) => table,
+        None => return Vec::new(),
+    };
+    table
+        .iter()
+        // `symbol` and `when` can be tables too
+        .filter(|(name, config)| config.is_table() && !["symbol", "when"].contains(&name.as_str()))
+        .map(|(name, config)| (name.as_str(), config))
+        .collect()
+}
@@ -0,0 +1,26 @@
+use crate::config::{ModuleConfig, RootModuleConfig, SegmentConfig};
+
+use ansi_term::{Color, Style};
+use starship_module_config_derive::ModuleConfig;
+use std::collections::HashMap;
+
+#[derive(Clone, ModuleConfig)]
+pub struct GcloudConfig<'a> {
+    pub symbol: SegmentConfig<'a>,
+    pub format: &'a str,
+    pub project_aliases: HashMap<String, &'a str>,
+    pub style: Style,
+    pub disabled: bool,
+}
+
+impl<'a> RootModuleConfig<'a> for GcloudConfig<'a> {
+    fn new() -> Self {
+        GcloudConfig {
+            symbol: SegmentConfig::new("☁️ "),
+            format: "$symbol$account(\\($project\\))",
+            project_aliases: HashMap::new(),
+            style: Color::Blue.bold(),
+            disabled: false,
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::config::{ModuleConfig, RootModuleConfig, SegmentConfig};
+use crate::config::{ConfigWarning, ModuleConfig, RootModuleConfig, SegmentConfig};
 
 use ansi_term::{Color, Style};
 use starship_module_config_derive::ModuleConfig;
@@ -24,4 +24,15 @@ impl<'a> RootModuleConfig<'a> for GitBranchConfig<'a> {
             disabled: false,
         }
     }
+    fn check(&self) -> Vec<ConfigWarning> {
+        if self.truncation_length <= 0 {
+            let message = format!(
+                "should be a positive value, found {}",
+                self.truncation_length
+            );
+            vec![ConfigWarning::new(message).in_table("truncation_length")]
+        } else {
+            Vec::new()
+        }
+    }
 }
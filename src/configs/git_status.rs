@@ -11,6 +11,9 @@ pub struct GitStatusConfig<'a> {
     pub behind: SegmentConfig<'a>,
     pub diverged: SegmentConfig<'a>,
     pub show_sync_count: bool,
+    pub too_large: SegmentConfig<'a>,
+    pub max_files: usize,
+    pub unlimited_repos: Vec<&'a str>,
     pub conflicted: SegmentConfig<'a>,
     pub conflicted_count: CountConfig,
     pub deleted: SegmentConfig<'a>,
@@ -39,6 +42,9 @@ impl<'a> RootModuleConfig<'a> for GitStatusConfig<'a> {
             diverged: SegmentConfig::new("⇕"),
             conflicted: SegmentConfig::new("="),
             show_sync_count: false,
+            too_large: SegmentConfig::new("…"),
+            max_files: 0,
+            unlimited_repos: Vec::new(),
             conflicted_count: CountConfig::default(),
             deleted: SegmentConfig::new("✘"),
             deleted_count: CountConfig::default(),
@@ -7,6 +7,8 @@ use starship_module_config_derive::ModuleConfig;
 pub struct GoConfig<'a> {
     pub symbol: SegmentConfig<'a>,
     pub version: SegmentConfig<'a>,
+    pub format: Option<&'a str>,
+    pub mismatch_style: Style,
     pub style: Style,
     pub disabled: bool,
 }
@@ -16,6 +18,8 @@ impl<'a> RootModuleConfig<'a> for GoConfig<'a> {
         GoConfig {
             symbol: SegmentConfig::new("🐹 "),
             version: SegmentConfig::default(),
+            format: None,
+            mismatch_style: Color::Red.bold(),
             style: Color::Cyan.bold(),
             disabled: false,
         }
@@ -0,0 +1,28 @@
+use crate::config::{ModuleConfig, RootModuleConfig, SegmentConfig};
+
+use ansi_term::{Color, Style};
+use starship_module_config_derive::ModuleConfig;
+use std::collections::HashMap;
+
+#[derive(Clone, ModuleConfig)]
+pub struct HashicorpConfig<'a> {
+    pub nomad_symbol: SegmentConfig<'a>,
+    pub consul_symbol: SegmentConfig<'a>,
+    pub address: SegmentConfig<'a>,
+    pub address_aliases: HashMap<String, &'a str>,
+    pub style: Style,
+    pub disabled: bool,
+}
+
+impl<'a> RootModuleConfig<'a> for HashicorpConfig<'a> {
+    fn new() -> Self {
+        HashicorpConfig {
+            nomad_symbol: SegmentConfig::new("Ⓝ "),
+            consul_symbol: SegmentConfig::new("Ⓒ "),
+            address: SegmentConfig::default(),
+            address_aliases: HashMap::new(),
+            style: Color::Green.bold(),
+            disabled: false,
+        }
+    }
+}
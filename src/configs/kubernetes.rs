@@ -1,4 +1,5 @@
-use crate::config::{ModuleConfig, RootModuleConfig, SegmentConfig};
+use crate::config::{ConfigWarning, ModuleConfig, RootModuleConfig, SegmentConfig};
+use crate::configs::redact::Redact;
 
 use ansi_term::{Color, Style};
 use starship_module_config_derive::ModuleConfig;
@@ -9,9 +10,12 @@ pub struct KubernetesConfig<'a> {
     pub symbol: SegmentConfig<'a>,
     pub context: SegmentConfig<'a>,
     pub namespace: SegmentConfig<'a>,
+    pub format: Option<&'a str>,
     pub style: Style,
     pub disabled: bool,
     pub context_aliases: HashMap<String, &'a str>,
+    pub contexts: HashMap<String, ContextConfig<'a>>,
+    pub redact: Redact,
 }
 
 impl<'a> RootModuleConfig<'a> for KubernetesConfig<'a> {
@@ -20,9 +24,92 @@ impl<'a> RootModuleConfig<'a> for KubernetesConfig<'a> {
             symbol: SegmentConfig::new("☸ "),
             context: SegmentConfig::default(),
             namespace: SegmentConfig::default(),
+            format: None,
             style: Color::Cyan.bold(),
             disabled: true,
             context_aliases: HashMap::new(),
+            contexts: HashMap::new(),
+            redact: Redact::None,
         }
     }
 }
+
+/// How a specific context is displayed instead of the way every context is, for the
+/// options that are set
+#[derive(Clone, Default)]
+pub struct ContextConfig<'a> {
+    pub alias: Option<&'a str>,
+    pub style: Option<Style>,
+    pub disabled: Option<bool>,
+}
+
+const CONTEXT_OPTIONS: &[&str] = &["alias", "style", "disabled"];
+
+impl<'a> ModuleConfig<'a> for ContextConfig<'a> {
+    fn from_config(config: &'a toml::Value) -> Option<Self> {
+        let config = config.as_table()?;
+        Some(ContextConfig {
+            alias: config.get("alias").and_then(<&str>::from_config),
+            style: config.get("style").and_then(Style::from_config),
+            disabled: config.get("disabled").and_then(bool::from_config),
+        })
+    }
+
+    fn load_config(&self, config: &'a toml::Value) -> Self {
+        match Self::from_config(config) {
+            Some(context) => ContextConfig {
+                alias: context.alias.or(self.alias),
+                style: context.style.or(self.style),
+                disabled: context.disabled.or(self.disabled),
+            },
+            None => self.clone(),
+        }
+    }
+
+    fn to_config(&self) -> Option<toml::Value> {
+        let options = [
+            self.alias.to_config(),
+            self.style.to_config(),
+            self.disabled.to_config(),
+        ];
+        let table = CONTEXT_OPTIONS
+            .iter()
+            .zip(options.iter())
+            .filter_map(|(name, value)| Some((name.to_string(), value.clone()?)))
+            .collect();
+        Some(toml::Value::Table(table))
+    }
+
+    fn check_config(&self, config: &'a toml::Value) -> Vec<ConfigWarning> {
+        let config = match config.as_table() {
+            Some(config) => config,
+            None => return vec![ConfigWarning::expected("a table", config)],
+        };
+        config
+            .iter()
+            .flat_map(|(key, value)| {
+                let warnings = match key.as_str() {
+                    "alias" => "".check_config(value),
+                    "style" => Style::default().check_config(value),
+                    "disabled" => false.check_config(value),
+                    _ => vec![ConfigWarning::new(String::from("unknown option"))],
+                };
+                warnings
+                    .into_iter()
+                    .map(move |warning| warning.in_table(key))
+            })
+            .collect()
+    }
+
+    fn schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "alias": <&str>::schema(),
+                "style": Style::schema(),
+                "disabled": bool::schema(),
+            },
+            "additionalProperties": false,
+        })
+    }
+}
@@ -1,10 +1,19 @@
+use crate::config::{schema_with_defaults, ConfigWarning, RootModuleConfig};
+use toml::Value;
+
+pub mod ansible;
 pub mod aws;
 pub mod battery;
+pub mod bun;
 pub mod character;
+pub mod chroot;
+pub mod ci;
 pub mod cmd_duration;
+pub mod commit_signing;
 pub mod conda;
 pub mod crystal;
 pub mod custom;
+pub mod dbt;
 pub mod directory;
 pub mod docker_context;
 pub mod dotnet;
@@ -12,11 +21,14 @@ pub mod elixir;
 pub mod elm;
 pub mod env_var;
 pub mod erlang;
+pub mod fill;
+pub mod gcloud;
 pub mod git_branch;
 pub mod git_commit;
 pub mod git_state;
 pub mod git_status;
 pub mod go;
+pub mod hashicorp;
 pub mod haskell;
 pub mod hg_branch;
 pub mod hostname;
@@ -30,14 +42,113 @@ pub mod nodejs;
 pub mod ocaml;
 pub mod package;
 pub mod php;
+pub mod proxy;
 pub mod python;
+pub mod redact;
 pub mod ruby;
 pub mod rust;
 pub mod singularity;
+pub mod solidity;
 mod starship_root;
+pub mod symbol_sets;
 pub mod terraform;
+pub mod tex;
 pub mod time;
+pub mod typst;
 pub mod username;
+pub mod vpn;
 pub mod zig;
 
 pub use starship_root::*;
+
+/// Calls a generic function with the config type of a module, by the name of the module,
+/// returning `None` if there is no module with this name. This is the one list of the
+/// modules with a config, e.g. `with_module_config!("aws", check, config)` calls
+/// `check::<AwsConfig>(config)`.
+macro_rules! with_module_config {
+    ($name:expr, $f:ident $(, $arg:expr)*) => {
+        match $name {
+            "ansible" => Some($f::<$crate::configs::ansible::AnsibleConfig>($($arg),*)),
+            "aws" => Some($f::<$crate::configs::aws::AwsConfig>($($arg),*)),
+            "battery" => Some($f::<$crate::configs::battery::BatteryConfig>($($arg),*)),
+            "bun" => Some($f::<$crate::configs::bun::BunConfig>($($arg),*)),
+            "character" => Some($f::<$crate::configs::character::CharacterConfig>($($arg),*)),
+            "chroot" => Some($f::<$crate::configs::chroot::ChrootConfig>($($arg),*)),
+            "ci" => Some($f::<$crate::configs::ci::CiConfig>($($arg),*)),
+            "cmd_duration" => Some($f::<$crate::configs::cmd_duration::CmdDurationConfig>($($arg),*)),
+            "commit_signing" => Some($f::<$crate::configs::commit_signing::CommitSigningConfig>($($arg),*)),
+            "conda" => Some($f::<$crate::configs::conda::CondaConfig>($($arg),*)),
+            "crystal" => Some($f::<$crate::configs::crystal::CrystalConfig>($($arg),*)),
+            "dbt" => Some($f::<$crate::configs::dbt::DbtConfig>($($arg),*)),
+            "directory" => Some($f::<$crate::configs::directory::DirectoryConfig>($($arg),*)),
+            "docker_context" => Some($f::<$crate::configs::docker_context::DockerContextConfig>($($arg),*)),
+            "dotnet" => Some($f::<$crate::configs::dotnet::DotnetConfig>($($arg),*)),
+            "elixir" => Some($f::<$crate::configs::elixir::ElixirConfig>($($arg),*)),
+            "elm" => Some($f::<$crate::configs::elm::ElmConfig>($($arg),*)),
+            "env_var" => Some($f::<$crate::configs::env_var::EnvVarConfig>($($arg),*)),
+            "erlang" => Some($f::<$crate::configs::erlang::ErlangConfig>($($arg),*)),
+            "fill" => Some($f::<$crate::configs::fill::FillConfig>($($arg),*)),
+            "gcloud" => Some($f::<$crate::configs::gcloud::GcloudConfig>($($arg),*)),
+            "git_branch" => Some($f::<$crate::configs::git_branch::GitBranchConfig>($($arg),*)),
+            "git_commit" => Some($f::<$crate::configs::git_commit::GitCommitConfig>($($arg),*)),
+            "git_state" => Some($f::<$crate::configs::git_state::GitStateConfig>($($arg),*)),
+            "git_status" => Some($f::<$crate::configs::git_status::GitStatusConfig>($($arg),*)),
+            "golang" => Some($f::<$crate::configs::go::GoConfig>($($arg),*)),
+            "hashicorp" => Some($f::<$crate::configs::hashicorp::HashicorpConfig>($($arg),*)),
+            "haskell" => Some($f::<$crate::configs::haskell::HaskellConfig>($($arg),*)),
+            "hg_branch" => Some($f::<$crate::configs::hg_branch::HgBranchConfig>($($arg),*)),
+            "hostname" => Some($f::<$crate::configs::hostname::HostnameConfig>($($arg),*)),
+            "java" => Some($f::<$crate::configs::java::JavaConfig>($($arg),*)),
+            "jobs" => Some($f::<$crate::configs::jobs::JobsConfig>($($arg),*)),
+            "julia" => Some($f::<$crate::configs::julia::JuliaConfig>($($arg),*)),
+            "kubernetes" => Some($f::<$crate::configs::kubernetes::KubernetesConfig>($($arg),*)),
+            "memory_usage" => Some($f::<$crate::configs::memory_usage::MemoryConfig>($($arg),*)),
+            "nix_shell" => Some($f::<$crate::configs::nix_shell::NixShellConfig>($($arg),*)),
+            "nodejs" => Some($f::<$crate::configs::nodejs::NodejsConfig>($($arg),*)),
+            "ocaml" => Some($f::<$crate::configs::ocaml::OCamlConfig>($($arg),*)),
+            "package" => Some($f::<$crate::configs::package::PackageConfig>($($arg),*)),
+            "php" => Some($f::<$crate::configs::php::PhpConfig>($($arg),*)),
+            "proxy" => Some($f::<$crate::configs::proxy::ProxyConfig>($($arg),*)),
+            "python" => Some($f::<$crate::configs::python::PythonConfig>($($arg),*)),
+            "ruby" => Some($f::<$crate::configs::ruby::RubyConfig>($($arg),*)),
+            "rust" => Some($f::<$crate::configs::rust::RustConfig>($($arg),*)),
+            "singularity" => Some($f::<$crate::configs::singularity::SingularityConfig>($($arg),*)),
+            "solidity" => Some($f::<$crate::configs::solidity::SolidityConfig>($($arg),*)),
+            "terraform" => Some($f::<$crate::configs::terraform::TerraformConfig>($($arg),*)),
+            "tex" => Some($f::<$crate::configs::tex::TexConfig>($($arg),*)),
+            "time" => Some($f::<$crate::configs::time::TimeConfig>($($arg),*)),
+            "typst" => Some($f::<$crate::configs::typst::TypstConfig>($($arg),*)),
+            "username" => Some($f::<$crate::configs::username::UsernameConfig>($($arg),*)),
+            "vpn" => Some($f::<$crate::configs::vpn::VpnConfig>($($arg),*)),
+            "zig" => Some($f::<$crate::configs::zig::ZigConfig>($($arg),*)),
+            _ => None,
+        }
+    };
+}
+
+#[allow(unused_imports)] // Only used by `print-config`, which isn't part of the library
+pub(crate) use with_module_config;
+
+/// Check the configuration of a module, or `None` if there is no module with this name
+pub fn check_module_config(name: &str, config: &Value) -> Option<Vec<ConfigWarning>> {
+    fn check<'a, T: RootModuleConfig<'a>>(config: &'a Value) -> Vec<ConfigWarning> {
+        let mut warnings = T::new().check_config(config);
+        warnings.extend(T::load(config).check());
+        warnings
+    }
+
+    with_module_config!(name, check, config)
+}
+
+/// The JSON Schema of the configuration of a module, with its default values, or `None`
+/// if there is no module with this name
+pub fn module_schema(name: &str) -> Option<serde_json::Value> {
+    fn schema<'a, T: RootModuleConfig<'a>>() -> serde_json::Value {
+        match T::new().to_config() {
+            Some(defaults) => schema_with_defaults(T::schema(), &defaults),
+            None => T::schema(),
+        }
+    }
+
+    with_module_config!(name, schema)
+}
@@ -2,10 +2,14 @@ use crate::config::{ModuleConfig, RootModuleConfig, SegmentConfig};
 
 use ansi_term::{Color, Style};
 use starship_module_config_derive::ModuleConfig;
+use std::collections::HashMap;
 
 #[derive(Clone, ModuleConfig)]
 pub struct NodejsConfig<'a> {
     pub symbol: SegmentConfig<'a>,
+    pub show_package_manager: bool,
+    pub package_manager: SegmentConfig<'a>,
+    pub package_manager_symbols: HashMap<String, &'a str>,
     pub style: Style,
     pub disabled: bool,
 }
@@ -14,6 +18,9 @@ impl<'a> RootModuleConfig<'a> for NodejsConfig<'a> {
     fn new() -> Self {
         NodejsConfig {
             symbol: SegmentConfig::new("⬢ "),
+            show_package_manager: true,
+            package_manager: SegmentConfig::default(),
+            package_manager_symbols: HashMap::new(),
             style: Color::Green.bold(),
             disabled: false,
         }
@@ -7,6 +7,7 @@ use starship_module_config_derive::ModuleConfig;
 pub struct PhpConfig<'a> {
     pub symbol: SegmentConfig<'a>,
     pub style: Style,
+    pub mismatch_style: Style,
     pub disabled: bool,
 }
 
@@ -15,6 +16,7 @@ impl<'a> RootModuleConfig<'a> for PhpConfig<'a> {
         PhpConfig {
             symbol: SegmentConfig::new("🐘 "),
             style: Color::Fixed(147).bold(),
+            mismatch_style: Color::Red.bold(),
             disabled: false,
         }
     }
@@ -0,0 +1,25 @@
+use crate::config::{ModuleConfig, RootModuleConfig, SegmentConfig};
+
+use ansi_term::{Color, Style};
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig)]
+pub struct ProxyConfig<'a> {
+    pub symbol: SegmentConfig<'a>,
+    pub host: SegmentConfig<'a>,
+    pub show_host: bool,
+    pub style: Style,
+    pub disabled: bool,
+}
+
+impl<'a> RootModuleConfig<'a> for ProxyConfig<'a> {
+    fn new() -> Self {
+        ProxyConfig {
+            symbol: SegmentConfig::new("🔀 "),
+            host: SegmentConfig::default(),
+            show_host: false,
+            style: Color::Purple.bold(),
+            disabled: false,
+        }
+    }
+}
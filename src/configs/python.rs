@@ -2,6 +2,7 @@ use crate::config::{ModuleConfig, RootModuleConfig, SegmentConfig};
 
 use ansi_term::{Color, Style};
 use starship_module_config_derive::ModuleConfig;
+use std::collections::HashMap;
 
 #[derive(Clone, ModuleConfig)]
 pub struct PythonConfig<'a> {
@@ -10,6 +11,7 @@ pub struct PythonConfig<'a> {
     pub pyenv_prefix: SegmentConfig<'a>,
     pub pyenv_version_name: bool,
     pub scan_for_pyfiles: bool,
+    pub virtualenv_aliases: HashMap<String, &'a str>,
     pub style: Style,
     pub disabled: bool,
 }
@@ -22,6 +24,7 @@ impl<'a> RootModuleConfig<'a> for PythonConfig<'a> {
             pyenv_prefix: SegmentConfig::new("pyenv "),
             pyenv_version_name: false,
             scan_for_pyfiles: true,
+            virtualenv_aliases: HashMap::new(),
             style: Color::Yellow.bold(),
             disabled: false,
         }
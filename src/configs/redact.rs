@@ -0,0 +1,40 @@
+use crate::config::ModuleConfig;
+
+/// How a module hides a value that could be a secret, while still showing that it's set
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Redact {
+    /// The value is shown as it is
+    None,
+    /// The value is replaced by a short hash of it, to tell values apart
+    Hash,
+    /// Only the first characters of the value are shown
+    Truncate,
+    /// The value is replaced by asterisks
+    Mask,
+}
+
+impl<'a> ModuleConfig<'a> for Redact {
+    fn from_config(config: &toml::Value) -> Option<Self> {
+        match config.as_str()? {
+            "none" => Some(Redact::None),
+            "hash" => Some(Redact::Hash),
+            "truncate" => Some(Redact::Truncate),
+            "mask" => Some(Redact::Mask),
+            _ => None,
+        }
+    }
+
+    fn to_config(&self) -> Option<toml::Value> {
+        let redact = match self {
+            Redact::None => "none",
+            Redact::Hash => "hash",
+            Redact::Truncate => "truncate",
+            Redact::Mask => "mask",
+        };
+        Some(toml::Value::String(redact.to_owned()))
+    }
+
+    fn schema() -> serde_json::Value {
+        serde_json::json!({ "enum": ["none", "hash", "truncate", "mask"] })
+    }
+}
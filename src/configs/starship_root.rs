@@ -1,69 +1,134 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
 
 use starship_module_config_derive::ModuleConfig;
+use std::collections::HashMap;
 
 #[derive(Clone, ModuleConfig)]
 pub struct StarshipRootConfig<'a> {
+    pub format: &'a str,
+    pub right_format: &'a str,
+    pub continuation_format: &'a str,
+    pub transient_format: &'a str,
+    pub transient_right_format: &'a str,
     pub add_newline: bool,
-    pub prompt_order: Vec<&'a str>,
     pub scan_timeout: u64,
+    pub command_timeout: u64,
+    pub command_timeout_placeholder: &'a str,
+    pub command_cache_ttl: u64,
+    pub prompt_cache_ttl: u64,
+    pub async_modules: Vec<&'a str>,
+    pub async_placeholder: &'a str,
+    pub trusted_projects: Vec<&'a str>,
+    pub strict_commands: bool,
+    pub allowed_env: Vec<&'a str>,
+    pub palette: Option<&'a str>,
+    pub palettes: HashMap<String, HashMap<String, &'a str>>,
+    pub powerline: bool,
+    pub powerline_separator: &'a str,
+    pub symbol_set: &'a str,
+    pub report_cwd: bool,
+    pub window_title: &'a str,
+    pub iterm2_integration: bool,
+    pub force_color: bool,
+    pub truecolor: Option<bool>,
+    pub log_level: &'a str,
+    pub quiet: bool,
 }
 
+/// The default prompt format
+// NOTE: If this const value is changed then Default Prompt Format subheading inside
+// prompt heading of config docs needs to be updated according to changes made here.
+pub const PROMPT_FORMAT: &str = concat!(
+    "$username",
+    "$hostname",
+    "$ci",
+    "$singularity",
+    "$chroot",
+    "$kubernetes",
+    "$directory",
+    "$git_branch",
+    "$git_commit",
+    "$git_state",
+    "$git_status",
+    "$hg_branch",
+    "$commit_signing",
+    "$docker_context",
+    "$package",
+    // ↓ Toolchain version modules ↓
+    // (Let's keep these sorted alphabetically)
+    "$bun",
+    "$dotnet",
+    "$elixir",
+    "$elm",
+    "$erlang",
+    "$golang",
+    "$haskell",
+    "$java",
+    "$julia",
+    "$nodejs",
+    "$ocaml",
+    "$php",
+    "$python",
+    "$ruby",
+    "$rust",
+    "$solidity",
+    "$terraform",
+    "$tex",
+    "$typst",
+    "$zig",
+    // ↑ Toolchain version modules ↑
+    "$nix_shell",
+    "$conda",
+    "$memory_usage",
+    "$aws",
+    "$gcloud",
+    "$hashicorp",
+    "$ansible",
+    "$dbt",
+    "$proxy",
+    "$vpn",
+    "$env_var",
+    "$crystal",
+    "$cmd_duration",
+    "$custom",
+    "$line_break",
+    "$jobs",
+    "$battery",
+    "$time",
+    "$character",
+);
+
 impl<'a> RootModuleConfig<'a> for StarshipRootConfig<'a> {
     fn new() -> Self {
         StarshipRootConfig {
+            format: PROMPT_FORMAT,
+            right_format: "",
+            continuation_format: "[∙](bright-black) ",
+            transient_format: "",
+            transient_right_format: "",
             add_newline: true,
-            // List of default prompt order
-            // NOTE: If this const value is changed then Default prompt order subheading inside
-            // prompt heading of config docs needs to be updated according to changes made here.
-            prompt_order: vec![
-                "username",
-                "hostname",
-                "singularity",
-                "kubernetes",
-                "directory",
-                "git_branch",
-                "git_commit",
-                "git_state",
-                "git_status",
-                "hg_branch",
-                "docker_context",
-                "package",
-                // ↓ Toolchain version modules ↓
-                // (Let's keep these sorted alphabetically)
-                "dotnet",
-                "elixir",
-                "elm",
-                "erlang",
-                "golang",
-                "haskell",
-                "java",
-                "julia",
-                "nodejs",
-                "ocaml",
-                "php",
-                "python",
-                "ruby",
-                "rust",
-                "terraform",
-                "zig",
-                // ↑ Toolchain version modules ↑
-                "nix_shell",
-                "conda",
-                "memory_usage",
-                "aws",
-                "env_var",
-                "crystal",
-                "cmd_duration",
-                "custom",
-                "line_break",
-                "jobs",
-                #[cfg(feature = "battery")]
-                "battery",
-                "time",
-                "character",
-            ],
             scan_timeout: 30,
+            command_timeout: 500,
+            command_timeout_placeholder: "",
+            command_cache_ttl: 0,
+            prompt_cache_ttl: 0,
+            async_modules: Vec::new(),
+            async_placeholder: "…",
+            trusted_projects: Vec::new(),
+            strict_commands: false,
+            allowed_env: Vec::new(),
+            palette: None,
+            palettes: HashMap::new(),
+            powerline: false,
+            powerline_separator: "\u{e0b0}",
+            symbol_set: "unicode",
+            report_cwd: false,
+            window_title: "",
+            iterm2_integration: false,
+            force_color: false,
+            truecolor: None,
+            log_level: "warn",
+            quiet: false,
         }
     }
 }
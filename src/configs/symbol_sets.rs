@@ -0,0 +1,141 @@
+/// A list of `(module, option, symbol)` entries replacing the default symbols of modules
+pub type SymbolSet = &'static [(&'static str, &'static str, &'static str)];
+
+/// The default symbols of every module
+const UNICODE_SYMBOLS: SymbolSet = &[];
+
+/// Symbols for fonts patched with Nerd Font glyphs
+const NERD_FONT_SYMBOLS: SymbolSet = &[
+    ("aws", "symbol", "\u{e7ad} "),
+    ("directory", "read_only_symbol", " \u{f023}"),
+    ("docker_context", "symbol", "\u{f308} "),
+    ("dotnet", "symbol", "\u{e77f} "),
+    ("elixir", "symbol", "\u{e62d} "),
+    ("elm", "symbol", "\u{e62c} "),
+    ("erlang", "symbol", "\u{e7b1} "),
+    ("git_branch", "symbol", "\u{f418} "),
+    ("golang", "symbol", "\u{e627} "),
+    ("haskell", "symbol", "\u{e777} "),
+    ("hg_branch", "symbol", "\u{f418} "),
+    ("java", "symbol", "\u{e256} "),
+    ("julia", "symbol", "\u{e624} "),
+    ("nix_shell", "symbol", "\u{f313} "),
+    ("nodejs", "symbol", "\u{e718} "),
+    ("ocaml", "symbol", "\u{e67a} "),
+    ("package", "symbol", "\u{f487} "),
+    ("php", "symbol", "\u{e608} "),
+    ("python", "symbol", "\u{e235} "),
+    ("ruby", "symbol", "\u{e791} "),
+    ("rust", "symbol", "\u{e7a8} "),
+];
+
+/// Symbols that can be displayed by any terminal and font
+const ASCII_SYMBOLS: SymbolSet = &[
+    ("ansible", "symbol", "ansible "),
+    ("aws", "symbol", "aws "),
+    ("battery", "charging_symbol", "+"),
+    ("battery", "discharging_symbol", "-"),
+    ("battery", "full_symbol", "="),
+    ("bun", "symbol", "bun "),
+    ("character", "error_symbol", "x"),
+    ("character", "success_symbol", ">"),
+    ("character", "vicmd_symbol", "<"),
+    ("chroot", "symbol", "chroot "),
+    ("ci", "buildkite_symbol", "buildkite "),
+    ("ci", "github_actions_symbol", "github "),
+    ("ci", "gitlab_ci_symbol", "gitlab "),
+    ("ci", "symbol", "ci "),
+    ("commit_signing", "no_key_symbol", "no-key "),
+    ("commit_signing", "symbol", "signed "),
+    ("crystal", "symbol", "cr "),
+    ("dbt", "symbol", "dbt "),
+    ("directory", "read_only_symbol", " ro"),
+    ("docker_context", "symbol", "docker "),
+    ("dotnet", "symbol", ".NET "),
+    ("elixir", "symbol", "exs "),
+    ("elm", "symbol", "elm "),
+    ("erlang", "symbol", "erl "),
+    ("gcloud", "symbol", "gcp "),
+    ("git_branch", "symbol", "git "),
+    ("git_branch", "truncation_symbol", "..."),
+    ("git_status", "ahead", "^"),
+    ("git_status", "behind", "v"),
+    ("git_status", "deleted", "x"),
+    ("git_status", "diverged", "<>"),
+    ("git_status", "renamed", "r"),
+    ("golang", "symbol", "go "),
+    ("hashicorp", "consul_symbol", "consul "),
+    ("hashicorp", "nomad_symbol", "nomad "),
+    ("haskell", "symbol", "hs "),
+    ("hg_branch", "symbol", "hg "),
+    ("hg_branch", "truncation_symbol", "..."),
+    ("java", "symbol", "java "),
+    ("jobs", "symbol", "*"),
+    ("julia", "symbol", "jl "),
+    ("kubernetes", "symbol", "k8s "),
+    ("memory_usage", "symbol", "mem "),
+    ("nix_shell", "symbol", "nix "),
+    ("nodejs", "symbol", "node "),
+    ("ocaml", "symbol", "ml "),
+    ("package", "symbol", "pkg "),
+    ("php", "symbol", "php "),
+    ("proxy", "symbol", "proxy "),
+    ("python", "symbol", "py "),
+    ("ruby", "symbol", "rb "),
+    ("rust", "symbol", "rs "),
+    ("solidity", "symbol", "sol "),
+    ("terraform", "symbol", "tf "),
+    ("tex", "symbol", "tex "),
+    ("typst", "symbol", "typ "),
+    ("vpn", "symbol", "vpn "),
+    ("zig", "symbol", "zig "),
+];
+
+/// The names of every symbol set
+pub const SYMBOL_SETS: &[&str] = &["unicode", "nerd-font", "ascii"];
+
+/// Get a symbol set by the name used in the `symbol_set` option
+pub fn get_symbol_set(name: &str) -> Option<SymbolSet> {
+    match name {
+        "unicode" => Some(UNICODE_SYMBOLS),
+        "nerd-font" => Some(NERD_FONT_SYMBOLS),
+        "ascii" => Some(ASCII_SYMBOLS),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::ALL_MODULES;
+
+    #[test]
+    fn symbol_sets_only_contain_known_modules() {
+        for set in &[NERD_FONT_SYMBOLS, ASCII_SYMBOLS] {
+            for (module, _, _) in set.iter() {
+                if *module == "battery" && cfg!(not(feature = "battery")) {
+                    continue;
+                }
+                assert!(ALL_MODULES.contains(module), "unknown module {}", module);
+            }
+        }
+    }
+
+    #[test]
+    fn symbol_set_names_are_known() {
+        for name in SYMBOL_SETS {
+            assert!(
+                get_symbol_set(name).is_some(),
+                "unknown symbol set {}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn ascii_symbols_are_ascii() {
+        for (module, option, symbol) in ASCII_SYMBOLS {
+            assert!(symbol.is_ascii(), "{}.{} is not ascii", module, option);
+        }
+    }
+}
@@ -9,6 +9,7 @@ pub struct TerraformConfig<'a> {
     pub workspace: SegmentConfig<'a>,
     pub version: SegmentConfig<'a>,
     pub show_version: bool,
+    pub exec: bool,
     pub style: Style,
     pub disabled: bool,
 }
@@ -20,6 +21,7 @@ impl<'a> RootModuleConfig<'a> for TerraformConfig<'a> {
             workspace: SegmentConfig::default(),
             version: SegmentConfig::default(),
             show_version: false,
+            exec: false,
             style: Color::Fixed(105).bold(),
             disabled: false,
         }
@@ -6,6 +6,7 @@ use starship_module_config_derive::ModuleConfig;
 #[derive(Clone, ModuleConfig)]
 pub struct TimeConfig<'a> {
     pub use_12hr: bool,
+    pub use_locale: bool,
     pub format: Option<&'a str>,
     pub style: Style,
     pub disabled: bool,
@@ -16,6 +17,7 @@ impl<'a> RootModuleConfig<'a> for TimeConfig<'a> {
     fn new() -> Self {
         TimeConfig {
             use_12hr: false,
+            use_locale: false,
             format: None,
             style: Color::Yellow.bold(),
             disabled: true,
@@ -0,0 +1,29 @@
+use crate::config::{ModuleConfig, RootModuleConfig, SegmentConfig};
+
+use ansi_term::{Color, Style};
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig)]
+pub struct VpnConfig<'a> {
+    pub symbol: SegmentConfig<'a>,
+    pub interface: SegmentConfig<'a>,
+    pub interface_prefixes: Vec<&'a str>,
+    pub detect_command: Option<&'a str>,
+    pub show_interface: bool,
+    pub style: Style,
+    pub disabled: bool,
+}
+
+impl<'a> RootModuleConfig<'a> for VpnConfig<'a> {
+    fn new() -> Self {
+        VpnConfig {
+            symbol: SegmentConfig::new("🛡️ "),
+            interface: SegmentConfig::default(),
+            interface_prefixes: vec!["tun", "tap", "wg", "ppp"],
+            detect_command: None,
+            show_interface: true,
+            style: Color::Green.bold(),
+            disabled: false,
+        }
+    }
+}
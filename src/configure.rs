@@ -4,11 +4,19 @@ use std::io::ErrorKind;
 use std::process;
 use std::process::Command;
 
-use starship::config::StarshipConfig;
-use std::fs::File;
-use std::io::Write;
+use crate::config::{
+    migrate_options, schema_with_defaults, ModuleConfig, RootModuleConfig, StarshipConfig,
+};
+use crate::configs;
+use crate::configs::custom::CustomConfig;
+use crate::configs::symbol_sets::SYMBOL_SETS;
+use crate::configs::StarshipRootConfig;
+use crate::module::ALL_MODULES;
+use serde_json::json;
+use std::fs;
 use toml::map::Map;
 use toml::Value;
+use toml_edit::Document;
 
 const STD_EDITOR: &str = "vi";
 
@@ -16,46 +24,245 @@ pub fn update_configuration(name: &str, value: &str) {
     let config_path = get_config_path();
 
     let keys: Vec<&str> = name.split('.').collect();
-    if keys.len() != 2 {
-        log::error!("Please pass in a config key with a '.'");
+    if keys.iter().any(|key| key.is_empty()) {
+        eprintln!("Error: {:?} is not a valid config key", name);
         process::exit(1);
     }
 
-    let starship_config = StarshipConfig::initialize();
-    let mut config = starship_config
-        .config
-        .expect("Failed to load starship config");
+    // Edit the file as a document, so that comments and formatting are kept
+    let config_str = match fs::read_to_string(&config_path) {
+        Ok(config_str) => config_str,
+        Err(ref error) if error.kind() == ErrorKind::NotFound => String::new(),
+        Err(error) => panic!("Unable to read config file: {:?}", error),
+    };
+    let mut doc = config_str.parse::<Document>().unwrap_or_else(|error| {
+        eprintln!("Error: Unable to parse the config file: {}", error);
+        process::exit(1);
+    });
 
-    if let Some(table) = config.as_table_mut() {
-        if !table.contains_key(keys[0]) {
-            table.insert(keys[0].to_string(), Value::Table(Map::new()));
+    let (key, parents) = keys.split_last().unwrap();
+    let mut table = &mut doc.root;
+    for parent in parents {
+        // Create missing tables as `[section]`s instead of inline tables
+        if table[parent].is_none() {
+            let mut parent_table = toml_edit::Table::new();
+            parent_table.set_implicit(true);
+            table[parent] = toml_edit::Item::Table(parent_table);
         }
+        if !table[parent].is_table_like() {
+            eprintln!("Error: {} is not a table", parent);
+            process::exit(1);
+        }
+        table = &mut table[parent];
+    }
 
-        if let Some(values) = table.get(keys[0]).unwrap().as_table() {
-            let mut updated_values = values.clone();
+    table[key] = if let Ok(value) = value.parse::<bool>() {
+        toml_edit::value(value)
+    } else if let Ok(value) = value.parse::<i64>() {
+        toml_edit::value(value)
+    } else {
+        toml_edit::value(value)
+    };
 
-            if value.parse::<bool>().is_ok() {
-                updated_values.insert(
-                    keys[1].to_string(),
-                    Value::Boolean(value.parse::<bool>().unwrap()),
-                );
-            } else if value.parse::<i64>().is_ok() {
-                updated_values.insert(
-                    keys[1].to_string(),
-                    Value::Integer(value.parse::<i64>().unwrap()),
-                );
-            } else {
-                updated_values.insert(keys[1].to_string(), Value::String(value.to_string()));
+    fs::write(&config_path, doc.to_string()).expect("Error writing starship config");
+}
+
+/// Replaces the deprecated options in the configuration file with the options that
+/// replaced them, keeping the comments and formatting of the file
+pub fn migrate_configuration() {
+    let config_path = get_config_path();
+    let config_str = match fs::read_to_string(&config_path) {
+        Ok(config_str) => config_str,
+        Err(ref error) if error.kind() == ErrorKind::NotFound => String::new(),
+        Err(error) => panic!("Unable to read config file: {:?}", error),
+    };
+    let (mut config, mut doc) = match (config_str.parse::<Value>(), config_str.parse::<Document>())
+    {
+        (Ok(Value::Table(config)), Ok(doc)) => (config, doc),
+        _ => {
+            eprintln!("Error: Unable to parse the config file");
+            process::exit(1);
+        }
+    };
+
+    let migrations = migrate_options(&mut config);
+    if migrations.is_empty() {
+        println!("There are no deprecated options to migrate");
+        return;
+    }
+    for migration in &migrations {
+        let table = if migration.table.is_empty() {
+            &mut doc.root
+        } else {
+            &mut doc.root[migration.table]
+        };
+        table[migration.name] = toml_edit::Item::None;
+        if let Some(value) = &migration.value {
+            match value.to_string().parse::<toml_edit::Value>() {
+                Ok(value) => table[migration.replacement] = toml_edit::value(value),
+                Err(_) => eprintln!("Unable to write {}", migration.replacement),
             }
+        }
+        println!("{}", migration);
+    }
+
+    fs::write(&config_path, doc.to_string()).expect("Error writing starship config");
+}
+
+/// Prints every problem found in the configuration, and exits with an error if there are any
+pub fn check_configuration() {
+    let config_path = get_config_path();
+    if let Ok(config_str) = fs::read_to_string(&config_path) {
+        if let Err(error) = config_str.parse::<Value>() {
+            println!("Unable to parse the config file: {}", error);
+            process::exit(1);
+        }
+    }
+
+    let warnings = StarshipConfig::initialize().check();
+    for warning in &warnings {
+        println!("{}", warning);
+    }
+    if !warnings.is_empty() {
+        process::exit(1);
+    }
+}
 
-            table.insert(keys[0].to_string(), Value::Table(updated_values));
+/// Prints a JSON Schema of the configuration, which editors can use to complete and validate it
+pub fn print_schema() {
+    let mut schema = StarshipRootConfig::schema();
+    if let Some(defaults) = StarshipRootConfig::new().to_config() {
+        schema = schema_with_defaults(schema, &defaults);
+    }
+    schema["$schema"] = json!("http://json-schema.org/draft-07/schema#");
+    schema["title"] = json!("Starship configuration");
+
+    let properties = schema["properties"]
+        .as_object_mut()
+        .expect("The root config has no properties");
+    // Multi-line formats can be given as an array of lines
+    let default_format = properties["format"]["default"].take();
+    properties.insert(
+        String::from("format"),
+        json!({
+            "anyOf": [
+                { "type": "string" },
+                { "type": "array", "items": { "type": "string" } },
+            ],
+            "default": default_format,
+        }),
+    );
+    properties["symbol_set"]["enum"] = json!(SYMBOL_SETS);
+
+    for module in ALL_MODULES {
+        if let Some(module_schema) = configs::module_schema(module) {
+            properties.insert((*module).to_owned(), with_module_options(module_schema));
         }
+    }
+    let mut custom_schema = CustomConfig::schema();
+    if let Some(defaults) = CustomConfig::new().to_config() {
+        custom_schema = schema_with_defaults(custom_schema, &defaults);
+    }
+    properties.insert(
+        String::from("custom"),
+        json!({
+            "type": "object",
+            "additionalProperties": with_module_options(custom_schema),
+        }),
+    );
+    properties.insert(
+        String::from("plugin"),
+        json!({
+            "type": "object",
+            "additionalProperties": { "type": "object" },
+        }),
+    );
 
-        let config_str =
-            toml::to_string_pretty(&table).expect("Failed to serialize the config to string");
-        File::create(&config_path)
-            .and_then(|mut file| file.write_all(config_str.as_ref()))
-            .expect("Error writing starship config");
+    let schema_str =
+        serde_json::to_string_pretty(&schema).expect("Failed to serialize the schema to string");
+    println!("{}", schema_str);
+}
+
+/// Add the options that every module accepts to the schema of a module config
+fn with_module_options(mut schema: serde_json::Value) -> serde_json::Value {
+    schema["properties"]["bg"] = json!({ "type": "string" });
+    schema["properties"]["symbol_set"] = json!({ "enum": SYMBOL_SETS });
+    schema["properties"]["timeout"] = json!({ "type": "integer", "minimum": 0 });
+    schema["properties"]["cache_ttl"] = json!({ "type": "integer", "minimum": 0 });
+    schema["properties"]["max_width"] = json!({ "type": "integer", "minimum": 1 });
+    schema["properties"]["max_width_symbol"] = json!({ "type": "string" });
+    schema["properties"]["style_rules"] = json!({
+        "type": "object",
+        "additionalProperties": { "type": "string" },
+    });
+    // Custom modules have a `when` command of their own
+    if schema["properties"].get("when").is_none() {
+        let strings = json!({
+            "anyOf": [{ "type": "string" }, { "type": "array", "items": { "type": "string" } }],
+        });
+        schema["properties"]["when"] = json!({
+            "anyOf": [
+                { "type": "string" },
+                {
+                    "type": "object",
+                    "properties": {
+                        "command": { "type": "string" },
+                        "env": strings,
+                        "path": strings,
+                        "files": strings,
+                        "directories": strings,
+                        "expression": { "type": "string" },
+                    },
+                    "additionalProperties": false,
+                },
+            ],
+        });
+    }
+    schema
+}
+
+/// Flips a boolean key of a module, which is `disabled` unless another key is given
+pub fn toggle_configuration(name: &str, key: &str) {
+    let starship_config = StarshipConfig::initialize();
+    let config = match get_effective_config(&starship_config, name) {
+        Some(config) => config,
+        None => {
+            eprintln!(
+                "Error: Unknown module {}. Use starship module --list to list out all supported modules.",
+                name
+            );
+            process::exit(1);
+        }
+    };
+
+    match config.get(key).and_then(Value::as_bool) {
+        Some(value) => update_configuration(&format!("{}.{}", name, key), &(!value).to_string()),
+        None => {
+            eprintln!("Error: {}.{} is not a boolean option", name, key);
+            process::exit(1);
+        }
+    }
+}
+
+/// Prints the value of a config key, or its default value if it isn't set
+pub fn print_configuration_value(name: &str) {
+    let starship_config = StarshipConfig::initialize();
+    let config = Value::Table(get_full_config(&starship_config));
+
+    let value = name
+        .split('.')
+        .try_fold(&config, |value, key| value.get(key));
+    match value {
+        Some(Value::String(value)) => println!("{}", value),
+        Some(table @ Value::Table(_)) => print!(
+            "{}",
+            toml::to_string_pretty(table).expect("Failed to serialize the config to string")
+        ),
+        Some(value) => println!("{}", value),
+        None => {
+            eprintln!("Error: Unknown config key {}", name);
+            process::exit(1);
+        }
     }
 }
 
@@ -90,6 +297,78 @@ pub fn edit_configuration() {
     };
 }
 
+/// Prints the configuration in effect, with the default value of every option that
+/// isn't set in the configuration file. Prints only one module if a name is given.
+pub fn print_configuration(name: Option<&str>) {
+    let starship_config = StarshipConfig::initialize();
+
+    let table = match name {
+        Some(name) => match get_effective_config(&starship_config, name) {
+            Some(config) => {
+                let mut table = Map::new();
+                table.insert(name.to_owned(), config);
+                table
+            }
+            None => {
+                eprintln!(
+                    "Error: Unknown module {}. Use starship module --list to list out all supported modules.",
+                    name
+                );
+                process::exit(1);
+            }
+        },
+        None => get_full_config(&starship_config),
+    };
+
+    let config_str = toml::to_string_pretty(&Value::Table(table))
+        .expect("Failed to serialize the config to string");
+    print!("{}", config_str);
+}
+
+/// Returns the configuration of the prompt and every module merged over their defaults
+fn get_full_config(starship_config: &StarshipConfig) -> Map<String, Value> {
+    let mut table = Map::new();
+    if let Some(Value::Table(root)) = starship_config.get_root_config().to_config() {
+        table.extend(root);
+    }
+    // Multi-line formats can be given as an array, which isn't part of the root config
+    if let Some(format @ Value::Array(_)) = starship_config.get_module_config("format") {
+        table.insert(String::from("format"), format.clone());
+    }
+    for module in ALL_MODULES {
+        if let Some(config) = get_effective_config(starship_config, module) {
+            table.insert((*module).to_owned(), config);
+        }
+    }
+    if let Some(custom_modules) = starship_config.get_custom_modules() {
+        let custom = custom_modules
+            .keys()
+            .filter_map(|module| {
+                let config = get_effective_config(starship_config, &format!("custom.{}", module))?;
+                Some((module.to_owned(), config))
+            })
+            .collect::<Map<String, Value>>();
+        table.insert(String::from("custom"), Value::Table(custom));
+    }
+    table
+}
+
+/// Returns the configuration of a module merged over its defaults, or `None` if the
+/// module doesn't exist or has no configuration
+fn get_effective_config(starship_config: &StarshipConfig, name: &str) -> Option<Value> {
+    if let Some(custom_module) = name.strip_prefix("custom.") {
+        let config = starship_config.get_custom_module_config(custom_module)?;
+        return CustomConfig::load(config).to_config();
+    }
+
+    fn effective<'a, T: RootModuleConfig<'a>>(config: Option<&'a Value>) -> Option<Value> {
+        T::try_load(config).to_config()
+    }
+
+    let config = starship_config.get_module_config(name);
+    configs::with_module_config!(name, effective, config).flatten()
+}
+
 fn get_editor() -> OsString {
     get_editor_internal(env::var_os("VISUAL"), env::var_os("EDITOR"))
 }
@@ -172,4 +451,18 @@ mod tests {
         let actual = get_editor_internal(None, None);
         assert_eq!("vi", actual);
     }
+
+    #[test]
+    fn effective_config_of_every_module() {
+        let config = StarshipConfig::new(None);
+        for module in ALL_MODULES {
+            assert_eq!(
+                configs::module_schema(module).is_some(),
+                get_effective_config(&config, module).is_some(),
+                "{}",
+                module
+            );
+        }
+        assert!(get_effective_config(&config, "commit_signing").is_some());
+    }
 }
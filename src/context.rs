@@ -1,15 +1,23 @@
+use crate::cache;
 use crate::config::StarshipConfig;
+use crate::logger;
 use crate::module::Module;
 
 use crate::modules;
+use crate::profile;
+use crate::utils::{self, CommandOutput, ExecError, ExecPolicy};
 use clap::ArgMatches;
 use git2::{ErrorCode::UnbornBranch, Repository, RepositoryState};
 use once_cell::sync::OnceCell;
+use process_control::Output;
+use regex::Regex;
 use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::process::Child;
 use std::string::String;
+use std::sync::Mutex;
 use std::time::{Duration, SystemTime};
 
 /// Context contains data or common methods that may be used by multiple modules.
@@ -22,6 +30,10 @@ pub struct Context<'a> {
     /// The current working directory that starship is being called in.
     pub current_dir: PathBuf,
 
+    /// The current directory as the shell reports it, from `--logical-path`, which keeps
+    /// the symlinks it was entered through, unlike a canonicalized `current_dir`
+    pub logical_dir: PathBuf,
+
     /// A struct containing directory contents in a lookup-optimised format.
     dir_contents: OnceCell<DirContents>,
 
@@ -31,8 +43,43 @@ pub struct Context<'a> {
     /// Private field to store Git information for modules who need it
     repo: OnceCell<Repo>,
 
+    /// Private field to store the root of the Mercurial repository, if there is one
+    hg_root: OnceCell<Option<PathBuf>>,
+
     /// The shell the user is assumed to be running
     pub shell: Shell,
+
+    /// Whether the modules in `async_modules` are replaced with placeholders, to be
+    /// rendered by a later invocation in the background
+    pub skip_async: bool,
+
+    /// The modules that had a command killed for running longer than its timeout
+    timed_out_modules: Mutex<HashSet<String>>,
+
+    /// The environment variables modules see instead of those of the process, if set
+    pub(crate) env: Option<HashMap<String, String>>,
+
+    /// Outputs returned by `exec_cmd` instead of running the commands, by command line
+    pub(crate) cmd_outputs: HashMap<String, Option<CommandOutput>>,
+
+    /// The compiled patterns of the `style_rules` of the modules, by pattern
+    pub(crate) style_rules: OnceCell<HashMap<String, Regex>>,
+
+    /// The options of the root config read for every command and scan, see `RootSettings`
+    root_settings: OnceCell<RootSettings>,
+}
+
+/// The options of the root config that are read each time a module runs a command or
+/// scans the directory. The typed configs borrow from the TOML the context owns, so
+/// they can't be kept in it, and these are copied out of the root config once instead
+/// of deserializing all of it, palettes included, for every command.
+#[derive(Debug)]
+struct RootSettings {
+    scan_timeout: u64,
+    command_timeout: u64,
+    command_cache_ttl: u64,
+    trusted_projects: Vec<String>,
+    strict_commands: bool,
 }
 
 impl<'a> Context<'a> {
@@ -41,11 +88,11 @@ impl<'a> Context<'a> {
     pub fn new(arguments: ArgMatches) -> Context {
         // Retrieve the "path" flag. If unavailable, use the current directory instead.
         let path = arguments
-            .value_of("path")
+            .value_of_os("path")
             .map(From::from)
             .unwrap_or_else(|| {
-                env::var("PWD").map(PathBuf::from).unwrap_or_else(|err| {
-                    log::debug!("Unable to get path from $PWD: {}", err);
+                env::var_os("PWD").map(PathBuf::from).unwrap_or_else(|| {
+                    log::debug!("Unable to get path from $PWD");
                     env::current_dir().expect("Unable to identify current directory. Error")
                 })
             });
@@ -58,7 +105,32 @@ impl<'a> Context<'a> {
     where
         T: Into<PathBuf>,
     {
-        let config = StarshipConfig::initialize();
+        Context::new_with(arguments, dir.into(), None)
+    }
+
+    /// Create a new instance of Context with its own environment instead of the one of
+    /// the process, like a session has for each prompt it renders. The directory is the
+    /// one of the "path" flag, or else `$PWD` of the environment or `cwd`.
+    pub fn new_with_env(
+        arguments: ArgMatches,
+        env: HashMap<String, String>,
+        cwd: PathBuf,
+    ) -> Context {
+        let path = arguments
+            .value_of_os("path")
+            .map(PathBuf::from)
+            .or_else(|| env.get("PWD").map(PathBuf::from))
+            .unwrap_or(cwd);
+        Context::new_with(arguments, path, Some(env))
+    }
+
+    fn new_with(
+        arguments: ArgMatches,
+        dir: PathBuf,
+        env: Option<HashMap<String, String>>,
+    ) -> Context {
+        let profile = arguments.value_of("profile_name");
+        let config = StarshipConfig::initialize_with_env(None, profile, env.as_ref());
 
         // Unwrap the clap arguments into a simple hashtable
         // we only care about single arguments at this point, there isn't a
@@ -67,26 +139,125 @@ impl<'a> Context<'a> {
             .args
             .iter()
             .filter(|(_, v)| !v.vals.is_empty())
-            .map(|(a, b)| (*a, b.vals.first().cloned().unwrap().into_string().unwrap()))
+            .map(|(a, b)| (*a, b.vals[0].to_string_lossy().into_owned()))
             .collect();
 
-        // TODO: Currently gets the physical directory. Get the logical directory.
-        let current_dir = Context::expand_tilde(dir.into());
+        let current_dir = Context::expand_tilde(dir);
+        let logical_dir = arguments
+            .value_of_os("logical_path")
+            .map(|path| Context::expand_tilde(PathBuf::from(path)))
+            .unwrap_or_else(|| current_dir.clone());
 
-        let shell = Context::get_shell();
+        let shell = Context::get_shell(&properties, env.as_ref());
+        let skip_async = arguments.is_present("skip_async");
 
-        Context {
+        let mut context = Context {
             config,
             properties,
             current_dir,
+            logical_dir,
             dir_contents: OnceCell::new(),
             repo: OnceCell::new(),
+            hg_root: OnceCell::new(),
             shell,
+            skip_async,
+            timed_out_modules: Mutex::new(HashSet::new()),
+            env,
+            cmd_outputs: HashMap::new(),
+            style_rules: OnceCell::new(),
+            root_settings: OnceCell::new(),
+        };
+        if let Some(project_config) = context.get_project_config() {
+            context.config = StarshipConfig::initialize_with_env(
+                Some(&project_config),
+                profile,
+                context.env.as_ref(),
+            );
+        }
+        let root_config = context.config.get_root_config();
+        logger::configure(root_config.log_level, root_config.quiet);
+        context
+    }
+
+    /// The `.starship.toml` at the root of the repository, if there is one and the
+    /// repository is in one of the `trusted_projects`, or `$STARSHIP_TRUST_PROJECTS` is
+    /// set. Project configs can run commands, like any config, so they are only used
+    /// once they are trusted.
+    fn get_project_config(&self) -> Option<PathBuf> {
+        // Read from the config itself, which the project config is merged into after
+        let trusted_projects = self.config.get_root_config().trusted_projects;
+        if !self.is_trust_all() && trusted_projects.is_empty() {
+            return None;
+        }
+
+        let root = self.get_repo().ok()?.root.as_ref()?;
+        let project_config = root.join(".starship.toml");
+        if self.is_trusted_in(&trusted_projects, root) && project_config.is_file() {
+            Some(project_config)
+        } else {
+            None
+        }
+    }
+
+    /// Whether a repository is in one of the `trusted_projects`, or every one is trusted
+    pub fn is_trusted_project(&self, root: &Path) -> bool {
+        self.is_trusted_in(&self.root_settings().trusted_projects, root)
+    }
+
+    fn is_trusted_in<S: AsRef<str>>(&self, trusted_projects: &[S], root: &Path) -> bool {
+        self.is_trust_all()
+            || trusted_projects
+                .iter()
+                .any(|dir| root.starts_with(Context::expand_tilde(PathBuf::from(dir.as_ref()))))
+    }
+
+    fn root_settings(&self) -> &RootSettings {
+        self.root_settings.get_or_init(|| {
+            let config = self.config.get_root_config();
+            RootSettings {
+                scan_timeout: config.scan_timeout,
+                command_timeout: config.command_timeout,
+                command_cache_ttl: config.command_cache_ttl,
+                trusted_projects: config
+                    .trusted_projects
+                    .iter()
+                    .map(|dir| (*dir).to_owned())
+                    .collect(),
+                strict_commands: config.strict_commands,
+            }
+        })
+    }
+
+    /// How the executables of the commands run by modules are found. Those inside the
+    /// current repository are refused unless it's trusted, and with `strict_commands`
+    /// so are those inside the current directory outside of repositories.
+    pub fn exec_policy(&self) -> ExecPolicy {
+        let strict = self.root_settings().strict_commands;
+        // A home directory that is a work tree, of a dotfiles repository, isn't a checkout
+        // of someone else's code, and contains the tools installed for the user
+        let home = dirs::home_dir();
+        let root = self
+            .get_repo()
+            .ok()
+            .and_then(|repo| repo.root.as_ref())
+            .filter(|root| Some(*root) != home.as_ref());
+        let untrusted_dir = match root {
+            Some(root) if self.is_trusted_project(root) => None,
+            Some(root) => Some(root.clone()),
+            None if strict => Some(self.current_dir.clone()),
+            None => None,
+        };
+        ExecPolicy {
+            untrusted_dir,
+            strict,
+            path: self.get_env("PATH"),
+            dir: Some(self.current_dir.clone()),
+            env: self.env.clone(),
         }
     }
 
     /// Convert a `~` in a path to the home directory
-    fn expand_tilde(dir: PathBuf) -> PathBuf {
+    pub fn expand_tilde(dir: PathBuf) -> PathBuf {
         if dir.starts_with("~") {
             let without_home = dir.strip_prefix("~").unwrap();
             return dirs::home_dir().unwrap().join(without_home);
@@ -121,6 +292,189 @@ impl<'a> Context<'a> {
         Some(disabled == Some(true))
     }
 
+    /// Get an environment variable, which modules should use instead of `std::env::var`
+    /// so that tests can set the environment they are rendered in. A value that isn't
+    /// valid UTF-8 has its invalid bytes replaced.
+    pub fn get_env<K: AsRef<str>>(&self, key: K) -> Option<String> {
+        utils::get_env(self.env.as_ref(), key.as_ref())
+    }
+
+    /// All environment variables, in no particular order, as `get_env` reads them
+    pub(crate) fn get_env_vars(&self) -> Vec<(String, String)> {
+        match &self.env {
+            Some(env) => env
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            None => env::vars_os()
+                .map(|(key, value)| {
+                    (
+                        key.to_string_lossy().into_owned(),
+                        value.to_string_lossy().into_owned(),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// Whether every project is trusted, because `$STARSHIP_TRUST_PROJECTS` is set to `1`
+    fn is_trust_all(&self) -> bool {
+        self.get_env("STARSHIP_TRUST_PROJECTS").as_deref() == Some("1")
+    }
+
+    /// The width of the terminal, as passed by the shell with `--terminal-width`.
+    /// Falls back to asking the terminal directly, or to 80 columns.
+    pub fn get_terminal_width(&self) -> usize {
+        self.properties
+            .get("terminal_width")
+            .and_then(|width| width.parse::<usize>().ok())
+            .or_else(|| term_size::dimensions().map(|(width, _)| width))
+            .unwrap_or(80)
+    }
+
+    /// Execute a command for a module, like `utils::exec_cmd`. The command is killed if
+    /// it runs longer than the `timeout` of the module, or else the global `command_timeout`.
+    /// If the module has a `cache_ttl`, or else the global `command_cache_ttl` is set, the
+    /// output is taken from the cache while it's younger than that.
+    pub fn exec_cmd(&self, module: &str, cmd: &str, args: &[&str]) -> Option<CommandOutput> {
+        self.exec_cmd_with_ttl(module, cmd, args, self.get_cache_ttl(module))
+    }
+
+    /// Like `exec_cmd`, for a module whose commands are cached for `default_ttl` unless
+    /// its `cache_ttl` option is set, as they're too slow to run on every prompt
+    pub fn exec_cmd_with_default_ttl(
+        &self,
+        module: &str,
+        cmd: &str,
+        args: &[&str],
+        default_ttl: Duration,
+    ) -> Option<CommandOutput> {
+        let ttl = self
+            .get_module_duration_option(module, "cache_ttl")
+            .map_or(default_ttl, Duration::from_secs);
+        self.exec_cmd_with_ttl(module, cmd, args, ttl)
+    }
+
+    fn exec_cmd_with_ttl(
+        &self,
+        module: &str,
+        cmd: &str,
+        args: &[&str],
+        ttl: Duration,
+    ) -> Option<CommandOutput> {
+        let _span = profile::span("command", format!("{} {}", cmd, args.join(" ")));
+        if let Some(output) = self.cmd_outputs.get(&command_line(cmd, args)) {
+            return output.clone();
+        }
+        let cache = cache::cache_dir()
+            .filter(|_| ttl > Duration::from_secs(0))
+            .map(|dir| (dir, self.cache_key(cmd, args)));
+        if let Some((dir, key)) = &cache {
+            if let Some(output) = cache::get(dir, key, ttl) {
+                log::trace!("Using the cached output of {:?} for {}", key, module);
+                return Some(output);
+            }
+        }
+
+        let timeout = self.get_command_timeout(module);
+        match utils::exec_cmd_with_policy(cmd, args, Some(timeout), &self.exec_policy()) {
+            Ok(output) => {
+                if let Some((dir, key)) = &cache {
+                    cache::set(dir, key, &output);
+                }
+                Some(output)
+            }
+            Err(ExecError::TimedOut) => {
+                self.set_timed_out(module, cmd, timeout);
+                None
+            }
+            Err(ExecError::Failed) => None,
+        }
+    }
+
+    /// Wait for a command started by a module to exit and collect its output. Like with
+    /// `exec_cmd`, the command is killed if it runs longer than the timeout of the module.
+    pub fn wait_with_timeout(&self, module: &str, cmd: &str, child: Child) -> Option<Output> {
+        let _span = profile::span("command", cmd);
+        let timeout = self.get_command_timeout(module);
+        match utils::wait_with_timeout(child, Some(timeout)) {
+            Ok(output) => Some(output),
+            Err(ExecError::TimedOut) => {
+                self.set_timed_out(module, cmd, timeout);
+                None
+            }
+            Err(ExecError::Failed) => None,
+        }
+    }
+
+    /// The time the commands of a module may run, from its `timeout` option or else
+    /// the global `command_timeout`, in milliseconds
+    pub fn get_command_timeout(&self, module: &str) -> Duration {
+        self.get_module_timeout(module)
+            .unwrap_or_else(|| Duration::from_millis(self.root_settings().command_timeout))
+    }
+
+    /// The `timeout` option of a module, if it's set
+    pub fn get_module_timeout(&self, module: &str) -> Option<Duration> {
+        self.get_module_duration_option(module, "timeout")
+            .map(Duration::from_millis)
+    }
+
+    /// How long the outputs of the commands of a module are cached for, from its
+    /// `cache_ttl` option or else the global `command_cache_ttl`, in seconds
+    pub fn get_cache_ttl(&self, module: &str) -> Duration {
+        let ttl = self
+            .get_module_duration_option(module, "cache_ttl")
+            .unwrap_or_else(|| self.root_settings().command_cache_ttl);
+        Duration::from_secs(ttl)
+    }
+
+    fn get_module_duration_option(&self, module: &str, option: &str) -> Option<u64> {
+        let config = if let Some(custom_module) = module.strip_prefix("custom.") {
+            self.config.get_custom_module_config(custom_module)
+        } else if let Some(plugin) = module.strip_prefix("plugin.") {
+            self.config.get_plugin_config(plugin)
+        } else {
+            self.config.get_module_config(module)
+        };
+        config
+            .and_then(|config| config.get(option)?.as_integer())
+            .filter(|duration| *duration >= 0)
+            .map(|duration| duration as u64)
+    }
+
+    /// Identifies the output of a command in the cache. Besides the command itself, the
+    /// output depends on the directory and `$PATH`, e.g. with version managers.
+    fn cache_key(&self, cmd: &str, args: &[&str]) -> String {
+        format!(
+            "{} {}\n{}\n{}",
+            cmd,
+            args.join(" "),
+            self.current_dir.to_string_lossy(),
+            self.get_env("PATH").unwrap_or_default()
+        )
+    }
+
+    /// Record that a command of a module was killed for running longer than `timeout`,
+    /// warning about it once per module
+    pub fn set_timed_out(&self, module: &str, cmd: &str, timeout: Duration) {
+        let mut timed_out_modules = self.timed_out_modules.lock().unwrap();
+        if timed_out_modules.insert(module.to_owned()) {
+            log::warn!(
+                "Executing command {:?} for the {} module timed out after {}ms. \
+                 Use the `timeout` option of the module or `command_timeout` to allow more time.",
+                cmd,
+                module,
+                timeout.as_millis()
+            );
+        }
+    }
+
+    /// Whether a command of a module was killed for running longer than its timeout
+    pub fn has_timed_out(&self, module: &str) -> bool {
+        self.timed_out_modules.lock().unwrap().contains(module)
+    }
+
     // returns a new ScanDir struct with reference to current dir_files of context
     // see ScanDir for methods
     pub fn try_begin_scan(&'a self) -> Option<ScanDir<'a>> {
@@ -136,6 +490,7 @@ impl<'a> Context<'a> {
     pub fn get_repo(&self) -> Result<&Repo, std::io::Error> {
         self.repo
             .get_or_try_init(|| -> Result<Repo, std::io::Error> {
+                let _span = profile::span("context", "discover repository");
                 let repository = Repository::discover(&self.current_dir).ok();
                 let branch = repository
                     .as_ref()
@@ -144,29 +499,64 @@ impl<'a> Context<'a> {
                     .as_ref()
                     .and_then(|repo| repo.workdir().map(Path::to_path_buf));
                 let state = repository.as_ref().map(|repo| repo.state());
+                let head = repository
+                    .as_ref()
+                    .and_then(|repo| repo.head().ok()?.target())
+                    .map(|oid| oid.to_string());
 
                 Ok(Repo {
                     branch,
                     root,
                     state,
+                    head,
                 })
             })
     }
 
+    /// The root of the Mercurial repository the current directory is in, the directory
+    /// with its `.hg`, found once when a module first asks for it
+    pub fn get_hg_root(&self) -> Option<&Path> {
+        self.hg_root
+            .get_or_init(|| {
+                let _span = profile::span("context", "discover hg repository");
+                self.current_dir
+                    .ancestors()
+                    .find(|dir| dir.join(".hg").is_dir())
+                    .map(Path::to_path_buf)
+            })
+            .as_deref()
+    }
+
     pub fn dir_contents(&self) -> Result<&DirContents, std::io::Error> {
         self.dir_contents.get_or_try_init(|| {
-            let timeout = Duration::from_millis(self.config.get_root_config().scan_timeout);
+            let _span = profile::span("context", "scan directory");
+            let timeout = Duration::from_millis(self.root_settings().scan_timeout);
             DirContents::from_path_with_timeout(&self.current_dir, timeout)
         })
     }
 
-    fn get_shell() -> Shell {
-        let shell = std::env::var("STARSHIP_SHELL").unwrap_or_default();
+    /// The shell passed with `--shell`, or else the one set in `STARSHIP_SHELL` by the
+    /// init scripts
+    fn get_shell(
+        properties: &HashMap<&str, String>,
+        env: Option<&HashMap<String, String>>,
+    ) -> Shell {
+        let shell = properties
+            .get("shell")
+            .cloned()
+            .or_else(|| utils::get_env(env, "STARSHIP_SHELL"))
+            .unwrap_or_default();
         match shell.as_str() {
             "bash" => Shell::Bash,
+            "cmd" => Shell::Cmd,
+            "elvish" => Shell::Elvish,
             "fish" => Shell::Fish,
             "ion" => Shell::Ion,
+            "nu" => Shell::Nu,
             "powershell" => Shell::PowerShell,
+            "sh" => Shell::Sh,
+            "tcsh" => Shell::Tcsh,
+            "xonsh" => Shell::Xonsh,
             "zsh" => Shell::Zsh,
             _ => Shell::Unknown,
         }
@@ -191,7 +581,10 @@ impl DirContents {
         Self::from_path_with_timeout(base, Duration::from_secs(30))
     }
 
-    pub fn from_path_with_timeout(base: &PathBuf, timeout: Duration) -> Result<Self, std::io::Error> {
+    pub fn from_path_with_timeout(
+        base: &PathBuf,
+        timeout: Duration,
+    ) -> Result<Self, std::io::Error> {
         let start = SystemTime::now();
 
         let mut folders: HashSet<PathBuf> = HashSet::new();
@@ -204,7 +597,13 @@ impl DirContents {
             .filter_map(Result::ok)
             .for_each(|entry| {
                 let path = PathBuf::from(entry.path().strip_prefix(base).unwrap());
-                if entry.path().is_dir() {
+                // The type of an entry usually comes with the listing, so only symlinks
+                // need another syscall to find out what they point to
+                let is_dir = match entry.file_type() {
+                    Ok(file_type) if !file_type.is_symlink() => file_type.is_dir(),
+                    _ => entry.path().is_dir(),
+                };
+                if is_dir {
                     folders.insert(path);
                 } else {
                     if !path.to_string_lossy().starts_with('.') {
@@ -243,25 +642,13 @@ impl DirContents {
         self.file_names.contains(name)
     }
 
-    pub fn has_any_file_name(&self, names: &[&str]) -> bool {
-        names.iter().any(|name| self.has_file_name(name))
-    }
-
     pub fn has_folder(&self, path: &str) -> bool {
         self.folders.contains(Path::new(path))
     }
 
-    pub fn has_any_folder(&self, paths: &[&str]) -> bool {
-        paths.iter().any(|path| self.has_folder(path))
-    }
-
     pub fn has_extension(&self, ext: &str) -> bool {
         self.extensions.contains(ext)
     }
-
-    pub fn has_any_extension(&self, exts: &[&str]) -> bool {
-        exts.iter().any(|ext| self.has_extension(ext))
-    }
 }
 
 pub struct Repo {
@@ -275,6 +662,9 @@ pub struct Repo {
 
     /// State
     pub state: Option<RepositoryState>,
+
+    /// The id of the commit HEAD points to, unless the branch has no commits yet
+    pub head: Option<String>,
 }
 
 // A struct of Criteria which will be used to verify current PathBuf is
@@ -305,9 +695,38 @@ impl<'a> ScanDir<'a> {
     /// based on the current Pathbuf check to see
     /// if any of this criteria match or exist and returning a boolean
     pub fn is_match(&self) -> bool {
-        self.dir_contents.has_any_extension(self.extensions)
-            || self.dir_contents.has_any_folder(self.folders)
-            || self.dir_contents.has_any_file_name(self.files)
+        self.find_match().is_some()
+    }
+
+    /// Like `is_match`, but describes the first file, folder or extension that
+    /// matched, to explain why a module is shown
+    pub fn find_match(&self) -> Option<String> {
+        if let Some(file) = self
+            .files
+            .iter()
+            .find(|file| self.dir_contents.has_file_name(file))
+        {
+            return Some(format!("found `{}`", file));
+        }
+        if let Some(folder) = self
+            .folders
+            .iter()
+            .find(|folder| self.dir_contents.has_folder(folder))
+        {
+            return Some(format!("found the `{}` folder", folder));
+        }
+        self.extensions
+            .iter()
+            .find(|ext| self.dir_contents.has_extension(ext))
+            .map(|ext| format!("found a `.{}` file", ext))
+    }
+}
+
+/// A command and its arguments as they would be typed, e.g. `node --version`
+pub(crate) fn command_line(cmd: &str, args: &[&str]) -> String {
+    match args.len() {
+        0 => String::from(cmd),
+        _ => format!("{} {}", cmd, args.join(" ")),
     }
 }
 
@@ -325,21 +744,46 @@ fn get_current_branch(repository: &Repository) -> Option<String> {
         }
     };
 
-    let shorthand = head.shorthand();
-
-    shorthand.map(std::string::ToString::to_string)
+    // Branch names don't have to be valid UTF-8
+    Some(String::from_utf8_lossy(head.shorthand_bytes()).into_owned())
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Shell {
     Bash,
+    Cmd,
+    Elvish,
     Fish,
     Ion,
+    Nu,
     PowerShell,
+    Sh,
+    Tcsh,
+    Xonsh,
     Zsh,
     Unknown,
 }
 
+impl Shell {
+    /// The name the shell is passed to starship by, e.g. `powershell`
+    pub fn name(self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Cmd => "cmd",
+            Shell::Elvish => "elvish",
+            Shell::Fish => "fish",
+            Shell::Ion => "ion",
+            Shell::Nu => "nu",
+            Shell::PowerShell => "powershell",
+            Shell::Sh => "sh",
+            Shell::Tcsh => "tcsh",
+            Shell::Xonsh => "xonsh",
+            Shell::Zsh => "zsh",
+            Shell::Unknown => "unknown",
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -356,6 +800,36 @@ mod tests {
         Ok(dir)
     }
 
+    #[test]
+    fn cache_key_of_path() {
+        let mut context = Context::new_with_dir(ArgMatches::default(), env::temp_dir());
+        let mut with_path = |path: &str| {
+            let env = [(String::from("PATH"), path.to_owned())];
+            context.env = Some(env.iter().cloned().collect());
+            context.cache_key("node", &["--version"])
+        };
+        assert_ne!(with_path("/usr/bin"), with_path("/home/astronaut/.nvm/bin"));
+        assert!(with_path("/usr/bin").ends_with("\n/usr/bin"));
+    }
+
+    #[test]
+    fn new_with_env_of_session() {
+        let env: HashMap<String, String> = [
+            ("PWD", "/home/astronaut/src"),
+            ("STARSHIP_SHELL", "fish"),
+            ("STARSHIP_CONFIG", "/nonexistent/starship.toml"),
+        ]
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+        let context = Context::new_with_env(ArgMatches::default(), env, PathBuf::from("/"));
+
+        assert_eq!(context.current_dir, PathBuf::from("/home/astronaut/src"));
+        assert_eq!(context.shell, Shell::Fish);
+        assert_eq!(context.get_env("PATH"), None);
+        assert_eq!(context.exec_policy().path, None);
+    }
+
     #[test]
     fn test_scan_dir() -> Result<(), Box<dyn std::error::Error>> {
         let empty = testdir(&[])?;
@@ -417,4 +891,43 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_scan_dir_find_match() -> Result<(), Box<dyn std::error::Error>> {
+        let node = testdir(&["node_modules/lodash/main.js", "index.js"])?;
+        let node_dc = DirContents::from_path(&PathBuf::from(node.path()))?;
+        let scan_dir = ScanDir {
+            dir_contents: &node_dc,
+            files: &["package.json"],
+            extensions: &["js"],
+            folders: &["node_modules"],
+        };
+        assert_eq!(
+            scan_dir.find_match(),
+            Some(String::from("found the `node_modules` folder"))
+        );
+        assert_eq!(
+            scan_dir.set_folders(&[]).find_match(),
+            Some(String::from("found a `.js` file"))
+        );
+        node.close()?;
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_scan_dir_follows_symlinks() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = testdir(&["target/main.rs"])?;
+        std::os::unix::fs::symlink(dir.path().join("target"), dir.path().join("src"))?;
+        std::os::unix::fs::symlink(dir.path().join("target/main.rs"), dir.path().join("lib.rs"))?;
+        let dc = DirContents::from_path(&PathBuf::from(dir.path()))?;
+
+        assert!(dc.has_folder("src"));
+        assert!(dc.has_file("lib.rs"));
+        assert!(dc.has_extension("rs"));
+        dir.close()?;
+
+        Ok(())
+    }
 }
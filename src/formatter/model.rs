@@ -9,6 +9,7 @@ pub enum FormatElement<'a> {
     Text(Cow<'a, str>),
     Variable(Cow<'a, str>),
     TextGroup(TextGroup<'a>),
+    Conditional(Vec<FormatElement<'a>>),
 }
 
 pub enum StyleElement<'a> {
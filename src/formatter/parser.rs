@@ -17,6 +17,10 @@ fn _parse_textgroup(textgroup: Pair<Rule>) -> TextGroup {
     }
 }
 
+fn _parse_conditional(conditional: Pair<Rule>) -> Vec<FormatElement> {
+    _parse_format(conditional.into_inner().next().unwrap())
+}
+
 fn _parse_variable(variable: Pair<Rule>) -> &str {
     variable.into_inner().next().unwrap().as_str()
 }
@@ -24,7 +28,10 @@ fn _parse_variable(variable: Pair<Rule>) -> &str {
 fn _parse_text(text: Pair<Rule>) -> String {
     let mut result = String::new();
     for pair in text.into_inner() {
-        result.push_str(pair.as_str());
+        match pair.as_rule() {
+            Rule::escaped_newline => result.push('\n'),
+            _ => result.push_str(pair.as_str()),
+        }
     }
     result
 }
@@ -37,6 +44,7 @@ fn _parse_format(format: Pair<Rule>) -> Vec<FormatElement> {
             Rule::text => result.push(FormatElement::Text(_parse_text(pair).into())),
             Rule::variable => result.push(FormatElement::Variable(_parse_variable(pair).into())),
             Rule::textgroup => result.push(FormatElement::TextGroup(_parse_textgroup(pair))),
+            Rule::conditional => result.push(FormatElement::Conditional(_parse_conditional(pair))),
             _ => unreachable!(),
         }
     }
@@ -68,6 +76,7 @@ pub fn parse(format: &str) -> Result<Vec<FormatElement>, Error<Rule>> {
             Rule::text => result.push(FormatElement::Text(_parse_text(pair).into())),
             Rule::variable => result.push(FormatElement::Variable(_parse_variable(pair).into())),
             Rule::textgroup => result.push(FormatElement::TextGroup(_parse_textgroup(pair))),
+            Rule::conditional => result.push(FormatElement::Conditional(_parse_conditional(pair))),
             _ => unreachable!(),
         }
     }
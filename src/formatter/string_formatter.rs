@@ -39,6 +39,30 @@ impl<'a> StringFormatter<'a> {
             .map(|(format, variables)| Self { format, variables })
     }
 
+    /// Returns the names of all variables in the format string, in order of appearance
+    pub fn get_variables(&self) -> Vec<String> {
+        fn _push_variables(variables: &mut Vec<String>, format: &[FormatElement]) {
+            for el in format {
+                match el {
+                    FormatElement::Variable(name) => {
+                        if !variables.iter().any(|variable| variable == name) {
+                            variables.push(name.to_string());
+                        }
+                    }
+                    FormatElement::TextGroup(textgroup) => {
+                        _push_variables(variables, &textgroup.format)
+                    }
+                    FormatElement::Conditional(format) => _push_variables(variables, format),
+                    FormatElement::Text(_) => {}
+                }
+            }
+        }
+
+        let mut variables = Vec::new();
+        _push_variables(&mut variables, &self.format);
+        variables
+    }
+
     /// Maps variable name to its value
     pub fn map(mut self, mapper: impl Fn(&str) -> Option<String> + Sync) -> Self {
         self.variables.par_iter_mut().for_each(|(key, value)| {
@@ -85,6 +109,27 @@ impl<'a> StringFormatter<'a> {
             parse_style_string(&style_string)
         }
 
+        /// Whether any variable in the format has a non-empty value
+        fn _should_show_elements<'a>(
+            format: &[FormatElement<'a>],
+            variables: &'a VariableMapType,
+        ) -> bool {
+            format.iter().any(|el| match el {
+                FormatElement::Variable(name) => match variables.get(name.as_ref()) {
+                    Some(Some(VariableValue::Plain(text))) => !text.is_empty(),
+                    Some(Some(VariableValue::Styled(segments))) => {
+                        segments.iter().any(|segment| !segment.value.is_empty())
+                    }
+                    _ => false,
+                },
+                FormatElement::TextGroup(textgroup) => {
+                    _should_show_elements(&textgroup.format, variables)
+                }
+                FormatElement::Conditional(format) => _should_show_elements(format, variables),
+                FormatElement::Text(_) => false,
+            })
+        }
+
         fn _parse_format<'a>(
             mut format: Vec<FormatElement<'a>>,
             style: Option<Style>,
@@ -105,6 +150,13 @@ impl<'a> StringFormatter<'a> {
                         };
                         _parse_textgroup(textgroup, &variables)
                     }
+                    FormatElement::Conditional(format) => {
+                        if _should_show_elements(&format, variables) {
+                            _parse_format(format, style, variables)
+                        } else {
+                            Vec::new()
+                        }
+                    }
                     FormatElement::Variable(name) => variables
                         .get(name.as_ref())
                         .map(|segments| {
@@ -146,35 +198,37 @@ fn _get_variables<'a>(format: &[FormatElement<'a>]) -> VariableMapType {
         variables: &mut VariableMapType,
         textgroup: &'a TextGroup<'a>,
     ) {
-        for el in &textgroup.format {
+        _push_variables_from_format(variables, &textgroup.format);
+        for el in &textgroup.style {
+            if let StyleElement::Variable(name) = el {
+                _push_variable(variables, name.as_ref())
+            }
+        }
+    }
+
+    fn _push_variables_from_format<'a>(
+        variables: &mut VariableMapType,
+        format: &'a [FormatElement<'a>],
+    ) {
+        for el in format {
             match el {
                 FormatElement::Variable(name) => _push_variable(variables, name.as_ref()),
                 FormatElement::TextGroup(textgroup) => {
                     _push_variables_from_textgroup(variables, &textgroup)
                 }
+                FormatElement::Conditional(format) => {
+                    _push_variables_from_format(variables, format)
+                }
                 _ => {}
             }
         }
-        for el in &textgroup.style {
-            if let StyleElement::Variable(name) = el {
-                _push_variable(variables, name.as_ref())
-            }
-        }
     }
 
     fn _push_variable<'a>(variables: &mut VariableMapType, name: &'a str) {
         variables.insert(name.to_owned(), None);
     }
 
-    for el in format {
-        match el {
-            FormatElement::Variable(name) => _push_variable(&mut variables, name.as_ref()),
-            FormatElement::TextGroup(textgroup) => {
-                _push_variables_from_textgroup(&mut variables, &textgroup)
-            }
-            _ => {}
-        }
-    }
+    _push_variables_from_format(&mut variables, format);
 
     variables
 }
@@ -185,6 +239,8 @@ fn _new_segment(name: String, value: String, style: Option<Style>) -> Segment {
         _name: name,
         value,
         style,
+        module: None,
+        hyperlink: None,
     }
 }
 
@@ -251,6 +307,19 @@ mod tests {
         match_next!(result_iter, r#"\[$text](red bold)"#, None);
     }
 
+    #[test]
+    fn test_escaped_newline() {
+        const FORMAT_STR: &str = r#"first\n[second\nthird](red) \\n"#;
+        let style = Some(Color::Red.normal());
+
+        let formatter = StringFormatter::new(FORMAT_STR).unwrap().map(empty_mapper);
+        let result = formatter.parse(None);
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "first\n", None);
+        match_next!(result_iter, "second\nthird", style);
+        match_next!(result_iter, " \\n", None);
+    }
+
     #[test]
     fn test_nested_textgroup() {
         const FORMAT_STR: &str = "outer [middle [inner](blue)](red bold)";
@@ -310,6 +379,99 @@ mod tests {
         match_next!(result_iter, "styled_no_modifier", styled_no_modifier_style);
     }
 
+    #[test]
+    fn test_scoped_variable() {
+        const FORMAT_STR: &str = "${custom.foo}";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|variable| match variable {
+                "custom.foo" => Some("text".to_owned()),
+                _ => None,
+            });
+        let result = formatter.parse(None);
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "text", None);
+    }
+
+    #[test]
+    fn test_get_variables() {
+        const FORMAT_STR: &str = "$b [$a ${c.d}](red) $b";
+
+        let formatter = StringFormatter::new(FORMAT_STR).unwrap();
+        assert_eq!(formatter.get_variables(), vec!["b", "a", "c.d"]);
+    }
+
+    #[test]
+    fn test_conditional() {
+        const FORMAT_STR: &str = "($some) should render but ($none) shouldn't";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|var| match var {
+                "some" => Some("$some".to_string()),
+                _ => None,
+            });
+        let result = formatter.parse(None);
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "$some", None);
+        match_next!(result_iter, " should render but ", None);
+        match_next!(result_iter, " shouldn't", None);
+        assert!(result_iter.next().is_none());
+    }
+
+    #[test]
+    fn test_conditional_with_empty_value() {
+        const FORMAT_STR: &str = "git(:[$empty](red))";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|var| match var {
+                "empty" => Some(String::new()),
+                _ => None,
+            });
+        let result = formatter.parse(None);
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "git", None);
+        assert!(result_iter.next().is_none());
+    }
+
+    #[test]
+    fn test_nested_conditional() {
+        const FORMAT_STR: &str = "($some ($none)) and ($none ($some))";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|var| match var {
+                "some" => Some("$some".to_string()),
+                _ => None,
+            });
+        let result = formatter.parse(None);
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "$some", None);
+        match_next!(result_iter, " ", None);
+        match_next!(result_iter, " and ", None);
+        match_next!(result_iter, "", None);
+        match_next!(result_iter, " ", None);
+        match_next!(result_iter, "$some", None);
+        assert!(result_iter.next().is_none());
+    }
+
+    #[test]
+    fn test_conditional_styled_variable() {
+        const FORMAT_STR: &str = "([$var](red) )";
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map_variables_to_segments(|variable| match variable {
+                "var" => Some(vec![_new_segment("_1".to_owned(), "text".to_owned(), None)]),
+                _ => None,
+            });
+        let result = formatter.parse(None);
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "text", Some(Color::Red.normal()));
+        match_next!(result_iter, " ", None);
+    }
+
     #[test]
     fn test_parse_error() {
         // brackets without escape
@@ -322,5 +484,15 @@ mod tests {
             const FORMAT_STR: &str = "$ ";
             assert!(StringFormatter::new(FORMAT_STR).is_err());
         }
+        // Unclosed conditional group
+        {
+            const FORMAT_STR: &str = "($var";
+            assert!(StringFormatter::new(FORMAT_STR).is_err());
+        }
+        // Unclosed scoped variable
+        {
+            const FORMAT_STR: &str = "${custom.foo";
+            assert!(StringFormatter::new(FORMAT_STR).is_err());
+        }
     }
 }
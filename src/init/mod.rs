@@ -86,6 +86,10 @@ fi"#,
             let script = format!("source <(\"{}\" init zsh --print-full-init)", starship);
             Some(script)
         }
+        Some("sh") => {
+            let script = format!("eval \"$(\"{}\" init sh --print-full-init)\"", starship);
+            Some(script)
+        }
         Some("fish") => {
             // Fish does process substitution with pipes and psub instead of bash syntax
             let script = format!(
@@ -110,9 +114,34 @@ fi"#,
             Some(script)
         }
         Some("ion") => {
-            let script = format!("eval $({} init ion --print-full-init)", starship);
+            let script = format!("eval $(\"{}\" init ion --print-full-init)", starship);
+            Some(script)
+        }
+        Some("elvish") => {
+            let script = format!(
+                "eval (\"{}\" init elvish --print-full-init | slurp)",
+                starship
+            );
             Some(script)
         }
+        Some("xonsh") => {
+            let script = format!("execx($(\"{}\" init xonsh --print-full-init))", starship);
+            Some(script)
+        }
+        Some("tcsh") => {
+            let script = format!("eval `(\"{}\" init tcsh --print-full-init)`", starship);
+            Some(script)
+        }
+        Some("nu") => {
+            // Nushell can't evaluate a script at runtime, so the full script is printed
+            // to be saved to a file that is loaded from the nushell config
+            return init_main("nu");
+        }
+        Some("cmd") => {
+            // Clink loads its scripts from files, so the full script is printed to be
+            // loaded from a Lua script in its scripts directory
+            return init_main("cmd");
+        }
         None => {
             println!(
                 "Invalid shell name provided: {}\\n\
@@ -126,7 +155,7 @@ fi"#,
         Some(shell_basename) => {
             println!(
                 "printf \"\\n{0} is not yet supported by starship.\\n\
-                 For the time being, we support bash, zsh, fish, and ion.\\n\
+                 For the time being, we support bash, zsh, fish, powershell, ion, elvish, nu, xonsh, tcsh, cmd, and sh.\\n\
                  Please open an issue in the starship repo if you would like to \
                  see support for {0}:\\nhttps://github.com/starship/starship/issues/new\"\\n\\n",
                 shell_basename
@@ -151,6 +180,12 @@ pub fn init_main(shell_name: &str) -> io::Result<()> {
         "fish" => Some(FISH_INIT),
         "powershell" => Some(PWSH_INIT),
         "ion" => Some(ION_INIT),
+        "elvish" => Some(ELVISH_INIT),
+        "nu" => Some(NU_INIT),
+        "xonsh" => Some(XONSH_INIT),
+        "tcsh" => Some(TCSH_INIT),
+        "cmd" => Some(CMD_INIT),
+        "sh" => Some(SH_INIT),
         _ => {
             println!(
                 "printf \"Shell name detection failed on phase two init.\\n\
@@ -193,3 +228,15 @@ const FISH_INIT: &str = include_str!("starship.fish");
 const PWSH_INIT: &str = include_str!("starship.ps1");
 
 const ION_INIT: &str = include_str!("starship.ion");
+
+const ELVISH_INIT: &str = include_str!("starship.elvish");
+
+const NU_INIT: &str = include_str!("starship.nu");
+
+const XONSH_INIT: &str = include_str!("starship.xonsh");
+
+const TCSH_INIT: &str = include_str!("starship.tcsh");
+
+const CMD_INIT: &str = include_str!("starship.lua");
+
+const SH_INIT: &str = include_str!("starship.sh");
@@ -21,6 +21,8 @@ starship_preexec() {
     if [ "$PREEXEC_READY" = "true" ]; then
         PREEXEC_READY=false
         STARSHIP_START_TIME=$(::STARSHIP:: time)
+        # Mark the start of the command output for terminals supporting semantic prompts
+        printf '\e]133;C\a'
     fi
     
     : "$PREV_LAST_ARG"
@@ -28,8 +30,14 @@ starship_preexec() {
 
 # Will be run before the prompt is drawn
 starship_precmd() {
-    # Save the status, because commands in this pipeline will change $?
-    STATUS=$?
+    # Save the status and the status of each command of the pipeline, because commands
+    # in this function will change them
+    STARSHIP_CMD_STATUS=$? STARSHIP_PIPE_STATUS=(${PIPESTATUS[@]})
+
+    # Mark the end of the command output, if a command has been run since the last prompt
+    if [ "$PREEXEC_READY" = "false" ]; then
+        printf '\e]133;D;%s\a' "$STARSHIP_CMD_STATUS"
+    fi
 
     # Run the bash precmd function, if it's set. If not set, evaluates to no-op
     "${starship_precmd_user_func-:}"
@@ -38,10 +46,10 @@ starship_precmd() {
     if [[ $STARSHIP_START_TIME ]]; then
         STARSHIP_END_TIME=$(::STARSHIP:: time)
         STARSHIP_DURATION=$((STARSHIP_END_TIME - STARSHIP_START_TIME))
-        PS1="$(::STARSHIP:: prompt --status=$STATUS --jobs="$(jobs -p | wc -l)" --cmd-duration=$STARSHIP_DURATION)"
+        PS1="$(::STARSHIP:: prompt --terminal-width="$COLUMNS" --logical-path="$PWD" --status=$STARSHIP_CMD_STATUS --pipestatus="${STARSHIP_PIPE_STATUS[*]}" --jobs="$(jobs -p | wc -l)" --cmd-duration=$STARSHIP_DURATION)"
         unset STARSHIP_START_TIME
     else
-        PS1="$(::STARSHIP:: prompt --status=$STATUS --jobs="$(jobs -p | wc -l)")"
+        PS1="$(::STARSHIP:: prompt --terminal-width="$COLUMNS" --logical-path="$PWD" --status=$STARSHIP_CMD_STATUS --pipestatus="${STARSHIP_PIPE_STATUS[*]}" --jobs="$(jobs -p | wc -l)")"
     fi
     PREEXEC_READY=true;  # Signal that we can safely restart the timer
 }
@@ -69,7 +77,7 @@ else
     # add multiple instances of the starship function and keep other user functions if any.
     if [[ -z "$PROMPT_COMMAND" ]]; then
         PROMPT_COMMAND="starship_precmd"
-    elif [[ "$PROMPT_COMMAND" != *"starship_precmd" ]]; then
+    elif [[ "$PROMPT_COMMAND" != *"starship_precmd"* ]]; then
         # Remove any trailing semicolon before appending (PR #784)
         PROMPT_COMMAND="${PROMPT_COMMAND%;};starship_precmd;"
     fi
@@ -78,3 +86,6 @@ fi
 # Set up the start time and STARSHIP_SHELL, which controls shell-specific sequences
 STARSHIP_START_TIME=$(::STARSHIP:: time)
 export STARSHIP_SHELL="bash"
+
+# The continuation prompt doesn't depend on the state of the shell, so it's only rendered once
+PS2="$(::STARSHIP:: prompt --continuation)"
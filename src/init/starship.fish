@@ -1,16 +1,70 @@
 function fish_prompt
+    # Save the status and the status of each command of the pipeline first, every
+    # command below replaces them. $pipestatus is empty before fish 3.1.
+    set -l starship_pipestatus $status $pipestatus
+    set -l exit_code $starship_pipestatus[1]
+    set -e starship_pipestatus[1]
+    if set -q STARSHIP_TRANSIENT
+        echo -n -s $STARSHIP_TRANSIENT_PROMPT
+        return
+    end
+    set -l keymap insert
     switch "$fish_key_bindings"
         case fish_hybrid_key_bindings fish_vi_key_bindings
             set keymap "$fish_bind_mode"
-        case '*'
-            set keymap insert
     end
-    set -l exit_code $status
     # Account for changes in variable name between v2.7 and v3.0
     set -l starship_duration "$CMD_DURATION$cmd_duration"
-    ::STARSHIP:: prompt --status=$exit_code --keymap=$keymap --cmd-duration=$starship_duration --jobs=(count (jobs -p))
+    ::STARSHIP:: prompt --terminal-width="$COLUMNS" --logical-path=$PWD --status=$exit_code --pipestatus="$starship_pipestatus" --keymap=$keymap --cmd-duration=$starship_duration --jobs=(count (jobs -p))
+end
+
+function fish_right_prompt
+    set -l starship_pipestatus $status $pipestatus
+    set -l exit_code $starship_pipestatus[1]
+    set -e starship_pipestatus[1]
+    if set -q STARSHIP_TRANSIENT
+        echo -n -s $STARSHIP_TRANSIENT_RIGHT_PROMPT
+        return
+    end
+    set -l keymap insert
+    switch "$fish_key_bindings"
+        case fish_hybrid_key_bindings fish_vi_key_bindings
+            set keymap "$fish_bind_mode"
+    end
+    set -l starship_duration "$CMD_DURATION$cmd_duration"
+    ::STARSHIP:: prompt --terminal-width="$COLUMNS" --right --status=$exit_code --pipestatus="$starship_pipestatus" --keymap=$keymap --cmd-duration=$starship_duration --jobs=(count (jobs -p))
 end
 
+# Once a command has been accepted, replace its prompt with the transient prompt, if configured
+function __starship_transient_execute
+    set -e STARSHIP_TRANSIENT
+    if commandline --is-valid
+        set -g STARSHIP_TRANSIENT_PROMPT (::STARSHIP:: prompt --terminal-width="$COLUMNS" --transient)
+        if test -n "$STARSHIP_TRANSIENT_PROMPT"
+            set -g STARSHIP_TRANSIENT_RIGHT_PROMPT (::STARSHIP:: prompt --terminal-width="$COLUMNS" --transient --right)
+            set -g STARSHIP_TRANSIENT 1
+            commandline -f repaint
+        end
+    end
+    commandline -f execute
+end
+
+function __starship_transient_reset --on-event fish_postexec
+    set -e STARSHIP_TRANSIENT
+end
+
+# Mark the command output for terminals supporting semantic prompts
+function __starship_mark_output_start --on-event fish_preexec
+    printf '\e]133;C\a'
+end
+
+function __starship_mark_output_end --on-event fish_postexec
+    printf '\e]133;D;%s\a' $status
+end
+
+bind \r __starship_transient_execute
+bind -M insert \r __starship_transient_execute
+
 # disable virtualenv prompt, it breaks starship
 set VIRTUAL_ENV_DISABLE_PROMPT 1
 
@@ -0,0 +1,53 @@
+-- Clink draws the prompt of cmd.exe by running the prompt filters it knows about.
+-- Docs: https://chrisant996.github.io/clink/clink.html#customizing-the-prompt
+--
+-- The command duration is the time between the end of an edit, when a command is
+-- accepted, and the beginning of the next one. It is reset once the prompt is drawn,
+-- so that a prompt drawn without running a command doesn't show it again.
+
+-- The STARSHIP between the colons will be replaced with the quoted path to the
+-- starship executable
+local starship = [[::STARSHIP::]]
+
+local start_time = nil
+local cmd_duration = nil
+
+local function starship_time()
+    return io.popen(starship .. " time"):read("*n")
+end
+
+clink.onendedit(function ()
+    start_time = starship_time()
+end)
+
+clink.onbeginedit(function ()
+    if start_time then
+        cmd_duration = starship_time() - start_time
+        start_time = nil
+    else
+        cmd_duration = nil
+    end
+end)
+
+local function starship_args()
+    -- os.geterrorlevel() needs the cmd.get_errorlevel setting of clink, which is on
+    -- by default
+    local args = " --status=" .. os.geterrorlevel()
+    if cmd_duration then
+        args = args .. " --cmd-duration=" .. cmd_duration
+    end
+    return args
+end
+
+local starship_prompt = clink.promptfilter(5)
+
+function starship_prompt:filter(prompt)
+    return io.popen(starship .. " prompt" .. starship_args()):read("*a")
+end
+
+function starship_prompt:rightfilter(prompt)
+    return io.popen(starship .. " prompt --right" .. starship_args()):read("*a")
+end
+
+-- Export the correct name of the shell
+os.setenv("STARSHIP_SHELL", "cmd")
@@ -3,24 +3,76 @@
 # Starship assumes UTF-8
 [Console]::OutputEncoding = [System.Text.Encoding]::UTF8
 function global:prompt {
+    # Save the status of the last command first, every command below replaces it
+    $origDollarQuestion = $global:?
+    $origLastExitCode = $global:LASTEXITCODE
+
+    if ($global:STARSHIP_TRANSIENT) {
+        $transient = $global:STARSHIP_TRANSIENT
+        $global:STARSHIP_TRANSIENT = $null
+        return $transient
+    }
+
+    # $LASTEXITCODE is only set by native programs, failed cmdlets only set $? to false
+    $status = 0
+    if (!$origDollarQuestion) {
+        $status = if ($origLastExitCode) { $origLastExitCode } else { 1 }
+    }
+
     $out = $null
+    # Mark the end of the previous command's output for terminals supporting semantic prompts
+    if ($global:STARSHIP_COMMAND_RUNNING) {
+        $global:STARSHIP_COMMAND_RUNNING = $false
+        Write-Host -NoNewline "$([char]27)]133;D;$status$([char]7)"
+    }
+
     # @ makes sure the result is an array even if single or no values are returned
     $jobs = @(Get-Job | Where-Object { $_.State -eq 'Running' }).Count
 
     $env:PWD = $PWD
     $current_directory = (Convert-Path $PWD)
 
-    if ($lastCmd = Get-History -Count 1) {
+    # Only show the duration of a command once, not again when the prompt is redrawn
+    # without running a command
+    $lastCmd = Get-History -Count 1
+    if ($lastCmd -and $lastCmd.Id -ne $global:STARSHIP_LAST_HISTORY_ID) {
+        $global:STARSHIP_LAST_HISTORY_ID = $lastCmd.Id
         $duration = [math]::Round(($lastCmd.EndExecutionTime - $lastCmd.StartExecutionTime).TotalMilliseconds)
         # & ensures the path is interpreted as something to execute
-        $out = @(&::STARSHIP:: prompt "--path=$current_directory" --status=$lastexitcode --jobs=$jobs --cmd-duration=$duration)
+        $out = @(&::STARSHIP:: prompt "--path=$current_directory" "--logical-path=$PWD" "--terminal-width=$($Host.UI.RawUI.WindowSize.Width)" "--status=$status" "--jobs=$jobs" "--cmd-duration=$duration")
     } else {
-        $out = @(&::STARSHIP:: prompt "--path=$current_directory" --status=$lastexitcode --jobs=$jobs)
+        $out = @(&::STARSHIP:: prompt "--path=$current_directory" "--logical-path=$PWD" "--terminal-width=$($Host.UI.RawUI.WindowSize.Width)" "--status=$status" "--jobs=$jobs")
     }
 
+    # Restore $LASTEXITCODE, which was replaced by running starship
+    $global:LASTEXITCODE = $origLastExitCode
+
     # Convert stdout (array of lines) to expected return type string
     # `n is an escaped newline
     $out -join "`n"
+
+    # Restore $?, which can't be assigned, by running a command that sets it to the
+    # original value without any output. This has to be last, every command sets $?.
+    if ($origDollarQuestion) {
+        1 | Where-Object { $false }
+    } else {
+        Write-Error '' -ErrorAction 'Ignore'
+    }
+}
+
+# Once a command has been accepted, replace its prompt with the transient prompt, if configured
+if (Get-Module PSReadLine) {
+    Set-PSReadLineKeyHandler -Key Enter -ScriptBlock {
+        $transient = @(&::STARSHIP:: prompt --transient) -join "`n"
+        if ($transient) {
+            $global:STARSHIP_TRANSIENT = $transient
+            [Microsoft.PowerShell.PSConsoleReadLine]::InvokePrompt()
+        }
+        [Microsoft.PowerShell.PSConsoleReadLine]::AcceptLine()
+        # Mark the start of the command output
+        $global:STARSHIP_COMMAND_RUNNING = $true
+        Write-Host -NoNewline "$([char]27)]133;C$([char]7)"
+    }
 }
 
 $ENV:STARSHIP_SHELL = "powershell"
@@ -0,0 +1,11 @@
+# POSIX shells like dash and busybox ash have no hooks that run before a command or a
+# prompt, but they expand command substitutions in PS1 every time it is drawn. The
+# status of the last command is still known then. The duration of a command and the
+# number of jobs are not, so the modules showing them stay hidden.
+#
+# The STARSHIP between the colons will be replaced with the actual path to the
+# starship executable.
+PS1='$(::STARSHIP:: prompt --status=$?)'
+
+# Export the correct name of the shell
+export STARSHIP_SHELL="sh"
@@ -1,13 +1,78 @@
+//! Starship renders its prompt with this library, which other tools can use to render
+//! it too, e.g. in a status line, without running `starship` for every update.
+//!
+//! A [`Context`] holds everything a prompt is rendered from: the configuration, the
+//! directory, and the arguments `starship prompt` would be given. The configuration is
+//! read from `$STARSHIP_CONFIG` or `~/.config/starship.toml`, like for the prompt.
+//!
+//! ```no_run
+//! let context = starship::Context::new_with_dir(clap::ArgMatches::default(), "/srv/app");
+//! for module in starship::list_modules(&context) {
+//!     println!("{}", module);
+//! }
+//! println!("{}", starship::render_prompt(context));
+//! ```
+//!
+//! The modules of this library are shared with the `starship` binary, and change along
+//! with it. The functions and types at the root of the crate are kept stable instead.
+
 #[macro_use]
 extern crate pest_derive;
 
-// Lib is present to allow for benchmarking
+pub mod cache;
 pub mod config;
 pub mod configs;
 pub mod context;
 pub mod formatter;
+pub mod logger;
 pub mod module;
 pub mod modules;
+pub mod powerline;
 pub mod print;
+pub mod profile;
 pub mod segment;
+pub mod test;
 mod utils;
+
+pub use crate::config::{RootModuleConfig, StarshipConfig};
+pub use crate::configs::StarshipRootConfig;
+pub use crate::context::{Context, Shell};
+pub use crate::module::{Module, ALL_MODULES};
+pub use crate::modules::ModuleFn;
+pub use crate::utils::CommandOutput;
+
+/// Render the prompt for a context, as `starship prompt` prints it
+pub fn render_prompt(context: Context) -> String {
+    print::get_prompt(context)
+}
+
+/// Render a single module, as `starship module` prints it, or `None` if it isn't shown
+pub fn render_module(name: &str, context: Context) -> Option<String> {
+    print::get_module(name, context)
+}
+
+/// Register a module, which can then be used by its name in the `format` and the
+/// configuration like a built-in module. A module registered with the name of a
+/// built-in module replaces it.
+///
+/// ```no_run
+/// use starship::config::SegmentConfig;
+/// use starship::{Context, Module};
+///
+/// fn greeting<'a>(context: &'a Context) -> Option<Module<'a>> {
+///     let mut module = context.new_module("greeting");
+///     module.create_segment("text", &SegmentConfig::new("hello "));
+///     Some(module)
+/// }
+///
+/// starship::register_module("greeting", "A friendly greeting", greeting);
+/// ```
+pub fn register_module(name: &str, description: &'static str, module: ModuleFn) {
+    modules::register(name, description, module)
+}
+
+/// The names of all modules, including the registered modules, the custom modules in the configuration of the
+/// context and the plugins that were found
+pub fn list_modules(context: &Context) -> Vec<String> {
+    print::get_module_names(context)
+}
@@ -0,0 +1,380 @@
+//! Logs are written to a file per shell session in the `logs` directory of the cache,
+//! instead of to the terminal the prompt is shown in. `$STARSHIP_LOG`, or else the
+//! `log_level` of the configuration, sets the level they are logged from, and errors
+//! are printed as well unless `quiet` is set. A warning or error is only reported once
+//! per session, rather than on every prompt. Panics are logged as errors, with their
+//! backtrace, every time.
+
+use crate::cache;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use once_cell::sync::OnceCell;
+use std::backtrace::Backtrace;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// A log file is rotated once it's larger than this, in bytes
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+
+/// The logs of sessions that didn't log anything for this long are removed
+const MAX_LOG_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// The target panics are logged with
+const PANIC_TARGET: &str = "starship::panic";
+
+static LOGGER: OnceCell<StarshipLogger> = OnceCell::new();
+
+pub struct StarshipLogger {
+    settings: Mutex<Settings>,
+    path: Option<PathBuf>,
+    /// The log file, opened once the first record is logged, so that sessions that
+    /// don't log anything leave no file behind
+    file: OnceCell<Option<Mutex<File>>>,
+    /// The warnings and errors reported in this session, by the hash of their message,
+    /// read once the first one is logged
+    reported: OnceCell<Mutex<HashSet<u64>>>,
+}
+
+struct Settings {
+    level: LevelFilter,
+    quiet: bool,
+}
+
+/// Log the records of starship at the level in `$STARSHIP_LOG`, or else its warnings
+/// and errors, to the log file of the session
+pub fn init() {
+    let level = env_level().unwrap_or(LevelFilter::Warn);
+    let logger = StarshipLogger {
+        settings: Mutex::new(Settings {
+            level,
+            quiet: false,
+        }),
+        path: logs_dir().map(|dir| dir.join(format!("session_{}.log", session_key()))),
+        file: OnceCell::new(),
+        reported: OnceCell::new(),
+    };
+    LOGGER.set(logger).ok();
+    if let Some(logger) = LOGGER.get() {
+        log::set_max_level(level);
+        log::set_logger(logger).ok();
+    }
+
+    // Panics are logged like errors, with the backtrace in the log file only, as the
+    // prompt is still shown
+    panic::set_hook(Box::new(|info| {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| (*message).to_owned())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| String::from("Box<dyn Any>"));
+        let message = match info.location() {
+            Some(location) => format!("Panicked at {}: {}", location, message),
+            None => format!("Panicked: {}", message),
+        };
+        if let Some(logger) = LOGGER.get() {
+            let backtrace = Backtrace::force_capture().to_string();
+            logger.report(Level::Error, PANIC_TARGET, &message, Some(&backtrace));
+        }
+    }));
+}
+
+/// Apply the `log_level` and `quiet` of the configuration, once it's loaded, where
+/// `$STARSHIP_LOG` takes precedence over `log_level`. Without the logger of starship,
+/// e.g. in a program using the library, nothing changes.
+pub fn configure(log_level: &str, quiet: bool) {
+    let logger = match LOGGER.get() {
+        Some(logger) => logger,
+        None => return,
+    };
+    let level = env_level().or_else(|| log_level.parse().ok());
+    let mut settings = logger.settings.lock().unwrap();
+    if let Some(level) = level {
+        settings.level = level;
+        log::set_max_level(level);
+    }
+    settings.quiet = quiet;
+    drop(settings);
+
+    if level.is_none() {
+        log::warn!("Unknown log_level \"{}\"", log_level);
+    }
+}
+
+/// Print the log of the session that logged last
+pub fn print_latest() {
+    let log = logs_dir()
+        .and_then(|dir| latest_log(&dir))
+        .and_then(|path| fs::read_to_string(path).ok());
+    match log {
+        Some(log) => print!("{}", log),
+        None => println!("Nothing was logged yet"),
+    }
+}
+
+fn env_level() -> Option<LevelFilter> {
+    env::var("STARSHIP_LOG").ok()?.parse().ok()
+}
+
+impl Log for StarshipLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.settings.lock().unwrap().level
+            && metadata.target().starts_with("starship")
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let message = record.args().to_string();
+        self.report(record.level(), record.target(), &message, None);
+    }
+
+    fn flush(&self) {
+        if let Some(Some(file)) = self.file.get() {
+            file.lock().unwrap().flush().ok();
+        }
+    }
+}
+
+impl StarshipLogger {
+    /// Log a message to the log file, followed by `details` if there are any, which
+    /// unlike the message aren't printed with an error
+    fn report(&self, level: Level, target: &str, message: &str, details: Option<&str>) {
+        let (level_filter, quiet) = {
+            let settings = self.settings.lock().unwrap();
+            (settings.level, settings.quiet)
+        };
+        // While debugging, every warning is logged where it happens
+        let is_reported = level <= Level::Warn
+            && level_filter <= LevelFilter::Warn
+            && self.is_reported(target, message);
+        // Every panic is logged with its backtrace, as the fallback prompt hides them
+        if is_reported && target != PANIC_TARGET {
+            return;
+        }
+        if level == Level::Error && !quiet && !is_reported {
+            eprintln!("[ERROR] - ({}): {}", target, message);
+        }
+
+        let file = self
+            .file
+            .get_or_init(|| open_log(self.path.as_ref()?).map(Mutex::new));
+        if let Some(file) = file {
+            let mut line = format!(
+                "[{} {} {:<5}] ({}): {}\n",
+                chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+                std::process::id(),
+                level,
+                target,
+                message
+            );
+            if let Some(details) = details {
+                line.push_str(details.trim_end());
+                line.push('\n');
+            }
+            // Prompts of the same session may log at once, so every line is written
+            // at once to the end of the file
+            file.lock().unwrap().write_all(line.as_bytes()).ok();
+        }
+    }
+
+    /// Whether a message was reported in this session already. Otherwise, it's recorded
+    /// next to the log file of the session, for the prompts to come.
+    fn is_reported(&self, target: &str, message: &str) -> bool {
+        let mut hasher = DefaultHasher::new();
+        (target, message).hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let path = self
+            .path
+            .as_ref()
+            .map(|path| path.with_extension("reported"));
+        let reported = self.reported.get_or_init(|| {
+            let hashes = path
+                .as_ref()
+                .and_then(|path| fs::read_to_string(path).ok())
+                .map(|hashes| read_hashes(&hashes))
+                .unwrap_or_default();
+            Mutex::new(hashes)
+        });
+        if !reported.lock().unwrap().insert(hash) {
+            return true;
+        }
+        if let Some(path) = path {
+            let file = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| OpenOptions::new().create(true).append(true).open(&path));
+            if let Ok(mut file) = file {
+                file.write_all(format!("{:016x}\n", hash).as_bytes()).ok();
+            }
+        }
+        false
+    }
+}
+
+fn read_hashes(hashes: &str) -> HashSet<u64> {
+    hashes
+        .lines()
+        .filter_map(|hash| u64::from_str_radix(hash, 16).ok())
+        .collect()
+}
+
+fn logs_dir() -> Option<PathBuf> {
+    cache::cache_dir().map(|dir| dir.join("logs"))
+}
+
+/// Identifies the shell session, from `$STARSHIP_SESSION_KEY`, or else the process of
+/// the shell that started starship
+fn session_key() -> String {
+    match env::var("STARSHIP_SESSION_KEY") {
+        Ok(key) if !key.is_empty() => key,
+        _ => parent_id(),
+    }
+}
+
+#[cfg(unix)]
+fn parent_id() -> String {
+    std::os::unix::process::parent_id().to_string()
+}
+
+#[cfg(not(unix))]
+fn parent_id() -> String {
+    String::from("default")
+}
+
+/// Open a log file to append to, after moving it to `<file>.old` if it grew larger than
+/// `MAX_LOG_SIZE`. Starting the log of a session removes the outdated logs of others.
+fn open_log(path: &Path) -> Option<File> {
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.len() > MAX_LOG_SIZE => {
+            fs::rename(path, path.with_extension("log.old")).ok();
+        }
+        Ok(_) => {}
+        Err(_) => {
+            let dir = path.parent()?;
+            fs::create_dir_all(dir).ok()?;
+            remove_old_logs(dir);
+        }
+    }
+    OpenOptions::new().create(true).append(true).open(path).ok()
+}
+
+fn remove_old_logs(dir: &Path) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let age = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+        if age.filter(|age| *age > MAX_LOG_AGE).is_some() {
+            fs::remove_file(entry.path()).ok();
+        }
+    }
+}
+
+/// The log file in a directory that was written to last
+fn latest_log(dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("log"))
+        .filter_map(|path| Some((fs::metadata(&path).ok()?.modified().ok()?, path)))
+        .max()
+        .map(|(_, path)| path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotate_large_log() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("session_1.log");
+        fs::write(&path, vec![b'a'; MAX_LOG_SIZE as usize + 1])?;
+
+        let mut file = open_log(&path).unwrap();
+        file.write_all(b"b\n")?;
+        assert_eq!(fs::read_to_string(&path)?, "b\n");
+        assert_eq!(
+            fs::metadata(dir.path().join("session_1.log.old"))?.len(),
+            MAX_LOG_SIZE + 1
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn append_to_small_log() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("logs").join("session_1.log");
+
+        open_log(&path).unwrap().write_all(b"a\n")?;
+        open_log(&path).unwrap().write_all(b"b\n")?;
+        assert_eq!(fs::read_to_string(&path)?, "a\nb\n");
+        dir.close()
+    }
+
+    #[test]
+    fn report_every_panic() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("session_1.log");
+        let logger = StarshipLogger {
+            settings: Mutex::new(Settings {
+                level: LevelFilter::Warn,
+                quiet: true,
+            }),
+            path: Some(path.clone()),
+            file: OnceCell::new(),
+            reported: OnceCell::new(),
+        };
+        for _ in 0..2 {
+            logger.report(Level::Warn, "starship::config", "Unknown key", None);
+            logger.report(Level::Error, PANIC_TARGET, "Panicked", Some("backtrace"));
+        }
+
+        let log = fs::read_to_string(&path)?;
+        assert_eq!(log.matches("Unknown key").count(), 1);
+        assert_eq!(log.matches("Panicked").count(), 2);
+        assert_eq!(log.matches("backtrace").count(), 2);
+        dir.close()
+    }
+
+    #[test]
+    fn read_reported_hashes() {
+        let expected: HashSet<u64> = [0x1f, 0xfedc_ba98_7654_3210].iter().copied().collect();
+        assert_eq!(
+            read_hashes("000000000000001f\nfedcba9876543210\ninvalid\n"),
+            expected
+        );
+    }
+
+    #[test]
+    fn find_latest_log() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("session_1.log"), "a\n")?;
+        std::thread::sleep(Duration::from_millis(20));
+        fs::write(dir.path().join("session_2.log"), "b\n")?;
+        std::thread::sleep(Duration::from_millis(20));
+        fs::write(dir.path().join("session_3.log.old"), "c\n")?;
+
+        assert_eq!(
+            latest_log(dir.path()),
+            Some(dir.path().join("session_2.log"))
+        );
+        dir.close()
+    }
+}
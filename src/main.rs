@@ -1,3 +1,4 @@
+use std::io;
 use std::time::SystemTime;
 
 #[macro_use]
@@ -6,23 +7,30 @@ extern crate clap;
 extern crate pest_derive;
 
 mod bug_report;
+mod cache;
 mod config;
 mod configs;
 mod configure;
 mod context;
 mod formatter;
 mod init;
+mod logger;
 mod module;
 mod modules;
+mod powerline;
+mod presets;
 mod print;
+mod profile;
 mod segment;
+mod session;
+#[cfg(test)]
+mod test;
 mod utils;
 
-use crate::module::ALL_MODULES;
-use clap::{App, AppSettings, Arg, SubCommand};
+use clap::{App, AppSettings, Arg, Shell, SubCommand};
 
 fn main() {
-    pretty_env_logger::init();
+    logger::init();
 
     let status_code_arg = Arg::with_name("status_code")
         .short("s")
@@ -31,6 +39,12 @@ fn main() {
         .help("The status code of the previously run command")
         .takes_value(true);
 
+    let pipestatus_arg = Arg::with_name("pipestatus")
+        .long("pipestatus")
+        .value_name("PIPESTATUS")
+        .help("The status codes of the previously run pipeline, separated by spaces")
+        .takes_value(true);
+
     let path_arg = Arg::with_name("path")
         .short("p")
         .long("path")
@@ -38,13 +52,38 @@ fn main() {
         .help("The path that the prompt should render for")
         .takes_value(true);
 
+    let logical_path_arg = Arg::with_name("logical_path")
+        .short("P")
+        .long("logical-path")
+        .value_name("LOGICAL_PATH")
+        .help("The logical path the shell reports for the current directory, e.g. $PWD, with its symlinks")
+        .takes_value(true);
+
     let shell_arg = Arg::with_name("shell")
         .value_name("SHELL")
         .help(
-            "The name of the currently running shell\nCurrently supported options: bash, zsh, fish, powershell, ion",
+            "The name of the currently running shell\nCurrently supported options: bash, zsh, fish, powershell, ion, elvish, nu, xonsh, tcsh, cmd, sh",
         )
         .required(true);
 
+    let profile_arg = Arg::with_name("profile")
+        .long("profile")
+        .value_name("FILE")
+        .help("Write a trace of where the time went to FILE, like $STARSHIP_PROFILE")
+        .takes_value(true);
+
+    let shell_name_arg = Arg::with_name("shell")
+        .long("shell")
+        .value_name("SHELL")
+        .help("The name of the currently running shell, overriding $STARSHIP_SHELL")
+        .takes_value(true);
+
+    let profile_name_arg = Arg::with_name("profile_name")
+        .long("profile-name")
+        .value_name("NAME")
+        .help("The profile of the config to use, overriding $STARSHIP_PROFILE_NAME")
+        .takes_value(true);
+
     let cmd_duration_arg = Arg::with_name("cmd_duration")
         .short("d")
         .long("cmd-duration")
@@ -67,11 +106,22 @@ fn main() {
         .help("The number of currently running jobs")
         .takes_value(true);
 
+    let terminal_width_arg = Arg::with_name("terminal_width")
+        .short("w")
+        .long("terminal-width")
+        .value_name("WIDTH")
+        .help("The width of the current interactive terminal")
+        .takes_value(true);
+
+    let force_color_arg = Arg::with_name("force_color")
+        .long("force-color")
+        .help("Style the output even if NO_COLOR is set, TERM is dumb or it isn't a terminal");
+
     let init_scripts_arg = Arg::with_name("print_full_init")
         .long("print-full-init")
         .help("Print the main initialization script (as opposed to the init stub)");
 
-    let matches =
+    let mut app =
         App::new("starship")
             .about("The cross-shell prompt for astronauts. ☄🌌️")
             // pull the version number from Cargo.toml
@@ -89,18 +139,64 @@ fn main() {
             .subcommand(
                 SubCommand::with_name("prompt")
                     .about("Prints the full starship prompt")
+                    .arg(
+                        Arg::with_name("right")
+                            .long("right")
+                            .help("Print the right prompt instead of the left prompt"),
+                    )
+                    .arg(
+                        Arg::with_name("continuation")
+                            .long("continuation")
+                            .help("Print the continuation prompt instead of the left prompt")
+                            .conflicts_with("right"),
+                    )
+                    .arg(
+                        Arg::with_name("transient")
+                            .long("transient")
+                            .help("Print the transient prompt, which replaces previous prompts")
+                            .conflicts_with("continuation"),
+                    )
+                    .arg(
+                        Arg::with_name("skip_async")
+                            .long("skip-async")
+                            .help("Show placeholders for the modules in async_modules, to render them later"),
+                    )
+                    .arg(
+                        Arg::with_name("modules")
+                            .long("modules")
+                            .value_name("MODULES")
+                            .help("Print only these modules, separated by commas, without a new line before them")
+                            .conflicts_with_all(&["right", "continuation", "transient"])
+                            .takes_value(true),
+                    )
+                    .arg(
+                        Arg::with_name("format")
+                            .long("format")
+                            .value_name("FORMAT")
+                            .help("Print the prompt as text, or as a JSON array of its segments")
+                            .possible_values(&["text", "json"])
+                            .default_value("text")
+                            .takes_value(true),
+                    )
                     .arg(&status_code_arg)
+                    .arg(&pipestatus_arg)
                     .arg(&path_arg)
+                    .arg(&logical_path_arg)
                     .arg(&cmd_duration_arg)
                     .arg(&keymap_arg)
-                    .arg(&jobs_arg),
+                    .arg(&jobs_arg)
+                    .arg(&terminal_width_arg)
+                    .arg(&shell_name_arg)
+                    .arg(&profile_name_arg)
+                    .arg(&profile_arg)
+                    .arg(&force_color_arg),
             )
             .subcommand(
                 SubCommand::with_name("module")
                     .about("Prints a specific prompt module")
                     .arg(
                         Arg::with_name("name")
-                            .help("The name of the module to be printed")
+                            .help("The name of the module to be printed, e.g. `directory` or `custom.<name>`")
                             .required(true)
                             .required_unless("list"),
                     )
@@ -111,26 +207,82 @@ fn main() {
                             .help("List out all supported modules"),
                     )
                     .arg(&status_code_arg)
+                    .arg(&pipestatus_arg)
                     .arg(&path_arg)
+                    .arg(&logical_path_arg)
                     .arg(&cmd_duration_arg)
                     .arg(&keymap_arg)
-                    .arg(&jobs_arg),
+                    .arg(&jobs_arg)
+                    .arg(&terminal_width_arg)
+                    .arg(&shell_name_arg)
+                    .arg(&profile_name_arg)
+                    .arg(&profile_arg)
+                    .arg(&force_color_arg),
             )
             .subcommand(
                 SubCommand::with_name("config")
                     .alias("configure")
-                    .about("Edit the starship configuration")
+                    .about("Edit the starship configuration, or get or set one of its keys")
+                    .arg(
+                        Arg::with_name("name")
+                            .help("Configuration key to get or set, e.g. directory.truncation_length")
+                            .required(false),
+                    )
+                    .arg(Arg::with_name("value").help("Value to place into that key"))
+                    .arg(
+                        Arg::with_name("check")
+                            .long("check")
+                            .help("Check the configuration for mistakes")
+                            .conflicts_with("name"),
+                    )
+                    .arg(
+                        Arg::with_name("schema")
+                            .long("schema")
+                            .help("Print a JSON Schema of the configuration")
+                            .conflicts_with_all(&["name", "check"]),
+                    )
+                    .subcommand(SubCommand::with_name("migrate").about(
+                        "Replace deprecated options in the configuration file with their replacements",
+                    )),
+            )
+            .subcommand(
+                SubCommand::with_name("toggle")
+                    .about("Toggle a boolean option of a module, which is `disabled` by default")
+                    .arg(
+                        Arg::with_name("name")
+                            .help("The name of the module to toggle")
+                            .required(true),
+                    )
+                    .arg(
+                        Arg::with_name("key")
+                            .help("The boolean option to toggle")
+                            .default_value("disabled"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("print-config")
+                    .about("Prints the configuration in effect, including default values")
                     .arg(
                         Arg::with_name("name")
-                            .help("Configuration key to edit")
-                            .required(false)
-                            .requires("value"),
+                            .help("The name of a module to print the configuration of"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("preset")
+                    .about("Prints a preset configuration, or lists the presets")
+                    .arg(Arg::with_name("name").help("The name of the preset to print")),
+            )
+            .subcommand(
+                SubCommand::with_name("bug-report")
+                    .about(
+                        "Create a pre-populated GitHub issue with information about your configuration",
                     )
-                    .arg(Arg::with_name("value").help("Value to place into that key")),
+                    .arg(
+                        Arg::with_name("open")
+                            .long("open")
+                            .help("Open the issue in the browser instead of printing it"),
+                    ),
             )
-            .subcommand(SubCommand::with_name("bug-report").about(
-                "Create a pre-populated GitHub issue with information about your configuration",
-            ))
             .subcommand(
                 SubCommand::with_name("time")
                     .about("Prints time in milliseconds")
@@ -139,7 +291,85 @@ fn main() {
             .subcommand(
                 SubCommand::with_name("explain").about("Explains the currently showing modules"),
             )
-            .get_matches();
+            .subcommand(
+                SubCommand::with_name("timings")
+                    .about("Prints how long each module of the prompt takes to render"),
+            )
+            .subcommand(
+                SubCommand::with_name("benchmark")
+                    .about("Renders the prompt a number of times and prints the latencies of its modules")
+                    .arg(
+                        Arg::with_name("runs")
+                            .short("n")
+                            .long("runs")
+                            .value_name("RUNS")
+                            .help("How many times the prompt is rendered")
+                            .default_value("10"),
+                    )
+                    .arg(
+                        Arg::with_name("module")
+                            .short("m")
+                            .long("module")
+                            .value_name("MODULE")
+                            .help("Render only this module instead of the prompt")
+                            .takes_value(true),
+                    )
+                    .arg(
+                        Arg::with_name("save")
+                            .long("save")
+                            .value_name("FILE")
+                            .help("Save the latencies to FILE, to compare later runs with")
+                            .takes_value(true),
+                    )
+                    .arg(
+                        Arg::with_name("baseline")
+                            .long("baseline")
+                            .value_name("FILE")
+                            .help("Compare the latencies with the ones saved to FILE")
+                            .takes_value(true),
+                    )
+                    .arg(&path_arg),
+            )
+            .subcommand(
+                SubCommand::with_name("cache")
+                    .about("Manage the cached outputs of commands run by modules, and cached prompts")
+                    .setting(AppSettings::SubcommandRequiredElseHelp)
+                    .subcommand(
+                        SubCommand::with_name("clear")
+                            .about("Removes all cached command outputs and prompts"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("logs")
+                    .about("Prints the log of the shell session that logged last, see $STARSHIP_LOG"),
+            )
+            .subcommand(
+                SubCommand::with_name("session")
+                    .about("Run a session that renders prompts without starting starship each time")
+                    .setting(AppSettings::SubcommandRequiredElseHelp)
+                    .subcommand(
+                        SubCommand::with_name("start")
+                            .about("Serve prompts on the socket in $STARSHIP_SESSION"),
+                    )
+                    .subcommand(SubCommand::with_name("stop").about("Stop the running session")),
+            )
+            .subcommand(
+                SubCommand::with_name("completions")
+                    .about("Prints the shell completions of starship")
+                    .arg(
+                        Arg::with_name("shell")
+                            .value_name("SHELL")
+                            .help("The shell to print completions for")
+                            .required(true)
+                            .possible_values(&Shell::variants()),
+                    ),
+            );
+
+    let matches = app.clone().get_matches();
+
+    let (subcommand, sub_m) = matches.subcommand();
+    profile::enable(sub_m.and_then(|sub_m| sub_m.value_of("profile")));
+    let span = profile::span("starship", format!("starship {}", subcommand));
 
     match matches.subcommand() {
         ("init", Some(sub_m)) => {
@@ -150,29 +380,42 @@ fn main() {
                 init::init_stub(shell_name).expect("can't init_stub");
             }
         }
-        ("prompt", Some(sub_m)) => print::prompt(sub_m.clone()),
+        ("prompt", Some(sub_m)) => match session::render().filter(|_| !profile::is_enabled()) {
+            Some(prompt) => print!("{}", prompt),
+            None => print::prompt(sub_m.clone()),
+        },
         ("module", Some(sub_m)) => {
             if sub_m.is_present("list") {
-                println!("Supported modules list");
-                println!("----------------------");
-                for modules in ALL_MODULES {
-                    println!("{}", modules);
-                }
+                print::list_modules(sub_m.clone());
             }
             if let Some(module_name) = sub_m.value_of("name") {
                 print::module(module_name, sub_m.clone());
             }
         }
         ("config", Some(sub_m)) => {
-            if let Some(name) = sub_m.value_of("name") {
-                if let Some(value) = sub_m.value_of("value") {
-                    configure::update_configuration(name, value)
+            if sub_m.subcommand_matches("migrate").is_some() {
+                configure::migrate_configuration()
+            } else if sub_m.is_present("check") {
+                configure::check_configuration()
+            } else if sub_m.is_present("schema") {
+                configure::print_schema()
+            } else if let Some(name) = sub_m.value_of("name") {
+                match sub_m.value_of("value") {
+                    Some(value) => configure::update_configuration(name, value),
+                    None => configure::print_configuration_value(name),
                 }
             } else {
                 configure::edit_configuration()
             }
         }
-        ("bug-report", Some(_)) => bug_report::create(),
+        ("toggle", Some(sub_m)) => {
+            if let (Some(name), Some(key)) = (sub_m.value_of("name"), sub_m.value_of("key")) {
+                configure::toggle_configuration(name, key)
+            }
+        }
+        ("print-config", Some(sub_m)) => configure::print_configuration(sub_m.value_of("name")),
+        ("preset", Some(sub_m)) => presets::print_preset(sub_m.value_of("name")),
+        ("bug-report", Some(sub_m)) => bug_report::create(sub_m.is_present("open")),
         ("time", _) => {
             match SystemTime::now()
                 .duration_since(SystemTime::UNIX_EPOCH)
@@ -183,6 +426,37 @@ fn main() {
             }
         }
         ("explain", Some(sub_m)) => print::explain(sub_m.clone()),
+        ("timings", Some(sub_m)) => print::timings(sub_m.clone()),
+        ("benchmark", Some(sub_m)) => print::benchmark(sub_m.clone()),
+        ("cache", Some(sub_m)) => {
+            if let ("clear", Some(_)) = sub_m.subcommand() {
+                cache::clear()
+            }
+        }
+        ("logs", _) => logger::print_latest(),
+        ("session", Some(sub_m)) => match sub_m.subcommand() {
+            ("start", Some(_)) => session::start(|args, env, cwd| {
+                let args = std::iter::once(String::from("starship")).chain(args);
+                match app.clone().get_matches_from_safe(args) {
+                    Ok(matches) => match matches.subcommand() {
+                        ("prompt", Some(sub_m)) => {
+                            Some(print::render_prompt(sub_m.clone(), env, cwd))
+                        }
+                        _ => None,
+                    },
+                    Err(_) => None,
+                }
+            }),
+            ("stop", Some(_)) => session::stop(),
+            _ => {}
+        },
+        ("completions", Some(sub_m)) => {
+            let shell = value_t!(sub_m, "shell", Shell).unwrap_or_else(|e| e.exit());
+            app.gen_completions_to("starship", shell, &mut io::stdout().lock());
+        }
         _ => {}
     }
+
+    drop(span);
+    profile::write();
 }
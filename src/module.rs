@@ -1,21 +1,28 @@
-use crate::config::SegmentConfig;
+use crate::config::{display_style, SegmentConfig};
 use crate::context::Shell;
 use crate::segment::Segment;
-use crate::utils::wrap_colorseq_for_shell;
+use crate::utils::{display_width, truncate_to_width, wrap_colorseq_for_shell};
 use ansi_term::Style;
 use ansi_term::{ANSIString, ANSIStrings};
 use std::fmt;
+use unicode_segmentation::UnicodeSegmentation;
 
 // List of all modules
 // Keep these ordered alphabetically.
 // Default ordering is handled in configs/mod.rs
 pub const ALL_MODULES: &[&str] = &[
+    "ansible",
     "aws",
     #[cfg(feature = "battery")]
     "battery",
+    "bun",
     "character",
+    "chroot",
+    "ci",
     "cmd_duration",
+    "commit_signing",
     "conda",
+    "dbt",
     "directory",
     "docker_context",
     "dotnet",
@@ -23,11 +30,14 @@ pub const ALL_MODULES: &[&str] = &[
     "elm",
     "erlang",
     "env_var",
+    "fill",
+    "gcloud",
     "git_branch",
     "git_commit",
     "git_state",
     "git_status",
     "golang",
+    "hashicorp",
     "haskell",
     "hg_branch",
     "hostname",
@@ -41,18 +51,77 @@ pub const ALL_MODULES: &[&str] = &[
     "nodejs",
     "ocaml",
     "package",
+    "proxy",
     "python",
     "ruby",
     "crystal",
     "rust",
     "php",
     "terraform",
+    "tex",
     "singularity",
+    "solidity",
     "time",
+    "typst",
     "username",
+    "vpn",
     "zig",
 ];
 
+/// Groups of modules that a single variable of the prompt format expands to, in the
+/// order they are shown in
+pub const MODULE_GROUPS: &[(&str, &[&str])] = &[
+    (
+        "vcs",
+        &[
+            "git_branch",
+            "git_commit",
+            "git_state",
+            "git_status",
+            "hg_branch",
+            "commit_signing",
+        ],
+    ),
+    (
+        "languages",
+        &[
+            "bun",
+            "crystal",
+            "dotnet",
+            "elixir",
+            "elm",
+            "erlang",
+            "golang",
+            "haskell",
+            "java",
+            "julia",
+            "nodejs",
+            "ocaml",
+            "php",
+            "python",
+            "ruby",
+            "rust",
+            "solidity",
+            "terraform",
+            "tex",
+            "typst",
+            "zig",
+        ],
+    ),
+    (
+        "cloud",
+        &["aws", "gcloud", "kubernetes", "docker_context", "hashicorp"],
+    ),
+];
+
+/// The modules of a group, if a name is the one of a group
+pub fn module_group(name: &str) -> Option<&'static [&'static str]> {
+    MODULE_GROUPS
+        .iter()
+        .find(|(group, _)| *group == name)
+        .map(|(_, modules)| *modules)
+}
+
 /// A module is a collection of segments showing data for a single integration
 /// (e.g. The git module shows the current git branch and status)
 pub struct Module<'a> {
@@ -76,6 +145,9 @@ pub struct Module<'a> {
 
     /// The suffix used to separate the current module from the next one.
     suffix: Affix,
+
+    /// Why the module is shown, e.g. the file or environment variable that was found
+    reason: Option<String>,
 }
 
 impl<'a> Module<'a> {
@@ -89,6 +161,7 @@ impl<'a> Module<'a> {
             prefix: Affix::default_prefix(name),
             segments: Vec::new(),
             suffix: Affix::default_suffix(name),
+            reason: None,
         }
     }
 
@@ -112,6 +185,19 @@ impl<'a> Module<'a> {
         &self._name
     }
 
+    /// Set why the module is shown, to be displayed by `starship explain`
+    pub fn set_reason<T>(&mut self, reason: T)
+    where
+        T: Into<String>,
+    {
+        self.reason = Some(reason.into());
+    }
+
+    /// Get why the module is shown, if known
+    pub fn get_reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+
     /// Get module's description
     pub fn get_description(&self) -> &String {
         &self.description
@@ -126,6 +212,52 @@ impl<'a> Module<'a> {
         self.segments.iter().map(Segment::get_value).collect()
     }
 
+    /// Gets the values of the segments that aren't symbols, like the branch of `git_branch`
+    pub fn get_value_segments(&self) -> Vec<&str> {
+        self.segments
+            .iter()
+            .filter(|segment| !segment.is_symbol())
+            .map(Segment::get_value)
+            .collect()
+    }
+
+    /// Replace the style of every segment
+    pub fn set_segments_style(&mut self, style: Style) {
+        for segment in &mut self.segments {
+            segment.set_style(style);
+        }
+    }
+
+    /// Shorten the segments to at most `max_width` columns in total. If anything was
+    /// cut off, the first grapheme of `symbol` takes the place of the last ones kept.
+    pub fn truncate(&mut self, max_width: usize, symbol: &str) {
+        let width: usize = self
+            .segments
+            .iter()
+            .map(|segment| display_width(&segment.value))
+            .sum();
+        if width <= max_width {
+            return;
+        }
+
+        let symbol = symbol.graphemes(true).next().unwrap_or("");
+        let mut remaining = max_width.saturating_sub(display_width(symbol));
+        let mut symbol = Some(symbol);
+        for segment in &mut self.segments {
+            let width = display_width(&segment.value);
+            if width <= remaining {
+                remaining -= width;
+                continue;
+            }
+            let mut value = truncate_to_width(&segment.value, remaining).to_owned();
+            if let Some(symbol) = symbol.take() {
+                value.push_str(symbol);
+            }
+            segment.set_value(value);
+            remaining = 0;
+        }
+    }
+
     /// Get the module's prefix
     pub fn get_prefix(&mut self) -> &mut Affix {
         &mut self.prefix
@@ -172,8 +304,19 @@ impl<'a> Module<'a> {
         ansi_strings
     }
 
-    pub fn to_string_without_prefix(&self, shell: Shell) -> String {
-        ANSIStrings(&self.ansi_strings_for_shell(shell)[1..]).to_string()
+    /// Returns the module's prefix, segments and suffix as a vector of segments,
+    /// to be used as the value of the module's variable in a format string.
+    ///
+    /// The prefix and suffix segments are named `_prefix` and `_suffix` respectively.
+    pub fn to_segments(&self) -> Vec<Segment> {
+        let mut segments = Vec::with_capacity(self.segments.len() + 2);
+        segments.push(self.prefix.to_segment("_prefix"));
+        segments.extend(self.segments.iter().cloned());
+        segments.push(self.suffix.to_segment("_suffix"));
+        for segment in &mut segments {
+            segment.module = Some(self._name.clone());
+        }
+        segments
     }
 }
 
@@ -245,7 +388,15 @@ impl Affix {
 
     /// Generates the colored ANSIString output.
     pub fn ansi_string(&self) -> ANSIString {
-        self.style.paint(&self.value)
+        display_style(self.style).paint(&self.value)
+    }
+
+    /// Converts the affix into a segment with the given name.
+    fn to_segment(&self, name: &str) -> Segment {
+        let mut segment = Segment::new(name);
+        segment.set_style(self.style);
+        segment.set_value(self.value.as_str());
+        segment
     }
 }
 
@@ -271,6 +422,7 @@ mod tests {
             prefix: Affix::default_prefix(name),
             segments: Vec::new(),
             suffix: Affix::default_suffix(name),
+            reason: None,
         };
 
         assert!(module.is_empty());
@@ -288,8 +440,39 @@ mod tests {
             prefix: Affix::default_prefix(name),
             segments: vec![Segment::new("test_segment")],
             suffix: Affix::default_suffix(name),
+            reason: None,
         };
 
         assert!(module.is_empty());
     }
+
+    #[test]
+    fn test_module_truncate() {
+        let mut module = Module::new("unit_test", "This is a unit test", None);
+        module.create_segment("symbol", &SegmentConfig::new("☸ "));
+        module.create_segment("context", &SegmentConfig::new("a-very-long-context"));
+        module.create_segment("namespace", &SegmentConfig::new(" (default)"));
+
+        module.truncate(8, "…");
+        assert_eq!(module.get_segments(), vec!["☸ ", "a-ver…", ""]);
+    }
+
+    #[test]
+    fn test_module_truncate_short_enough() {
+        let mut module = Module::new("unit_test", "This is a unit test", None);
+        module.create_segment("name", &SegmentConfig::new("e\u{301}toile"));
+
+        module.truncate(6, "…");
+        assert_eq!(module.get_segments(), vec!["e\u{301}toile"]);
+    }
+
+    #[test]
+    fn test_module_truncate_wide() {
+        let mut module = Module::new("unit_test", "This is a unit test", None);
+        module.create_segment("symbol", &SegmentConfig::new("🌳 "));
+        module.create_segment("name", &SegmentConfig::new("功能分支"));
+
+        module.truncate(8, "…");
+        assert_eq!(module.get_segments(), vec!["🌳 ", "功能…"]);
+    }
 }
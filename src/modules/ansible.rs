@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::ansible::AnsibleConfig;
+use crate::formatter::StringFormatter;
+use crate::utils;
+
+/// Creates a module with the ansible-core version and the inventory it runs against
+///
+/// Will display the version if any of the following criteria are met:
+///     - The current directory contains an `ansible.cfg`, `site.yml` or `playbook.yml` file
+///     - The current directory contains a `group_vars` or `host_vars` directory
+///
+/// The inventory is `$ANSIBLE_INVENTORY`, or else the `inventory` of the `ansible.cfg`
+/// Ansible reads, which is `$ANSIBLE_CONFIG`, the one of the current directory or
+/// `~/.ansible.cfg`.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    // Only inside a project, so that `ansible` isn't run in every directory
+    let reason = context
+        .try_begin_scan()?
+        .set_files(&["ansible.cfg", "site.yml", "playbook.yml"])
+        .set_folders(&["group_vars", "host_vars"])
+        .find_match()?;
+    let env_config = context
+        .get_env("ANSIBLE_CONFIG")
+        .filter(|path| !path.is_empty());
+    let env_inventory = context
+        .get_env("ANSIBLE_INVENTORY")
+        .filter(|inventory| !inventory.is_empty());
+
+    let mut module = context.new_module("ansible");
+    module.set_reason(reason);
+    let config: AnsibleConfig = AnsibleConfig::try_load(module.config);
+
+    module.set_style(config.style);
+
+    let version = context
+        .exec_cmd("ansible", "ansible", &["--version"])
+        .and_then(|output| parse_ansible_version(&output.stdout));
+    let inventory = env_inventory.or_else(|| {
+        let cfg = get_config_path(context, env_config.as_deref())?;
+        let cfg = utils::read_file(cfg).ok()?;
+        utils::get_ini_value(&cfg, "defaults", "inventory")
+            .or_else(|| utils::get_ini_value(&cfg, "defaults", "hostfile"))
+            .filter(|inventory| !inventory.is_empty())
+    });
+    let inventory = inventory.map(|inventory| alias(&inventory, &config.aliases));
+    let env_config = env_config.map(|path| alias(&path, &config.aliases));
+    // The inventory tells the hosts apart best, when it's known
+    let environment = inventory.clone().or_else(|| env_config.clone());
+    if version.is_none() && environment.is_none() {
+        return None;
+    }
+
+    let formatter = match StringFormatter::new(config.format) {
+        Ok(formatter) => formatter,
+        Err(error) => {
+            log::warn!("Error parsing `ansible.format`:\n{}", error);
+            return None;
+        }
+    };
+    let segments = formatter
+        .map(|variable| match variable {
+            "symbol" => Some(config.symbol.value.to_owned()),
+            "version" => version.clone(),
+            "inventory" => inventory.clone(),
+            "config" => env_config.clone(),
+            "environment" => environment.clone(),
+            _ => None,
+        })
+        .parse(Some(config.style));
+    module.set_segments(segments);
+
+    Some(module)
+}
+
+/// The `ansible.cfg` Ansible reads, the first one found of those it looks for
+fn get_config_path(context: &Context, env_config: Option<&str>) -> Option<PathBuf> {
+    if let Some(path) = env_config {
+        return Some(Context::expand_tilde(PathBuf::from(path)));
+    }
+    let local = context.current_dir.join("ansible.cfg");
+    if local.is_file() {
+        return Some(local);
+    }
+    Some(dirs::home_dir()?.join(".ansible.cfg")).filter(|path| path.is_file())
+}
+
+/// The alias of an inventory or config path, which is matched as it's written, as the
+/// path it's for, or by the name of its file or directory
+fn alias(path: &str, aliases: &HashMap<String, &str>) -> String {
+    let expanded = Context::expand_tilde(PathBuf::from(path));
+    let name = Path::new(path.trim_end_matches('/'))
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned());
+    aliases
+        .get(path)
+        .or_else(|| {
+            aliases
+                .iter()
+                .find(|(alias_path, _)| {
+                    Context::expand_tilde(PathBuf::from(alias_path)) == expanded
+                })
+                .map(|(_, alias)| alias)
+        })
+        .or_else(|| aliases.get(name.as_ref()?))
+        .map_or_else(|| path.to_owned(), |alias| (*alias).to_owned())
+}
+
+fn parse_ansible_version(ansible_stdout: &str) -> Option<String> {
+    // ansible --version output starts like this:
+    // ansible [core 2.15.4]
+    // or before ansible-core was split out:
+    // ansible 2.9.27
+    let first_line = ansible_stdout.lines().next()?;
+    let version = first_line
+        .trim_start_matches("ansible")
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .trim_start_matches("core")
+        .trim();
+    if version.is_empty() {
+        None
+    } else {
+        Some(format!("v{}", version))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::ModuleRenderer;
+    use crate::utils::CommandOutput;
+    use ansi_term::Color;
+    use std::fs::{self, File};
+    use std::io;
+
+    #[test]
+    fn test_parse_ansible_version() {
+        let core = "ansible [core 2.15.4]\n  config file = None\n";
+        assert_eq!(parse_ansible_version(core), Some(String::from("v2.15.4")));
+        let legacy = "ansible 2.9.27\n  config file = /etc/ansible/ansible.cfg\n";
+        assert_eq!(parse_ansible_version(legacy), Some(String::from("v2.9.27")));
+        assert_eq!(parse_ansible_version(""), None);
+    }
+
+    #[test]
+    fn folder_without_ansible_files() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("main.yml"))?.sync_all()?;
+        let actual = ModuleRenderer::new("ansible").path(dir.path()).collect();
+        assert_eq!(None, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_inventory_in_ansible_cfg() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("ansible.cfg"),
+            "[defaults]\ninventory = inventories/staging\nforks = 20\n",
+        )?;
+        let actual = ModuleRenderer::new("ansible").path(dir.path()).collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("🅰 v2.15.4 (inventories/staging)")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_playbook_layout() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir(dir.path().join("group_vars"))?;
+        let actual = ModuleRenderer::new("ansible")
+            .path(dir.path())
+            .cmd(
+                "ansible --version",
+                Some(CommandOutput::stdout("ansible 2.9.27\n")),
+            )
+            .collect();
+        let expected = Some(format!("via {} ", Color::Red.bold().paint("🅰 v2.9.27")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn env_outside_of_project() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let actual = ModuleRenderer::new("ansible")
+            .path(dir.path())
+            .env("ANSIBLE_CONFIG", "/etc/ansible/ops.cfg")
+            .env("ANSIBLE_INVENTORY", "/srv/ansible/inventories/production/")
+            .cmd("ansible --version", None)
+            .collect();
+        assert_eq!(None, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn inventory_from_env_with_alias() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("site.yml"))?.sync_all()?;
+        let actual = ModuleRenderer::new("ansible")
+            .path(dir.path())
+            .env("ANSIBLE_INVENTORY", "/srv/ansible/inventories/production/")
+            .config(toml::toml! {
+                [ansible]
+                [ansible.aliases]
+                production = "PROD"
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("🅰 v2.15.4 (PROD)")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn config_from_env() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cfg = dir.path().join("ops.cfg");
+        fs::write(&cfg, "[defaults]\nremote_user = deploy\n")?;
+        let cfg = cfg.to_str().unwrap();
+
+        File::create(dir.path().join("playbook.yml"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("ansible")
+            .path(dir.path())
+            .env("ANSIBLE_CONFIG", cfg)
+            .cmd("ansible --version", None)
+            .config(toml::toml! {
+                [ansible]
+                format = "$symbol$version\\[$config\\]"
+                [ansible.aliases]
+                "ops.cfg" = "ops"
+            })
+            .collect();
+        let expected = Some(format!("via {} ", Color::Red.bold().paint("🅰 [ops]")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+}
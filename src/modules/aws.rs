@@ -1,12 +1,14 @@
 use std::collections::HashMap;
-use std::env;
-use std::fs::File;
+use std::ffi::OsStr;
+use std::fs::{self, File};
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
+use chrono::{DateTime, Utc};
 use dirs::home_dir;
 
+use super::utils::redact::redact;
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::aws::{AwsConfig, AwsItems};
@@ -14,9 +16,10 @@ use crate::configs::aws::{AwsConfig, AwsItems};
 type Profile = String;
 type Region = String;
 
-fn get_aws_region_from_config(aws_profile: Option<&str>) -> Option<Region> {
-    let config_location = env::var("AWS_CONFIG_FILE")
-        .ok()
+/// A setting of a profile, or of the default one, in the AWS config file
+fn get_aws_config_value(context: &Context, aws_profile: Option<&str>, key: &str) -> Option<String> {
+    let config_location = context
+        .get_env("AWS_CONFIG_FILE")
         .and_then(|path| PathBuf::from_str(&path).ok())
         .or_else(|| {
             let mut home = home_dir()?;
@@ -28,54 +31,55 @@ fn get_aws_region_from_config(aws_profile: Option<&str>) -> Option<Region> {
     let reader = BufReader::new(file);
     let lines = reader.lines().filter_map(Result::ok);
 
-    let region_line = if let Some(ref aws_profile) = aws_profile {
-        lines
-            .skip_while(|line| line != &format!("[profile {}]", aws_profile))
-            .skip(1)
-            .take_while(|line| !line.starts_with('['))
-            .find(|line| line.starts_with("region"))
-    } else {
-        lines
-            .skip_while(|line| line != "[default]")
-            .skip(1)
-            .take_while(|line| !line.starts_with('['))
-            .find(|line| line.starts_with("region"))
-    }?;
-
-    let region = region_line.split('=').nth(1)?;
-    let region = region.trim();
-
-    Some(region.to_string())
+    let section = match aws_profile {
+        Some(aws_profile) => format!("[profile {}]", aws_profile),
+        None => String::from("[default]"),
+    };
+    let line = lines
+        .skip_while(|line| line != &section)
+        .skip(1)
+        .take_while(|line| !line.starts_with('['))
+        .find(|line| line.split('=').next().map(str::trim) == Some(key))?;
+
+    let (_, value) = line.split_once('=')?;
+    Some(value.trim().to_string())
+}
+
+fn get_aws_region_from_config(context: &Context, aws_profile: Option<&str>) -> Option<Region> {
+    get_aws_config_value(context, aws_profile, "region")
 }
 
-fn get_aws_profile_and_region() -> (Option<Profile>, Option<Region>) {
+fn get_aws_profile_and_region(context: &Context) -> (Option<Profile>, Option<Region>) {
     match (
-        env::var("AWS_VAULT")
-            .or_else(|_| env::var("AWS_PROFILE"))
-            .ok(),
-        env::var("AWS_REGION").ok(),
-        env::var("AWS_DEFAULT_REGION").ok(),
+        context
+            .get_env("AWS_VAULT")
+            .or_else(|| context.get_env("AWS_PROFILE")),
+        context.get_env("AWS_REGION"),
+        context.get_env("AWS_DEFAULT_REGION"),
     ) {
         (Some(p), Some(_), Some(dr)) => (Some(p), Some(dr)),
         (Some(p), Some(r), None) => (Some(p), Some(r)),
         (None, Some(r), None) => (None, Some(r)),
         (Some(p), None, Some(dr)) => (Some(p), Some(dr)),
-        (Some(ref p), None, None) => (Some(p.to_owned()), get_aws_region_from_config(Some(p))),
+        (Some(ref p), None, None) => (
+            Some(p.to_owned()),
+            get_aws_region_from_config(context, Some(p)),
+        ),
         (None, None, Some(dr)) => (None, Some(dr)),
         (None, Some(_), Some(dr)) => (None, Some(dr)),
-        (None, None, None) => (None, get_aws_region_from_config(None)),
+        (None, None, None) => (None, get_aws_region_from_config(context, None)),
     }
 }
 
-fn get_aws_region() -> Option<Region> {
+fn get_aws_region(context: &Context) -> Option<Region> {
     match (
-        env::var("AWS_REGION").ok(),
-        env::var("AWS_DEFAULT_REGION").ok(),
+        context.get_env("AWS_REGION"),
+        context.get_env("AWS_DEFAULT_REGION"),
     ) {
         (Some(r), None) => Some(r),
         (None, Some(dr)) => Some(dr),
         (Some(_), Some(dr)) => Some(dr),
-        (None, None) => get_aws_region_from_config(None),
+        (None, None) => get_aws_region_from_config(context, None),
     }
 }
 
@@ -86,6 +90,71 @@ fn alias_region(region: &str, aliases: &HashMap<String, &str>) -> String {
     }
 }
 
+/// When the credentials in use expire, as told by `aws-vault` or, for an SSO profile, by
+/// the AWS CLI's cache of SSO tokens
+fn get_credentials_expiration(context: &Context) -> Option<DateTime<Utc>> {
+    let from_env = context
+        .get_env("AWS_SESSION_EXPIRATION")
+        .or_else(|| context.get_env("AWS_CREDENTIAL_EXPIRATION"));
+    if let Some(expiration) = from_env {
+        return parse_expiration(&expiration);
+    }
+
+    let aws_profile = context
+        .get_env("AWS_VAULT")
+        .or_else(|| context.get_env("AWS_PROFILE"));
+    let start_url = get_aws_config_value(context, aws_profile.as_deref(), "sso_start_url")?;
+    get_sso_expiration(&home_dir()?.join(".aws/sso/cache"), &start_url)
+}
+
+/// When the cached token of an SSO portal expires
+fn get_sso_expiration(cache: &Path, start_url: &str) -> Option<DateTime<Utc>> {
+    fs::read_dir(cache)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(OsStr::to_str) == Some("json"))
+        .filter_map(|path| {
+            let token: serde_json::Value =
+                serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+            if token["startUrl"].as_str()? != start_url {
+                return None;
+            }
+            parse_expiration(token["expiresAt"].as_str()?)
+        })
+        .max()
+}
+
+/// Parse a timestamp like `2020-06-17T10:18:53Z`, which older versions of the AWS CLI
+/// wrote as `2020-06-17T10:18:53UTC`
+fn parse_expiration(expiration: &str) -> Option<DateTime<Utc>> {
+    let expiration = match expiration.strip_suffix("UTC") {
+        Some(timestamp) => format!("{}Z", timestamp),
+        None => expiration.to_owned(),
+    };
+    DateTime::parse_from_rfc3339(&expiration)
+        .map(|expiration| expiration.with_timezone(&Utc))
+        .map_err(|e| log::debug!("Unable to parse the expiration {}: {}", expiration, e))
+        .ok()
+}
+
+/// The time left before credentials expire, like `1h5m` or `14m`, and whether it's under the
+/// threshold, in seconds, of the warning
+fn render_remaining_time(
+    expiration: DateTime<Utc>,
+    now: DateTime<Utc>,
+    threshold: i64,
+) -> (String, bool) {
+    let seconds = (expiration - now).num_seconds();
+    let rendered = match seconds {
+        s if s <= 0 => String::from("expired"),
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m", s / 60),
+        s => format!("{}h{}m", s / 3600, s % 3600 / 60),
+    };
+    (rendered, seconds < threshold)
+}
+
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     const AWS_PREFIX: &str = "on ";
 
@@ -99,7 +168,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.create_segment("symbol", &config.symbol);
     match config.displayed_items {
         AwsItems::All => {
-            let (aws_profile, aws_region) = get_aws_profile_and_region();
+            let (aws_profile, aws_region) = get_aws_profile_and_region(context);
+            let aws_profile = aws_profile.map(|profile| redact(&profile, config.redact));
 
             let aws_segment = match (&aws_profile, &aws_region) {
                 (None, None) => return None,
@@ -110,16 +180,55 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             module.create_segment("all", &config.region.with_value(&aws_segment));
         }
         AwsItems::Profile => {
-            let aws_profile = env::var("AWS_PROFILE").ok()?;
+            let aws_profile = redact(&context.get_env("AWS_PROFILE")?, config.redact);
 
             module.create_segment("profile", &config.profile.with_value(&aws_profile));
         }
         AwsItems::Region => {
-            let aws_region = alias_region(&get_aws_region()?, &config.region_aliases);
+            let aws_region = alias_region(&get_aws_region(context)?, &config.region_aliases);
 
             module.create_segment("region", &config.region.with_value(&aws_region));
         }
     };
 
+    if let Some(expiration) = get_credentials_expiration(context) {
+        let (remaining, is_close) =
+            render_remaining_time(expiration, Utc::now(), config.expiration_threshold);
+        let rendered = format!(" ({})", remaining);
+        let mut segment = config.expiration.with_value(&rendered);
+        if is_close {
+            segment = segment.with_style(Some(config.expiration_style));
+        }
+        module.create_segment("expiration", &segment);
+    }
+
     Some(module)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn parse_expiration_formats() {
+        let expected = Utc.ymd(2020, 6, 17).and_hms(10, 18, 53);
+        assert_eq!(parse_expiration("2020-06-17T10:18:53Z"), Some(expected));
+        assert_eq!(parse_expiration("2020-06-17T10:18:53UTC"), Some(expected));
+        assert_eq!(
+            parse_expiration("2020-06-17T12:18:53+02:00"),
+            Some(expected)
+        );
+        assert_eq!(parse_expiration("tomorrow"), None);
+    }
+
+    #[test]
+    fn remaining_time() {
+        let now = Utc.ymd(2020, 6, 17).and_hms(10, 0, 0);
+        let render = |seconds| render_remaining_time(now + Duration::seconds(seconds), now, 900);
+        assert_eq!(render(4000), (String::from("1h6m"), false));
+        assert_eq!(render(840), (String::from("14m"), true));
+        assert_eq!(render(30), (String::from("30s"), true));
+        assert_eq!(render(-5), (String::from("expired"), true));
+    }
+}
@@ -1,3 +1,4 @@
+use super::utils::battery::{BatteryInfo, BatteryProvider, NativeBatteries, State};
 use super::{Context, Module, RootModuleConfig, Shell};
 use crate::configs::battery::BatteryConfig;
 
@@ -6,11 +7,12 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     // TODO: Update when v1.0 printing refactor is implemented to only
     // print escapes in a prompt context.
     let percentage_char = match context.shell {
-        Shell::Zsh => "%%", // % is an escape in zsh, see PROMPT in `man zshmisc`
+        // % is an escape in zsh and tcsh, see PROMPT in `man zshmisc`
+        Shell::Zsh | Shell::Tcsh => "%%",
         _ => "%",
     };
 
-    let battery_status = get_battery_status()?;
+    let battery_status = get_battery_status(&NativeBatteries)?;
     let BatteryStatus { state, percentage } = battery_status;
 
     let mut module = context.new_module("battery");
@@ -28,30 +30,26 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         module.get_prefix().set_value("");
 
         match state {
-            battery::State::Full => {
+            State::Full => {
                 module.create_segment("full_symbol", &battery_config.full_symbol);
             }
-            battery::State::Charging => {
+            State::Charging => {
                 module.create_segment("charging_symbol", &battery_config.charging_symbol);
             }
-            battery::State::Discharging => {
+            State::Discharging => {
                 module.create_segment("discharging_symbol", &battery_config.discharging_symbol);
             }
-            battery::State::Unknown => {
+            State::Unknown => {
                 log::debug!("Unknown detected");
                 if let Some(unknown_symbol) = battery_config.unknown_symbol {
                     module.create_segment("unknown_symbol", &unknown_symbol);
                 }
             }
-            battery::State::Empty => {
+            State::Empty => {
                 if let Some(empty_symbol) = battery_config.empty_symbol {
                     module.create_segment("empty_symbol", &empty_symbol);
                 }
             }
-            _ => {
-                log::debug!("Unhandled battery state `{}`", state);
-                return None;
-            }
         }
 
         let mut percent_string = Vec::<String>::with_capacity(2);
@@ -71,37 +69,20 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     }
 }
 
-fn get_battery_status() -> Option<BatteryStatus> {
-    let battery_manager = battery::Manager::new().ok()?;
-    let batteries = battery_manager.batteries().ok()?;
-    let battery_contructor = batteries
-        .filter_map(|battery| match battery {
-            Ok(battery) => {
-                log::debug!("Battery found: {:?}", battery);
-                Some(BatteryInfo {
-                    energy: battery.energy().value,
-                    energy_full: battery.energy_full().value,
-                    state: battery.state(),
-                })
-            }
-            Err(e) => {
-                log::debug!("Unable to access battery information:\n{}", &e);
-                None
-            }
-        })
-        .fold(
-            BatteryInfo {
-                energy: 0.0,
-                energy_full: 0.0,
-                state: battery::State::Unknown,
-            },
-            |mut acc, x| {
-                acc.energy += x.energy;
-                acc.energy_full += x.energy_full;
-                acc.state = merge_battery_states(acc.state, x.state);
-                acc
-            },
-        );
+fn get_battery_status(provider: &impl BatteryProvider) -> Option<BatteryStatus> {
+    let battery_contructor = provider.batteries().into_iter().fold(
+        BatteryInfo {
+            energy: 0.0,
+            energy_full: 0.0,
+            state: State::Unknown,
+        },
+        |mut acc, x| {
+            acc.energy += x.energy;
+            acc.energy_full += x.energy_full;
+            acc.state = merge_battery_states(acc.state, x.state);
+            acc
+        },
+    );
     if battery_contructor.energy_full != 0.0 {
         let battery = BatteryStatus {
             percentage: battery_contructor.energy / battery_contructor.energy_full * 100.0,
@@ -119,8 +100,8 @@ fn get_battery_status() -> Option<BatteryStatus> {
 ///                   Full if both are Full or one is Full and the other Unknow
 ///                   Empty if both are Empty or one is Empty and the other Unknow
 ///                   Unknown otherwise
-fn merge_battery_states(state1: battery::State, state2: battery::State) -> battery::State {
-    use battery::State::{Charging, Discharging, Unknown};
+fn merge_battery_states(state1: State, state2: State) -> State {
+    use State::{Charging, Discharging, Unknown};
     if state1 == Charging || state2 == Charging {
         Charging
     } else if state1 == Discharging || state2 == Discharging {
@@ -136,14 +117,44 @@ fn merge_battery_states(state1: battery::State, state2: battery::State) -> batte
     }
 }
 
-struct BatteryInfo {
-    energy: f32,
-    energy_full: f32,
-    state: battery::State,
-}
-
 #[derive(Debug)]
 struct BatteryStatus {
     percentage: f32,
-    state: battery::State,
+    state: State,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Batteries(Vec<(f32, f32, State)>);
+
+    impl BatteryProvider for Batteries {
+        fn batteries(&self) -> Vec<BatteryInfo> {
+            self.0
+                .iter()
+                .map(|&(energy, energy_full, state)| BatteryInfo {
+                    energy,
+                    energy_full,
+                    state,
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn battery_status_of_every_battery() {
+        let batteries = Batteries(vec![
+            (10.0, 40.0, State::Charging),
+            (25.0, 60.0, State::Full),
+        ]);
+        let status = get_battery_status(&batteries).unwrap();
+        assert_eq!(status.percentage, 35.0);
+        assert_eq!(status.state, State::Charging);
+    }
+
+    #[test]
+    fn battery_status_without_batteries() {
+        assert!(get_battery_status(&Batteries(Vec::new())).is_none());
+    }
 }
@@ -0,0 +1,71 @@
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::bun::BunConfig;
+
+/// Creates a module with the current Bun version
+///
+/// Will display the Bun version if any of the following criteria are met:
+///     - The current directory contains a `bun.lockb` file
+///     - The current directory contains a `bunfig.toml` file
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let reason = context
+        .try_begin_scan()?
+        .set_files(&["bun.lockb", "bunfig.toml"])
+        .find_match()?;
+
+    let bun_version_output = context
+        .exec_cmd("bun", "bun", &["--version"])?
+        .stdout
+        .trim()
+        .to_string();
+    let bun_version = format!("v{}", bun_version_output);
+
+    let mut module = context.new_module("bun");
+    module.set_reason(reason);
+    let config = BunConfig::try_load(module.config);
+
+    module.set_style(config.style);
+
+    module.create_segment("symbol", &config.symbol);
+    module.create_segment("version", &config.version.with_value(&bun_version));
+
+    Some(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::modules::utils::test::render_module;
+    use ansi_term::Color;
+    use std::fs::File;
+    use std::io;
+
+    #[test]
+    fn folder_without_bun_files() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("package.json"))?.sync_all()?;
+        let actual = render_module("bun", dir.path(), None);
+        let expected = None;
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_bun_lockfile() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("bun.lockb"))?.sync_all()?;
+        let actual = render_module("bun", dir.path(), None);
+        let expected = Some(format!("via {} ", Color::Red.bold().paint("🍞 v1.0.3")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_bunfig() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("bunfig.toml"))?.sync_all()?;
+        let actual = render_module("bun", dir.path(), None);
+        let expected = Some(format!("via {} ", Color::Red.bold().paint("🍞 v1.0.3")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+}
@@ -9,6 +9,8 @@ use crate::configs::character::CharacterConfig;
 /// (green by default)
 /// - If the exit-code was anything else, the arrow will be formatted with
 /// `style_failure` (red by default)
+///
+/// Each symbol can have a style of its own, and can be set for a shell in `shells`.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     enum ShellEditMode {
         Normal,
@@ -19,6 +21,11 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 
     let mut module = context.new_module("character");
     let config: CharacterConfig = CharacterConfig::try_load(module.config);
+    let shell_symbols = config
+        .shells
+        .get(context.shell.name())
+        .cloned()
+        .unwrap_or_default();
     module.get_prefix().set_value("");
 
     let props = &context.properties;
@@ -47,11 +54,20 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     /* If an error symbol is set in the config, use symbols to indicate
     success/failure, in addition to color */
     if config.use_symbol_for_status && !exit_success {
-        module.create_segment("error_symbol", &config.error_symbol)
+        let error_symbol = shell_symbols.error_symbol.unwrap_or(config.error_symbol);
+        module.create_segment("error_symbol", &error_symbol)
     } else {
         match mode {
-            ShellEditMode::Normal => module.create_segment("vicmd_symbol", &config.vicmd_symbol),
-            ShellEditMode::Insert => module.create_segment("symbol", &config.symbol),
+            ShellEditMode::Normal => {
+                let vicmd_symbol = shell_symbols.vicmd_symbol.unwrap_or(config.vicmd_symbol);
+                module.create_segment("vicmd_symbol", &vicmd_symbol)
+            }
+            ShellEditMode::Insert => {
+                let success_symbol = shell_symbols
+                    .success_symbol
+                    .unwrap_or(config.success_symbol);
+                module.create_segment("success_symbol", &success_symbol)
+            }
         }
     };
 
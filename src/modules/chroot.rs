@@ -0,0 +1,91 @@
+use std::path::Path;
+
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::chroot::ChrootConfig;
+use crate::utils;
+
+/// Creates a module with the name of the chroot the shell is running in
+///
+/// Will display the name of the chroot if the first of these gives one:
+///     - `$SCHROOT_CHROOT_NAME`, set in schroot sessions
+///     - `$debian_chroot`, or the `/etc/debian_chroot` file it's usually read from
+///     - The root directory being another one than the root of the init process, in
+///       which case the chroot is only named `chroot`
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let (name, reason) = get_chroot_name(context)?;
+
+    let mut module = context.new_module("chroot");
+    module.set_reason(reason);
+    let config: ChrootConfig = ChrootConfig::try_load(module.config);
+
+    module.get_prefix().set_value("in ");
+    module.set_style(config.style);
+    module.create_segment("symbol", &config.symbol);
+    module.create_segment("name", &config.name.with_value(&name));
+
+    Some(module)
+}
+
+fn get_chroot_name(context: &Context) -> Option<(String, &'static str)> {
+    let from_env = |name| {
+        context
+            .get_env(name)
+            .map(|value| value.trim().to_owned())
+            .filter(|value| !value.is_empty())
+    };
+
+    if let Some(name) = from_env("SCHROOT_CHROOT_NAME") {
+        return Some((name, "`$SCHROOT_CHROOT_NAME` is set"));
+    }
+    if let Some(name) = from_env("debian_chroot") {
+        return Some((name, "`$debian_chroot` is set"));
+    }
+    if let Ok(name) = utils::read_file("/etc/debian_chroot") {
+        let name = name.trim();
+        if !name.is_empty() {
+            return Some((name.to_owned(), "`/etc/debian_chroot` names the chroot"));
+        }
+    }
+    if is_other_root(Path::new("/"), Path::new("/proc/1/root")) {
+        return Some((
+            String::from("chroot"),
+            "`/` isn't the root of the init process",
+        ));
+    }
+    None
+}
+
+/// Whether a root directory is another one than the root of a process, like that of init
+/// in `/proc/1/root`, which can only be told when it can be read
+#[cfg(unix)]
+fn is_other_root(root: &Path, process_root: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (root.metadata(), process_root.metadata()) {
+        (Ok(root), Ok(process_root)) => {
+            root.dev() != process_root.dev() || root.ino() != process_root.ino()
+        }
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_other_root(_root: &Path, _process_root: &Path) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn compare_roots() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        assert!(is_other_root(dir.path(), Path::new("/")));
+        assert!(!is_other_root(Path::new("/"), Path::new("/")));
+        assert!(!is_other_root(Path::new("/"), &dir.path().join("missing")));
+        dir.close()
+    }
+}
@@ -0,0 +1,76 @@
+use super::{Context, Module, RootModuleConfig, SegmentConfig};
+
+use crate::configs::ci::CiConfig;
+
+/// A CI provider that can be detected from the environment
+#[derive(Debug, PartialEq)]
+enum CiProvider {
+    GithubActions,
+    GitlabCi,
+    Buildkite,
+    Generic,
+}
+
+impl CiProvider {
+    fn name(&self) -> &'static str {
+        match self {
+            CiProvider::GithubActions => "GitHub Actions",
+            CiProvider::GitlabCi => "GitLab CI",
+            CiProvider::Buildkite => "Buildkite",
+            CiProvider::Generic => "CI",
+        }
+    }
+}
+
+/// Creates a module showing the CI provider the shell is running under
+///
+/// Will display the CI provider if any of the following variables are set:
+///     - `$GITHUB_ACTIONS`
+///     - `$GITLAB_CI`
+///     - `$BUILDKITE`
+///     - `$CI` (reported as a generic provider)
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let provider = detect_provider(context)?;
+
+    let mut module = context.new_module("ci");
+    let config: CiConfig = CiConfig::try_load(module.config);
+
+    module.get_prefix().set_value("on ");
+    module.set_style(config.style);
+
+    let symbol: &SegmentConfig = match provider {
+        CiProvider::GithubActions => &config.github_actions_symbol,
+        CiProvider::GitlabCi => &config.gitlab_ci_symbol,
+        CiProvider::Buildkite => &config.buildkite_symbol,
+        CiProvider::Generic => &config.symbol,
+    };
+    module.create_segment("symbol", symbol);
+
+    if config.show_provider {
+        module.create_segment("provider", &SegmentConfig::new(provider.name()));
+    }
+
+    Some(module)
+}
+
+fn detect_provider(context: &Context) -> Option<CiProvider> {
+    // Providers are checked before the generic `$CI` because most of them also set it.
+    if is_env_set(context, "GITHUB_ACTIONS") {
+        Some(CiProvider::GithubActions)
+    } else if is_env_set(context, "GITLAB_CI") {
+        Some(CiProvider::GitlabCi)
+    } else if is_env_set(context, "BUILDKITE") {
+        Some(CiProvider::Buildkite)
+    } else if is_env_set(context, "CI") {
+        Some(CiProvider::Generic)
+    } else {
+        None
+    }
+}
+
+fn is_env_set(context: &Context, name: &str) -> bool {
+    match context.get_env(name) {
+        Some(value) => !value.trim().is_empty() && value != "false" && value != "0",
+        None => false,
+    }
+}
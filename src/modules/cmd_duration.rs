@@ -1,3 +1,4 @@
+use super::utils::locale::Locale;
 use super::{Context, Module, SegmentConfig};
 
 use crate::config::RootModuleConfig;
@@ -12,9 +13,13 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let config: CmdDurationConfig = CmdDurationConfig::try_load(module.config);
 
     let props = &context.properties;
+    // Some shells, like ion, report a fractional duration, of which only the whole
+    // milliseconds are shown
     let elapsed = props
         .get("cmd_duration")
         .unwrap_or(&"invalid_time".into())
+        .split('.')
+        .next()?
         .parse::<u128>()
         .ok()?;
 
@@ -35,11 +40,15 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         _ => config.style,
     };
 
+    let rendered_time = if config.use_locale && config.show_milliseconds && elapsed >= 1000 {
+        let locale = Locale::from_env(context);
+        render_fractional_time(elapsed, locale.decimal_separator)
+    } else {
+        render_time(elapsed, config.show_milliseconds)
+    };
+
     module.set_style(module_color);
-    module.create_segment(
-        "cmd_duration",
-        &SegmentConfig::new(&render_time(elapsed, config.show_milliseconds)),
-    );
+    module.create_segment("cmd_duration", &SegmentConfig::new(&rendered_time));
     module.get_prefix().set_value(config.prefix);
 
     Some(module)
@@ -67,6 +76,19 @@ fn render_time(raw_millis: u128, show_millis: bool) -> String {
     rendered_components.join("")
 }
 
+/// Render the time with the milliseconds as a fraction of the seconds, e.g. `1m3,250s`
+fn render_fractional_time(raw_millis: u128, decimal_separator: &str) -> String {
+    let (millis, whole_minutes) = (raw_millis % 60_000, raw_millis - raw_millis % 60_000);
+    let rendered_minutes = render_time(whole_minutes, false);
+    match (millis / 1000, millis % 1000) {
+        (_, 0) => format!("{}{}", rendered_minutes, render_time(millis, false)),
+        (seconds, fraction) => format!(
+            "{}{}{}{:03}s",
+            rendered_minutes, seconds, decimal_separator, fraction
+        ),
+    }
+}
+
 /// Render a single component of the time string, giving an empty string if component is zero
 fn render_time_component((component, suffix): (&u128, &&str)) -> String {
     match component {
@@ -99,4 +121,16 @@ mod tests {
     fn test_1d() {
         assert_eq!(render_time(86_400_000 as u128, true), "1d")
     }
+    #[test]
+    fn test_fractional_3250ms() {
+        assert_eq!(render_fractional_time(3_250, ","), "3,250s")
+    }
+    #[test]
+    fn test_fractional_60250ms() {
+        assert_eq!(render_fractional_time(60_250, ","), "1m0,250s")
+    }
+    #[test]
+    fn test_fractional_90s() {
+        assert_eq!(render_fractional_time(90_000, ","), "1m30s")
+    }
 }
@@ -1,5 +1,3 @@
-use std::env;
-
 use super::{Context, Module};
 
 use super::utils::directory::truncate;
@@ -11,12 +9,13 @@ use crate::configs::conda::CondaConfig;
 /// Will display the Conda environment iff `$CONDA_DEFAULT_ENV` is set.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     // Reference implementation: https://github.com/denysdovhan/spaceship-prompt/blob/master/sections/conda.zsh
-    let conda_env = env::var("CONDA_DEFAULT_ENV").unwrap_or_else(|_| "".into());
+    let conda_env = context.get_env("CONDA_DEFAULT_ENV").unwrap_or_default();
     if conda_env.trim().is_empty() {
         return None;
     }
 
     let mut module = context.new_module("conda");
+    module.set_reason("`$CONDA_DEFAULT_ENV` is set");
     let config = CondaConfig::try_load(module.config);
 
     let conda_env = truncate(conda_env, config.truncation_length);
@@ -1,7 +1,6 @@
 use super::{Context, Module, RootModuleConfig, SegmentConfig};
 
 use crate::configs::crystal::CrystalConfig;
-use crate::utils;
 
 /// Creates a module with the current Crystal version
 ///
@@ -9,20 +8,19 @@ use crate::utils;
 ///     - Current directory contains a `.cr` file
 ///     - Current directory contains a `shard.yml` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let is_crystal_project = context
+    let reason = context
         .try_begin_scan()?
         .set_files(&["shard.yml"])
         .set_extensions(&["cr"])
-        .is_match();
+        .find_match()?;
 
-    if !is_crystal_project {
-        return None;
-    }
-
-    let crystal_version = utils::exec_cmd("crystal", &["--version"])?.stdout;
+    let crystal_version = context
+        .exec_cmd("crystal", "crystal", &["--version"])?
+        .stdout;
     let formatted_version = format_crystal_version(&crystal_version)?;
 
     let mut module = context.new_module("crystal");
+    module.set_reason(reason);
     let config: CrystalConfig = CrystalConfig::try_load(module.config);
     module.set_style(config.style);
 
@@ -1,6 +1,8 @@
 use ansi_term::Color;
+use process_control::Output;
+use std::ffi::OsString;
 use std::io::Write;
-use std::process::{Command, Output, Stdio};
+use std::process::{Child, Command, Stdio};
 
 use super::{Context, Module, RootModuleConfig};
 
@@ -18,6 +20,7 @@ pub fn module<'a>(name: &'a str, context: &'a Context) -> Option<Module<'a>> {
         "modules::custom::module should only be called after ensuring that the module exists",
     );
     let config = CustomConfig::load(toml_config);
+    let module_name = format!("custom.{}", name);
 
     let mut scan_dir = context.try_begin_scan()?;
 
@@ -31,19 +34,23 @@ pub fn module<'a>(name: &'a str, context: &'a Context) -> Option<Module<'a>> {
         scan_dir = scan_dir.set_folders(&config.directories.0);
     }
 
-    let mut is_match = scan_dir.is_match();
-
-    if !is_match {
-        if let Some(when) = config.when {
-            is_match = exec_when(when, config.shell);
+    let mut output = None;
+    let reason = match (scan_dir.find_match(), config.when) {
+        (Some(reason), _) => reason,
+        (None, Some(when)) if runs_in_cmd_exe(config.shell) => {
+            // Starting processes is slow on Windows, so cmd.exe runs both commands at once
+            let cmd = format!("({}) >NUL 2>&1 && ({})", when, config.command);
+            output = Some(exec_command(&cmd, config.shell, context, &module_name)?);
+            format!("`{}` succeeded", when)
         }
-
-        if !is_match {
-            return None;
+        (None, Some(when)) if exec_when(when, config.shell, context, &module_name) => {
+            format!("`{}` succeeded", when)
         }
-    }
+        (None, _) => return None,
+    };
 
     let mut module = Module::new(name, config.description, Some(toml_config));
+    module.set_reason(reason);
     let style = config.style.unwrap_or_else(|| Color::Green.bold());
 
     if let Some(prefix) = config.prefix {
@@ -57,7 +64,11 @@ pub fn module<'a>(name: &'a str, context: &'a Context) -> Option<Module<'a>> {
         module.create_segment("symbol", &symbol);
     }
 
-    if let Some(output) = exec_command(config.command, config.shell) {
+    let output = match output {
+        Some(output) => Some(output),
+        None => exec_command(config.command, config.shell, context, &module_name),
+    };
+    if let Some(output) = output {
         let trimmed = output.trim();
 
         if trimmed.is_empty() {
@@ -75,6 +86,136 @@ pub fn module<'a>(name: &'a str, context: &'a Context) -> Option<Module<'a>> {
     }
 }
 
+/// The environment variables passed to the commands of custom modules, on top of the ones
+/// in their `allowed_env` and the top-level `allowed_env`. A `*` at the end matches any
+/// variable starting with what's before it, and a `*` on its own every variable.
+const ALLOWED_ENV: &[&str] = &[
+    "HOME",
+    "LANG",
+    "LC_*",
+    "LOGNAME",
+    "PATH",
+    "PWD",
+    "SHELL",
+    "SSH_AUTH_SOCK",
+    "STARSHIP_*",
+    "TERM",
+    "TMPDIR",
+    "TZ",
+    "USER",
+    "XDG_*",
+    // Needed by programs on Windows
+    "APPDATA",
+    "COMSPEC",
+    "LOCALAPPDATA",
+    "PATHEXT",
+    "SYSTEMDRIVE",
+    "SYSTEMROOT",
+    "TEMP",
+    "TMP",
+    "USERPROFILE",
+    "WINDIR",
+];
+
+/// The config of a custom module, or the default one for the `when` of other modules
+fn get_config<'a>(context: &'a Context, module: &str) -> CustomConfig<'a> {
+    let config = module
+        .strip_prefix("custom.")
+        .and_then(|name| context.config.get_custom_module_config(name));
+    match config {
+        Some(config) => CustomConfig::load(config),
+        None => CustomConfig::new(),
+    }
+}
+
+/// Whether a variable is in `ALLOWED_ENV` or one of the `allowed_env` lists
+fn is_allowed_env(name: &str, allowed_env: &[&str]) -> bool {
+    // Names of variables are case-insensitive on Windows
+    let name = if cfg!(windows) {
+        name.to_uppercase()
+    } else {
+        name.to_owned()
+    };
+    ALLOWED_ENV.iter().chain(allowed_env).any(|allowed| {
+        let allowed = if cfg!(windows) {
+            allowed.to_uppercase()
+        } else {
+            (*allowed).to_owned()
+        };
+        match allowed.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == allowed,
+        }
+    })
+}
+
+/// Sets up a command of a module, so that it only gets the allowed environment variables
+/// and its output, and nothing it could wait on, like the terminal to read from
+fn sandbox<'c>(command: &'c mut Command, context: &Context, module: &str) -> &'c mut Command {
+    let config = get_config(context, module);
+    let root_config = context.config.get_root_config();
+    let allowed_env: Vec<&str> = config
+        .allowed_env
+        .iter()
+        .chain(&root_config.allowed_env)
+        .copied()
+        .collect();
+    let env: Vec<(OsString, OsString)> = match &context.env {
+        Some(env) => env
+            .iter()
+            .map(|(name, value)| (name.into(), value.into()))
+            .collect(),
+        None => std::env::vars_os().collect(),
+    };
+    let env = env.into_iter().filter(
+        |(name, _)| matches!(name.to_str(), Some(name) if is_allowed_env(name, &allowed_env)),
+    );
+    command
+        .current_dir(&context.current_dir)
+        .env_clear()
+        .envs(env)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+}
+
+/// The output of a command, cut to the `max_output` bytes of its module so that a command
+/// printing a lot can't flood the prompt
+fn limit_output(output: &[u8], context: &Context, module: &str) -> String {
+    let max_output = get_config(context, module).max_output;
+    let output = String::from_utf8_lossy(output);
+    if output.len() <= max_output {
+        return output.into_owned();
+    }
+    log::debug!(
+        "The output of {} is longer than {} bytes, cutting it",
+        module,
+        max_output
+    );
+    let end = (0..=max_output)
+        .rev()
+        .find(|index| output.is_char_boundary(*index))
+        .unwrap_or(0);
+    output[..end].to_owned()
+}
+
+/// Whether commands are run by cmd.exe, which is the case on Windows unless another
+/// shell is set by `shell` or STARSHIP_SHELL
+#[cfg(windows)]
+fn runs_in_cmd_exe(shell: Option<&str>) -> bool {
+    let shell = shell
+        .map(String::from)
+        .or_else(|| std::env::var("STARSHIP_SHELL").ok());
+    match shell {
+        Some(shell) => ["cmd", "cmd.exe"].contains(&shell.to_lowercase().as_str()),
+        None => true,
+    }
+}
+
+#[cfg(not(windows))]
+fn runs_in_cmd_exe(_shell: Option<&str>) -> bool {
+    false
+}
+
 /// Return the invoking shell, using `shell` and fallbacking in order to STARSHIP_SHELL and "sh"
 #[cfg(not(windows))]
 fn get_shell(shell: Option<&str>) -> std::borrow::Cow<str> {
@@ -87,14 +228,27 @@ fn get_shell(shell: Option<&str>) -> std::borrow::Cow<str> {
     }
 }
 
+/// Wait for the command of the module `module` to exit, killing it if it runs longer
+/// than the timeout of the module
+fn wait_for_command(cmd: &str, child: Child, context: &Context, module: &str) -> Option<Output> {
+    context.wait_with_timeout(module, cmd, child)
+}
+
 /// Attempt to run the given command in a shell by passing it as `stdin` to `get_shell()`
 #[cfg(not(windows))]
-fn shell_command(cmd: &str, shell: Option<&str>) -> Option<Output> {
-    let command = Command::new(get_shell(shell).as_ref())
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn();
+fn shell_command(
+    cmd: &str,
+    shell: Option<&str>,
+    context: &Context,
+    module: &str,
+) -> Option<Output> {
+    let command = sandbox(
+        &mut Command::new(get_shell(shell).as_ref()),
+        context,
+        module,
+    )
+    .stdin(Stdio::piped())
+    .spawn();
 
     let mut child = match command {
         Ok(command) => command,
@@ -103,24 +257,28 @@ fn shell_command(cmd: &str, shell: Option<&str>) -> Option<Output> {
                 "Could not launch command with given shell or STARSHIP_SHELL env variable, retrying with /bin/env sh"
             );
 
-            Command::new("/bin/env")
+            sandbox(&mut Command::new("/bin/env"), context, module)
                 .arg("sh")
                 .stdin(Stdio::piped())
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
                 .spawn()
                 .ok()?
         }
     };
 
-    child.stdin.as_mut()?.write_all(cmd.as_bytes()).ok()?;
-    child.wait_with_output().ok()
+    // Closing stdin once the command is written, the command can't wait on it
+    child.stdin.take()?.write_all(cmd.as_bytes()).ok()?;
+    wait_for_command(cmd, child, context, module)
 }
 
 /// Attempt to run the given command in a shell by passing it as `stdin` to `get_shell()`,
 /// or by invoking cmd.exe /C.
 #[cfg(windows)]
-fn shell_command(cmd: &str, shell: Option<&str>) -> Option<Output> {
+fn shell_command(
+    cmd: &str,
+    shell: Option<&str>,
+    context: &Context,
+    module: &str,
+) -> Option<Output> {
     let shell = if let Some(shell) = shell {
         Some(std::borrow::Cow::Borrowed(shell))
     } else if let Ok(env_shell) = std::env::var("STARSHIP_SHELL") {
@@ -130,16 +288,15 @@ fn shell_command(cmd: &str, shell: Option<&str>) -> Option<Output> {
     };
 
     if let Some(forced_shell) = shell {
-        let command = Command::new(forced_shell.as_ref())
+        let command = sandbox(&mut Command::new(forced_shell.as_ref()), context, module)
             .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
             .spawn();
 
         if let Ok(mut child) = command {
-            child.stdin.as_mut()?.write_all(cmd.as_bytes()).ok()?;
+            // Closing stdin once the command is written, the command can't wait on it
+            child.stdin.take()?.write_all(cmd.as_bytes()).ok()?;
 
-            return child.wait_with_output().ok();
+            return wait_for_command(cmd, child, context, module);
         }
 
         log::debug!(
@@ -147,22 +304,20 @@ fn shell_command(cmd: &str, shell: Option<&str>) -> Option<Output> {
         );
     }
 
-    let command = Command::new("cmd.exe")
+    let command = sandbox(&mut Command::new("cmd.exe"), context, module)
         .arg("/C")
         .arg(cmd)
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
+        .stdin(Stdio::null())
         .spawn();
 
-    command.ok()?.wait_with_output().ok()
+    wait_for_command(cmd, command.ok()?, context, module)
 }
 
 /// Execute the given command capturing all output, and return whether it return 0
-fn exec_when(cmd: &str, shell: Option<&str>) -> bool {
+pub(crate) fn exec_when(cmd: &str, shell: Option<&str>, context: &Context, module: &str) -> bool {
     log::trace!("Running '{}'", cmd);
 
-    if let Some(output) = shell_command(cmd, shell) {
+    if let Some(output) = shell_command(cmd, shell, context, module) {
         if !output.status.success() {
             log::trace!("non-zero exit code '{:?}'", output.status.code());
             log::trace!(
@@ -184,10 +339,10 @@ fn exec_when(cmd: &str, shell: Option<&str>) -> bool {
 }
 
 /// Execute the given command, returning its output on success
-fn exec_command(cmd: &str, shell: Option<&str>) -> Option<String> {
+fn exec_command(cmd: &str, shell: Option<&str>, context: &Context, module: &str) -> Option<String> {
     log::trace!("Running '{}'", cmd);
 
-    if let Some(output) = shell_command(cmd, shell) {
+    if let Some(output) = shell_command(cmd, shell, context, module) {
         if !output.status.success() {
             log::trace!("Non-zero exit code '{:?}'", output.status.code());
             log::trace!(
@@ -201,7 +356,7 @@ fn exec_command(cmd: &str, shell: Option<&str>) -> Option<String> {
             return None;
         }
 
-        Some(String::from_utf8_lossy(&output.stdout).into())
+        Some(limit_output(&output.stdout, context, module))
     } else {
         None
     }
@@ -210,6 +365,7 @@ fn exec_command(cmd: &str, shell: Option<&str>) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::StarshipConfig;
 
     #[cfg(not(windows))]
     const SHELL: Option<&'static str> = Some("/bin/sh");
@@ -223,6 +379,20 @@ mod tests {
 
     const UNKNOWN_COMMAND: &str = "ydelsyiedsieudleylse dyesdesl";
 
+    fn context(config: Option<toml::Value>) -> Context<'static> {
+        let mut context = Context::new_with_dir(clap::ArgMatches::default(), ".");
+        context.config = StarshipConfig::new(config);
+        context
+    }
+
+    fn exec_when(cmd: &str, shell: Option<&str>) -> bool {
+        super::exec_when(cmd, shell, &context(None), "test")
+    }
+
+    fn exec_command(cmd: &str, shell: Option<&str>) -> Option<String> {
+        super::exec_command(cmd, shell, &context(None), "test")
+    }
+
     #[test]
     fn when_returns_right_value() {
         assert!(exec_when("echo hello", SHELL));
@@ -285,4 +455,73 @@ mod tests {
         assert_eq!(exec_command(FAILING_COMMAND, SHELL), None);
         assert_eq!(exec_command(UNKNOWN_COMMAND, SHELL), None);
     }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn command_gets_allowed_env() {
+        std::env::set_var("CUSTOM_TEST_SECRET", "secret");
+        assert_eq!(
+            exec_command("echo \"$HOME x$CUSTOM_TEST_SECRET\"", SHELL),
+            Some(format!("{} x\n", std::env::var("HOME").unwrap()))
+        );
+
+        let context = context(Some(toml::toml! {
+            [custom.test]
+            allowed_env = ["CUSTOM_TEST_*"]
+        }));
+        assert_eq!(
+            super::exec_command(
+                "echo \"x$CUSTOM_TEST_SECRET\"",
+                SHELL,
+                &context,
+                "custom.test"
+            ),
+            Some("xsecret\n".into())
+        );
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn when_gets_top_level_allowed_env() {
+        std::env::set_var("CUSTOM_TEST_VENV", "venv");
+        std::env::set_var("XDG_CUSTOM_TEST", "xdg");
+        let when = "test \"$CUSTOM_TEST_VENV$XDG_CUSTOM_TEST\" = venvxdg";
+
+        assert!(!super::exec_when(when, SHELL, &context(None), "conda"));
+
+        let allowed = context(Some(toml::toml! {
+            allowed_env = ["CUSTOM_TEST_VENV"]
+        }));
+        assert!(super::exec_when(when, SHELL, &allowed, "conda"));
+    }
+
+    #[test]
+    fn command_output_is_limited() {
+        let context = context(Some(toml::toml! {
+            [custom.test]
+            max_output = 5
+        }));
+        assert_eq!(
+            super::exec_command("echo hello world", SHELL, &context, "custom.test"),
+            Some("hello".into())
+        );
+        assert_eq!(
+            limit_output("강남".as_bytes(), &context, "custom.test"),
+            "강"
+        );
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn command_times_out() {
+        let context = context(Some(toml::toml! {
+            [custom.test]
+            timeout = 50
+        }));
+        assert_eq!(
+            super::exec_command("sleep 5", SHELL, &context, "custom.test"),
+            None
+        );
+        assert!(context.has_timed_out("custom.test"));
+    }
 }
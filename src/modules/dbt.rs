@@ -0,0 +1,109 @@
+use std::path::{Path, PathBuf};
+
+use yaml_rust::{Yaml, YamlLoader};
+
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::dbt::DbtConfig;
+use crate::formatter::StringFormatter;
+use crate::utils;
+
+/// Creates a module with the dbt profile and target of the current project
+///
+/// Will display the profile if the current directory, or one of its parents, contains a
+/// `dbt_project.yml` file. The target is `$DBT_TARGET`, or else the `target` of the
+/// profile in `profiles.yml`, which is looked for in `$DBT_PROFILES_DIR`, then in the
+/// project and then in `~/.dbt`.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let project_dir = context
+        .current_dir
+        .ancestors()
+        .find(|dir| dir.join("dbt_project.yml").is_file())?;
+    let project = load_yaml(&project_dir.join("dbt_project.yml"))?;
+    let profile = project["profile"].as_str()?.to_owned();
+
+    let profiles = get_profiles_path(context, project_dir).and_then(|path| load_yaml(&path));
+    let profile_config = profiles
+        .as_ref()
+        .map(|profiles| &profiles[profile.as_str()]);
+    let target = context
+        .get_env("DBT_TARGET")
+        .filter(|target| !target.is_empty())
+        .or_else(|| {
+            let target = profile_config?["target"].as_str()?;
+            render_env_var(context, target)
+        });
+    let adapter = target.as_ref().and_then(|target| {
+        let output = &profile_config?["outputs"][target.as_str()];
+        output["type"].as_str().map(str::to_owned)
+    });
+
+    let mut module = context.new_module("dbt");
+    module.set_reason("`dbt_project.yml` is in the project");
+    let config: DbtConfig = DbtConfig::try_load(module.config);
+
+    module.set_style(config.style);
+
+    let formatter = match StringFormatter::new(config.format) {
+        Ok(formatter) => formatter,
+        Err(error) => {
+            log::warn!("Error parsing `dbt.format`:\n{}", error);
+            return None;
+        }
+    };
+    let segments = formatter
+        .map(|variable| match variable {
+            "symbol" => Some(config.symbol.value.to_owned()),
+            "profile" => Some(profile.clone()),
+            "target" => target.clone(),
+            "type" => adapter.clone(),
+            _ => None,
+        })
+        .parse(Some(config.style));
+    module.set_segments(segments);
+
+    Some(module)
+}
+
+/// The `profiles.yml` dbt reads, the first one found of those it looks for
+fn get_profiles_path(context: &Context, project_dir: &Path) -> Option<PathBuf> {
+    if let Some(dir) = context.get_env("DBT_PROFILES_DIR") {
+        return Some(Context::expand_tilde(PathBuf::from(dir)).join("profiles.yml"));
+    }
+    let in_project = project_dir.join("profiles.yml");
+    if in_project.is_file() {
+        return Some(in_project);
+    }
+    Some(dirs::home_dir()?.join(".dbt").join("profiles.yml"))
+}
+
+fn load_yaml(path: &Path) -> Option<Yaml> {
+    let contents = utils::read_file(path).ok()?;
+    YamlLoader::load_from_str(&contents)
+        .ok()?
+        .into_iter()
+        .next()
+}
+
+/// The value of a target, which can be read from an env var with Jinja, like
+/// `{{ env_var('DBT_TARGET', 'dev') }}`
+fn render_env_var(context: &Context, value: &str) -> Option<String> {
+    let value = value.trim();
+    let expression = match value
+        .strip_prefix("{{")
+        .and_then(|value| value.strip_suffix("}}"))
+    {
+        Some(expression) => expression.trim(),
+        None => return Some(value.to_owned()).filter(|value| !value.is_empty()),
+    };
+    let arguments = expression
+        .strip_prefix("env_var(")?
+        .strip_suffix(')')?
+        .split(',')
+        .map(|argument| argument.trim().trim_matches(|c| c == '\'' || c == '"'))
+        .collect::<Vec<&str>>();
+    context
+        .get_env(arguments.first()?)
+        .or_else(|| arguments.get(1).map(|default| (*default).to_owned()))
+        .filter(|value| !value.is_empty())
+}
@@ -7,6 +7,7 @@ use super::{Context, Module};
 use super::utils::directory::truncate;
 use crate::config::{RootModuleConfig, SegmentConfig};
 use crate::configs::directory::DirectoryConfig;
+use crate::utils::file_url;
 
 /// Creates a module with the current directory
 ///
@@ -18,96 +19,172 @@ use crate::configs::directory::DirectoryConfig;
 ///
 /// **Truncation**
 /// Paths will be limited in length to `3` path components by default.
+///
+/// **Read-only**
+/// A lock symbol is appended if the current user can't write to the directory.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    const HOME_SYMBOL: &str = "~";
-
     let mut module = context.new_module("directory");
     let config: DirectoryConfig = DirectoryConfig::try_load(module.config);
 
     module.set_style(config.style);
 
-    // Using environment PWD is the standard approach for determining logical path
-    // If this is None for any reason, we fall back to reading the os-provided path
-    let physical_current_dir = if config.use_logical_path {
-        match std::env::var("PWD") {
-            Ok(x) => Some(PathBuf::from(x)),
-            Err(e) => {
-                log::debug!("Error getting PWD environment variable: {}", e);
-                None
-            }
-        }
+    // The logical path is the one the shell reports, with the symlinks the directory was
+    // entered through, while the physical one has them resolved
+    let current_dir = if config.use_logical_path {
+        normalize_path(&logical_path(&context.logical_dir))
     } else {
-        match std::env::current_dir() {
-            Ok(x) => Some(x),
+        match context.current_dir.canonicalize() {
+            Ok(dir) => normalize_path(&dir),
             Err(e) => {
                 log::debug!("Error getting physical current directory: {}", e);
-                None
+                normalize_path(&context.current_dir)
             }
         }
     };
-    let current_dir = Path::new(
-        physical_current_dir
-            .as_ref()
-            .unwrap_or_else(|| &context.current_dir),
-    );
+    let current_dir = &current_dir;
 
-    let home_dir = dirs::home_dir().unwrap();
+    let home_dir = normalize_path(&dirs::home_dir().unwrap());
     log::debug!("Current directory: {:?}", current_dir);
 
     let repo = &context.get_repo().ok()?;
 
-    let dir_string = match &repo.root {
+    // The innermost repository, if a git and an hg repository are nested
+    let repo_root = [repo.root.as_deref(), context.get_hg_root()]
+        .iter()
+        .flatten()
+        .max_by_key(|root| root.components().count())
+        .map(|root| normalize_path(root));
+    let repo_dir_string = match &repo_root {
         Some(repo_root) if config.truncate_to_repo && (repo_root != &home_dir) => {
-            let repo_folder_name = repo_root.file_name().unwrap().to_str().unwrap();
-
-            // Contract the path to the git repo root
-            contract_path(current_dir, repo_root, repo_folder_name)
+            contract_repo_path(current_dir, repo_root)
         }
-        // Contract the path to the home directory
-        _ => contract_path(current_dir, &home_dir, HOME_SYMBOL),
+        _ => None,
     };
+    // Contract the path to the home directory or a substituted prefix, outside of
+    // repositories
+    let mut prefixes: Vec<(PathBuf, &str)> = config
+        .substitutions
+        .iter()
+        .map(|(prefix, label)| {
+            let prefix = Context::expand_tilde(PathBuf::from(prefix));
+            (normalize_path(&prefix), *label)
+        })
+        .collect();
+    prefixes.push((home_dir, config.home_symbol));
+    let contracted_dir = contract_longest_prefix(current_dir, &prefixes);
+    let in_repo = repo_dir_string.is_some();
+    let dir_string = repo_dir_string.unwrap_or_else(|| contracted_dir.clone());
 
     // Truncate the dir string to the maximum number of path components
-    let truncated_dir_string = truncate(dir_string, config.truncation_length as usize);
+    let truncated_dir_string = truncate(dir_string.clone(), config.truncation_length as usize);
+
+    // The folder of the repository root is styled apart, unless it was truncated away
+    let repo_root_style = config
+        .repo_root_style
+        .filter(|_| in_repo && truncated_dir_string == dir_string);
+
+    // Link the path to the directory, so that it can be opened from the terminal
+    let hyperlink = if config.hyperlink {
+        Some(file_url(current_dir))
+    } else {
+        None
+    };
 
     if config.fish_style_pwd_dir_length > 0 {
         // If user is using fish style path, we need to add the segment first
-        let contracted_home_dir = contract_path(&current_dir, &home_dir, HOME_SYMBOL);
         let fish_style_dir = to_fish_style(
             config.fish_style_pwd_dir_length as usize,
-            contracted_home_dir,
+            contracted_dir,
             &truncated_dir_string,
         );
 
-        module.create_segment(
-            "path",
-            &SegmentConfig {
-                value: &fish_style_dir,
-                style: None,
-            },
-        );
+        module
+            .create_segment(
+                "path",
+                &SegmentConfig {
+                    value: &fish_style_dir,
+                    style: None,
+                },
+            )
+            .hyperlink = hyperlink.clone();
     }
 
-    module.create_segment(
-        "path",
-        &SegmentConfig {
-            value: &truncated_dir_string,
-            style: None,
-        },
-    );
+    let path = match repo_root_style {
+        Some(style) => {
+            let (repo_root_name, path) = match truncated_dir_string.split_once('/') {
+                Some((repo_root_name, path)) => (repo_root_name, format!("/{}", path)),
+                None => (truncated_dir_string.as_str(), String::new()),
+            };
+            module
+                .create_segment(
+                    "repo_root",
+                    &SegmentConfig {
+                        value: repo_root_name,
+                        style: Some(style),
+                    },
+                )
+                .hyperlink = hyperlink.clone();
+            path
+        }
+        None => truncated_dir_string,
+    };
+
+    if !path.is_empty() {
+        module
+            .create_segment(
+                "path",
+                &SegmentConfig {
+                    value: &path,
+                    style: None,
+                },
+            )
+            .hyperlink = hyperlink;
+    }
+
+    if config.check_read_only && !is_write_allowed(current_dir) {
+        module.create_segment("read_only_symbol", &config.read_only_symbol);
+    }
 
     module.get_prefix().set_value(config.prefix);
 
     Some(module)
 }
 
+/// Check whether the current user is allowed to write to the given directory
+#[cfg(not(target_os = "windows"))]
+fn is_write_allowed(dir: &Path) -> bool {
+    use nix::unistd::{access, AccessFlags};
+
+    match access(dir, AccessFlags::W_OK) {
+        Ok(_) => true,
+        // Permission denied and read-only filesystems are the only errors that mean
+        // the directory is read-only. Anything else shouldn't produce a false positive.
+        Err(nix::Error::Sys(errno)) => {
+            errno != nix::errno::Errno::EACCES && errno != nix::errno::Errno::EROFS
+        }
+        Err(_) => true,
+    }
+}
+
+/// Check whether the current user is allowed to write to the given directory
+#[cfg(target_os = "windows")]
+fn is_write_allowed(dir: &Path) -> bool {
+    match std::fs::metadata(dir) {
+        Ok(metadata) => !metadata.permissions().readonly(),
+        Err(e) => {
+            log::debug!("Unable to read metadata of current directory: {}", e);
+            true
+        }
+    }
+}
+
 /// Contract the root component of a path
 ///
 /// Replaces the `top_level_path` in a given `full_path` with the provided
 /// `top_level_replacement`.
 fn contract_path(full_path: &Path, top_level_path: &Path, top_level_replacement: &str) -> String {
     if !full_path.starts_with(top_level_path) {
-        return full_path.to_slash().unwrap();
+        return to_display_string(full_path);
     }
 
     if full_path == top_level_path {
@@ -118,14 +195,108 @@ fn contract_path(full_path: &Path, top_level_path: &Path, top_level_replacement:
         "{replacement}{separator}{path}",
         replacement = top_level_replacement,
         separator = "/",
-        path = full_path
-            .strip_prefix(top_level_path)
-            .unwrap()
-            .to_slash()
-            .unwrap()
+        path = to_display_string(full_path.strip_prefix(top_level_path).unwrap())
     )
 }
 
+/// Contract the longest of the given prefixes that a path starts with, replacing it
+/// with its label
+fn contract_longest_prefix(full_path: &Path, prefixes: &[(PathBuf, &str)]) -> String {
+    let longest = prefixes
+        .iter()
+        .filter(|(prefix, _)| full_path.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.components().count());
+    match longest {
+        Some((prefix, label)) => contract_path(full_path, prefix, label),
+        None => to_display_string(full_path),
+    }
+}
+
+/// Contract a path to the root of the repository it's in, shown by its folder name, or
+/// `None` if it isn't inside of it. Repositories are found at their physical paths, so a
+/// logical path entered through a symlink is compared by where it leads.
+fn contract_repo_path(full_path: &Path, repo_root: &Path) -> Option<String> {
+    let repo_folder_name = repo_root.file_name()?.to_string_lossy();
+    if full_path.starts_with(repo_root) {
+        return Some(contract_path(full_path, repo_root, &repo_folder_name));
+    }
+
+    let physical_path = normalize_path(&full_path.canonicalize().ok()?);
+    let physical_root = normalize_path(&repo_root.canonicalize().ok()?);
+    if physical_path.starts_with(&physical_root) {
+        Some(contract_path(
+            &physical_path,
+            &physical_root,
+            &repo_folder_name,
+        ))
+    } else {
+        None
+    }
+}
+
+/// A path with `/` as its separator, including in the `\\server\share` of UNC paths
+fn to_display_string(path: &Path) -> String {
+    let path = path.to_slash_lossy();
+    if cfg!(windows) {
+        path.replace('\\', "/")
+    } else {
+        path
+    }
+}
+
+/// The logical path of the current directory, which the MSYS and Cygwin shells of Windows
+/// report as their own paths like `/c/Users`
+fn logical_path(path: &Path) -> PathBuf {
+    if cfg!(windows) {
+        if let Some(path) = path.to_str().and_then(from_msys_path) {
+            return PathBuf::from(path);
+        }
+    }
+    path.to_path_buf()
+}
+
+/// Windows paths can be written in several ways, so they are compared and shown as
+/// they're usually written
+fn normalize_path(path: &Path) -> PathBuf {
+    if cfg!(windows) {
+        PathBuf::from(normalize_windows_path(&path.to_string_lossy()))
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Removes the `\\?\` prefix of a verbatim Windows path, which is what canonicalizing a
+/// path returns, and uppercases its drive letter
+fn normalize_windows_path(path: &str) -> String {
+    let path = match path.strip_prefix(r"\\?\UNC\") {
+        Some(unc_path) => format!(r"\\{}", unc_path),
+        None => path.strip_prefix(r"\\?\").unwrap_or(path).to_owned(),
+    };
+    match path.as_bytes() {
+        [drive, b':', ..] if drive.is_ascii_alphabetic() => {
+            format!("{}{}", drive.to_ascii_uppercase() as char, &path[1..])
+        }
+        _ => path,
+    }
+}
+
+/// Converts an MSYS path like `/c/Users`, or a Cygwin path like `/cygdrive/c/Users`,
+/// into the Windows path it stands for
+fn from_msys_path(path: &str) -> Option<String> {
+    let path = path.strip_prefix("/cygdrive").unwrap_or(path);
+    match path.as_bytes() {
+        [b'/', drive] | [b'/', drive, b'/', ..] if drive.is_ascii_alphabetic() => {
+            let rest = path.get(3..).unwrap_or("");
+            Some(format!(
+                "{}:\\{}",
+                drive.to_ascii_uppercase() as char,
+                rest.replace('/', "\\")
+            ))
+        }
+        _ => None,
+    }
+}
+
 /// Takes part before contracted path and replaces it with fish style path
 ///
 /// Will take the first letter of each directory before the contracted path and
@@ -138,8 +309,14 @@ fn contract_path(full_path: &Path, top_level_path: &Path, top_level_replacement:
 /// Absolute Path: `/some/Path/not/in_a/repo/but_nested`
 /// Contracted Path: `in_a/repo/but_nested`
 /// With Fish Style: `/s/P/n/in_a/repo/but_nested`
+///
+/// Nothing is added if the contracted path isn't the end of the path, which happens when
+/// a repository was entered through a symlink.
 fn to_fish_style(pwd_dir_length: usize, dir_string: String, truncated_dir_string: &str) -> String {
-    let replaced_dir_string = dir_string.trim_end_matches(truncated_dir_string).to_owned();
+    let replaced_dir_string = match dir_string.strip_suffix(truncated_dir_string) {
+        Some(replaced_dir_string) => replaced_dir_string.to_owned(),
+        None => return String::new(),
+    };
     let components = replaced_dir_string.split('/').collect::<Vec<&str>>();
 
     if components.is_empty() {
@@ -165,6 +342,57 @@ fn to_fish_style(pwd_dir_length: usize, dir_string: String, truncated_dir_string
 mod tests {
     use super::*;
 
+    #[test]
+    fn normalize_verbatim_windows_paths() {
+        assert_eq!(
+            normalize_windows_path(r"\\?\C:\Users\astronaut"),
+            r"C:\Users\astronaut"
+        );
+        assert_eq!(
+            normalize_windows_path(r"\\?\UNC\server\share\rocket"),
+            r"\\server\share\rocket"
+        );
+        assert_eq!(
+            normalize_windows_path(r"\\server\share\rocket"),
+            r"\\server\share\rocket"
+        );
+    }
+
+    #[test]
+    fn normalize_windows_drive_letters() {
+        assert_eq!(normalize_windows_path(r"c:\Users"), r"C:\Users");
+        assert_eq!(normalize_windows_path(r"\\?\d:\"), r"D:\");
+        assert_eq!(normalize_windows_path("/home/astronaut"), "/home/astronaut");
+    }
+
+    #[test]
+    fn convert_msys_paths() {
+        assert_eq!(
+            from_msys_path("/c/Users/astronaut"),
+            Some(String::from(r"C:\Users\astronaut"))
+        );
+        assert_eq!(from_msys_path("/d"), Some(String::from(r"D:\")));
+        assert_eq!(
+            from_msys_path("/cygdrive/c/Users"),
+            Some(String::from(r"C:\Users"))
+        );
+        assert_eq!(from_msys_path("/home/astronaut"), None);
+        assert_eq!(from_msys_path("/1/rocket"), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn contract_non_utf8_directory() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let full_path = Path::new(OsStr::from_bytes(b"/Users/astronaut/caf\xe9/rocket"));
+        let home = Path::new("/Users/astronaut");
+
+        let output = contract_path(full_path, home, "~");
+        assert_eq!(output, "~/caf\u{fffd}/rocket");
+    }
+
     #[test]
     fn contract_home_directory() {
         let full_path = Path::new("/Users/astronaut/schematics/rocket");
@@ -183,6 +411,21 @@ mod tests {
         assert_eq!(output, "rocket-controls/src");
     }
 
+    #[test]
+    fn contract_longest_prefix_directory() {
+        let full_path = Path::new("/Users/astronaut/mnt/storage/projects/rocket");
+        let prefixes = [
+            (PathBuf::from("/Users/astronaut/mnt/storage/projects"), "📦"),
+            (PathBuf::from("/Users/astronaut"), "~"),
+            (PathBuf::from("/Users/astronaut/mnt/backup"), "💾"),
+        ];
+
+        let output = contract_longest_prefix(full_path, &prefixes);
+        assert_eq!(output, "📦/rocket");
+        let output = contract_longest_prefix(Path::new("/opt"), &prefixes);
+        assert_eq!(output, "/opt");
+    }
+
     #[test]
     #[cfg(target_os = "windows")]
     fn contract_windows_style_home_directory() {
@@ -260,6 +503,13 @@ mod tests {
         assert_eq!(output, "~/s/t/C/C/");
     }
 
+    #[test]
+    fn fish_style_with_unrelated_contracted_path() {
+        let path = "~/links/rocket/src";
+        let output = to_fish_style(1, path.to_string(), "rocket-controls/src");
+        assert_eq!(output, "");
+    }
+
     #[test]
     fn fish_style_with_unicode() {
         let path = "~/starship/tmp/目录/a̐éö̲/目录";
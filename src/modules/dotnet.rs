@@ -1,12 +1,11 @@
 use std::ffi::OsStr;
 use std::iter::Iterator;
 use std::ops::Deref;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str;
 
 use super::{Context, Module, RootModuleConfig};
 use crate::configs::dotnet::DotnetConfig;
-use crate::utils;
 
 type JValue = serde_json::Value;
 
@@ -18,42 +17,53 @@ const PROJECT_JSON_FILE: &str = "project.json";
 /// Will display if any of the following files are present in
 /// the current directory:
 /// global.json, project.json, *.sln, *.csproj, *.fsproj, *.xproj
+///
+/// The target frameworks of the closest csproj or fsproj are shown after the version.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     // First check if this is a DotNet Project before doing the O(n)
     // check for the version using the JSON files
-    let is_dotnet_project = context
+    let reason = context
         .try_begin_scan()?
         .set_files(&[GLOBAL_JSON_FILE, PROJECT_JSON_FILE])
         .set_extensions(&["sln", "csproj", "fsproj", "xproj"])
-        .is_match();
-
-    if !is_dotnet_project {
-        return None;
-    }
+        .find_match()?;
 
     let dotnet_files = get_local_dotnet_files(context).ok()?;
 
     let mut module = context.new_module("dotnet");
+    module.set_reason(reason);
     let config = DotnetConfig::try_load(module.config);
 
     // Internally, this module uses its own mechanism for version detection.
     // Typically it is twice as fast as running `dotnet --version`.
     let enable_heuristic = config.heuristic;
+    let repo_root = context.get_repo().ok().and_then(|r| r.root.as_deref());
     let version = if enable_heuristic {
-        let repo_root = context.get_repo().ok().and_then(|r| r.root.as_deref());
-        estimate_dotnet_version(&dotnet_files, &context.current_dir, repo_root)?
+        estimate_dotnet_version(context, &dotnet_files, &context.current_dir, repo_root)?
     } else {
-        get_version_from_cli()?
+        get_version_from_cli(context)?
     };
 
     module.set_style(config.style);
     module.create_segment("symbol", &config.symbol);
     module.create_segment("version", &config.version.with_value(&version.0));
 
+    if config.show_target_framework {
+        if let Some(target_frameworks) = get_target_frameworks(context, repo_root) {
+            module.create_segment(
+                "target_framework",
+                &config
+                    .target_framework
+                    .with_value(&format!(" {}", target_frameworks.join("/"))),
+            );
+        }
+    }
+
     Some(module)
 }
 
 fn estimate_dotnet_version<'a>(
+    context: &Context,
     files: &[DotNetFile<'a>],
     current_dir: &Path,
     repo_root: Option<&Path>,
@@ -67,19 +77,19 @@ fn estimate_dotnet_version<'a>(
         .or_else(|| files.iter().next())?;
 
     match relevant_file.file_type {
-        FileType::GlobalJson => {
-            get_pinned_sdk_version_from_file(relevant_file.path).or_else(get_latest_sdk_from_cli)
-        }
+        FileType::GlobalJson => get_pinned_sdk_version_from_file(relevant_file.path)
+            .or_else(|| get_latest_sdk_from_cli(context)),
         FileType::SolutionFile => {
             // With this heuristic, we'll assume that a "global.json" won't
             // be found in any directory above the solution file.
-            get_latest_sdk_from_cli()
+            get_latest_sdk_from_cli(context)
         }
         _ => {
             // If we see a dotnet project, we'll check a small number of neighboring
             // directories to see if we can find a global.json. Otherwise, assume the
             // latest SDK is in use.
-            try_find_nearby_global_json(current_dir, repo_root).or_else(get_latest_sdk_from_cli)
+            try_find_nearby_global_json(current_dir, repo_root)
+                .or_else(|| get_latest_sdk_from_cli(context))
         }
     }
 }
@@ -169,6 +179,63 @@ fn get_pinned_sdk_version(json: &str) -> Option<Version> {
     }
 }
 
+/// The target frameworks of the closest project file, looked for in the current directory
+/// and then in its parents up to the root of the repository
+fn get_target_frameworks(context: &Context, repo_root: Option<&Path>) -> Option<Vec<String>> {
+    let current_dir = context.current_dir.as_path();
+    let project_file = context
+        .current_dir
+        .ancestors()
+        .take_while(|dir| match repo_root {
+            Some(root) => dir.starts_with(root),
+            None => *dir == current_dir,
+        })
+        .find_map(find_project_file)?;
+    log::debug!(
+        "Reading the target frameworks of: {}",
+        project_file.display()
+    );
+    parse_target_frameworks(&crate::utils::read_file(project_file).ok()?)
+}
+
+fn find_project_file(dir: &Path) -> Option<PathBuf> {
+    let mut project_files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let extension = map_str_to_lower(path.extension());
+            matches!(extension.as_deref(), Some("csproj") | Some("fsproj")) && path.is_file()
+        })
+        .collect();
+    // Several projects in a directory are unusual, and have the same one picked every time
+    project_files.sort();
+    project_files.into_iter().next()
+}
+
+/// The frameworks in `<TargetFramework>` or `<TargetFrameworks>` of a project file,
+/// unless they're set by MSBuild properties
+fn parse_target_frameworks(project: &str) -> Option<Vec<String>> {
+    ["TargetFrameworks", "TargetFramework"]
+        .iter()
+        .find_map(|element| {
+            let start_tag = format!("<{}>", element);
+            let start = project.find(&start_tag)? + start_tag.len();
+            let end = start + project[start..].find('<')?;
+            Some(&project[start..end])
+        })
+        .filter(|value| !value.contains('$'))
+        .map(|value| {
+            value
+                .split(';')
+                .map(str::trim)
+                .filter(|framework| !framework.is_empty())
+                .map(str::to_owned)
+                .collect::<Vec<String>>()
+        })
+        .filter(|frameworks| !frameworks.is_empty())
+}
+
 fn get_local_dotnet_files<'a>(context: &'a Context) -> Result<Vec<DotNetFile<'a>>, std::io::Error> {
     Ok(context
         .dir_contents()?
@@ -206,13 +273,13 @@ fn map_str_to_lower(value: Option<&OsStr>) -> Option<String> {
     Some(value?.to_str()?.to_ascii_lowercase())
 }
 
-fn get_version_from_cli() -> Option<Version> {
-    let version_output = utils::exec_cmd("dotnet", &["--version"])?;
+fn get_version_from_cli(context: &Context) -> Option<Version> {
+    let version_output = context.exec_cmd("dotnet", "dotnet", &["--version"])?;
     Some(Version(format!("v{}", version_output.stdout.trim())))
 }
 
-fn get_latest_sdk_from_cli() -> Option<Version> {
-    match utils::exec_cmd("dotnet", &["--list-sdks"]) {
+fn get_latest_sdk_from_cli(context: &Context) -> Option<Version> {
+    match context.exec_cmd("dotnet", "dotnet", &["--list-sdks"]) {
         Some(sdks_output) => {
             fn parse_failed<T>() -> Option<T> {
                 log::warn!("Unable to parse the output from `dotnet --list-sdks`.");
@@ -243,7 +310,7 @@ fn get_latest_sdk_from_cli() -> Option<Version> {
                 "Received a non-success exit code from `dotnet --list-sdks`. \
                  Falling back to `dotnet --version`.",
             );
-            get_version_from_cli()
+            get_version_from_cli(context)
         }
     }
 }
@@ -291,3 +358,82 @@ fn should_ignore_empty_global_json() {
     let version = get_pinned_sdk_version(json_text);
     assert!(version.is_none());
 }
+
+#[test]
+fn should_parse_target_framework() {
+    let project = r#"
+        <Project Sdk="Microsoft.NET.Sdk">
+          <PropertyGroup>
+            <OutputType>Exe</OutputType>
+            <TargetFramework>net8.0</TargetFramework>
+          </PropertyGroup>
+        </Project>
+    "#;
+
+    let frameworks = parse_target_frameworks(project).unwrap();
+    assert_eq!(vec!["net8.0"], frameworks);
+}
+
+#[test]
+fn should_parse_target_frameworks() {
+    let project = r#"
+        <Project Sdk="Microsoft.NET.Sdk">
+          <PropertyGroup>
+            <TargetFrameworks>netstandard2.0; net6.0;</TargetFrameworks>
+          </PropertyGroup>
+        </Project>
+    "#;
+
+    let frameworks = parse_target_frameworks(project).unwrap();
+    assert_eq!(vec!["netstandard2.0", "net6.0"], frameworks);
+}
+
+#[test]
+fn should_ignore_target_framework_from_property() {
+    let project = "<Project><PropertyGroup><TargetFramework>$(DefaultTfm)</TargetFramework></PropertyGroup></Project>";
+
+    assert!(parse_target_frameworks(project).is_none());
+    assert!(parse_target_frameworks("<Project />").is_none());
+}
+
+#[test]
+fn should_show_target_framework_of_project() -> std::io::Result<()> {
+    use crate::test::ModuleRenderer;
+    use crate::utils::CommandOutput;
+    use ansi_term::Color;
+
+    let dir = tempfile::tempdir()?;
+    let project = dir.path().join("starship");
+    std::fs::create_dir(&project)?;
+    std::fs::write(
+        project.join("starship.fsproj"),
+        "<Project><PropertyGroup><TargetFrameworks>net6.0;net8.0</TargetFrameworks></PropertyGroup></Project>",
+    )?;
+    let render = |config: Option<toml::Value>| {
+        let renderer = ModuleRenderer::new("dotnet").path(&project).cmd(
+            "dotnet --list-sdks",
+            Some(CommandOutput::stdout("8.0.100 [/usr/share/dotnet/sdk]\n")),
+        );
+        match config {
+            Some(config) => renderer.config(config),
+            None => renderer,
+        }
+        .collect()
+    };
+
+    let expected = Some(format!(
+        "via {} ",
+        Color::Blue.bold().paint("•NET v8.0.100 net6.0/net8.0")
+    ));
+    assert_eq!(expected, render(None));
+    let expected = Some(format!(
+        "via {} ",
+        Color::Blue.bold().paint("•NET v8.0.100")
+    ));
+    let config = toml::toml! {
+        [dotnet]
+        show_target_framework = false
+    };
+    assert_eq!(expected, render(Some(config)));
+    dir.close()
+}
@@ -14,15 +14,15 @@ Elixir (?P<elixir>\\d[.\\d]+).*";
 /// Will display the Rust version if any of the following criteria are met:
 ///     - Current directory contains a `mix.exs` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let is_elixir_project = context.try_begin_scan()?.set_files(&["mix.exs"]).is_match();
+    let reason = context
+        .try_begin_scan()?
+        .set_files(&["mix.exs"])
+        .find_match()?;
 
-    if !is_elixir_project {
-        return None;
-    }
-
-    let (otp_version, elixir_version) = get_elixir_version()?;
+    let (otp_version, elixir_version) = get_elixir_version(context)?;
 
     let mut module = context.new_module("elixir");
+    module.set_reason(reason);
     let config = ElixirConfig::try_load(module.config);
     module.set_style(config.style);
 
@@ -38,10 +38,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_elixir_version() -> Option<(String, String)> {
-    use crate::utils;
-
-    let output = utils::exec_cmd("elixir", &["--version"])?.stdout;
+fn get_elixir_version(context: &Context) -> Option<(String, String)> {
+    let output = context.exec_cmd("elixir", "elixir", &["--version"])?.stdout;
 
     parse_elixir_version(&output)
 }
@@ -1,7 +1,6 @@
 use super::{Context, Module, RootModuleConfig, SegmentConfig};
 
 use crate::configs::elm::ElmConfig;
-use crate::utils;
 
 /// Creates a module with the current Elm version
 ///
@@ -12,21 +11,18 @@ use crate::utils;
 ///     - The current directory contains a `elm-stuff` folder
 ///     - The current directory contains a `*.elm` files
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let is_elm_project = context
+    let reason = context
         .try_begin_scan()?
         .set_files(&["elm.json", "elm-package.json", ".elm-version"])
         .set_extensions(&["elm"])
         .set_folders(&["elm-stuff"])
-        .is_match();
+        .find_match()?;
 
-    if !is_elm_project {
-        return None;
-    }
-
-    let elm_version = utils::exec_cmd("elm", &["--version"])?.stdout;
+    let elm_version = context.exec_cmd("elm", "elm", &["--version"])?.stdout;
     let formatted_version = Some(format!("v{}", elm_version.trim()))?;
 
     let mut module = context.new_module("elm");
+    module.set_reason(reason);
     let config: ElmConfig = ElmConfig::try_load(module.config);
     module.set_style(config.style);
 
@@ -1,5 +1,4 @@
-use std::env;
-
+use super::utils::redact::redact;
 use super::{Context, Module, SegmentConfig};
 
 use crate::config::RootModuleConfig;
@@ -12,31 +11,65 @@ use crate::configs::env_var::EnvVarConfig;
 ///     - env_var.variable is defined
 ///     - a variable named as the value of env_var.variable is defined
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let mut module = context.new_module("env_var");
+    let module = context.new_module("env_var");
     let config: EnvVarConfig = EnvVarConfig::try_load(module.config);
 
-    let env_value = get_env_value(config.variable?, config.default)?;
+    let variable = config.variable?;
+    show_variable(module, context, &config, variable)
+}
+
+/// Creates a module for one of the `[env_var.<name>]` tables, with the value of the
+/// environment variable `name`, or else of the one set as its `variable`
+pub fn instance<'a>(name: &str, context: &'a Context) -> Option<Module<'a>> {
+    let toml_config = context
+        .config
+        .get_module_config("env_var")?
+        .get(name)
+        .filter(|config| config.is_table())?;
+    let module = Module::new(
+        &format!("env_var.{}", name),
+        super::description("env_var"),
+        Some(toml_config),
+    );
+    let config = EnvVarConfig::load(toml_config);
+    if config.disabled {
+        return None;
+    }
+
+    let variable = config.variable.unwrap_or(name);
+    show_variable(module, context, &config, variable)
+}
+
+fn show_variable<'a>(
+    mut module: Module<'a>,
+    context: &Context,
+    config: &EnvVarConfig<'a>,
+    variable: &str,
+) -> Option<Module<'a>> {
+    let env_value = get_env_value(context, variable, config.default)?;
 
     module.set_style(config.style);
     module.get_prefix().set_value("with ");
 
-    if let Some(symbol) = config.symbol {
-        module.create_segment("symbol", &symbol);
+    if let Some(symbol) = &config.symbol {
+        module.create_segment("symbol", symbol);
     }
 
     // TODO: Use native prefix and suffix instead of stacking custom ones together with env_value.
-    let env_var_stacked = format!("{}{}{}", config.prefix, env_value, config.suffix);
+    let env_var_stacked = format!(
+        "{}{}{}",
+        config.prefix,
+        redact(&env_value, config.redact),
+        config.suffix
+    );
     module.create_segment("env_var", &SegmentConfig::new(&env_var_stacked));
 
     Some(module)
 }
 
-fn get_env_value(name: &str, default: Option<&str>) -> Option<String> {
-    match env::var_os(name) {
-        Some(os_value) => match os_value.into_string() {
-            Ok(value) => Some(value),
-            Err(_error) => None,
-        },
+fn get_env_value(context: &Context, name: &str, default: Option<&str>) -> Option<String> {
+    match context.get_env(name) {
+        Some(value) => Some(value),
         None => default.map(|value| value.to_owned()),
     }
 }
@@ -8,18 +8,15 @@ use crate::configs::erlang::ErlangConfig;
 ///     - Current directory contains a rebar.config file
 ///     - Current directory contains a erlang.mk file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let is_erlang_project = context
+    let reason = context
         .try_begin_scan()?
         .set_files(&["rebar.config", "erlang.mk"])
-        .is_match();
+        .find_match()?;
 
-    if !is_erlang_project {
-        return None;
-    }
-
-    let erlang_version = get_erlang_version()?;
+    let erlang_version = get_erlang_version(context)?;
 
     let mut module = context.new_module("erlang");
+    module.set_reason(reason);
     let config = ErlangConfig::try_load(module.config);
     module.set_style(config.style);
 
@@ -29,10 +26,9 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_erlang_version() -> Option<String> {
-    use crate::utils;
-
-    Some(utils::exec_cmd(
+fn get_erlang_version(context: &Context) -> Option<String> {
+    Some(context.exec_cmd(
+        "erlang",
         "erl",
         &[
             "-noshell",
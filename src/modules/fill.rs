@@ -0,0 +1,24 @@
+use super::{Context, Module, RootModuleConfig};
+
+use crate::config::SegmentConfig;
+use crate::configs::fill::FillConfig;
+
+/// The name of the segment that is expanded to the remaining width of its line
+pub const FILL_SEGMENT: &str = "fill";
+
+/// Creates a module that fills the rest of the line with its symbol
+///
+/// The segment only holds the symbol to repeat, it's expanded to the right width
+/// once the whole line has been rendered.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("fill");
+    let config = FillConfig::try_load(module.config);
+
+    module.set_style(config.style);
+    module.get_prefix().set_value("");
+    module.get_suffix().set_value("");
+
+    module.create_segment(FILL_SEGMENT, &SegmentConfig::new(config.symbol));
+
+    Some(module)
+}
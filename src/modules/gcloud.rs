@@ -0,0 +1,101 @@
+use std::path::PathBuf;
+
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::gcloud::GcloudConfig;
+use crate::formatter::StringFormatter;
+use crate::utils;
+
+/// The properties of the active gcloud configuration
+struct GcloudProperties {
+    account: Option<String>,
+    project: Option<String>,
+    region: Option<String>,
+    zone: Option<String>,
+}
+
+/// Creates a module with the account and project of the active gcloud configuration
+///
+/// Will display the account and project if any of them is set, either in the active
+/// configuration of `$CLOUDSDK_CONFIG` (`~/.config/gcloud` by default) or by the matching
+/// `$CLOUDSDK_*` env var.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let properties = get_gcloud_properties(context);
+    if properties.account.is_none() && properties.project.is_none() {
+        return None;
+    }
+
+    let mut module = context.new_module("gcloud");
+    let config: GcloudConfig = GcloudConfig::try_load(module.config);
+
+    module.set_style(config.style);
+    module.get_prefix().set_value("on ");
+
+    let project =
+        properties
+            .project
+            .as_ref()
+            .map(|project| match config.project_aliases.get(project) {
+                Some(alias) => (*alias).to_owned(),
+                None => project.to_owned(),
+            });
+
+    let formatter = match StringFormatter::new(config.format) {
+        Ok(formatter) => formatter,
+        Err(error) => {
+            log::warn!("Error parsing `gcloud.format`:\n{}", error);
+            return None;
+        }
+    };
+    let segments = formatter
+        .map(|variable| match variable {
+            "symbol" => Some(config.symbol.value.to_owned()),
+            "account" => properties.account.clone(),
+            "project" => project.clone(),
+            "region" => properties.region.clone(),
+            "zone" => properties.zone.clone(),
+            _ => None,
+        })
+        .parse(Some(config.style));
+    module.set_segments(segments);
+
+    Some(module)
+}
+
+fn get_gcloud_properties(context: &Context) -> GcloudProperties {
+    let config = get_active_config_path(context).and_then(|path| utils::read_file(path).ok());
+    let property = |section: &str, key: &str| {
+        let env_var = format!("CLOUDSDK_{}_{}", section, key).to_uppercase();
+        context
+            .get_env(&env_var)
+            .or_else(|| utils::get_ini_value(config.as_ref()?, section, key))
+            .filter(|value| !value.is_empty())
+    };
+
+    GcloudProperties {
+        account: property("core", "account"),
+        project: property("core", "project"),
+        region: property("compute", "region"),
+        zone: property("compute", "zone"),
+    }
+}
+
+/// The file of the configuration gcloud uses, which is the one named in
+/// `$CLOUDSDK_ACTIVE_CONFIG_NAME` or in the `active_config` file
+fn get_active_config_path(context: &Context) -> Option<PathBuf> {
+    let config_dir = match context.get_env("CLOUDSDK_CONFIG") {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::home_dir()?.join(".config").join("gcloud"),
+    };
+    let name = context
+        .get_env("CLOUDSDK_ACTIVE_CONFIG_NAME")
+        .or_else(|| utils::read_file(config_dir.join("active_config")).ok())
+        .map(|name| name.trim().to_owned())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| String::from("default"));
+    Some(
+        config_dir
+            .join("configurations")
+            .join(format!("config_{}", name)),
+    )
+}
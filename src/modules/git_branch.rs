@@ -3,6 +3,7 @@ use unicode_segmentation::UnicodeSegmentation;
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::git_branch::GitBranchConfig;
+use crate::utils::{display_width, truncate_to_width};
 
 /// Creates a module with the Git branch in the current directory
 ///
@@ -17,13 +18,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let truncation_symbol = get_graphemes(config.truncation_symbol, 1);
     module.create_segment("symbol", &config.symbol);
 
-    // TODO: Once error handling is implemented, warn the user if their config
-    // truncation length is nonsensical
+    // A nonsensical truncation length is reported when the config is checked
     let len = if config.truncation_length <= 0 {
-        log::warn!(
-            "\"truncation_length\" should be a positive value, found {}",
-            config.truncation_length
-        );
         std::usize::MAX
     } else {
         config.truncation_length as usize
@@ -31,12 +27,12 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 
     let repo = context.get_repo().ok()?;
     let branch_name = repo.branch.as_ref()?;
-    let truncated_graphemes = get_graphemes(&branch_name, len);
+    let truncated = truncate_to_width(branch_name, len).to_owned();
     // The truncation symbol should only be added if we truncated
-    let truncated_and_symbol = if len < graphemes_len(&branch_name) {
-        truncated_graphemes + &truncation_symbol
+    let truncated_and_symbol = if len < display_width(branch_name) {
+        truncated + &truncation_symbol
     } else {
-        truncated_graphemes
+        truncated
     };
 
     module.create_segment(
@@ -53,7 +49,3 @@ fn get_graphemes(text: &str, length: usize) -> String {
         .collect::<Vec<&str>>()
         .concat()
 }
-
-fn graphemes_len(text: &str) -> usize {
-    UnicodeSegmentation::graphemes(&text[..], true).count()
-}
@@ -4,8 +4,15 @@ use super::{Context, Module, RootModuleConfig};
 
 use crate::config::SegmentConfig;
 use crate::configs::git_status::{CountConfig, GitStatusConfig};
-use std::borrow::BorrowMut;
+use once_cell::sync::Lazy;
 use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
 
 /// Creates a module with the Git branch in the current directory
 ///
@@ -21,11 +28,12 @@ use std::collections::HashMap;
 ///   - `+` — A new file has been added to the staging area
 ///   - `»` — A renamed file has been added to the staging area
 ///   - `✘` — A file's deletion has been added to the staging area
+///   - `…` — The repository is too large to get its status in time
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let repo = context.get_repo().ok()?;
     let branch_name = repo.branch.as_ref()?;
     let repo_root = repo.root.as_ref()?;
-    let mut repository = Repository::open(repo_root).ok()?;
+    let repository = Repository::open(repo_root).ok()?;
 
     let mut module = context.new_module("git_status");
     let config: GitStatusConfig = GitStatusConfig::try_load(module.config);
@@ -40,9 +48,32 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         .set_style(config.style);
     module.set_style(config.style);
 
-    let repo_status = get_repo_status(repository.borrow_mut());
+    // The status of a very large repository takes long to get, so it's skipped once the
+    // index has more than `max_files` entries or it takes longer than its `timeout`
+    let unlimited = config
+        .unlimited_repos
+        .iter()
+        .any(|path| Context::expand_tilde(PathBuf::from(path)) == *repo_root);
+    let index_entries = count_index_entries(&repository.path().join("index"));
+    let repo_status = if unlimited {
+        get_repo_status(repo_root)
+    } else if config.max_files > 0 && matches!(index_entries, Some(n) if n > config.max_files) {
+        log::debug!(
+            "Repo index has {:?} entries, more than max_files",
+            index_entries
+        );
+        Err(StatusError::TooLarge)
+    } else if let Some(timeout) = context.get_module_timeout("git_status") {
+        get_repo_status_with_timeout(repo_root, timeout)
+    } else {
+        get_repo_status(repo_root)
+    };
     log::debug!("Repo status: {:?}", repo_status);
 
+    if let Err(StatusError::TooLarge) = repo_status {
+        module.create_segment("too_large", &config.too_large);
+    }
+
     let ahead_behind = get_ahead_behind(&repository, branch_name);
     if ahead_behind == Ok((0, 0)) {
         log::trace!("No ahead/behind found");
@@ -187,8 +218,69 @@ fn create_segment_with_count<'a>(
     }
 }
 
+/// The number of entries in the git index at `path`, from its header
+fn count_index_entries(path: &Path) -> Option<usize> {
+    let mut header = [0; 12];
+    File::open(path).ok()?.read_exact(&mut header).ok()?;
+    if &header[..4] != b"DIRC" {
+        return None;
+    }
+    let mut count = [0; 4];
+    count.copy_from_slice(&header[8..]);
+    Some(u32::from_be_bytes(count) as usize)
+}
+
+/// Receives the status of a repository from the thread reading it
+type StatusReceiver = Receiver<Result<RepoStatus, StatusError>>;
+
+/// The statuses of repositories still being read after they timed out, by their root
+static PENDING_STATUSES: Lazy<Mutex<HashMap<PathBuf, StatusReceiver>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Gets the status of the repository at `repo_root` on another thread, giving up once
+/// it takes longer than `timeout`. The thread can't be stopped, so if the status of the
+/// repository is still being read after an earlier timeout, like in a session, that read
+/// is waited for instead of starting another one.
+fn get_repo_status_with_timeout(
+    repo_root: &Path,
+    timeout: Duration,
+) -> Result<RepoStatus, StatusError> {
+    let pending = PENDING_STATUSES.lock().unwrap().remove(repo_root);
+    let receiver = match pending {
+        Some(receiver) if matches!(receiver.try_recv(), Err(TryRecvError::Empty)) => receiver,
+        // The status read after an earlier timeout is outdated once it's done
+        _ => {
+            let (sender, receiver) = mpsc::channel();
+            let repo_root = repo_root.to_owned();
+            thread::spawn(move || sender.send(get_repo_status(&repo_root)).ok());
+            receiver
+        }
+    };
+    match receiver.recv_timeout(timeout) {
+        Ok(repo_status) => repo_status,
+        Err(RecvTimeoutError::Disconnected) => Err(StatusError::Git),
+        Err(RecvTimeoutError::Timeout) => {
+            PENDING_STATUSES
+                .lock()
+                .unwrap()
+                .insert(repo_root.to_owned(), receiver);
+            log::warn!(
+                "Getting the status of the repository timed out after {}ms. \
+                 Use the `timeout` option of git_status to allow more time.",
+                timeout.as_millis()
+            );
+            Err(StatusError::TooLarge)
+        }
+    }
+}
+
 /// Gets the number of files in various git states (staged, modified, deleted, etc...)
-fn get_repo_status(repository: &mut Repository) -> Result<RepoStatus, git2::Error> {
+fn get_repo_status(repo_root: &Path) -> Result<RepoStatus, StatusError> {
+    let mut repository = Repository::open(repo_root)?;
+    Ok(count_repo_status(&mut repository)?)
+}
+
+fn count_repo_status(repository: &mut Repository) -> Result<RepoStatus, git2::Error> {
     let mut status_options = git2::StatusOptions::new();
 
     match repository.config()?.get_entry("status.showUntrackedFiles") {
@@ -295,6 +387,20 @@ fn get_ahead_behind(
     repository.graph_ahead_behind(branch_oid, tracking_oid)
 }
 
+/// Why the status of a repository isn't shown
+#[derive(Debug)]
+enum StatusError {
+    Git,
+    TooLarge,
+}
+
+impl From<git2::Error> for StatusError {
+    fn from(error: git2::Error) -> Self {
+        log::debug!("Unable to get the repo status: {}", error);
+        StatusError::Git
+    }
+}
+
 #[derive(Default, Debug, Copy, Clone)]
 struct RepoStatus {
     conflicted: usize,
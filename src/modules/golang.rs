@@ -1,6 +1,8 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::go::GoConfig;
+use crate::formatter::StringFormatter;
+use crate::segment::Segment;
 use crate::utils;
 
 /// Creates a module with the current Go version
@@ -15,7 +17,7 @@ use crate::utils;
 ///     - Current directory contains a `Godeps` directory
 ///     - Current directory contains a file with the `.go` extension
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let is_go_project = context
+    let reason = context
         .try_begin_scan()?
         .set_files(&[
             "go.mod",
@@ -27,25 +29,106 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         ])
         .set_extensions(&["go"])
         .set_folders(&["Godeps"])
-        .is_match();
-
-    if !is_go_project {
-        return None;
-    }
+        .find_match()?;
 
     let mut module = context.new_module("golang");
+    module.set_reason(reason);
     let config: GoConfig = GoConfig::try_load(module.config);
 
     module.set_style(config.style);
-    module.create_segment("symbol", &config.symbol);
 
-    let formatted_version =
-        format_go_version(&utils::exec_cmd("go", &["version"])?.stdout.as_str())?;
-    module.create_segment("version", &config.version.with_value(&formatted_version));
+    let formatted_version = format_go_version(
+        context
+            .exec_cmd("golang", "go", &["version"])?
+            .stdout
+            .as_str(),
+    )?;
+    let mod_version = get_go_mod_version(context);
+    // The toolchain refuses to build a module that needs a newer version of Go
+    let is_mismatched = mod_version.as_ref().is_some_and(|mod_version| {
+        parse_version(&formatted_version[1..]) < parse_version(mod_version)
+    });
+    let version_style = if is_mismatched {
+        Some(config.mismatch_style)
+    } else {
+        config.version.style
+    };
+
+    if let Some(format) = config.format {
+        let formatter = match StringFormatter::new(format) {
+            Ok(formatter) => formatter,
+            Err(error) => {
+                log::warn!("Error parsing `golang.format`:\n{}", error);
+                return None;
+            }
+        };
+        let segments = formatter
+            .map_variables_to_segments(|variable| {
+                let (value, style) = match variable {
+                    "symbol" => (config.symbol.value.to_owned(), config.symbol.style),
+                    "version" => (formatted_version.clone(), version_style),
+                    "mod_version" => (format!("v{}", mod_version.as_ref()?), None),
+                    _ => return None,
+                };
+                let mut segment = Segment::new(variable);
+                segment.set_value(value);
+                if let Some(style) = style {
+                    segment.set_style(style);
+                }
+                Some(vec![segment])
+            })
+            .parse(Some(config.style));
+        module.set_segments(segments);
+        return Some(module);
+    }
+
+    module.create_segment("symbol", &config.symbol);
+    module.create_segment(
+        "version",
+        &config
+            .version
+            .with_value(&formatted_version)
+            .with_style(version_style),
+    );
 
     Some(module)
 }
 
+/// The version of Go required by the `go` directive of the closest `go.mod`
+fn get_go_mod_version(context: &Context) -> Option<String> {
+    let go_mod = context
+        .current_dir
+        .ancestors()
+        .find_map(|dir| utils::read_file(dir.join("go.mod")).ok())?;
+    parse_go_mod_version(&go_mod)
+}
+
+fn parse_go_mod_version(go_mod: &str) -> Option<String> {
+    go_mod.lines().find_map(|line| {
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("go"), Some(version)) => Some(version.to_owned()),
+            _ => None,
+        }
+    })
+}
+
+/// The numbers of a version like `1.14` or `1.21rc2`, to compare versions with. A
+/// prerelease counts as its release, and `1.21.0` as `1.21`.
+fn parse_version(version: &str) -> Vec<u64> {
+    let mut numbers: Vec<u64> = version
+        .split('.')
+        .map_while(|part| {
+            let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+            digits.parse().ok()
+        })
+        .collect();
+    while numbers.last() == Some(&0) {
+        numbers.pop();
+    }
+    numbers
+}
+
 fn format_go_version(go_stdout: &str) -> Option<String> {
     // go version output looks like this:
     // go version go1.13.3 linux/amd64
@@ -67,7 +150,7 @@ fn format_go_version(go_stdout: &str) -> Option<String> {
 mod tests {
     use super::*;
     use crate::modules::utils::test::render_module;
-    use ansi_term::Color;
+    use ansi_term::{ANSIStrings, Color};
     use std::fs::{self, File};
     use std::io;
 
@@ -175,6 +258,62 @@ mod tests {
         dir.close()
     }
 
+    #[test]
+    fn folder_with_go_mod_requiring_newer_go() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("go.mod"),
+            "module github.com/starship/starship\n\ngo 1.14\n",
+        )?;
+
+        let actual = render_module("golang", dir.path(), None);
+        let expected = Some(format!(
+            "via {} ",
+            ANSIStrings(&[
+                Color::Cyan.bold().paint("🐹 "),
+                Color::Red.bold().paint("v1.12.1"),
+            ])
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_go_mod_format() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("go.mod"),
+            "module github.com/starship/starship\n\ngo 1.12\n",
+        )?;
+        let sub_dir = dir.path().join("cmd");
+        fs::create_dir(&sub_dir)?;
+        File::create(sub_dir.join("main.go"))?.sync_all()?;
+
+        let config = toml::toml! {
+            [golang]
+            format = "$symbol$version( \\(mod $mod_version\\))"
+        };
+        let actual = render_module("golang", &sub_dir, Some(config));
+        let expected = Some(format!(
+            "via {} ",
+            Color::Cyan.bold().paint("🐹 v1.12.1 (mod v1.12)")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn compare_versions() {
+        assert!(parse_version("1.12.1") < parse_version("1.14"));
+        assert!(parse_version("1.9") < parse_version("1.10"));
+        assert_eq!(parse_version("1.21.0"), parse_version("1.21"));
+        assert_eq!(parse_version("1.21rc2"), parse_version("1.21"));
+        assert_eq!(
+            parse_go_mod_version("module example.com/m\n\ngo 1.21.0 // toolchain\n"),
+            Some("1.21.0".to_string())
+        );
+    }
+
     #[test]
     fn test_format_go_version() {
         let input = "go version go1.12 darwin/amd64";
@@ -0,0 +1,60 @@
+use super::proxy::get_proxy_host;
+use super::{Context, Module, RootModuleConfig, SegmentConfig};
+
+use crate::configs::hashicorp::HashicorpConfig;
+
+/// Creates a module with the Nomad and Consul clusters the CLIs talk to
+///
+/// Will display the address of each cluster if its variable is set:
+///     - `$NOMAD_ADDR` for Nomad
+///     - `$CONSUL_HTTP_ADDR` for Consul
+///
+/// Addresses are shown as their host and port, or as their alias in `address_aliases`.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let nomad_addr = get_address(context, "NOMAD_ADDR");
+    let consul_addr = get_address(context, "CONSUL_HTTP_ADDR");
+    if nomad_addr.is_none() && consul_addr.is_none() {
+        return None;
+    }
+
+    let mut module = context.new_module("hashicorp");
+    let config: HashicorpConfig = HashicorpConfig::try_load(module.config);
+
+    module.set_style(config.style);
+    module.get_prefix().set_value("on ");
+
+    let clusters = [
+        ("nomad", &config.nomad_symbol, nomad_addr),
+        ("consul", &config.consul_symbol, consul_addr),
+    ];
+    let shown = clusters
+        .iter()
+        .filter_map(|(name, symbol, addr)| Some((name, symbol, addr.as_ref()?)));
+    for (index, (name, symbol, addr)) in shown.enumerate() {
+        if index > 0 {
+            module.create_segment("separator", &SegmentConfig::new(" "));
+        }
+        module.create_segment(&format!("{}_symbol", name), symbol);
+        let address = display_address(&config, addr);
+        module.create_segment(name, &config.address.with_value(address));
+    }
+
+    Some(module)
+}
+
+fn get_address(context: &Context, name: &str) -> Option<String> {
+    context
+        .get_env(name)
+        .filter(|value| !value.trim().is_empty())
+}
+
+/// The alias of an address, by the address as it's set or by its host, or its host
+fn display_address<'a>(config: &HashicorpConfig<'a>, addr: &'a str) -> &'a str {
+    let host = get_proxy_host(addr);
+    config
+        .address_aliases
+        .get(addr.trim())
+        .or_else(|| config.address_aliases.get(host))
+        .copied()
+        .unwrap_or(host)
+}
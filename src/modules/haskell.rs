@@ -1,7 +1,6 @@
 use super::{Context, Module, RootModuleConfig, SegmentConfig};
 
 use crate::configs::haskell::HaskellConfig;
-use crate::utils;
 
 /// Creates a module with the current Haskell Stack version
 ///
@@ -10,31 +9,30 @@ use crate::utils;
 ///     - Current directory contains a `.cabal` file
 ///     - Current directory contains a `package.yaml` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let is_haskell_project = context
+    let reason = context
         .try_begin_scan()?
         .set_files(&["package.yaml", "stack.yaml", "package.yml", "stack.yml"])
         .set_extensions(&["cabal"])
-        .is_match();
+        .find_match()?;
 
-    if !is_haskell_project {
-        return None;
-    }
-
-    let haskell_version = utils::exec_cmd(
-        "stack",
-        &[
-            "--no-install-ghc",
-            "--lock-file",
-            "read-only",
-            "ghc",
-            "--",
-            "--numeric-version",
-        ],
-    )?
-    .stdout;
+    let haskell_version = context
+        .exec_cmd(
+            "haskell",
+            "stack",
+            &[
+                "--no-install-ghc",
+                "--lock-file",
+                "read-only",
+                "ghc",
+                "--",
+                "--numeric-version",
+            ],
+        )?
+        .stdout;
     let formatted_version = Some(format!("v{}", haskell_version.trim()))?;
 
     let mut module = context.new_module("haskell");
+    module.set_reason(reason);
     let config: HaskellConfig = HaskellConfig::try_load(module.config);
     module.set_style(config.style);
 
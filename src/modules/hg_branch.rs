@@ -3,18 +3,15 @@ use unicode_segmentation::UnicodeSegmentation;
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::hg_branch::HgBranchConfig;
+use crate::utils::{display_width, truncate_to_width};
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Creates a module with the Hg bookmark or branch in the current directory
 ///
 /// Will display the bookmark or branch name if the current directory is an hg repo
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    // My own hack stacked on top to find the hg directory recursively.
-    let hg_path = match find_hg_directory(context.current_dir.clone()) {
-        Some(hg_path) => hg_path,
-        None => return None
-    };
+    let hg_path = context.get_hg_root()?.join(".hg");
 
     let mut module = context.new_module("hg_branch");
     let config = HgBranchConfig::try_load(module.config);
@@ -24,13 +21,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 
     module.create_segment("symbol", &config.symbol);
 
-    // TODO: Once error handling is implemented, warn the user if their config
-    // truncation length is nonsensical
+    // A nonsensical truncation length is reported when the config is checked
     let len = if config.truncation_length <= 0 {
-        log::warn!(
-            "\"truncation_length\" should be a positive value, found {}",
-            config.truncation_length
-        );
         std::usize::MAX
     } else {
         config.truncation_length as usize
@@ -39,13 +31,13 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let branch_name =
         get_hg_current_bookmark(hg_path.clone()).unwrap_or_else(|| get_hg_commit_name(hg_path));
 
-    let truncated_graphemes = get_graphemes(&branch_name, len);
+    let truncated = truncate_to_width(&branch_name, len).to_owned();
     // The truncation symbol should only be added if we truncated
-    let truncated_and_symbol = if len < graphemes_len(&branch_name) {
+    let truncated_and_symbol = if len < display_width(&branch_name) {
         let truncation_symbol = get_graphemes(config.truncation_symbol, 1);
-        truncated_graphemes + &truncation_symbol
+        truncated + &truncation_symbol
     } else {
-        truncated_graphemes
+        truncated
     };
 
     module.create_segment(
@@ -56,38 +48,9 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-/// Recursively ascends through the current path until either the root is reached or
-/// a .hg directory is found.
-fn find_hg_directory(mut current_path: PathBuf) -> Option<PathBuf> {
-    while current_path != PathBuf::new() {
-        let read_dir = match current_path.read_dir() {
-            Ok(read_dir) => read_dir,
-            Err(_e) => return None
-        };
-
-        for direntry in read_dir {
-            let entry = match direntry {
-                Ok(entry) => entry,
-                Err(_e) => return None,
-            };
-
-            let file_type = match entry.file_type() {
-                Ok(file_type) => file_type,
-                Err(_e) => return None,
-            };
-
-            if file_type.is_dir() && entry.file_name() == ".hg" {
-                return Some(entry.path());
-            }
-        }
-        current_path.pop();
-    }
-    None
-}
-
 fn get_hg_commit_name(hg_path: PathBuf) -> String {
     // This is reading the entire namejournal file, which is somewhat large. Faster than running hg id, though.
-    let namejournal = std::fs::read_to_string(hg_path.join("namejournal"))
+    let namejournal = read_lossy(&hg_path.join("namejournal"))
         .map(|s| s.trim().into())
         .unwrap_or_else(|_| "".to_string());
     let lines: Vec<&str> = namejournal.split("\n").collect();
@@ -99,11 +62,16 @@ fn get_hg_commit_name(hg_path: PathBuf) -> String {
 }
 
 fn get_hg_current_bookmark(hg_path: PathBuf) -> Option<String> {
-    std::fs::read_to_string(hg_path.join("bookmarks.current"))
+    read_lossy(&hg_path.join("bookmarks.current"))
         .map(|s| s.trim().into())
         .ok()
 }
 
+/// Reads a file, replacing any bytes that aren't valid UTF-8, which hg allows in names
+fn read_lossy(path: &Path) -> std::io::Result<String> {
+    std::fs::read(path).map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+}
+
 fn get_graphemes(text: &str, length: usize) -> String {
     UnicodeSegmentation::graphemes(text, true)
         .take(length)
@@ -111,6 +79,17 @@ fn get_graphemes(text: &str, length: usize) -> String {
         .concat()
 }
 
-fn graphemes_len(text: &str) -> usize {
-    UnicodeSegmentation::graphemes(&text[..], true).count()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_utf8_bookmark() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("bookmarks.current"), b"caf\xe9\n")?;
+
+        let bookmark = get_hg_current_bookmark(dir.path().to_path_buf());
+        assert_eq!(bookmark, Some(String::from("caf\u{fffd}")));
+        dir.close()
+    }
 }
@@ -1,5 +1,3 @@
-use std::env;
-
 use super::{Context, Module, SegmentConfig};
 use std::ffi::OsString;
 
@@ -15,20 +13,14 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("hostname");
     let config: HostnameConfig = HostnameConfig::try_load(module.config);
 
-    let ssh_connection = env::var("SSH_CONNECTION").ok();
+    let ssh_connection = context.get_env("SSH_CONNECTION");
     if config.ssh_only && ssh_connection.is_none() {
         return None;
     }
 
     let os_hostname: OsString = gethostname::gethostname();
 
-    let host = match os_hostname.into_string() {
-        Ok(host) => host,
-        Err(bad) => {
-            log::debug!("hostname is not valid UTF!\n{:?}", bad);
-            return None;
-        }
-    };
+    let host = os_hostname.to_string_lossy().into_owned();
 
     //rustc doesn't let you do an "if" and an "if let" in the same if statement
     // if this changes in the future this can become a lot cleaner
@@ -3,7 +3,6 @@ use crate::configs::java::JavaConfig;
 use super::{Context, Module, RootModuleConfig, SegmentConfig};
 
 use crate::modules::utils::java_version_parser;
-use crate::utils;
 
 /// Creates a module with the current Java version
 ///
@@ -11,19 +10,16 @@ use crate::utils;
 ///     - Current directory contains a file with a `.java`, `.class`, `.gradle` or `.jar` extension
 ///     - Current directory contains a `pom.xml`, `build.gradle.kts`, `build.sbt` or `.java-version` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let is_java_project = context
+    let reason = context
         .try_begin_scan()?
         .set_files(&["pom.xml", "build.gradle.kts", "build.sbt", ".java-version"])
         .set_extensions(&["java", "class", "jar", "gradle"])
-        .is_match();
+        .find_match()?;
 
-    if !is_java_project {
-        return None;
-    }
-
-    match get_java_version() {
+    match get_java_version(context) {
         Some(java_version) => {
             let mut module = context.new_module("java");
+            module.set_reason(reason);
             let config: JavaConfig = JavaConfig::try_load(module.config);
             module.set_style(config.style);
 
@@ -37,13 +33,13 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     }
 }
 
-fn get_java_version() -> Option<String> {
-    let java_command = match std::env::var("JAVA_HOME") {
-        Ok(java_home) => format!("{}/bin/java", java_home),
-        Err(_) => String::from("java"),
+fn get_java_version(context: &Context) -> Option<String> {
+    let java_command = match context.get_env("JAVA_HOME") {
+        Some(java_home) => format!("{}/bin/java", java_home),
+        None => String::from("java"),
     };
 
-    let output = utils::exec_cmd(&java_command.as_str(), &["-Xinternalversion"])?;
+    let output = context.exec_cmd("java", &java_command, &["-Xinternalversion"])?;
     Some(format!("{}{}", output.stdout, output.stderr))
 }
 
@@ -1,7 +1,6 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::julia::JuliaConfig;
-use crate::utils;
 
 /// Creates a module with the current Julia version
 ///
@@ -10,24 +9,25 @@ use crate::utils;
 ///     - Current directory contains a `Manifest.toml` file
 ///     - Current directory contains a file with the `.jl` extension
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let is_julia_project = context
+    let reason = context
         .try_begin_scan()?
         .set_files(&["Project.toml", "Manifest.toml"])
         .set_extensions(&["jl"])
-        .is_match();
-
-    if !is_julia_project {
-        return None;
-    }
+        .find_match()?;
 
     let mut module = context.new_module("julia");
+    module.set_reason(reason);
     let config: JuliaConfig = JuliaConfig::try_load(module.config);
 
     module.set_style(config.style);
     module.create_segment("symbol", &config.symbol);
 
-    let formatted_version =
-        format_julia_version(&utils::exec_cmd("julia", &["--version"])?.stdout.as_str())?;
+    let formatted_version = format_julia_version(
+        context
+            .exec_cmd("julia", "julia", &["--version"])?
+            .stdout
+            .as_str(),
+    )?;
     module.create_segment("version", &config.version.with_value(&formatted_version));
 
     Some(module)
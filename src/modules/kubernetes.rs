@@ -3,14 +3,25 @@ use yaml_rust::YamlLoader;
 use std::env;
 use std::path;
 
+use super::utils::redact::redact;
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::kubernetes::KubernetesConfig;
+use crate::formatter::StringFormatter;
 use crate::utils;
 
 const KUBERNETES_PREFIX: &str = "on ";
 
-fn get_kube_context(contents: &str) -> Option<(String, String)> {
+/// The current context of a kubeconfig file, with what it's made of
+#[derive(Debug, PartialEq)]
+struct KubeContext {
+    name: String,
+    namespace: String,
+    cluster: String,
+    user: String,
+}
+
+fn get_kube_context(contents: &str) -> Option<KubeContext> {
     let yaml_docs = YamlLoader::load_from_str(&contents).ok()?;
     if yaml_docs.is_empty() {
         return None;
@@ -23,67 +34,106 @@ fn get_kube_context(contents: &str) -> Option<(String, String)> {
         return None;
     }
 
-    let ns = conf["contexts"]
-        .as_vec()
-        .and_then(|contexts| {
-            contexts
-                .iter()
-                .filter_map(|ctx| Some((ctx, ctx["name"].as_str()?)))
-                .find(|(_, name)| *name == current_ctx)
-                .and_then(|(ctx, _)| ctx["context"]["namespace"].as_str())
-        })
-        .unwrap_or("");
-
-    Some((current_ctx.to_string(), ns.to_string()))
+    let ctx = conf["contexts"].as_vec().and_then(|contexts| {
+        contexts
+            .iter()
+            .find(|ctx| ctx["name"].as_str() == Some(current_ctx))
+    });
+    let field = |name: &str| {
+        ctx.and_then(|ctx| ctx["context"][name].as_str())
+            .unwrap_or("")
+            .to_string()
+    };
+
+    Some(KubeContext {
+        name: current_ctx.to_string(),
+        namespace: field("namespace"),
+        cluster: field("cluster"),
+        user: field("user"),
+    })
 }
 
-fn parse_kubectl_file(filename: &path::PathBuf) -> Option<(String, String)> {
+fn parse_kubectl_file(filename: &path::PathBuf) -> Option<KubeContext> {
     let contents = utils::read_file(filename).ok()?;
     get_kube_context(&contents)
 }
 
-pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let kube_cfg = match env::var("KUBECONFIG") {
-        Ok(paths) => env::split_paths(&paths)
-            .filter_map(|filename| parse_kubectl_file(&filename))
-            .next(),
-        Err(_) => {
-            let filename = dirs::home_dir()?.join(".kube").join("config");
-            parse_kubectl_file(&filename)
-        }
-    };
+/// The kubeconfig files, from `$KUBECONFIG` or else `~/.kube/config`, of which the
+/// first one with a current context is used
+pub fn config_paths(context: &Context) -> Vec<path::PathBuf> {
+    match context.get_env("KUBECONFIG") {
+        Some(paths) => env::split_paths(&paths).collect(),
+        None => dirs::home_dir()
+            .map(|home| home.join(".kube").join("config"))
+            .into_iter()
+            .collect(),
+    }
+}
 
-    match kube_cfg {
-        Some(kube_cfg) => {
-            let (kube_ctx, kube_ns) = kube_cfg;
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let kube_ctx = config_paths(context).iter().find_map(parse_kubectl_file)?;
 
-            let mut module = context.new_module("kubernetes");
-            let config: KubernetesConfig = KubernetesConfig::try_load(module.config);
-            if config.disabled {
-                return None;
-            };
+    let mut module = context.new_module("kubernetes");
+    let config: KubernetesConfig = KubernetesConfig::try_load(module.config);
+    if config.disabled {
+        return None;
+    };
 
-            module.set_style(config.style);
-            module.get_prefix().set_value(KUBERNETES_PREFIX);
+    let ctx_config = config
+        .contexts
+        .get(&kube_ctx.name)
+        .cloned()
+        .unwrap_or_default();
+    if ctx_config.disabled == Some(true) {
+        return None;
+    }
+    let style = ctx_config.style.unwrap_or(config.style);
 
-            module.create_segment("symbol", &config.symbol);
+    module.set_style(style);
+    module.get_prefix().set_value(KUBERNETES_PREFIX);
 
-            let displayed_context = match config.context_aliases.get(&kube_ctx) {
-                None => &kube_ctx,
-                Some(&alias) => alias,
-            };
+    // An alias is chosen by the user, so it isn't redacted
+    let displayed_context = match ctx_config
+        .alias
+        .or_else(|| config.context_aliases.get(&kube_ctx.name).copied())
+    {
+        None => redact(&kube_ctx.name, config.redact),
+        Some(alias) => alias.to_owned(),
+    };
 
-            module.create_segment("context", &config.context.with_value(&displayed_context));
-            if kube_ns != "" {
-                module.create_segment(
-                    "namespace",
-                    &config.namespace.with_value(&format!(" ({})", kube_ns)),
-                );
+    if let Some(format) = config.format {
+        let formatter = match StringFormatter::new(format) {
+            Ok(formatter) => formatter,
+            Err(error) => {
+                log::warn!("Error parsing `kubernetes.format`:\n{}", error);
+                return None;
             }
-            Some(module)
-        }
-        None => None,
+        };
+        let segments = formatter
+            .map(|variable| match variable {
+                "symbol" => Some(config.symbol.value.to_owned()),
+                "context" => Some(displayed_context.clone()),
+                "namespace" => Some(kube_ctx.namespace.clone()),
+                "cluster" => Some(redact(&kube_ctx.cluster, config.redact)),
+                "user" => Some(redact(&kube_ctx.user, config.redact)),
+                _ => None,
+            })
+            .parse(Some(style));
+        module.set_segments(segments);
+        return Some(module);
+    }
+
+    module.create_segment("symbol", &config.symbol);
+    module.create_segment("context", &config.context.with_value(&displayed_context));
+    if !kube_ctx.namespace.is_empty() {
+        module.create_segment(
+            "namespace",
+            &config
+                .namespace
+                .with_value(&format!(" ({})", kube_ctx.namespace)),
+        );
     }
+    Some(module)
 }
 
 #[cfg(test)]
@@ -132,7 +182,12 @@ preferences: {}
 users: []
 "#;
         let result = get_kube_context(&input);
-        let expected = Some(("test_context".to_string(), "".to_string()));
+        let expected = Some(KubeContext {
+            name: "test_context".to_string(),
+            namespace: "".to_string(),
+            cluster: "test_cluster".to_string(),
+            user: "test_user".to_string(),
+        });
 
         assert_eq!(result, expected);
     }
@@ -154,7 +209,12 @@ preferences: {}
 users: []
 "#;
         let result = get_kube_context(&input);
-        let expected = Some(("test_context".to_string(), "test_namespace".to_string()));
+        let expected = Some(KubeContext {
+            name: "test_context".to_string(),
+            namespace: "test_namespace".to_string(),
+            cluster: "test_cluster".to_string(),
+            user: "test_user".to_string(),
+        });
 
         assert_eq!(result, expected);
     }
@@ -181,7 +241,12 @@ preferences: {}
 users: []
 "#;
         let result = get_kube_context(&input);
-        let expected = Some(("test_context".to_string(), "test_namespace".to_string()));
+        let expected = Some(KubeContext {
+            name: "test_context".to_string(),
+            namespace: "test_namespace".to_string(),
+            cluster: "test_cluster".to_string(),
+            user: "test_user".to_string(),
+        });
 
         assert_eq!(result, expected);
     }
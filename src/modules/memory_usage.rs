@@ -20,7 +20,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     // TODO: Update when v1.0 printing refactor is implemented to only
     // print escapes in a prompt context.
     let percent_sign = match context.shell {
-        Shell::Zsh => "%%", // % is an escape in zsh, see PROMPT in `man zshmisc`
+        // % is an escape in zsh and tcsh, see PROMPT in `man zshmisc`
+        Shell::Zsh | Shell::Tcsh => "%%",
         _ => "%",
     };
 
@@ -1,29 +1,39 @@
-// While adding out new module add out module to src/module.rs ALL_MODULES const array also.
+// While adding out new module add out module to src/module.rs ALL_MODULES const array
+// and to BUILTIN_MODULES below also.
+mod ansible;
 mod aws;
+mod bun;
 mod character;
+mod chroot;
+mod ci;
 mod cmd_duration;
+mod commit_signing;
 mod conda;
 mod crystal;
 pub(crate) mod custom;
+mod dbt;
 mod directory;
 mod docker_context;
 mod dotnet;
 mod elixir;
 mod elm;
-mod env_var;
+pub(crate) mod env_var;
 mod erlang;
+mod fill;
+mod gcloud;
 mod git_branch;
 mod git_commit;
 mod git_state;
 mod git_status;
 mod golang;
+mod hashicorp;
 mod haskell;
 mod hg_branch;
 mod hostname;
 mod java;
 mod jobs;
 mod julia;
-mod kubernetes;
+pub(crate) mod kubernetes;
 mod line_break;
 mod memory_usage;
 mod nix_shell;
@@ -31,14 +41,20 @@ mod nodejs;
 mod ocaml;
 mod package;
 mod php;
+pub(crate) mod plugin;
+mod proxy;
 mod python;
 mod ruby;
 mod rust;
 mod singularity;
+mod solidity;
 mod terraform;
+mod tex;
 mod time;
+mod typst;
 mod username;
-mod utils;
+pub(crate) mod utils;
+mod vpn;
 mod zig;
 
 #[cfg(feature = "battery")]
@@ -47,53 +63,142 @@ mod battery;
 use crate::config::{RootModuleConfig, SegmentConfig};
 use crate::context::{Context, Shell};
 use crate::module::Module;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+pub use fill::FILL_SEGMENT;
+
+/// Creates a module for a context, or `None` if it isn't shown
+pub type ModuleFn = for<'a, 'b> fn(&'a Context<'b>) -> Option<Module<'a>>;
+
+// Keep these ordered alphabetically.
+// Default ordering is handled in configs/mod.rs
+const BUILTIN_MODULES: &[(&str, ModuleFn)] = &[
+    ("ansible", ansible::module),
+    ("aws", aws::module),
+    #[cfg(feature = "battery")]
+    ("battery", battery::module),
+    ("bun", bun::module),
+    ("character", character::module),
+    ("chroot", chroot::module),
+    ("ci", ci::module),
+    ("cmd_duration", cmd_duration::module),
+    ("commit_signing", commit_signing::module),
+    ("conda", conda::module),
+    ("crystal", crystal::module),
+    ("dbt", dbt::module),
+    ("directory", directory::module),
+    ("docker_context", docker_context::module),
+    ("dotnet", dotnet::module),
+    ("elixir", elixir::module),
+    ("elm", elm::module),
+    ("env_var", env_var::module),
+    ("erlang", erlang::module),
+    ("fill", fill::module),
+    ("gcloud", gcloud::module),
+    ("git_branch", git_branch::module),
+    ("git_commit", git_commit::module),
+    ("git_state", git_state::module),
+    ("git_status", git_status::module),
+    ("golang", golang::module),
+    ("hashicorp", hashicorp::module),
+    ("haskell", haskell::module),
+    ("hg_branch", hg_branch::module),
+    ("hostname", hostname::module),
+    ("java", java::module),
+    ("jobs", jobs::module),
+    ("julia", julia::module),
+    ("kubernetes", kubernetes::module),
+    ("line_break", line_break::module),
+    ("memory_usage", memory_usage::module),
+    ("nix_shell", nix_shell::module),
+    ("nodejs", nodejs::module),
+    ("ocaml", ocaml::module),
+    ("package", package::module),
+    ("php", php::module),
+    ("proxy", proxy::module),
+    ("python", python::module),
+    ("ruby", ruby::module),
+    ("rust", rust::module),
+    ("singularity", singularity::module),
+    ("solidity", solidity::module),
+    ("terraform", terraform::module),
+    ("tex", tex::module),
+    ("time", time::module),
+    ("typst", typst::module),
+    ("username", username::module),
+    ("vpn", vpn::module),
+    ("zig", zig::module),
+];
+
+struct Registration {
+    module: ModuleFn,
+    description: &'static str,
+}
+
+/// The modules by name, starting out with the built-in ones
+static REGISTRY: Lazy<RwLock<HashMap<String, Registration>>> = Lazy::new(|| {
+    let registry = BUILTIN_MODULES
+        .iter()
+        .map(|(name, module)| {
+            let registration = Registration {
+                module: *module,
+                description: builtin_description(name),
+            };
+            ((*name).to_owned(), registration)
+        })
+        .collect();
+    RwLock::new(registry)
+});
+
+/// Register a module, to be used by that name like the built-in modules. A module
+/// registered with the name of a built-in module replaces it.
+#[allow(dead_code)] // Only called through the library, not by the binary
+pub fn register(name: &str, description: &'static str, module: ModuleFn) {
+    let registration = Registration {
+        module,
+        description,
+    };
+    match REGISTRY.write() {
+        Ok(mut registry) => {
+            registry.insert(name.to_owned(), registration);
+        }
+        Err(_) => log::warn!("Unable to register the module {}", name),
+    }
+}
+
+/// Whether a module of that name was registered, including the built-in modules
+pub fn is_registered(name: &str) -> bool {
+    match REGISTRY.read() {
+        Ok(registry) => registry.contains_key(name),
+        Err(_) => false,
+    }
+}
+
+/// The names of the registered modules that aren't built in, in alphabetical order
+pub fn registered_names() -> Vec<String> {
+    let mut names: Vec<String> = match REGISTRY.read() {
+        Ok(registry) => registry
+            .keys()
+            .filter(|name| !BUILTIN_MODULES.iter().any(|(builtin, _)| builtin == name))
+            .cloned()
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    names.sort();
+    names
+}
 
 pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
-    match module {
-        // Keep these ordered alphabetically.
-        // Default ordering is handled in configs/mod.rs
-        "aws" => aws::module(context),
-        #[cfg(feature = "battery")]
-        "battery" => battery::module(context),
-        "character" => character::module(context),
-        "cmd_duration" => cmd_duration::module(context),
-        "conda" => conda::module(context),
-        "directory" => directory::module(context),
-        "docker_context" => docker_context::module(context),
-        "dotnet" => dotnet::module(context),
-        "elixir" => elixir::module(context),
-        "elm" => elm::module(context),
-        "erlang" => erlang::module(context),
-        "env_var" => env_var::module(context),
-        "git_branch" => git_branch::module(context),
-        "git_commit" => git_commit::module(context),
-        "git_state" => git_state::module(context),
-        "git_status" => git_status::module(context),
-        "golang" => golang::module(context),
-        "haskell" => haskell::module(context),
-        "hg_branch" => hg_branch::module(context),
-        "hostname" => hostname::module(context),
-        "java" => java::module(context),
-        "jobs" => jobs::module(context),
-        "julia" => julia::module(context),
-        "kubernetes" => kubernetes::module(context),
-        "line_break" => line_break::module(context),
-        "memory_usage" => memory_usage::module(context),
-        "nix_shell" => nix_shell::module(context),
-        "nodejs" => nodejs::module(context),
-        "ocaml" => ocaml::module(context),
-        "package" => package::module(context),
-        "php" => php::module(context),
-        "python" => python::module(context),
-        "ruby" => ruby::module(context),
-        "rust" => rust::module(context),
-        "singularity" => singularity::module(context),
-        "terraform" => terraform::module(context),
-        "time" => time::module(context),
-        "crystal" => crystal::module(context),
-        "username" => username::module(context),
-        "zig" => zig::module(context),
-        _ => {
+    // The lock is released before the module runs, which may register modules itself
+    let module_fn = REGISTRY
+        .read()
+        .ok()
+        .and_then(|registry| Some(registry.get(module)?.module));
+    match module_fn {
+        Some(module_fn) => module_fn(context),
+        None => {
             eprintln!("Error: Unknown module {}. Use starship module --list to list out all supported modules.", module);
             None
         }
@@ -101,25 +206,42 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
 }
 
 pub fn description(module: &str) -> &'static str {
+    let registered = REGISTRY
+        .read()
+        .ok()
+        .and_then(|registry| Some(registry.get(module)?.description));
+    registered.unwrap_or_else(|| builtin_description(module))
+}
+
+fn builtin_description(module: &str) -> &'static str {
     match module {
+        "ansible" => "The current ansible-core version and inventory",
         "aws" => "The current AWS region and profile",
         "battery" => "The current charge of the device's battery and its current charging status",
+        "bun" => "The currently installed version of Bun",
         "character" => {
             "A character (usually an arrow) beside where the text is entered in your terminal"
         }
+        "chroot" => "The name of the chroot the shell is running in",
+        "ci" => "The CI provider the current shell is running under",
         "cmd_duration" => "How long the last command took to execute",
+        "commit_signing" => "Whether commits are signed, and with a key that can be used",
         "conda" => "The current conda environment, if $CONDA_DEFAULT_ENV is set",
         "crystal" => "The currently installed version of Crystal",
+        "dbt" => "The dbt profile and target of the current project",
         "directory" => "The current working directory",
         "docker_context" => "The current docker context",
         "dotnet" => "The relevant version of the .NET Core SDK for the current directory",
         "env_var" => "Displays the current value of a selected environment variable",
         "erlang" => "Current OTP version",
+        "fill" => "Fills the remaining space on the line with a symbol",
+        "gcloud" => "The account and project of the active gcloud configuration",
         "git_branch" => "The active branch of the repo in your current directory",
         "git_commit" => "The active commit of the repo in your current directory",
         "git_state" => "The current git operation, and it's progress",
         "git_status" => "Symbol representing the state of the repo",
         "golang" => "The currently installed version of Golang",
+        "hashicorp" => "The Nomad and Consul clusters the CLIs talk to",
         "haskell" => "The currently used version of Haskell",
         "hg_branch" => "The active branch of the repo in your current directory",
         "hostname" => "The system hostname",
@@ -134,13 +256,59 @@ pub fn description(module: &str) -> &'static str {
         "ocaml" => "The currently installed version of OCaml",
         "package" => "The package version of the current directory's project",
         "php" => "The currently installed version of PHP",
+        "proxy" => "Whether a proxy is configured through the environment",
         "python" => "The currently installed version of Python",
         "ruby" => "The currently installed version of Ruby",
         "rust" => "The currently installed version of Rust",
+        "solidity" => "The currently installed version of the Solidity compiler",
         "terraform" => "The currently selected terraform workspace and version",
+        "tex" => "The currently installed TeX distribution",
         "time" => "The current local time",
+        "typst" => "The currently installed version of Typst",
         "username" => "The active user's username",
+        "vpn" => "The connected VPN interface",
         "zig" => "The currently installed version of Zig",
         _ => "<no description>",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::ModuleRenderer;
+
+    fn greeting<'a>(context: &'a Context) -> Option<Module<'a>> {
+        let mut module = context.new_module("test_greeting");
+        module.create_segment("text", &SegmentConfig::new("hello"));
+        Some(module)
+    }
+
+    fn farewell<'a>(context: &'a Context) -> Option<Module<'a>> {
+        let mut module = context.new_module("test_greeting");
+        module.create_segment("text", &SegmentConfig::new("bye"));
+        Some(module)
+    }
+
+    #[test]
+    fn registered_module() {
+        assert!(is_registered("aws"));
+        assert!(!is_registered("test_greeting"));
+        assert_eq!(ModuleRenderer::new("test_greeting").collect(), None);
+
+        register("test_greeting", "A greeting", greeting);
+        assert!(is_registered("test_greeting"));
+        assert!(registered_names().contains(&String::from("test_greeting")));
+        assert!(!registered_names().contains(&String::from("aws")));
+        assert_eq!(description("test_greeting"), "A greeting");
+        assert_eq!(
+            ModuleRenderer::new("test_greeting").collect(),
+            Some(String::from("via hello "))
+        );
+
+        register("test_greeting", "A farewell", farewell);
+        assert_eq!(
+            ModuleRenderer::new("test_greeting").collect(),
+            Some(String::from("via bye "))
+        );
+    }
+}
@@ -1,5 +1,3 @@
-use std::env;
-
 use super::{Context, Module, RootModuleConfig, SegmentConfig};
 
 use crate::configs::nix_shell::NixShellConfig;
@@ -29,7 +27,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_style(config.style);
     module.create_segment("symbol", &config.symbol);
 
-    let shell_type = env::var("IN_NIX_SHELL").ok()?;
+    let shell_type = context.get_env("IN_NIX_SHELL")?;
     let shell_type_segment: SegmentConfig = match shell_type.as_ref() {
         "1" | "impure" => config.impure_msg,
         "pure" => config.pure_msg,
@@ -37,9 +35,10 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             return None;
         }
     };
+    module.set_reason("`$IN_NIX_SHELL` is set");
 
     if config.use_name {
-        if let Ok(name) = env::var("name") {
+        if let Some(name) = context.get_env("name") {
             module.create_segment(
                 "nix_shell",
                 &shell_type_segment.with_value(&format!("{} ({})", name, shell_type_segment.value)),
@@ -1,8 +1,19 @@
+use std::path::Path;
+
 use super::{Context, Module, RootModuleConfig, SegmentConfig};
 
 use crate::configs::nodejs::NodejsConfig;
 use crate::utils;
 
+/// The lockfiles of the package managers, in the order they are looked for
+const LOCKFILES: &[(&str, &str)] = &[
+    ("pnpm-lock.yaml", "pnpm"),
+    ("yarn.lock", "yarn"),
+    ("bun.lockb", "bun"),
+    ("package-lock.json", "npm"),
+    ("npm-shrinkwrap.json", "npm"),
+];
+
 /// Creates a module with the current Node.js version
 ///
 /// Will display the Node.js version if any of the following criteria are met:
@@ -10,20 +21,17 @@ use crate::utils;
 ///     - Current directory contains a `package.json` or `.node-version` file
 ///     - Current directory contains a `node_modules` directory
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let is_js_project = context
+    let reason = context
         .try_begin_scan()?
         .set_files(&["package.json", ".node-version"])
         .set_extensions(&["js"])
         .set_folders(&["node_modules"])
-        .is_match();
-
-    if !is_js_project {
-        return None;
-    }
+        .find_match()?;
 
-    let node_version = utils::exec_cmd("node", &["--version"])?.stdout;
+    let node_version = context.exec_cmd("nodejs", "node", &["--version"])?.stdout;
 
     let mut module = context.new_module("nodejs");
+    module.set_reason(reason);
     let config: NodejsConfig = NodejsConfig::try_load(module.config);
 
     module.set_style(config.style);
@@ -32,15 +40,77 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.create_segment("symbol", &config.symbol);
     module.create_segment("version", &SegmentConfig::new(formatted_version));
 
+    if config.show_package_manager {
+        if let Some((name, version)) = get_package_manager(context) {
+            let name = config
+                .package_manager_symbols
+                .get(&name)
+                .map_or(name.as_str(), |symbol| *symbol);
+            let package_manager = match version {
+                Some(version) => format!(" {}@{}", name, version),
+                None => format!(" {}", name),
+            };
+            module.create_segment(
+                "package_manager",
+                &config.package_manager.with_value(&package_manager),
+            );
+        }
+    }
+
     Some(module)
 }
 
+/// The package manager of the project, and its version if it's pinned, from the
+/// `packageManager` field of `package.json` or else from the lockfile in the directory.
+/// Directories up to the root of the repository are looked in too, for workspaces.
+fn get_package_manager(context: &Context) -> Option<(String, Option<String>)> {
+    let repo_root = context
+        .get_repo()
+        .ok()
+        .and_then(|repo| repo.root.as_deref());
+    let current_dir = context.current_dir.as_path();
+    context
+        .current_dir
+        .ancestors()
+        .take_while(|dir| match repo_root {
+            Some(root) => dir.starts_with(root),
+            None => *dir == current_dir,
+        })
+        .find_map(|dir| read_package_manager(dir).or_else(|| find_lockfile(dir)))
+}
+
+fn read_package_manager(dir: &Path) -> Option<(String, Option<String>)> {
+    let package_json = utils::read_file(dir.join("package.json")).ok()?;
+    let package_json: serde_json::Value = serde_json::from_str(&package_json).ok()?;
+    parse_package_manager(package_json.get("packageManager")?.as_str()?)
+}
+
+/// Parse a `packageManager` field like `pnpm@8.6.0+sha256.0123…`, leaving out the hash
+fn parse_package_manager(package_manager: &str) -> Option<(String, Option<String>)> {
+    let mut parts = package_manager.splitn(2, '@');
+    let name = parts.next().filter(|name| !name.is_empty())?;
+    let version = parts
+        .next()
+        .and_then(|version| version.split('+').next())
+        .filter(|version| !version.is_empty());
+    Some((name.to_owned(), version.map(str::to_owned)))
+}
+
+fn find_lockfile(dir: &Path) -> Option<(String, Option<String>)> {
+    LOCKFILES
+        .iter()
+        .find(|(lockfile, _)| dir.join(lockfile).is_file())
+        .map(|(_, name)| ((*name).to_owned(), None))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::modules::utils::test::render_module;
-    use ansi_term::Color;
+    use ansi_term::{ANSIStrings, Color};
     use std::fs::{self, File};
     use std::io;
+    use std::path::Path;
+    use std::process::Command;
 
     #[test]
     fn folder_without_node_files() -> io::Result<()> {
@@ -95,4 +165,122 @@ mod tests {
         assert_eq!(expected, actual);
         dir.close()
     }
+
+    #[test]
+    fn folder_with_lockfile() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("package.json"))?.sync_all()?;
+        File::create(dir.path().join("yarn.lock"))?.sync_all()?;
+
+        let actual = render_module("nodejs", dir.path(), None);
+        let expected = Some(format!(
+            "via {} ",
+            Color::Green.bold().paint("⬢ v12.0.0 yarn")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_package_manager_field() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"name": "starship", "packageManager": "pnpm@8.6.0+sha256.0123abcd"}"#,
+        )?;
+        File::create(dir.path().join("package-lock.json"))?.sync_all()?;
+
+        let actual = render_module("nodejs", dir.path(), None);
+        let expected = Some(format!(
+            "via {} ",
+            Color::Green.bold().paint("⬢ v12.0.0 pnpm@8.6.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn workspace_package_with_package_manager_symbol() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        Command::new("git")
+            .arg("init")
+            .arg("--quiet")
+            .current_dir(dir.path())
+            .status()?;
+        File::create(dir.path().join("bun.lockb"))?.sync_all()?;
+        let package = dir.path().join("packages").join("docs");
+        fs::create_dir_all(&package)?;
+        File::create(package.join("package.json"))?.sync_all()?;
+
+        let config = toml::toml! {
+            [nodejs]
+            package_manager = { style = "bold yellow" }
+            [nodejs.package_manager_symbols]
+            bun = "🥟"
+        };
+        let actual = render_module("nodejs", &package, Some(config));
+        let expected = Some(format!(
+            "via {} ",
+            ANSIStrings(&[
+                Color::Green.bold().paint("⬢ v12.0.0"),
+                Color::Yellow.bold().paint(" 🥟"),
+            ])
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn no_package_manager_shown() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("pnpm-lock.yaml"))?.sync_all()?;
+        File::create(dir.path().join("index.js"))?.sync_all()?;
+
+        let config = toml::toml! {
+            [nodejs]
+            show_package_manager = false
+        };
+        let actual = render_module("nodejs", dir.path(), Some(config));
+        let expected = Some(format!("via {} ", Color::Green.bold().paint("⬢ v12.0.0")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn refuse_node_inside_repository() -> io::Result<()> {
+        use crate::test::ModuleRenderer;
+        use std::os::unix::fs::PermissionsExt;
+
+        let install_node = |dir: &Path| -> io::Result<String> {
+            let bin = dir.join("bin");
+            fs::create_dir_all(&bin)?;
+            fs::write(bin.join("node"), "#!/bin/sh\n")?;
+            fs::set_permissions(bin.join("node"), fs::Permissions::from_mode(0o755))?;
+            Ok(bin.to_string_lossy().into_owned())
+        };
+        let dir = tempfile::tempdir()?;
+        Command::new("git")
+            .arg("init")
+            .arg("--quiet")
+            .current_dir(dir.path())
+            .status()?;
+        File::create(dir.path().join("package.json"))?.sync_all()?;
+        let outside = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .env("PATH", install_node(dir.path())?)
+            .collect();
+        assert_eq!(None, actual);
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .env("PATH", install_node(outside.path())?)
+            .collect();
+        let expected = Some(format!("via {} ", Color::Green.bold().paint("⬢ v12.0.0")));
+        assert_eq!(expected, actual);
+        outside.close()?;
+        dir.close()
+    }
 }
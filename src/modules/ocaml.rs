@@ -1,7 +1,6 @@
 use super::{Context, Module, RootModuleConfig, SegmentConfig};
 
 use crate::configs::ocaml::OCamlConfig;
-use crate::utils;
 
 /// Creates a module with the current OCaml version
 ///
@@ -13,21 +12,18 @@ use crate::utils;
 ///     - Current directory contains a `.merlin` file
 ///     - Current directory contains a file with `.ml`, `.mli`, `.re` or `.rei` extension
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let is_ocaml_project = context
+    let reason = context
         .try_begin_scan()?
         .set_files(&["dune", "dune-project", "jbuild", "jbuild-ignore", ".merlin"])
         .set_folders(&["_opam", "esy.lock"])
         .set_extensions(&["opam", "ml", "mli", "re", "rei"])
-        .is_match();
+        .find_match()?;
 
-    if !is_ocaml_project {
-        return None;
-    }
-
-    let ocaml_version = utils::exec_cmd("ocaml", &["-vnum"])?.stdout;
+    let ocaml_version = context.exec_cmd("ocaml", "ocaml", &["-vnum"])?.stdout;
     let formatted_version = format!("v{}", &ocaml_version);
 
     let mut module = context.new_module("ocaml");
+    module.set_reason(reason);
     let config = OCamlConfig::try_load(module.config);
     module.set_style(config.style);
 
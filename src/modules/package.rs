@@ -1,5 +1,3 @@
-use std::path::PathBuf;
-
 use super::{Context, Module};
 use crate::utils;
 
@@ -16,7 +14,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("package");
     let config: PackageConfig = PackageConfig::try_load(module.config);
 
-    match get_package_version(&context.current_dir, &config) {
+    match get_package_version(context, &config) {
         Some(package_version) => {
             module.set_style(config.style);
             module.get_prefix().set_value("is ");
@@ -102,20 +100,31 @@ fn extract_mix_version(file_contents: &str) -> Option<String> {
     Some(formatted_version)
 }
 
-fn get_package_version(base_dir: &PathBuf, config: &PackageConfig) -> Option<String> {
-    if let Ok(cargo_toml) = utils::read_file(base_dir.join("Cargo.toml")) {
+fn get_package_version(context: &Context, config: &PackageConfig) -> Option<String> {
+    // Only the files found by the directory scan are read, rather than trying to open
+    // every kind of manifest in every directory
+    let dir_contents = context.dir_contents().ok()?;
+    let read_file = |name: &str| {
+        if dir_contents.has_file(name) {
+            utils::read_file(context.current_dir.join(name)).ok()
+        } else {
+            None
+        }
+    };
+
+    if let Some(cargo_toml) = read_file("Cargo.toml") {
         extract_cargo_version(&cargo_toml)
-    } else if let Ok(package_json) = utils::read_file(base_dir.join("package.json")) {
+    } else if let Some(package_json) = read_file("package.json") {
         extract_package_version(&package_json, config.display_private)
-    } else if let Ok(poetry_toml) = utils::read_file(base_dir.join("pyproject.toml")) {
+    } else if let Some(poetry_toml) = read_file("pyproject.toml") {
         extract_poetry_version(&poetry_toml)
-    } else if let Ok(composer_json) = utils::read_file(base_dir.join("composer.json")) {
+    } else if let Some(composer_json) = read_file("composer.json") {
         extract_composer_version(&composer_json)
-    } else if let Ok(build_gradle) = utils::read_file(base_dir.join("build.gradle")) {
+    } else if let Some(build_gradle) = read_file("build.gradle") {
         extract_gradle_version(&build_gradle)
-    } else if let Ok(project_toml) = utils::read_file(base_dir.join("Project.toml")) {
+    } else if let Some(project_toml) = read_file("Project.toml") {
         extract_project_version(&project_toml)
-    } else if let Ok(mix_file) = utils::read_file(base_dir.join("mix.exs")) {
+    } else if let Some(mix_file) = read_file("mix.exs") {
         extract_mix_version(&mix_file)
     } else {
         None
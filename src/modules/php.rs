@@ -8,18 +8,18 @@ use crate::utils;
 /// Will display the PHP version if any of the following criteria are met:
 ///     - Current directory contains a `.php` file
 ///     - Current directory contains a `composer.json` or `.php-version` file
+///
+/// The version is shown in `mismatch_style` if it doesn't satisfy the `require.php`
+/// constraint of the closest `composer.json`.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let is_php_project = context
+    let reason = context
         .try_begin_scan()?
         .set_files(&["composer.json", ".php-version"])
         .set_extensions(&["php"])
-        .is_match();
-
-    if !is_php_project {
-        return None;
-    }
+        .find_match()?;
 
-    match utils::exec_cmd(
+    match context.exec_cmd(
+        "php",
         "php",
         &[
             "-r",
@@ -30,13 +30,25 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             let php_version = php_cmd_output.stdout;
 
             let mut module = context.new_module("php");
+            module.set_reason(reason);
             let config: PhpConfig = PhpConfig::try_load(module.config);
 
             module.set_style(config.style);
 
             let formatted_version = format_php_version(&php_version)?;
+            let is_mismatched = get_composer_constraint(context)
+                .and_then(|constraint| matches_constraint(php_version.trim(), &constraint))
+                == Some(false);
+            let version_style = if is_mismatched {
+                Some(config.mismatch_style)
+            } else {
+                None
+            };
             module.create_segment("symbol", &config.symbol);
-            module.create_segment("version", &SegmentConfig::new(&formatted_version));
+            module.create_segment(
+                "version",
+                &SegmentConfig::new(&formatted_version).with_style(version_style),
+            );
 
             Some(module)
         }
@@ -51,12 +63,142 @@ fn format_php_version(php_version: &str) -> Option<String> {
     Some(formatted_version)
 }
 
+/// The PHP version constraint in `require.php` of the closest `composer.json`
+fn get_composer_constraint(context: &Context) -> Option<String> {
+    let composer_json = context
+        .current_dir
+        .ancestors()
+        .find_map(|dir| utils::read_file(dir.join("composer.json")).ok())?;
+    let composer_json: serde_json::Value = serde_json::from_str(&composer_json).ok()?;
+    let constraint = composer_json.get("require")?.get("php")?.as_str()?;
+    Some(constraint.to_owned())
+}
+
+/// Whether a version satisfies a Composer constraint like `^7.4 || ~8.0.1` or
+/// `>=7.2 <8.0`, or `None` if the constraint can't be parsed
+fn matches_constraint(version: &str, constraint: &str) -> Option<bool> {
+    let version = parse_version(version)?.0;
+    let mut is_matched = false;
+    for alternative in constraint.split('|').filter(|part| !part.trim().is_empty()) {
+        is_matched |= matches_all(&version, alternative)?;
+    }
+    Some(is_matched)
+}
+
+/// Whether a version satisfies every constraint of a list separated by commas or spaces
+fn matches_all(version: &[u64], constraints: &str) -> Option<bool> {
+    let words: Vec<&str> = constraints
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|word| !word.is_empty())
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+
+    let mut is_matched = true;
+    let mut index = 0;
+    while index < words.len() {
+        // A hyphen range like `7.2 - 7.4`, whose upper bound includes its patches when
+        // it's partial
+        if words.get(index + 1) == Some(&"-") {
+            let (lower, _) = parse_version(words[index])?;
+            let (upper, upper_len) = parse_version(words.get(index + 2)?)?;
+            is_matched &= version >= lower.as_slice()
+                && if upper_len < 3 {
+                    version < bump(&upper, upper_len - 1).as_slice()
+                } else {
+                    version <= upper.as_slice()
+                };
+            index += 3;
+            continue;
+        }
+        is_matched &= matches_one(version, words[index])?;
+        index += 1;
+    }
+    Some(is_matched)
+}
+
+fn matches_one(version: &[u64], constraint: &str) -> Option<bool> {
+    // Stability flags like `@dev` don't matter for the version of PHP
+    let constraint = constraint.split('@').next()?;
+    if constraint == "*" {
+        return Some(true);
+    }
+
+    let operator_len = constraint
+        .find(|c: char| c.is_ascii_digit() || c == 'v')
+        .unwrap_or(constraint.len());
+    let (operator, bound) = constraint.split_at(operator_len);
+    let (bound, bound_len) = parse_version(bound)?;
+    let bound = bound.as_slice();
+    let is_matched = match operator {
+        ">=" => version >= bound,
+        ">" => version > bound,
+        "<=" => version <= bound,
+        "<" => version < bound,
+        "!=" => version != bound,
+        "" | "=" | "==" if constraint.contains('*') || constraint.ends_with(".x") => {
+            version >= bound && version < bump(bound, bound_len - 1).as_slice()
+        }
+        "" | "=" | "==" => version == bound,
+        "^" => {
+            // The leftmost non-zero number can't change
+            let position = bound[..bound_len]
+                .iter()
+                .position(|&number| number != 0)
+                .unwrap_or(bound_len - 1);
+            version >= bound && version < bump(bound, position).as_slice()
+        }
+        "~" => {
+            let position = if bound_len > 1 { bound_len - 2 } else { 0 };
+            version >= bound && version < bump(bound, position).as_slice()
+        }
+        _ => return None,
+    };
+    Some(is_matched)
+}
+
+/// The numbers of a version like `v7.4.1` or `8.*`, padded with zeros to compare them,
+/// along with how many were given
+fn parse_version(version: &str) -> Option<(Vec<u64>, usize)> {
+    let version = version.trim().trim_start_matches('v');
+    let mut numbers = Vec::new();
+    for part in version.split('.') {
+        if part == "*" || part == "x" {
+            break;
+        }
+        let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+        numbers.push(digits.parse().ok()?);
+        if digits.len() < part.len() {
+            // A suffix like `-RC1` ends the version
+            break;
+        }
+    }
+    let len = numbers.len();
+    if len == 0 {
+        return None;
+    }
+    numbers.resize(len.max(3), 0);
+    Some((numbers, len))
+}
+
+/// The smallest version above the versions starting with the numbers of a version up to
+/// `position`
+fn bump(version: &[u64], position: usize) -> Vec<u64> {
+    let mut bumped: Vec<u64> = version.to_vec();
+    bumped[position] += 1;
+    for number in bumped.iter_mut().skip(position + 1) {
+        *number = 0;
+    }
+    bumped
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::modules::utils::test::render_module;
-    use ansi_term::Color;
-    use std::fs::File;
+    use ansi_term::{ANSIStrings, Color};
+    use std::fs::{self, File};
     use std::io;
 
     #[test]
@@ -120,4 +262,63 @@ mod tests {
         assert_eq!(expected, actual);
         dir.close()
     }
+
+    #[test]
+    fn test_matches_constraint() {
+        assert_eq!(matches_constraint("7.3.8", "^7.2"), Some(true));
+        assert_eq!(matches_constraint("7.3.8", "^7.4 || ^8.0"), Some(false));
+        assert_eq!(matches_constraint("8.1.2", "^7.4 | ^8.0"), Some(true));
+        assert_eq!(matches_constraint("0.3.1", "^0.2"), Some(false));
+        assert_eq!(matches_constraint("7.3.8", ">=7.1 <7.4"), Some(true));
+        assert_eq!(matches_constraint("7.3.8", ">=7.1,<7.3"), Some(false));
+        assert_eq!(matches_constraint("7.3.8", "~7.3.0"), Some(true));
+        assert_eq!(matches_constraint("7.4.0", "~7.3.0"), Some(false));
+        assert_eq!(matches_constraint("7.9.0", "~7.3"), Some(true));
+        assert_eq!(matches_constraint("7.3.8", "7.3.*"), Some(true));
+        assert_eq!(matches_constraint("7.4.0", "7.3.*"), Some(false));
+        assert_eq!(matches_constraint("7.3.8", "7.2 - 7.3"), Some(true));
+        assert_eq!(matches_constraint("7.3.8", "7.2 - 7.3.1"), Some(false));
+        assert_eq!(matches_constraint("7.3.8", ">=7.3@dev"), Some(true));
+        assert_eq!(matches_constraint("7.3.8", "*"), Some(true));
+        assert_eq!(matches_constraint("7.3.8", "latest"), None);
+    }
+
+    #[test]
+    fn folder_with_mismatched_composer_requirement() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("composer.json"),
+            r#"{"require": {"php": "^7.4 || ^8.0"}}"#,
+        )?;
+
+        let actual = render_module("php", dir.path(), None);
+
+        let expected = Some(format!(
+            "via {} ",
+            ANSIStrings(&[
+                Color::Fixed(147).bold().paint("🐘 "),
+                Color::Red.bold().paint("v7.3.8"),
+            ])
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_matched_composer_requirement() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("composer.json"),
+            r#"{"require": {"php": ">=7.1", "ext-json": "*"}}"#,
+        )?;
+
+        let actual = render_module("php", dir.path(), None);
+
+        let expected = Some(format!(
+            "via {} ",
+            Color::Fixed(147).bold().paint("🐘 v7.3.8")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
 }
@@ -0,0 +1,374 @@
+use once_cell::sync::Lazy;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use super::utils::wasm::{self, Capabilities};
+use super::{Context, Module};
+
+use crate::config::parse_style_string;
+use crate::segment::Segment;
+use crate::utils::is_executable;
+
+const PLUGIN_PREFIX: &str = "starship-plugin-";
+
+/// The extension of the WebAssembly plugins in the plugins directory
+const WASM_EXTENSION: &str = ".wasm";
+
+/// The directory of plugins, from `$STARSHIP_PLUGINS` or else `~/.config/starship/plugins`
+fn plugins_dir(context: &Context) -> Option<PathBuf> {
+    match context.get_env("STARSHIP_PLUGINS") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => dirs::home_dir().map(|dir| dir.join(".config/starship/plugins")),
+    }
+}
+
+/// The plugins found in a directory, sorted by their names
+type DirPlugins = Vec<(String, PathBuf)>;
+
+/// The plugins of the directories already read, by the directory and the kind of plugins,
+/// along with the modification time of the directory when it was read, so that a
+/// session finds the plugins installed since
+static DIR_PLUGINS: Lazy<Mutex<HashMap<(PathBuf, PluginFile), (SystemTime, DirPlugins)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Finds the plugins, as their names and paths: the WebAssembly modules in the plugins
+/// directory, named `<name>.wasm`, and the executables on the `$PATH` of the context
+/// named `starship-plugin-<name>`. A plugin found in several directories is run from the
+/// first one.
+pub fn discover(context: &Context) -> Vec<(String, PathBuf)> {
+    let path_dirs: Vec<PathBuf> = context
+        .get_env("PATH")
+        .map(|path| env::split_paths(&path).collect())
+        .unwrap_or_default();
+    let dirs = plugins_dir(context)
+        .map(|dir| (dir, PluginFile::Wasm))
+        .into_iter()
+        .chain(
+            path_dirs
+                .into_iter()
+                .map(|dir| (dir, PluginFile::Executable)),
+        );
+
+    let mut names = HashSet::new();
+    let mut plugins = Vec::new();
+    for (dir, kind) in dirs {
+        let found = read_plugins(&context.current_dir.join(dir), kind)
+            .into_iter()
+            .filter(|(name, _)| !names.contains(name))
+            .collect::<Vec<(String, PathBuf)>>();
+        names.extend(found.iter().map(|(name, _)| name.clone()));
+        plugins.extend(found);
+    }
+    plugins
+}
+
+/// The plugins of a kind in a directory, which are only read again once the directory
+/// changed
+fn read_plugins(dir: &Path, kind: PluginFile) -> DirPlugins {
+    let modified = match fs::metadata(dir).and_then(|metadata| metadata.modified()) {
+        Ok(modified) => modified,
+        Err(_) => return Vec::new(),
+    };
+    let key = (dir.to_owned(), kind);
+    if let Some((read, plugins)) = DIR_PLUGINS.lock().unwrap().get(&key) {
+        if *read == modified {
+            return plugins.clone();
+        }
+    }
+
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut plugins = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let file_name = entry.file_name().into_string().ok()?;
+            let name = plugin_name(&file_name, kind)?;
+            let is_plugin = match kind {
+                PluginFile::Wasm => entry.path().is_file(),
+                PluginFile::Executable => is_executable(&entry.path()),
+            };
+            if !is_plugin {
+                return None;
+            }
+            Some((name.to_owned(), entry.path()))
+        })
+        .collect::<DirPlugins>();
+    plugins.sort();
+    DIR_PLUGINS
+        .lock()
+        .unwrap()
+        .insert(key, (modified, plugins.clone()));
+    plugins
+}
+
+/// How a plugin is run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum PluginFile {
+    /// A WebAssembly module, run in a sandbox
+    Wasm,
+    /// A program on `$PATH`, run as it is
+    Executable,
+}
+
+impl PluginFile {
+    fn of(path: &Path) -> Self {
+        match path.extension() {
+            Some(extension) if extension == &WASM_EXTENSION[1..] => PluginFile::Wasm,
+            _ => PluginFile::Executable,
+        }
+    }
+}
+
+/// The name of the plugin a file is, if it's named like one
+fn plugin_name(file_name: &str, kind: PluginFile) -> Option<&str> {
+    let name = match kind {
+        PluginFile::Wasm => file_name.strip_suffix(WASM_EXTENSION)?,
+        PluginFile::Executable => file_name.strip_prefix(PLUGIN_PREFIX)?,
+    };
+    #[cfg(windows)]
+    let name = match kind {
+        PluginFile::Wasm => name,
+        PluginFile::Executable => name.strip_suffix(".exe")?,
+    };
+    if name.is_empty() || name.contains('.') {
+        return None;
+    }
+    Some(name)
+}
+
+/// Creates a module from the output of a plugin
+///
+/// The plugin is passed the context of the prompt as JSON, and is expected to output
+/// the segments of the module as JSON: a program on stdin and stdout, before its timeout
+/// runs out, and a WebAssembly module through its `render` function, in a sandbox.
+/// If it fails, or outputs no segments, the module isn't shown.
+pub fn module<'a>(name: &str, path: &Path, context: &'a Context) -> Option<Module<'a>> {
+    let toml_config = context.config.get_plugin_config(name);
+    let disabled = toml_config.and_then(|config| config.get("disabled")?.as_bool());
+    if disabled == Some(true) {
+        return None;
+    }
+
+    let module_name = format!("plugin.{}", name);
+    let mut request = json!({
+        "name": name,
+        "current_dir": context.current_dir.to_string_lossy(),
+        "properties": context.properties,
+        "config": toml_config.map_or(Ok(json!({})), serde_json::to_value).ok()?,
+    });
+
+    let cmd = path.to_string_lossy();
+    let output = match PluginFile::of(path) {
+        PluginFile::Wasm => {
+            // A sandboxed plugin only sees the environment variables it's allowed to
+            let env: serde_json::Map<String, serde_json::Value> = toml_config
+                .and_then(|config| config.get("allowed_env")?.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(toml::Value::as_str)
+                .filter_map(|name| Some((name.to_owned(), json!(context.get_env(name)?))))
+                .collect();
+            request["env"] = serde_json::Value::Object(env);
+            let read_files = toml_config.and_then(|config| config.get("read_files")?.as_bool());
+            let capabilities = Capabilities {
+                read_dir: Some(context.current_dir.clone()).filter(|_| read_files == Some(true)),
+            };
+            run_wasm(name, path, &request, capabilities)?
+        }
+        PluginFile::Executable => run_executable(&module_name, path, &request, context)?,
+    };
+
+    let segments = parse_segments(&output)
+        .map_err(|error| log::warn!("Invalid output of the plugin {}: {}", cmd, error))
+        .ok()?;
+    let mut module = Module::new(&module_name, "An external plugin", toml_config);
+    module.set_reason(format!("`{}` is a plugin", cmd));
+    module.set_segments(segments);
+    if module.is_empty() {
+        return None;
+    }
+    Some(module)
+}
+
+/// Runs the WebAssembly module of a plugin, returning its output
+fn run_wasm(
+    name: &str,
+    path: &Path,
+    request: &serde_json::Value,
+    capabilities: Capabilities,
+) -> Option<Vec<u8>> {
+    let wasm = fs::read(path)
+        .map_err(|error| log::debug!("Unable to read the plugin {:?}: {}", path, error))
+        .ok()?;
+    wasm::run(name, &wasm, request.to_string().as_bytes(), capabilities)
+        .map_err(|error| log::warn!("The plugin {:?} failed: {}", path, error))
+        .ok()
+}
+
+/// Runs the program of a plugin, returning what it printed on stdout
+fn run_executable(
+    module_name: &str,
+    path: &Path,
+    request: &serde_json::Value,
+    context: &Context,
+) -> Option<Vec<u8>> {
+    let cmd = path.to_string_lossy();
+    // Plugins found on `PATH` could be inside the current repository
+    let policy = context.exec_policy();
+    if !policy.allows(path) {
+        return None;
+    }
+    let mut child = policy
+        .command(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| log::debug!("Unable to run the plugin {}: {}", cmd, error))
+        .ok()?;
+    // Closing stdin lets the plugin know that the whole request was written. A plugin
+    // that doesn't read it may have exited already, which isn't an error.
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Err(error) = stdin.write_all(request.to_string().as_bytes()) {
+            log::debug!(
+                "Unable to write the request to the plugin {}: {}",
+                cmd,
+                error
+            );
+        }
+    }
+    let output = context.wait_with_timeout(module_name, &cmd, child)?;
+    if !output.status.success() {
+        log::debug!(
+            "The plugin {} failed with {:?}: {}",
+            cmd,
+            output.status.code(),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        return None;
+    }
+    Some(output.stdout)
+}
+
+/// Parses the output of a plugin, an object with the `segments` of the module,
+/// which each have a `text` and optionally a `style`:
+///
+/// ```json
+/// {"segments": [{"text": "☁ ", "style": "bold blue"}, {"text": "eu-west-1"}]}
+/// ```
+fn parse_segments(output: &[u8]) -> Result<Vec<Segment>, String> {
+    let output: serde_json::Value =
+        serde_json::from_slice(output).map_err(|error| error.to_string())?;
+    let segments = match output["segments"].as_array() {
+        Some(segments) => segments,
+        None => return Err(String::from("expected an array of `segments`")),
+    };
+
+    segments
+        .iter()
+        .enumerate()
+        .map(|(index, segment)| {
+            let text = segment["text"]
+                .as_str()
+                .ok_or_else(|| format!("segment {} has no `text`", index))?;
+            let mut result = Segment::new(&format!("segment_{}", index));
+            result.set_value(text);
+            if let Some(style) = segment["style"].as_str() {
+                let style = parse_style_string(style)
+                    .ok_or_else(|| format!("segment {} has an invalid `style`", index))?;
+                result.set_style(style);
+            }
+            Ok(result)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ansi_term::Color;
+
+    #[test]
+    fn parse_plugin_segments() {
+        let segments = parse_segments(
+            br#"{"segments": [{"text": "A ", "style": "bold blue"}, {"text": "eu-west-1"}]}"#,
+        )
+        .unwrap();
+        let values: Vec<&str> = segments.iter().map(Segment::get_value).collect();
+        assert_eq!(values, vec!["A ", "eu-west-1"]);
+        assert_eq!(segments[0].style, Some(Color::Blue.bold()));
+        assert_eq!(segments[1].style, None);
+    }
+
+    #[test]
+    fn parse_invalid_plugin_output() {
+        assert!(parse_segments(b"eu-west-1").is_err());
+        assert!(parse_segments(br#"{"segments": [{"style": "bold"}]}"#).is_err());
+        assert!(parse_segments(br#"{"segments": [{"text": "a", "style": "loud"}]}"#).is_err());
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn plugin_names() {
+        assert_eq!(
+            plugin_name("starship-plugin-weather", PluginFile::Executable),
+            Some("weather")
+        );
+        assert_eq!(
+            plugin_name("starship-plugin-", PluginFile::Executable),
+            None
+        );
+        assert_eq!(plugin_name("starship", PluginFile::Executable), None);
+        assert_eq!(
+            plugin_name("weather.wasm", PluginFile::Wasm),
+            Some("weather")
+        );
+        assert_eq!(plugin_name("weather", PluginFile::Wasm), None);
+        assert_eq!(plugin_name("README.md", PluginFile::Wasm), None);
+        assert_eq!(plugin_name(".wasm", PluginFile::Wasm), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn discover_with_env_of_context() -> std::io::Result<()> {
+        use clap::ArgMatches;
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir()?;
+        let bin = dir.path().join("bin");
+        let plugins = dir.path().join("plugins");
+        fs::create_dir(&bin)?;
+        fs::create_dir(&plugins)?;
+        let weather = bin.join("starship-plugin-weather");
+        fs::write(&weather, "#!/bin/sh\n")?;
+        fs::set_permissions(&weather, fs::Permissions::from_mode(0o755))?;
+        fs::write(plugins.join("clock.wasm"), "")?;
+
+        let mut context = Context::new_with_dir(ArgMatches::default(), dir.path());
+        let env = [("PATH", "bin"), ("STARSHIP_PLUGINS", "plugins")];
+        context.env = Some(
+            env.iter()
+                .map(|(key, value)| ((*key).to_owned(), (*value).to_owned()))
+                .collect(),
+        );
+        let expected = vec![
+            (String::from("clock"), plugins.join("clock.wasm")),
+            (String::from("weather"), weather.clone()),
+        ];
+        assert_eq!(discover(&context), expected);
+
+        // Read again once the directory changed
+        fs::remove_file(&weather)?;
+        assert_eq!(discover(&context), expected[..1].to_vec());
+        dir.close()
+    }
+}
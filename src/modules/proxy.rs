@@ -0,0 +1,82 @@
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::proxy::ProxyConfig;
+
+/// The proxy variables that are checked, in order of precedence. Both the lowercase and
+/// the uppercase spelling of each variable are honored, as most tools accept either.
+const PROXY_VARS: &[&str] = &["https_proxy", "http_proxy", "all_proxy"];
+
+/// Creates a module showing that a proxy is configured for the current shell
+///
+/// Will display the proxy symbol if any of `$http_proxy`, `$https_proxy` or `$all_proxy`
+/// (or their uppercase variants) are set. If `show_host` is enabled, the host of the
+/// proxy with the highest precedence is shown as well.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let proxy_url = get_proxy_url(context)?;
+
+    let mut module = context.new_module("proxy");
+    let config: ProxyConfig = ProxyConfig::try_load(module.config);
+
+    module.set_style(config.style);
+    module.create_segment("symbol", &config.symbol);
+
+    if config.show_host {
+        let host = get_proxy_host(&proxy_url);
+        module.create_segment("host", &config.host.with_value(host));
+    }
+
+    Some(module)
+}
+
+fn get_proxy_url(context: &Context) -> Option<String> {
+    PROXY_VARS
+        .iter()
+        .flat_map(|name| vec![name.to_string(), name.to_uppercase()])
+        .filter_map(|name| context.get_env(name))
+        .find(|value| !value.trim().is_empty())
+}
+
+/// Extract the host (and port, if any) from a proxy URL, dropping the scheme,
+/// any credentials and any trailing path
+pub fn get_proxy_host(url: &str) -> &str {
+    let url = url.trim();
+    let without_scheme = match url.find("://") {
+        Some(index) => &url[index + 3..],
+        None => url,
+    };
+    let without_credentials = match without_scheme.rfind('@') {
+        Some(index) => &without_scheme[index + 1..],
+        None => without_scheme,
+    };
+
+    without_credentials
+        .split('/')
+        .next()
+        .unwrap_or(without_credentials)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_proxy_host() {
+        assert_eq!(
+            get_proxy_host("http://proxy.example.com:3128"),
+            "proxy.example.com:3128"
+        );
+        assert_eq!(get_proxy_host("socks5://10.0.0.1:1080/"), "10.0.0.1:1080");
+        assert_eq!(
+            get_proxy_host("proxy.example.com:8080"),
+            "proxy.example.com:8080"
+        );
+    }
+
+    #[test]
+    fn test_get_proxy_host_strips_credentials() {
+        assert_eq!(
+            get_proxy_host("http://user:p@ss@proxy.example.com:3128/path"),
+            "proxy.example.com:3128"
+        );
+    }
+}
@@ -1,5 +1,5 @@
-use std::env;
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use super::{Context, Module, RootModuleConfig, SegmentConfig};
 use crate::configs::python::PythonConfig;
@@ -18,7 +18,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("python");
     let config: PythonConfig = PythonConfig::try_load(module.config);
 
-    let is_py_project = {
+    let project_reason = {
         let base = context.try_begin_scan()?.set_files(&[
             "requirements.txt",
             ".python-version",
@@ -29,32 +29,35 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             "__init__.py",
         ]);
         if config.scan_for_pyfiles {
-            base.set_extensions(&["py"]).is_match()
+            base.set_extensions(&["py"]).find_match()
         } else {
-            base.is_match()
+            base.find_match()
         }
     };
 
-    let is_venv = env::var("VIRTUAL_ENV").ok().is_some();
-
-    if !is_py_project && !is_venv {
-        return None;
-    }
+    let reason = match project_reason {
+        Some(reason) => reason,
+        None if context.get_env("VIRTUAL_ENV").is_some() => String::from("`$VIRTUAL_ENV` is set"),
+        None => return None,
+    };
+    module.set_reason(reason);
 
     module.set_style(config.style);
     module.create_segment("symbol", &config.symbol);
 
     if config.pyenv_version_name {
-        let python_version = utils::exec_cmd("pyenv", &["version-name"])?.stdout;
+        let python_version = context
+            .exec_cmd("python", "pyenv", &["version-name"])?
+            .stdout;
         module.create_segment("pyenv_prefix", &config.pyenv_prefix);
         module.create_segment("version", &SegmentConfig::new(&python_version.trim()));
     } else {
-        let python_version = get_python_version()?;
+        let python_version = get_python_version(context)?;
         let formatted_version = format_python_version(&python_version);
         module.create_segment("version", &SegmentConfig::new(&formatted_version));
     };
 
-    if let Some(virtual_env) = get_python_virtual_env() {
+    if let Some(virtual_env) = get_python_virtual_env(context, &config.virtualenv_aliases) {
         module.create_segment(
             "virtualenv",
             &SegmentConfig::new(&format!(" ({})", virtual_env)),
@@ -64,8 +67,12 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_python_version() -> Option<String> {
-    match utils::exec_cmd("python", &["--version"]) {
+fn get_python_version(context: &Context) -> Option<String> {
+    if let Some(version) = get_virtual_env_python_version(context) {
+        return Some(version);
+    }
+
+    match context.exec_cmd("python", "python", &["--version"]) {
         Some(output) => {
             if output.stdout.is_empty() {
                 Some(output.stderr)
@@ -77,6 +84,45 @@ fn get_python_version() -> Option<String> {
     }
 }
 
+/// Read the Python version of the active virtual environment from its `pyvenv.cfg`,
+/// which is quicker than starting Python, especially on Windows
+fn get_virtual_env_python_version(context: &Context) -> Option<String> {
+    parse_pyvenv_cfg_version(&read_pyvenv_cfg(context)?)
+}
+
+fn read_pyvenv_cfg(context: &Context) -> Option<String> {
+    let virtual_env = context.get_env("VIRTUAL_ENV")?;
+    utils::read_file(Path::new(&virtual_env).join("pyvenv.cfg")).ok()
+}
+
+fn parse_pyvenv_cfg(pyvenv_cfg: &str) -> HashMap<&str, &str> {
+    pyvenv_cfg
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(2, '=');
+            Some((parts.next()?.trim(), parts.next()?.trim()))
+        })
+        .collect()
+}
+
+fn parse_pyvenv_cfg_version(pyvenv_cfg: &str) -> Option<String> {
+    let values = parse_pyvenv_cfg(pyvenv_cfg);
+
+    // `virtualenv` writes e.g. `version_info = 3.8.2.final.0`, and its own version
+    // as `version`, while `venv` writes the Python version as `version`
+    match values.get("version_info") {
+        Some(version_info) => Some(
+            version_info
+                .split('.')
+                .take(3)
+                .collect::<Vec<_>>()
+                .join("."),
+        ),
+        None if !values.contains_key("virtualenv") => values.get("version").map(|v| v.to_string()),
+        None => None,
+    }
+}
+
 fn format_python_version(python_stdout: &str) -> String {
     format!(
         "v{}",
@@ -87,12 +133,39 @@ fn format_python_version(python_stdout: &str) -> String {
     )
 }
 
-fn get_python_virtual_env() -> Option<String> {
-    env::var("VIRTUAL_ENV").ok().and_then(|venv| {
-        Path::new(&venv)
-            .file_name()
-            .map(|filename| String::from(filename.to_str().unwrap_or("")))
-    })
+/// The name of the active virtual environment, which is its alias, by path or by name,
+/// else the prompt it was created with, else the name of its folder
+fn get_python_virtual_env(context: &Context, aliases: &HashMap<String, &str>) -> Option<String> {
+    let venv = PathBuf::from(context.get_env("VIRTUAL_ENV")?);
+    let name = venv
+        .file_name()
+        .map(|filename| filename.to_string_lossy().into_owned());
+
+    let alias = aliases
+        .iter()
+        .find(|(path, _)| Context::expand_tilde(PathBuf::from(path)) == venv)
+        .map(|(_, alias)| *alias)
+        .or_else(|| aliases.get(name.as_ref()?).copied());
+    alias
+        .map(str::to_owned)
+        .or_else(|| parse_pyvenv_cfg_prompt(&read_pyvenv_cfg(context)?))
+        .or(name)
+}
+
+/// The `prompt` a virtual environment was created with, which `venv` writes quoted
+fn parse_pyvenv_cfg_prompt(pyvenv_cfg: &str) -> Option<String> {
+    let prompt = *parse_pyvenv_cfg(pyvenv_cfg).get("prompt")?;
+    let unquoted = ['\'', '"'].iter().find_map(|&quote| {
+        prompt
+            .strip_prefix(quote)
+            .and_then(|prompt| prompt.strip_suffix(quote))
+    });
+    let prompt = unquoted.unwrap_or(prompt);
+    if prompt.is_empty() {
+        None
+    } else {
+        Some(prompt.to_owned())
+    }
 }
 
 #[cfg(test)]
@@ -110,4 +183,34 @@ mod tests {
         let input = "Python 3.6.10 :: Anaconda, Inc.";
         assert_eq!(format_python_version(input), "v3.6.10");
     }
+
+    #[test]
+    fn test_parse_pyvenv_cfg_version_venv() {
+        let input = "home = /usr/bin\ninclude-system-site-packages = false\nversion = 3.8.2\n";
+        assert_eq!(parse_pyvenv_cfg_version(input), Some(String::from("3.8.2")));
+    }
+
+    #[test]
+    fn test_parse_pyvenv_cfg_version_virtualenv() {
+        let input = "home = /usr/bin\nimplementation = CPython\nversion_info = 3.8.2.final.0\nvirtualenv = 20.0.21\n";
+        assert_eq!(parse_pyvenv_cfg_version(input), Some(String::from("3.8.2")));
+    }
+
+    #[test]
+    fn test_parse_pyvenv_cfg_prompt() {
+        let venv = "home = /usr/bin\nversion = 3.9.0\nprompt = 'starship docs'\n";
+        assert_eq!(
+            parse_pyvenv_cfg_prompt(venv),
+            Some("starship docs".to_string())
+        );
+        let virtualenv = "home = /usr/bin\nvirtualenv = 20.0.21\nprompt = api\n";
+        assert_eq!(parse_pyvenv_cfg_prompt(virtualenv), Some("api".to_string()));
+        assert_eq!(parse_pyvenv_cfg_prompt("home = /usr/bin\n"), None);
+    }
+
+    #[test]
+    fn test_parse_pyvenv_cfg_version_missing() {
+        let input = "home = /usr/bin\nversion = 20.0.0\nvirtualenv = 20.0.0\n";
+        assert_eq!(parse_pyvenv_cfg_version(input), None);
+    }
 }
@@ -1,3 +1,5 @@
+use std::path::{Path, PathBuf};
+
 use super::{Context, Module, RootModuleConfig, SegmentConfig};
 
 use crate::configs::ruby::RubyConfig;
@@ -9,20 +11,22 @@ use crate::utils;
 ///     - Current directory contains a `.rb` file
 ///     - Current directory contains a `Gemfile` or `.ruby-version` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let is_rb_project = context
+    let reason = context
         .try_begin_scan()?
         .set_files(&["Gemfile", ".ruby-version"])
         .set_extensions(&["rb"])
-        .is_match();
-
-    if !is_rb_project {
-        return None;
-    }
+        .find_match()?;
 
-    let ruby_version = utils::exec_cmd("ruby", &["-v"])?.stdout;
-    let formatted_version = format_ruby_version(&ruby_version)?;
+    let formatted_version = match get_selected_version(context) {
+        Some(version) => format_selected_version(&version),
+        None => {
+            let ruby_version = context.exec_cmd("ruby", "ruby", &["-v"])?.stdout;
+            format_ruby_version(&ruby_version)?
+        }
+    };
 
     let mut module = context.new_module("ruby");
+    module.set_reason(reason);
     let config: RubyConfig = RubyConfig::try_load(module.config);
     module.set_style(config.style);
 
@@ -32,6 +36,83 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+/// The Ruby version selected by a version manager or the project, which is, by priority:
+///     - `$RBENV_VERSION`, set by `rbenv shell`
+///     - `$RUBY_VERSION`, set by chruby and RVM for the Ruby they activated
+///     - The `.ruby-version` file in the current directory or its closest parent
+///     - The `ruby` directive of the `Gemfile`, if it's an exact version
+///     - The global version of rbenv
+///
+/// A version of `system` is that of the Ruby on `$PATH`, so it isn't one.
+fn get_selected_version(context: &Context) -> Option<String> {
+    let from_env = |name| context.get_env(name);
+    let from_version_file = || {
+        context
+            .current_dir
+            .ancestors()
+            .find_map(|dir| read_version_file(&dir.join(".ruby-version")))
+    };
+    let from_gemfile = || {
+        let gemfile = utils::read_file(context.current_dir.join("Gemfile")).ok()?;
+        parse_gemfile_ruby_version(&gemfile)
+    };
+    let from_rbenv_global = || {
+        let rbenv_root = match context.get_env("RBENV_ROOT") {
+            Some(root) => PathBuf::from(root),
+            None => PathBuf::from(context.get_env("HOME")?).join(".rbenv"),
+        };
+        read_version_file(&rbenv_root.join("version"))
+    };
+
+    from_env("RBENV_VERSION")
+        .or_else(|| from_env("RUBY_VERSION"))
+        .map(|version| version.trim().to_owned())
+        .filter(|version| !version.is_empty())
+        .or_else(from_version_file)
+        .or_else(from_gemfile)
+        .or_else(from_rbenv_global)
+        .filter(|version| version != "system")
+}
+
+/// The version in a file like `.ruby-version`, which is its first word
+fn read_version_file(path: &Path) -> Option<String> {
+    let contents = utils::read_file(path).ok()?;
+    contents.split_whitespace().next().map(str::to_owned)
+}
+
+/// The version of a directive like `ruby "2.7.1"` in a `Gemfile`, which is left out if it's
+/// a requirement like `"~> 2.7"` rather than a version
+fn parse_gemfile_ruby_version(gemfile: &str) -> Option<String> {
+    gemfile.lines().find_map(|line| {
+        let line = line.trim_start();
+        let argument = line.strip_prefix("ruby")?.trim_start();
+        let argument = argument.strip_prefix('(').unwrap_or(argument);
+        let quote = argument
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')?;
+        let version = argument[1..].split(quote).next()?;
+        let is_exact =
+            !version.is_empty() && version.chars().all(|c| c.is_ascii_digit() || c == '.');
+        if is_exact {
+            Some(version.to_owned())
+        } else {
+            None
+        }
+    })
+}
+
+/// Format a selected version, like `2.7.1` or `ruby-2.7.1`, as `v2.7.1`. Versions of
+/// other implementations, like `jruby-9.2.11.1`, are kept as they are.
+fn format_selected_version(version: &str) -> String {
+    let version = version.strip_prefix("ruby-").unwrap_or(version);
+    if version.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("v{}", version)
+    } else {
+        version.to_owned()
+    }
+}
+
 fn format_ruby_version(ruby_version: &str) -> Option<String> {
     let version = ruby_version
         // split into ["ruby", "2.6.0p0", "linux/amd64"]
@@ -53,6 +134,7 @@ fn format_ruby_version(ruby_version: &str) -> Option<String> {
 mod tests {
     use super::*;
     use crate::modules::utils::test::render_module;
+    use crate::test::ModuleRenderer;
     use ansi_term::Color;
     use std::fs::File;
     use std::io;
@@ -104,6 +186,124 @@ mod tests {
         dir.close()
     }
 
+    #[test]
+    fn folder_with_ruby_version_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join(".ruby-version"), "ruby-2.7.1\n")?;
+        std::fs::write(dir.path().join("Gemfile"), "ruby '2.6.6'\n")?;
+
+        let actual = ModuleRenderer::new("ruby")
+            .path(dir.path())
+            .cmd("ruby -v", None)
+            .collect();
+
+        let expected = Some(format!("via {} ", Color::Red.bold().paint("💎 v2.7.1")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn subfolder_of_ruby_version_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join(".ruby-version"), "jruby-9.2.11.1")?;
+        let lib = dir.path().join("lib");
+        std::fs::create_dir(&lib)?;
+        File::create(lib.join("any.rb"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("ruby")
+            .path(&lib)
+            .cmd("ruby -v", None)
+            .collect();
+
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("💎 jruby-9.2.11.1")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_gemfile_ruby_directive() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(
+            dir.path().join("Gemfile"),
+            "source \"https://rubygems.org\"\n\nruby \"2.6.6\"\n\ngem \"rails\"\n",
+        )?;
+
+        let actual = ModuleRenderer::new("ruby")
+            .path(dir.path())
+            .cmd("ruby -v", None)
+            .collect();
+
+        let expected = Some(format!("via {} ", Color::Red.bold().paint("💎 v2.6.6")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn version_manager_env() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join(".ruby-version"), "2.7.1")?;
+
+        let rbenv = ModuleRenderer::new("ruby")
+            .path(dir.path())
+            .env("RBENV_VERSION", "2.6.6")
+            .env("RUBY_VERSION", "2.5.8")
+            .collect();
+        let chruby = ModuleRenderer::new("ruby")
+            .path(dir.path())
+            .env("RUBY_VERSION", "2.5.8")
+            .collect();
+        let system = ModuleRenderer::new("ruby")
+            .path(dir.path())
+            .env("RBENV_VERSION", "system")
+            .collect();
+
+        let expected = |version| Some(format!("via {} ", Color::Red.bold().paint(version)));
+        assert_eq!(expected("💎 v2.6.6"), rbenv);
+        assert_eq!(expected("💎 v2.5.8"), chruby);
+        assert_eq!(expected("💎 v2.5.1"), system);
+        dir.close()
+    }
+
+    #[test]
+    fn rbenv_global_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let rbenv_root = tempfile::tempdir()?;
+        File::create(dir.path().join("Gemfile"))?.sync_all()?;
+        std::fs::write(rbenv_root.path().join("version"), "2.7.1\n")?;
+
+        let actual = ModuleRenderer::new("ruby")
+            .path(dir.path())
+            .env("RBENV_ROOT", rbenv_root.path().to_string_lossy())
+            .cmd("ruby -v", None)
+            .collect();
+
+        let expected = Some(format!("via {} ", Color::Red.bold().paint("💎 v2.7.1")));
+        assert_eq!(expected, actual);
+        rbenv_root.close()?;
+        dir.close()
+    }
+
+    #[test]
+    fn test_parse_gemfile_ruby_version() {
+        assert_eq!(
+            parse_gemfile_ruby_version("gem 'rake'\nruby '2.7.1'\n"),
+            Some("2.7.1".to_string())
+        );
+        assert_eq!(
+            parse_gemfile_ruby_version("ruby(\"2.7.1\", engine: \"jruby\")"),
+            Some("2.7.1".to_string())
+        );
+        assert_eq!(parse_gemfile_ruby_version("ruby '~> 2.7'"), None);
+        assert_eq!(
+            parse_gemfile_ruby_version("ruby file: '.ruby-version'"),
+            None
+        );
+        assert_eq!(parse_gemfile_ruby_version("gem 'ruby-progressbar'"), None);
+    }
+
     #[test]
     fn test_format_ruby_version() -> io::Result<()> {
         assert_eq!(
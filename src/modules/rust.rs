@@ -1,6 +1,7 @@
+use process_control::Output;
+use std::fs;
 use std::path::Path;
-use std::process::{Command, Output};
-use std::{env, fs};
+use std::process::Stdio;
 
 use super::{Context, Module, RootModuleConfig};
 
@@ -12,15 +13,11 @@ use crate::configs::rust::RustConfig;
 ///     - Current directory contains a file with a `.rs` extension
 ///     - Current directory contains a `Cargo.toml` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let is_rs_project = context
+    let reason = context
         .try_begin_scan()?
         .set_files(&["Cargo.toml"])
         .set_extensions(&["rs"])
-        .is_match();
-
-    if !is_rs_project {
-        return None;
-    }
+        .find_match()?;
 
     // `$CARGO_HOME/bin/rustc(.exe) --version` may attempt installing a rustup toolchain.
     // https://github.com/starship/starship/issues/417
@@ -38,25 +35,26 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     // - `rustup show`
     // - `rustup show active-toolchain`
     // - `rustup which`
-    let module_version = if let Some(toolchain) = env_rustup_toolchain()
-        .or_else(|| execute_rustup_override_list(&context.current_dir))
+    let module_version = if let Some(toolchain) = env_rustup_toolchain(context)
+        .or_else(|| execute_rustup_override_list(context))
         .or_else(|| find_rust_toolchain_file(&context))
     {
-        match execute_rustup_run_rustc_version(&toolchain) {
+        match execute_rustup_run_rustc_version(context, &toolchain) {
             RustupRunRustcVersionOutcome::RustcVersion(stdout) => format_rustc_version(stdout),
             RustupRunRustcVersionOutcome::ToolchainName(toolchain) => toolchain,
             RustupRunRustcVersionOutcome::RustupNotWorking => {
                 // If `rustup` is not in `$PATH` or cannot be executed for other reasons, we can
                 // safely execute `rustc --version`.
-                format_rustc_version(execute_rustc_version()?)
+                format_rustc_version(execute_rustc_version(context)?)
             }
             RustupRunRustcVersionOutcome::Err => return None,
         }
     } else {
-        format_rustc_version(execute_rustc_version()?)
+        format_rustc_version(execute_rustc_version(context)?)
     };
 
     let mut module = context.new_module("rust");
+    module.set_reason(reason);
     let config = RustConfig::try_load(module.config);
     module.set_style(config.style);
 
@@ -66,18 +64,16 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn env_rustup_toolchain() -> Option<String> {
-    let val = env::var("RUSTUP_TOOLCHAIN").ok()?;
+fn env_rustup_toolchain(context: &Context) -> Option<String> {
+    let val = context.get_env("RUSTUP_TOOLCHAIN")?;
     Some(val.trim().to_owned())
 }
 
-fn execute_rustup_override_list(cwd: &Path) -> Option<String> {
-    let Output { stdout, .. } = Command::new("rustup")
-        .args(&["override", "list"])
-        .output()
-        .ok()?;
-    let stdout = String::from_utf8(stdout).ok()?;
-    extract_toolchain_from_rustup_override_list(&stdout, cwd)
+fn execute_rustup_override_list(context: &Context) -> Option<String> {
+    let stdout = context
+        .exec_cmd("rust", "rustup", &["override", "list"])?
+        .stdout;
+    extract_toolchain_from_rustup_override_list(&stdout, &context.current_dir)
 }
 
 fn extract_toolchain_from_rustup_override_list(stdout: &str, cwd: &Path) -> Option<String> {
@@ -124,12 +120,29 @@ fn find_rust_toolchain_file(context: &Context) -> Option<String> {
     }
 }
 
-fn execute_rustup_run_rustc_version(toolchain: &str) -> RustupRunRustcVersionOutcome {
-    Command::new("rustup")
+fn execute_rustup_run_rustc_version(
+    context: &Context,
+    toolchain: &str,
+) -> RustupRunRustcVersionOutcome {
+    let policy = context.exec_policy();
+    let rustup = match policy.resolve("rustup") {
+        Some(rustup) => rustup,
+        None => return RustupRunRustcVersionOutcome::RustupNotWorking,
+    };
+    let child = policy
+        .command(rustup)
         .args(&["run", toolchain, "rustc", "--version"])
-        .output()
-        .map(extract_toolchain_from_rustup_run_rustc_version)
-        .unwrap_or(RustupRunRustcVersionOutcome::RustupNotWorking)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    match child {
+        Ok(child) => context
+            .wait_with_timeout("rust", "rustup", child)
+            .map(extract_toolchain_from_rustup_run_rustc_version)
+            .unwrap_or(RustupRunRustcVersionOutcome::Err),
+        Err(_) => RustupRunRustcVersionOutcome::RustupNotWorking,
+    }
 }
 
 fn extract_toolchain_from_rustup_run_rustc_version(output: Output) -> RustupRunRustcVersionOutcome {
@@ -148,11 +161,8 @@ fn extract_toolchain_from_rustup_run_rustc_version(output: Output) -> RustupRunR
     RustupRunRustcVersionOutcome::Err
 }
 
-fn execute_rustc_version() -> Option<String> {
-    match Command::new("rustc").arg("--version").output() {
-        Ok(output) => Some(String::from_utf8(output.stdout).unwrap()),
-        Err(_) => None,
-    }
+fn execute_rustc_version(context: &Context) -> Option<String> {
+    Some(context.exec_cmd("rust", "rustc", &["--version"])?.stdout)
 }
 
 fn format_rustc_version(mut rustc_stdout: String) -> String {
@@ -223,7 +233,7 @@ mod tests {
             stderr: vec![],
         });
         assert_eq!(
-            extract_toolchain_from_rustup_run_rustc_version(RUSTC_VERSION.clone()),
+            extract_toolchain_from_rustup_run_rustc_version(RUSTC_VERSION.clone().into()),
             RustupRunRustcVersionOutcome::RustcVersion("rustc 1.34.0\n".to_owned()),
         );
 
@@ -233,7 +243,7 @@ mod tests {
             stderr: b"error: toolchain 'channel-triple' is not installed\n"[..].to_owned(),
         });
         assert_eq!(
-            extract_toolchain_from_rustup_run_rustc_version(TOOLCHAIN_NAME.clone()),
+            extract_toolchain_from_rustup_run_rustc_version(TOOLCHAIN_NAME.clone().into()),
             RustupRunRustcVersionOutcome::ToolchainName("channel-triple".to_owned()),
         );
 
@@ -243,7 +253,7 @@ mod tests {
             stderr: vec![],
         });
         assert_eq!(
-            extract_toolchain_from_rustup_run_rustc_version(INVALID_STDOUT.clone()),
+            extract_toolchain_from_rustup_run_rustc_version(INVALID_STDOUT.clone().into()),
             RustupRunRustcVersionOutcome::Err,
         );
 
@@ -253,7 +263,7 @@ mod tests {
             stderr: b"\xc3\x28"[..].to_owned(),
         });
         assert_eq!(
-            extract_toolchain_from_rustup_run_rustc_version(INVALID_STDERR.clone()),
+            extract_toolchain_from_rustup_run_rustc_version(INVALID_STDERR.clone().into()),
             RustupRunRustcVersionOutcome::Err,
         );
 
@@ -263,7 +273,9 @@ mod tests {
             stderr: b"error:"[..].to_owned(),
         });
         assert_eq!(
-            extract_toolchain_from_rustup_run_rustc_version(UNEXPECTED_FORMAT_OF_ERROR.clone()),
+            extract_toolchain_from_rustup_run_rustc_version(
+                UNEXPECTED_FORMAT_OF_ERROR.clone().into()
+            ),
             RustupRunRustcVersionOutcome::Err,
         );
     }
@@ -1,20 +1,20 @@
-use std::env;
-
 use super::{Context, Module, SegmentConfig};
 
 use crate::config::RootModuleConfig;
 use crate::configs::singularity::SingularityConfig;
 
-/// Creates a module with the current Singularity image
+/// Creates a module with the current Singularity or Apptainer image
 ///
-/// Will display the Singularity image if `$SINGULARITY_NAME` is set.
+/// Will display the image if `$APPTAINER_NAME` or `$SINGULARITY_NAME` is set, which
+/// Apptainer and Singularity respectively do inside their containers.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let singularity_env = env::var("SINGULARITY_NAME").unwrap_or_else(|_| "".into());
-    if singularity_env.trim().is_empty() {
-        return None;
-    }
+    let (env_var, singularity_env) = ["APPTAINER_NAME", "SINGULARITY_NAME"]
+        .iter()
+        .filter_map(|&name| Some((name, context.get_env(name)?)))
+        .find(|(_, image)| !image.trim().is_empty())?;
 
     let mut module = context.new_module("singularity");
+    module.set_reason(format!("`${}` is set", env_var));
     let config = SingularityConfig::try_load(module.config);
 
     module.get_prefix().set_value(config.label);
@@ -0,0 +1,144 @@
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::solidity::SolidityConfig;
+
+/// Creates a module with the current Solidity compiler version
+///
+/// Will display the version of `solc`, or of Foundry's `forge` when there's no `solc`,
+/// if any of the following criteria are met:
+///     - The current directory contains a `foundry.toml` file
+///     - The current directory contains a `hardhat.config.*` file
+///     - The current directory contains a file with extension `.sol`
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let reason = context
+        .try_begin_scan()?
+        .set_files(&[
+            "foundry.toml",
+            "hardhat.config.js",
+            "hardhat.config.ts",
+            "hardhat.config.cjs",
+            "hardhat.config.mjs",
+        ])
+        .set_extensions(&["sol"])
+        .find_match()?;
+
+    let version = get_solc_version(context).or_else(|| get_forge_version(context))?;
+
+    let mut module = context.new_module("solidity");
+    module.set_reason(reason);
+    let config = SolidityConfig::try_load(module.config);
+
+    module.set_style(config.style);
+
+    module.create_segment("symbol", &config.symbol);
+    module.create_segment("version", &config.version.with_value(&version));
+
+    Some(module)
+}
+
+fn get_solc_version(context: &Context) -> Option<String> {
+    let output = context.exec_cmd("solidity", "solc", &["--version"])?.stdout;
+    parse_solc_version(&output)
+}
+
+fn get_forge_version(context: &Context) -> Option<String> {
+    let output = context
+        .exec_cmd("solidity", "forge", &["--version"])?
+        .stdout;
+    let version = parse_forge_version(&output)?;
+    Some(format!("forge {}", version))
+}
+
+fn parse_solc_version(solc_stdout: &str) -> Option<String> {
+    // solc --version output looks like this:
+    // solc, the solidity compiler commandline interface
+    // Version: 0.8.21+commit.d9974bed.Linux.g++
+    let version = solc_stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("Version: "))?
+        .split('+')
+        .next()?
+        .trim();
+    Some(format!("v{}", version))
+}
+
+fn parse_forge_version(forge_stdout: &str) -> Option<String> {
+    // forge --version output looks like this:
+    // forge 0.2.0 (5be158b 2023-10-02T00:23:00.000000000Z)
+    let version = forge_stdout.split_whitespace().nth(1)?;
+    Some(format!("v{}", version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::utils::test::render_module;
+    use crate::test::ModuleRenderer;
+    use crate::utils::CommandOutput;
+    use ansi_term::Color;
+    use std::fs::File;
+    use std::io;
+
+    #[test]
+    fn test_parse_versions() {
+        let solc = "\
+solc, the solidity compiler commandline interface
+Version: 0.8.21+commit.d9974bed.Linux.g++
+";
+        assert_eq!(parse_solc_version(solc), Some(String::from("v0.8.21")));
+        let forge = "forge 0.2.0 (5be158b 2023-10-02T00:23:00.000000000Z)\n";
+        assert_eq!(parse_forge_version(forge), Some(String::from("v0.2.0")));
+    }
+
+    #[test]
+    fn folder_without_solidity_files() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("package.json"))?.sync_all()?;
+        let actual = render_module("solidity", dir.path(), None);
+        let expected = None;
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_sol_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("Counter.sol"))?.sync_all()?;
+        let actual = render_module("solidity", dir.path(), None);
+        let expected = Some(format!("via {} ", Color::Blue.bold().paint("S v0.8.21")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_hardhat_config() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("hardhat.config.ts"))?.sync_all()?;
+        let actual = render_module("solidity", dir.path(), None);
+        let expected = Some(format!("via {} ", Color::Blue.bold().paint("S v0.8.21")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_foundry_without_solc() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("foundry.toml"))?.sync_all()?;
+        let actual = ModuleRenderer::new("solidity")
+            .path(dir.path())
+            .cmd("solc --version", None)
+            .cmd(
+                "forge --version",
+                Some(CommandOutput::stdout(
+                    "forge 0.2.0 (5be158b 2023-10-02T00:23:00.000000000Z)\n",
+                )),
+            )
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Blue.bold().paint("S forge v0.2.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+}
@@ -2,39 +2,48 @@ use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::terraform::TerraformConfig;
 use crate::utils;
-use std::env;
+use std::ffi::OsStr;
 use std::io;
 use std::path::PathBuf;
 
 /// Creates a module with the current Terraform version and workspace
 ///
 /// Will display the Terraform version and workspace if any of the following criteria are met:
-///     - Current directory contains a `.terraform` directory
+///     - Current directory contains a `.terraform` or `terraform.tfstate.d` directory
 ///     - Current directory contains a file with the `.tf` extension
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let is_terraform_project = context
+    let reason = context
         .try_begin_scan()?
-        .set_folders(&[".terraform"])
+        .set_folders(&[".terraform", "terraform.tfstate.d"])
         .set_extensions(&["tf"])
-        .is_match();
-
-    if !is_terraform_project {
-        return None;
-    }
+        .find_match()?;
 
     let mut module = context.new_module("terraform");
+    module.set_reason(reason);
     let config: TerraformConfig = TerraformConfig::try_load(module.config);
 
     module.set_style(config.style);
     module.create_segment("symbol", &config.symbol);
 
     if config.show_version {
-        let terraform_version =
-            format_terraform_version(&utils::exec_cmd("terraform", &["version"])?.stdout.as_str())?;
-        module.create_segment("version", &config.version.with_value(&terraform_version));
+        // Running `terraform version` is slow, so the version the configuration requires is
+        // shown instead unless the binary is allowed to be run
+        let terraform_version = if config.exec {
+            format_terraform_version(
+                context
+                    .exec_cmd("terraform", "terraform", &["version"])?
+                    .stdout
+                    .as_str(),
+            )
+        } else {
+            get_required_version(context).map(|version| version + " ")
+        };
+        if let Some(terraform_version) = terraform_version {
+            module.create_segment("version", &config.version.with_value(&terraform_version));
+        }
     }
 
-    let terraform_workspace = &get_terraform_workspace(&context.current_dir)?;
+    let terraform_workspace = &get_terraform_workspace(context)?;
     module.create_segment(
         "workspace",
         &config.workspace.with_value(&terraform_workspace),
@@ -44,17 +53,17 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 }
 
 // Determines the currently selected workspace (see https://github.com/hashicorp/terraform/blob/master/command/meta.go for the original implementation)
-fn get_terraform_workspace(cwd: &PathBuf) -> Option<String> {
+fn get_terraform_workspace(context: &Context) -> Option<String> {
     // Workspace can be explicitly overwritten by an env var
-    let workspace_override = env::var("TF_WORKSPACE");
-    if workspace_override.is_ok() {
-        return workspace_override.ok();
+    let workspace_override = context.get_env("TF_WORKSPACE");
+    if workspace_override.is_some() {
+        return workspace_override;
     }
 
     // Data directory containing current workspace can be overwritten by an env var
-    let datadir = match env::var("TF_DATA_DIR") {
-        Ok(s) => PathBuf::from(s),
-        Err(_) => cwd.join(".terraform"),
+    let datadir = match context.get_env("TF_DATA_DIR") {
+        Some(s) => PathBuf::from(s),
+        None => context.current_dir.join(".terraform"),
     };
     match utils::read_file(datadir.join("environment")) {
         Err(ref e) if e.kind() == io::ErrorKind::NotFound => Some("default".to_string()),
@@ -63,6 +72,34 @@ fn get_terraform_workspace(cwd: &PathBuf) -> Option<String> {
     }
 }
 
+/// The constraint on the version of Terraform set by `required_version` in the `.tf` files
+/// of the current directory
+fn get_required_version(context: &Context) -> Option<String> {
+    let mut files: Vec<_> = context
+        .dir_contents()
+        .ok()?
+        .files()
+        .filter(|file| file.extension().and_then(OsStr::to_str) == Some("tf"))
+        .collect();
+    files.sort();
+    files.into_iter().find_map(|file| {
+        let contents = utils::read_file(context.current_dir.join(file)).ok()?;
+        parse_required_version(&contents)
+    })
+}
+
+fn parse_required_version(contents: &str) -> Option<String> {
+    contents.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        if key.trim() != "required_version" {
+            return None;
+        }
+        let version = value.trim().strip_prefix('"')?;
+        let (version, _) = version.split_once('"')?;
+        Some(version.to_owned())
+    })
+}
+
 fn format_terraform_version(version: &str) -> Option<String> {
     // `terraform version` output looks like this
     // Terraform v0.12.14
@@ -82,6 +119,25 @@ fn format_terraform_version(version: &str) -> Option<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_required_version() {
+        let input = r#"
+terraform {
+  backend "s3" {}
+
+  required_version = ">= 0.12, < 0.14" # Pinned for the modules
+}
+"#;
+        assert_eq!(
+            parse_required_version(input),
+            Some(">= 0.12, < 0.14".to_string())
+        );
+        assert_eq!(
+            parse_required_version("resource \"null_resource\" \"a\" {}"),
+            None
+        );
+    }
+
     #[test]
     fn test_format_terraform_version_release() {
         let input = "Terraform v0.12.14";
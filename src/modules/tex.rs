@@ -0,0 +1,108 @@
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::tex::TexConfig;
+
+/// Creates a module with the current TeX distribution
+///
+/// Will display the TeX distribution, like `TeX Live 2023` or `MiKTeX 23.10`, if any of
+/// the following criteria are met:
+///     - The current directory contains a `latexmkrc` or `.latexmkrc` file
+///     - The current directory contains a file with extension `.tex`
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let reason = context
+        .try_begin_scan()?
+        .set_files(&["latexmkrc", ".latexmkrc"])
+        .set_extensions(&["tex"])
+        .find_match()?;
+
+    let tex_output = context.exec_cmd("tex", "tex", &["--version"])?.stdout;
+    let distribution = parse_tex_distribution(&tex_output)?;
+
+    let mut module = context.new_module("tex");
+    module.set_reason(reason);
+    let config = TexConfig::try_load(module.config);
+
+    module.set_style(config.style);
+
+    module.create_segment("symbol", &config.symbol);
+    module.create_segment("version", &config.version.with_value(&distribution));
+
+    Some(module)
+}
+
+fn parse_tex_distribution(tex_stdout: &str) -> Option<String> {
+    // tex --version output starts like this:
+    // TeX 3.141592653 (TeX Live 2023/Debian)
+    // or with MiKTeX:
+    // MiKTeX-TeX 3.141592653 (MiKTeX 23.10)
+    let first_line = tex_stdout.lines().next()?;
+    let start = first_line.find('(')? + 1;
+    let end = start + first_line[start..].find(')')?;
+    // The distribution is packaged by someone else after a `/`
+    let distribution = first_line[start..end].split('/').next()?.trim();
+    if distribution.is_empty() {
+        None
+    } else {
+        Some(distribution.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::utils::test::render_module;
+    use ansi_term::Color;
+    use std::fs::File;
+    use std::io;
+
+    #[test]
+    fn test_parse_tex_distribution() {
+        let tex_live = "TeX 3.141592653 (TeX Live 2023/Debian)\nkpathsea version 6.3.5\n";
+        assert_eq!(
+            parse_tex_distribution(tex_live),
+            Some(String::from("TeX Live 2023"))
+        );
+        let miktex = "MiKTeX-TeX 3.141592653 (MiKTeX 23.10)\n";
+        assert_eq!(
+            parse_tex_distribution(miktex),
+            Some(String::from("MiKTeX 23.10"))
+        );
+        assert_eq!(parse_tex_distribution("TeX 3.141592653\n"), None);
+    }
+
+    #[test]
+    fn folder_without_tex_files() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("README.md"))?.sync_all()?;
+        let actual = render_module("tex", dir.path(), None);
+        let expected = None;
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_tex_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("thesis.tex"))?.sync_all()?;
+        let actual = render_module("tex", dir.path(), None);
+        let expected = Some(format!(
+            "via {} ",
+            Color::Green.bold().paint("📜 TeX Live 2023")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_latexmkrc() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join(".latexmkrc"))?.sync_all()?;
+        let actual = render_module("tex", dir.path(), None);
+        let expected = Some(format!(
+            "via {} ",
+            Color::Green.bold().paint("📜 TeX Live 2023")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+}
@@ -1,5 +1,6 @@
 use chrono::{DateTime, FixedOffset, Local, Utc};
 
+use super::utils::locale::Locale;
 use super::{Context, Module};
 
 use crate::config::{RootModuleConfig, SegmentConfig};
@@ -15,26 +16,21 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     };
 
-    let default_format = if config.use_12hr { "%r" } else { "%T" };
-    let time_format = config.format.unwrap_or(default_format);
-
-    log::trace!(
-        "Timer module is enabled with format string: {}",
-        time_format
-    );
-
-    let formatted_time_string = if config.utc_time_offset != "local" {
-        match create_offset_time_string(Utc::now(), &config.utc_time_offset, &time_format) {
-            Ok(formatted_string) => formatted_string,
-            Err(_) => {
-                log::warn!(
-                    "Invalid utc_time_offset configuration provided! Falling back to \"local\"."
-                );
-                format_time(&time_format, Local::now())
-            }
+    let formatted_time_string = match config.format {
+        None if config.use_locale => {
+            let locale = Locale::from_env(context);
+            log::trace!("Timer module is enabled with locale: {:?}", locale);
+            format_locale_time(locale, Utc::now(), config.utc_time_offset)
+        }
+        _ => {
+            let default_format = if config.use_12hr { "%r" } else { "%T" };
+            let time_format = config.format.unwrap_or(default_format);
+            log::trace!(
+                "Timer module is enabled with format string: {}",
+                time_format
+            );
+            format_offset_time(Utc::now(), config.utc_time_offset, time_format)
         }
-    } else {
-        format_time(&time_format, Local::now())
     };
 
     module.set_style(config.style);
@@ -52,11 +48,53 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+/// Format the time with a format string, in the given offset or else locally
+fn format_offset_time(
+    utc_time: DateTime<Utc>,
+    utc_time_offset_str: &str,
+    time_format: &str,
+) -> String {
+    if utc_time_offset_str != "local" {
+        match create_offset_time_string(utc_time, utc_time_offset_str, time_format) {
+            Ok(formatted_string) => return formatted_string,
+            Err(_) => log::warn!(
+                "Invalid utc_time_offset configuration provided! Falling back to \"local\"."
+            ),
+        }
+    }
+    format_time(time_format, utc_time.with_timezone(&Local))
+}
+
+/// Format the time the way the locale writes it, in the given offset or else locally
+fn format_locale_time(
+    locale: &Locale,
+    utc_time: DateTime<Utc>,
+    utc_time_offset_str: &str,
+) -> String {
+    if utc_time_offset_str != "local" {
+        match create_offset_time(utc_time, utc_time_offset_str) {
+            Ok(target_time) => return locale.format_time(&target_time),
+            Err(_) => log::warn!(
+                "Invalid utc_time_offset configuration provided! Falling back to \"local\"."
+            ),
+        }
+    }
+    locale.format_time(&utc_time.with_timezone(&Local))
+}
+
 fn create_offset_time_string(
     utc_time: DateTime<Utc>,
     utc_time_offset_str: &str,
     time_format: &str,
 ) -> Result<String, &'static str> {
+    let target_time = create_offset_time(utc_time, utc_time_offset_str)?;
+    Ok(format_time_fixed_offset(time_format, target_time))
+}
+
+fn create_offset_time(
+    utc_time: DateTime<Utc>,
+    utc_time_offset_str: &str,
+) -> Result<DateTime<FixedOffset>, &'static str> {
     // Using floats to allow 30/45 minute offsets: https://www.timeanddate.com/time/time-zones-interesting.html
     let utc_time_offset_in_hours = match utc_time_offset_str.parse::<f32>() {
         Ok(parsed_value) => parsed_value,
@@ -71,7 +109,7 @@ fn create_offset_time_string(
         let target_time = utc_time.with_timezone(&timezone_offset);
         log::trace!("Time in target timezone now is {}", target_time);
 
-        Ok(format_time_fixed_offset(&time_format, target_time))
+        Ok(target_time)
     } else {
         Err("Invalid timezone offset.")
     }
@@ -308,4 +346,12 @@ mod tests {
             .err()
             .expect("Invalid timezone offset.");
     }
+
+    #[test]
+    fn test_locale_time_with_offset() {
+        let utc_time: DateTime<Utc> = Utc.ymd(2014, 7, 8).and_hms(15, 36, 47);
+        let locale = Locale::find("ko_KR.UTF-8");
+        let actual = format_locale_time(locale, utc_time, "+9");
+        assert_eq!(actual, "오전 12:36:47");
+    }
 }
@@ -0,0 +1,84 @@
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::typst::TypstConfig;
+
+/// Creates a module with the current Typst version
+///
+/// Will display the Typst version if any of the following criteria are met:
+///     - The current directory contains a `typst.toml` file
+///     - The current directory contains a file with extension `.typ`
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let reason = context
+        .try_begin_scan()?
+        .set_files(&["typst.toml"])
+        .set_extensions(&["typ"])
+        .find_match()?;
+
+    let typst_output = context.exec_cmd("typst", "typst", &["--version"])?.stdout;
+    let typst_version = format_typst_version(&typst_output)?;
+
+    let mut module = context.new_module("typst");
+    module.set_reason(reason);
+    let config = TypstConfig::try_load(module.config);
+
+    module.set_style(config.style);
+
+    module.create_segment("symbol", &config.symbol);
+    module.create_segment("version", &config.version.with_value(&typst_version));
+
+    Some(module)
+}
+
+fn format_typst_version(typst_stdout: &str) -> Option<String> {
+    // typst --version output looks like this:
+    // typst 0.10.0 (70ca0d25)
+    let version = typst_stdout.split_whitespace().nth(1)?;
+    Some(format!("v{}", version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::utils::test::render_module;
+    use ansi_term::Color;
+    use std::fs::File;
+    use std::io;
+
+    #[test]
+    fn test_format_typst_version() {
+        assert_eq!(
+            format_typst_version("typst 0.10.0 (70ca0d25)\n"),
+            Some(String::from("v0.10.0"))
+        );
+    }
+
+    #[test]
+    fn folder_without_typst_files() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("notes.txt"))?.sync_all()?;
+        let actual = render_module("typst", dir.path(), None);
+        let expected = None;
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_typ_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("paper.typ"))?.sync_all()?;
+        let actual = render_module("typst", dir.path(), None);
+        let expected = Some(format!("via {} ", Color::Cyan.bold().paint("t v0.10.0")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_typst_toml() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("typst.toml"))?.sync_all()?;
+        let actual = render_module("typst", dir.path(), None);
+        let expected = Some(format!("via {} ", Color::Cyan.bold().paint("t v0.10.0")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+}
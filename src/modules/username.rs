@@ -1,9 +1,6 @@
-use std::env;
-
 use super::{Context, Module, RootModuleConfig, SegmentConfig};
 
 use crate::configs::username::UsernameConfig;
-use crate::utils;
 
 /// Creates a module with the current user's username
 ///
@@ -12,12 +9,12 @@ use crate::utils;
 ///     - The current user is root (UID = 0)
 ///     - The user is currently connected as an SSH session (`$SSH_CONNECTION`)
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let user = env::var("USER").ok();
-    let logname = env::var("LOGNAME").ok();
-    let ssh_connection = env::var("SSH_CONNECTION").ok();
+    let user = context.get_env("USER");
+    let logname = context.get_env("LOGNAME");
+    let ssh_connection = context.get_env("SSH_CONNECTION");
 
     const ROOT_UID: Option<u32> = Some(0);
-    let user_uid = get_uid();
+    let user_uid = get_uid(context);
 
     let mut module = context.new_module("username");
     let config: UsernameConfig = UsernameConfig::try_load(module.config);
@@ -37,8 +34,9 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     }
 }
 
-fn get_uid() -> Option<u32> {
-    utils::exec_cmd("id", &["-u"])?
+fn get_uid(context: &Context) -> Option<u32> {
+    context
+        .exec_cmd("username", "id", &["-u"])?
         .stdout
         .trim()
         .parse::<u32>()
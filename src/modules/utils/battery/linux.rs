@@ -0,0 +1,167 @@
+use std::fs;
+use std::path::Path;
+
+use super::{BatteryInfo, State};
+
+/// The batteries the kernel lists in sysfs
+pub fn batteries() -> Vec<BatteryInfo> {
+    read_power_supplies(Path::new("/sys/class/power_supply"))
+}
+
+/// The batteries among the power supplies in a folder like `/sys/class/power_supply`,
+/// leaving out those of devices like wireless mice, which the kernel lists too
+fn read_power_supplies(root: &Path) -> Vec<BatteryInfo> {
+    let entries = match fs::read_dir(root) {
+        Ok(entries) => entries,
+        Err(e) => {
+            log::debug!("Unable to read the power supplies in {:?}: {}", root, e);
+            return Vec::new();
+        }
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|dir| read_string(dir, "type").as_deref() == Some("Battery"))
+        .filter(|dir| read_string(dir, "scope").as_deref() != Some("Device"))
+        .filter_map(|dir| {
+            let battery = read_battery(&dir);
+            log::debug!("Battery found in {:?}: {:?}", dir, battery);
+            battery
+        })
+        .collect()
+}
+
+fn read_battery(dir: &Path) -> Option<BatteryInfo> {
+    let state = match read_string(dir, "status").as_deref() {
+        Some("Charging") => State::Charging,
+        Some("Discharging") => State::Discharging,
+        Some("Full") => State::Full,
+        _ => State::Unknown,
+    };
+
+    // Batteries report either their energy or their charge, which is converted to
+    // energy so that batteries of both kinds can be added up
+    let (energy, energy_full) = match (read_energy(dir, "now"), read_energy(dir, "full")) {
+        (Some(energy), Some(energy_full)) => (energy, energy_full),
+        _ => (read_number(dir, "capacity")?, 100.0),
+    };
+    Some(BatteryInfo::new(energy, energy_full, state))
+}
+
+/// The energy of a battery, in µWh
+fn read_energy(dir: &Path, which: &str) -> Option<f32> {
+    read_number(dir, &format!("energy_{}", which)).or_else(|| {
+        let charge = read_number(dir, &format!("charge_{}", which))?;
+        let voltage =
+            read_number(dir, "voltage_min_design").or_else(|| read_number(dir, "voltage_now"))?;
+        Some(charge * voltage / 1_000_000.0)
+    })
+}
+
+fn read_number(dir: &Path, file: &str) -> Option<f32> {
+    read_string(dir, file)?.parse().ok()
+}
+
+fn read_string(dir: &Path, file: &str) -> Option<String> {
+    let value = fs::read_to_string(dir.join(file)).ok()?;
+    Some(value.trim().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_supply(root: &Path, name: &str, files: &[(&str, &str)]) -> std::io::Result<()> {
+        let dir = root.join(name);
+        fs::create_dir(&dir)?;
+        for (file, value) in files {
+            fs::write(dir.join(file), format!("{}\n", value))?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn read_sysfs_batteries() -> std::io::Result<()> {
+        let root = tempfile::tempdir()?;
+        write_supply(
+            root.path(),
+            "BAT0",
+            &[
+                ("type", "Battery"),
+                ("status", "Discharging"),
+                ("energy_now", "25000000"),
+                ("energy_full", "50000000"),
+            ],
+        )?;
+        write_supply(
+            root.path(),
+            "BAT1",
+            &[
+                ("type", "Battery"),
+                ("status", "Charging"),
+                ("charge_now", "1000000"),
+                ("charge_full", "4000000"),
+                ("voltage_min_design", "10000000"),
+            ],
+        )?;
+        write_supply(root.path(), "AC", &[("type", "Mains"), ("online", "1")])?;
+        write_supply(
+            root.path(),
+            "BAT2",
+            &[
+                ("type", "Battery"),
+                ("status", "Discharging"),
+                ("energy_now", "0"),
+                ("energy_full", "30000000"),
+            ],
+        )?;
+        write_supply(
+            root.path(),
+            "hidpp_battery_0",
+            &[("type", "Battery"), ("scope", "Device"), ("capacity", "5")],
+        )?;
+
+        let mut batteries = read_power_supplies(root.path());
+        batteries.sort_by(|a, b| a.energy.partial_cmp(&b.energy).unwrap());
+        assert_eq!(
+            batteries,
+            vec![
+                BatteryInfo {
+                    energy: 0.0,
+                    energy_full: 30_000_000.0,
+                    state: State::Empty,
+                },
+                BatteryInfo {
+                    energy: 10_000_000.0,
+                    energy_full: 40_000_000.0,
+                    state: State::Charging,
+                },
+                BatteryInfo {
+                    energy: 25_000_000.0,
+                    energy_full: 50_000_000.0,
+                    state: State::Discharging,
+                },
+            ]
+        );
+        root.close()
+    }
+
+    #[test]
+    fn read_sysfs_battery_capacity() -> std::io::Result<()> {
+        let root = tempfile::tempdir()?;
+        write_supply(
+            root.path(),
+            "battery",
+            &[("type", "Battery"), ("status", "Full"), ("capacity", "100")],
+        )?;
+
+        let expected = vec![BatteryInfo {
+            energy: 100.0,
+            energy_full: 100.0,
+            state: State::Full,
+        }];
+        assert_eq!(read_power_supplies(root.path()), expected);
+        root.close()
+    }
+}
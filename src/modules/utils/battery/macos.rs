@@ -0,0 +1,164 @@
+use std::ffi::{c_void, CString};
+use std::os::raw::c_char;
+
+use super::{BatteryInfo, State};
+
+type CFTypeRef = *const c_void;
+type CFTypeID = usize;
+type CFIndex = isize;
+
+const K_CF_NUMBER_SINT64_TYPE: CFIndex = 4;
+const K_CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    fn CFRelease(cf: CFTypeRef);
+    fn CFEqual(cf1: CFTypeRef, cf2: CFTypeRef) -> u8;
+    fn CFGetTypeID(cf: CFTypeRef) -> CFTypeID;
+    fn CFArrayGetCount(array: CFTypeRef) -> CFIndex;
+    fn CFArrayGetValueAtIndex(array: CFTypeRef, index: CFIndex) -> CFTypeRef;
+    fn CFDictionaryGetValue(dict: CFTypeRef, key: CFTypeRef) -> CFTypeRef;
+    fn CFStringCreateWithCString(
+        alloc: CFTypeRef,
+        c_str: *const c_char,
+        encoding: u32,
+    ) -> CFTypeRef;
+    fn CFNumberGetTypeID() -> CFTypeID;
+    fn CFNumberGetValue(number: CFTypeRef, the_type: CFIndex, value_ptr: *mut c_void) -> u8;
+    fn CFBooleanGetTypeID() -> CFTypeID;
+    fn CFBooleanGetValue(boolean: CFTypeRef) -> u8;
+}
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IOPSCopyPowerSourcesInfo() -> CFTypeRef;
+    fn IOPSCopyPowerSourcesList(blob: CFTypeRef) -> CFTypeRef;
+    fn IOPSGetPowerSourceDescription(blob: CFTypeRef, power_source: CFTypeRef) -> CFTypeRef;
+}
+
+/// The internal batteries among the power sources IOKit lists
+pub fn batteries() -> Vec<BatteryInfo> {
+    let info = match Owned::new(unsafe { IOPSCopyPowerSourcesInfo() }) {
+        Some(info) => info,
+        None => {
+            log::debug!("Unable to get the power sources");
+            return Vec::new();
+        }
+    };
+    let list = match Owned::new(unsafe { IOPSCopyPowerSourcesList(info.0) }) {
+        Some(list) => list,
+        None => return Vec::new(),
+    };
+
+    let count = unsafe { CFArrayGetCount(list.0) };
+    (0..count)
+        .filter_map(|index| {
+            let description = unsafe {
+                let power_source = CFArrayGetValueAtIndex(list.0, index);
+                IOPSGetPowerSourceDescription(info.0, power_source)
+            };
+            if description.is_null() {
+                return None;
+            }
+            let battery = read_battery(&Dictionary(description));
+            log::debug!("Battery found: {:?}", battery);
+            battery
+        })
+        .collect()
+}
+
+fn read_battery(description: &Dictionary) -> Option<BatteryInfo> {
+    if !description.has_string("Type", "InternalBattery") {
+        return None;
+    }
+
+    let state = if description.boolean("Is Charging") == Some(true) {
+        State::Charging
+    } else if description.boolean("Is Charged") == Some(true) {
+        State::Full
+    } else if description.has_string("Power Source State", "Battery Power") {
+        State::Discharging
+    } else {
+        State::Unknown
+    };
+    let energy = description.number("Current Capacity")? as f32;
+    let energy_full = description.number("Max Capacity")? as f32;
+    Some(BatteryInfo::new(energy, energy_full, state))
+}
+
+/// A Core Foundation object that is released when it's dropped
+struct Owned(CFTypeRef);
+
+impl Owned {
+    fn new(cf: CFTypeRef) -> Option<Self> {
+        if cf.is_null() {
+            None
+        } else {
+            Some(Owned(cf))
+        }
+    }
+
+    fn string(value: &str) -> Option<Self> {
+        let value = CString::new(value).ok()?;
+        Owned::new(unsafe {
+            CFStringCreateWithCString(std::ptr::null(), value.as_ptr(), K_CF_STRING_ENCODING_UTF8)
+        })
+    }
+}
+
+impl Drop for Owned {
+    fn drop(&mut self) {
+        unsafe { CFRelease(self.0) }
+    }
+}
+
+/// A `CFDictionary` owned by someone else
+struct Dictionary(CFTypeRef);
+
+impl Dictionary {
+    fn get(&self, key: &str) -> Option<CFTypeRef> {
+        let key = Owned::string(key)?;
+        let value = unsafe { CFDictionaryGetValue(self.0, key.0) };
+        if value.is_null() {
+            None
+        } else {
+            Some(value)
+        }
+    }
+
+    fn number(&self, key: &str) -> Option<i64> {
+        let value = self.get(key)?;
+        let mut number: i64 = 0;
+        let is_read = unsafe {
+            CFGetTypeID(value) == CFNumberGetTypeID()
+                && CFNumberGetValue(
+                    value,
+                    K_CF_NUMBER_SINT64_TYPE,
+                    &mut number as *mut i64 as *mut c_void,
+                ) != 0
+        };
+        if is_read {
+            Some(number)
+        } else {
+            None
+        }
+    }
+
+    fn boolean(&self, key: &str) -> Option<bool> {
+        let value = self.get(key)?;
+        unsafe {
+            if CFGetTypeID(value) == CFBooleanGetTypeID() {
+                Some(CFBooleanGetValue(value) != 0)
+            } else {
+                None
+            }
+        }
+    }
+
+    fn has_string(&self, key: &str, expected: &str) -> bool {
+        match (self.get(key), Owned::string(expected)) {
+            (Some(value), Some(expected)) => unsafe { CFEqual(value, expected.0) != 0 },
+            _ => false,
+        }
+    }
+}
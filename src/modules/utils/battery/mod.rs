@@ -0,0 +1,76 @@
+//! The charge of the device's batteries, read from what the OS provides for it: sysfs on
+//! Linux, IOKit on macOS and `GetSystemPowerStatus` on Windows.
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(windows)]
+mod windows;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use linux::batteries as native_batteries;
+#[cfg(target_os = "macos")]
+use macos::batteries as native_batteries;
+#[cfg(windows)]
+use windows::batteries as native_batteries;
+
+/// Whether a battery is being charged
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum State {
+    Unknown,
+    Charging,
+    Discharging,
+    Empty,
+    Full,
+}
+
+/// The charge of a battery, in whatever unit the OS gives it in, which is the same for
+/// both of them
+#[derive(Debug, PartialEq)]
+pub struct BatteryInfo {
+    pub energy: f32,
+    pub energy_full: f32,
+    pub state: State,
+}
+
+impl BatteryInfo {
+    /// A battery that's empty if it's discharging without any energy left
+    fn new(energy: f32, energy_full: f32, state: State) -> Self {
+        let state = match state {
+            State::Discharging if energy <= 0.0 => State::Empty,
+            state => state,
+        };
+        BatteryInfo {
+            energy,
+            energy_full,
+            state,
+        }
+    }
+}
+
+/// A source of the charge of the device's batteries
+pub trait BatteryProvider {
+    /// Every battery of the device, which is none if it can't be told
+    fn batteries(&self) -> Vec<BatteryInfo>;
+}
+
+/// The batteries of the device, as the OS reports them
+pub struct NativeBatteries;
+
+impl BatteryProvider for NativeBatteries {
+    fn batteries(&self) -> Vec<BatteryInfo> {
+        native_batteries()
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    windows
+)))]
+fn native_batteries() -> Vec<BatteryInfo> {
+    log::debug!("Reading batteries isn't supported on this OS");
+    Vec::new()
+}
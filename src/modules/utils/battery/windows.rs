@@ -0,0 +1,50 @@
+use super::{BatteryInfo, State};
+
+/// `SYSTEM_POWER_STATUS` of `winbase.h`
+#[allow(dead_code)] // The fields that aren't read are there for the layout
+#[repr(C)]
+#[derive(Default)]
+struct SystemPowerStatus {
+    ac_line_status: u8,
+    battery_flag: u8,
+    battery_life_percent: u8,
+    system_status_flag: u8,
+    battery_life_time: u32,
+    battery_full_life_time: u32,
+}
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn GetSystemPowerStatus(status: *mut SystemPowerStatus) -> i32;
+}
+
+const AC_OFFLINE: u8 = 0;
+const AC_ONLINE: u8 = 1;
+const BATTERY_CHARGING: u8 = 8;
+const NO_BATTERY: u8 = 128;
+const UNKNOWN: u8 = 255;
+
+/// The batteries as Windows reports them, which is as a single one
+pub fn batteries() -> Vec<BatteryInfo> {
+    let mut status = SystemPowerStatus::default();
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        log::debug!("Unable to get the power status");
+        return Vec::new();
+    }
+
+    if status.battery_flag == UNKNOWN
+        || status.battery_flag & NO_BATTERY != 0
+        || status.battery_life_percent == UNKNOWN
+    {
+        return Vec::new();
+    }
+
+    let percent = status.battery_life_percent;
+    let state = match status.ac_line_status {
+        _ if status.battery_flag & BATTERY_CHARGING != 0 => State::Charging,
+        AC_OFFLINE => State::Discharging,
+        AC_ONLINE if percent == 100 => State::Full,
+        _ => State::Unknown,
+    };
+    vec![BatteryInfo::new(f32::from(percent), 100.0, state)]
+}
@@ -0,0 +1,323 @@
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::vec::IntoIter;
+use toml::Value;
+
+use crate::context::Context;
+use crate::modules::custom;
+
+/// Whether the `when` condition of a module holds, which it does if it has none.
+///
+/// A string is a command that has to succeed. A table can set `env` (variables that
+/// have to be set, or `NAME=value` to have that value), `path` (directories the
+/// current directory has to be inside of), `files` and `directories` (one of which has
+/// to be in the current directory), `expression` (see `is_expression_met`) and
+/// `command`, all of which have to hold.
+pub fn is_met(module: &str, context: &Context) -> bool {
+    let config = match module.split_once('.') {
+        Some((module, instance)) => context
+            .config
+            .get_module_config(module)
+            .and_then(|config| config.get(instance)),
+        None => context.config.get_module_config(module),
+    };
+    let when = match config.and_then(|config| config.get("when")) {
+        Some(when) => when,
+        None => return true,
+    };
+
+    match when {
+        Value::String(cmd) => custom::exec_when(cmd, None, context, module),
+        Value::Table(table) => {
+            let met = table.iter().all(|(key, value)| match key.as_str() {
+                "env" => strings(value).iter().all(|var| is_env_set(context, var)),
+                "path" => strings(value)
+                    .iter()
+                    .any(|path| is_inside(&context.current_dir, path)),
+                "files" => match context.dir_contents() {
+                    Ok(dir) => strings(value).iter().any(|file| dir.has_file(file)),
+                    Err(_) => false,
+                },
+                "directories" => match context.dir_contents() {
+                    Ok(dir) => strings(value).iter().any(|folder| dir.has_folder(folder)),
+                    Err(_) => false,
+                },
+                "expression" => match value.as_str().map(|expr| is_expression_met(context, expr)) {
+                    Some(Ok(met)) => met,
+                    Some(Err(error)) => {
+                        log::warn!("Invalid `when` expression of {}: {}", module, error);
+                        true
+                    }
+                    None => {
+                        log::warn!("The `when` expression of {} should be a string", module);
+                        true
+                    }
+                },
+                // Run last, being slower than the other checks
+                "command" => true,
+                _ => {
+                    log::warn!("Unknown condition {:?} in the `when` of {}", key, module);
+                    true
+                }
+            });
+            match table.get("command").and_then(Value::as_str) {
+                Some(cmd) if met => custom::exec_when(cmd, None, context, module),
+                _ => met,
+            }
+        }
+        _ => {
+            log::warn!("The `when` of {} should be a string or a table", module);
+            true
+        }
+    }
+}
+
+/// A string or an array of strings as a list of them
+fn strings(value: &Value) -> Vec<&str> {
+    match value {
+        Value::String(value) => vec![value.as_str()],
+        Value::Array(values) => values.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Whether a variable is set and not empty, or has a value if it's `NAME=value`
+fn is_env_set(context: &Context, var: &str) -> bool {
+    match var.split_once('=') {
+        Some((name, expected)) => matches!(context.get_env(name), Some(value) if value == expected),
+        None => matches!(context.get_env(var), Some(value) if !value.is_empty()),
+    }
+}
+
+fn is_inside(current_dir: &Path, path: &str) -> bool {
+    current_dir.starts_with(Context::expand_tilde(PathBuf::from(path)))
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Var(String),
+    Str(String),
+    Eq,
+    Ne,
+    Not,
+    And,
+    Or,
+    Open,
+    Close,
+}
+
+/// Whether a simple expression over environment variables holds, like
+/// `$AWS_PROFILE == "prod" || ($CI && !$DEBUG)`. `$NAME` is the value of a variable, or
+/// an empty string if it isn't set, and strings are quoted with `"` or `'`. A value on
+/// its own holds if it isn't empty, and `==` and `!=` compare two values. Conditions are
+/// combined with `!`, `&&`, `||` and parentheses.
+fn is_expression_met(context: &Context, expression: &str) -> Result<bool, String> {
+    let mut tokens = tokenize(expression)?.into_iter().peekable();
+    let met = parse_or(context, &mut tokens)?;
+    match tokens.next() {
+        Some(token) => Err(format!("unexpected {:?}", token)),
+        None => Ok(met),
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '!' if chars.peek() == Some(&'=') => {
+                chars.next();
+                Token::Ne
+            }
+            '!' => Token::Not,
+            '=' if chars.next() == Some('=') => Token::Eq,
+            '&' if chars.next() == Some('&') => Token::And,
+            '|' if chars.next() == Some('|') => Token::Or,
+            '"' | '\'' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some(next) if next == c => break,
+                        Some(next) => value.push(next),
+                        None => return Err(format!("missing closing `{}`", c)),
+                    }
+                }
+                Token::Str(value)
+            }
+            '$' => {
+                let mut name = String::new();
+                while let Some(&next) = chars.peek() {
+                    if !(next.is_alphanumeric() || next == '_') {
+                        break;
+                    }
+                    name.push(next);
+                    chars.next();
+                }
+                if name.is_empty() {
+                    return Err(String::from("`$` without a variable name"));
+                }
+                Token::Var(name)
+            }
+            c => return Err(format!("unexpected {:?}", c)),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+type Tokens = Peekable<IntoIter<Token>>;
+
+fn parse_or(context: &Context, tokens: &mut Tokens) -> Result<bool, String> {
+    let mut met = parse_and(context, tokens)?;
+    while tokens.peek() == Some(&Token::Or) {
+        tokens.next();
+        met |= parse_and(context, tokens)?;
+    }
+    Ok(met)
+}
+
+fn parse_and(context: &Context, tokens: &mut Tokens) -> Result<bool, String> {
+    let mut met = parse_unary(context, tokens)?;
+    while tokens.peek() == Some(&Token::And) {
+        tokens.next();
+        met &= parse_unary(context, tokens)?;
+    }
+    Ok(met)
+}
+
+fn parse_unary(context: &Context, tokens: &mut Tokens) -> Result<bool, String> {
+    match tokens.peek() {
+        Some(Token::Not) => {
+            tokens.next();
+            Ok(!parse_unary(context, tokens)?)
+        }
+        Some(Token::Open) => {
+            tokens.next();
+            let met = parse_or(context, tokens)?;
+            match tokens.next() {
+                Some(Token::Close) => Ok(met),
+                _ => Err(String::from("missing `)`")),
+            }
+        }
+        _ => {
+            let value = parse_value(context, tokens)?;
+            match tokens.peek() {
+                Some(Token::Eq) => {
+                    tokens.next();
+                    Ok(value == parse_value(context, tokens)?)
+                }
+                Some(Token::Ne) => {
+                    tokens.next();
+                    Ok(value != parse_value(context, tokens)?)
+                }
+                _ => Ok(!value.is_empty()),
+            }
+        }
+    }
+}
+
+fn parse_value(context: &Context, tokens: &mut Tokens) -> Result<String, String> {
+    match tokens.next() {
+        Some(Token::Var(name)) => Ok(context.get_env(name).unwrap_or_default()),
+        Some(Token::Str(value)) => Ok(value),
+        Some(token) => Err(format!("unexpected {:?}", token)),
+        None => Err(String::from("unexpected end")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StarshipConfig;
+    use clap::ArgMatches;
+    use std::env;
+
+    fn context(config: toml::Value, dir: &Path) -> Context<'static> {
+        let mut context = Context::new_with_dir(ArgMatches::default(), dir);
+        context.config = StarshipConfig::new(Some(config));
+        context
+    }
+
+    #[test]
+    fn met_without_condition() {
+        let context = context(toml::toml! { [aws] }, &env::temp_dir());
+        assert!(is_met("aws", &context));
+        assert!(is_met("kubernetes", &context));
+    }
+
+    #[test]
+    fn met_inside_path() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let infra = dir.path().join("infra");
+        std::fs::create_dir(&infra)?;
+        let path = infra.to_string_lossy().into_owned();
+        let config = toml::toml! {
+            [kubernetes.when]
+            path = path
+        };
+
+        assert!(is_met("kubernetes", &context(config.clone(), &infra)));
+        assert!(!is_met("kubernetes", &context(config, dir.path())));
+        dir.close()
+    }
+
+    #[test]
+    fn met_with_files() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::File::create(dir.path().join("Chart.yaml"))?.sync_all()?;
+
+        let found = toml::toml! {
+            [kubernetes.when]
+            files = ["Chart.yaml", "kustomization.yaml"]
+        };
+        assert!(is_met("kubernetes", &context(found, dir.path())));
+        let missing = toml::toml! {
+            [kubernetes.when]
+            files = ["kustomization.yaml"]
+            directories = ["charts"]
+        };
+        assert!(!is_met("kubernetes", &context(missing, dir.path())));
+        dir.close()
+    }
+
+    #[test]
+    fn met_with_expression() {
+        let mut context = context(toml::toml! { [aws] }, &env::temp_dir());
+        context.env = Some(
+            [("AWS_PROFILE", "prod"), ("CI", "true"), ("EMPTY", "")]
+                .iter()
+                .map(|(key, value)| ((*key).to_owned(), (*value).to_owned()))
+                .collect(),
+        );
+
+        assert_eq!(
+            is_expression_met(&context, "$AWS_PROFILE == 'prod'"),
+            Ok(true)
+        );
+        assert_eq!(
+            is_expression_met(&context, "$AWS_PROFILE != \"prod\""),
+            Ok(false)
+        );
+        assert_eq!(is_expression_met(&context, "$EMPTY || $UNSET"), Ok(false));
+        assert_eq!(
+            is_expression_met(&context, "!$UNSET && ($EMPTY || $CI == 'true')"),
+            Ok(true)
+        );
+        assert!(is_expression_met(&context, "$CI ==").is_err());
+        assert!(is_expression_met(&context, "($CI").is_err());
+        assert!(is_expression_met(&context, "$CI = 'true'").is_err());
+        assert!(is_expression_met(&context, "$CI == 'true").is_err());
+    }
+
+    #[test]
+    fn met_with_every_condition() {
+        let config = toml::toml! {
+            [kubernetes.when]
+            env = ["PATH", "STARSHIP_WHEN_UNSET"]
+        };
+        assert!(!is_met("kubernetes", &context(config, &env::temp_dir())));
+    }
+}
@@ -14,6 +14,11 @@ pub fn truncate(dir_string: String, length: usize) -> String {
         components.remove(0);
     }
 
+    // The `//server/share` a UNC path starts with counts as a single component
+    if dir_string.starts_with("//") && components.len() > 2 {
+        components.drain(..2);
+    }
+
     if components.len() <= length {
         return dir_string;
     }
@@ -67,4 +72,14 @@ mod tests {
         let output = truncate(path.to_string(), 3);
         assert_eq!(output, "engines/booster/rocket");
     }
+
+    #[test]
+    fn truncate_unc_path() {
+        let path = "//server/share/engines/booster";
+        assert_eq!(
+            truncate(path.to_string(), 3),
+            "//server/share/engines/booster"
+        );
+        assert_eq!(truncate(path.to_string(), 2), "engines/booster");
+    }
 }
@@ -0,0 +1,165 @@
+use chrono::Timelike;
+
+use crate::context::Context;
+
+/// How a locale writes the time of day and durations
+#[derive(Debug, PartialEq)]
+pub struct Locale {
+    /// Whether the time of day is written with a 12-hour clock
+    pub hour12: bool,
+    pub am: &'static str,
+    pub pm: &'static str,
+    /// Whether AM or PM is written before the time instead of after it
+    pub am_pm_first: bool,
+    /// The separator between hours, minutes and seconds
+    pub time_separator: &'static str,
+    /// The separator between whole and fractional numbers
+    pub decimal_separator: &'static str,
+}
+
+/// The conventions of the C locale, which is also used for unknown locales
+const DEFAULT: Locale = Locale {
+    hour12: false,
+    am: "AM",
+    pm: "PM",
+    am_pm_first: false,
+    time_separator: ":",
+    decimal_separator: ".",
+};
+
+const fn clock24(time_separator: &'static str, decimal_separator: &'static str) -> Locale {
+    Locale {
+        time_separator,
+        decimal_separator,
+        ..DEFAULT
+    }
+}
+
+const fn clock12(am: &'static str, pm: &'static str, am_pm_first: bool) -> Locale {
+    Locale {
+        hour12: true,
+        am,
+        pm,
+        am_pm_first,
+        ..DEFAULT
+    }
+}
+
+/// The locales with their conventions, by `language_TERRITORY` or only by language
+const LOCALES: &[(&str, Locale)] = &[
+    ("cs", clock24(":", ",")),
+    ("da", clock24(".", ",")),
+    ("de", clock24(":", ",")),
+    ("en", clock24(":", ".")),
+    ("en_AU", clock12("AM", "PM", false)),
+    ("en_CA", clock12("AM", "PM", false)),
+    ("en_IN", clock12("AM", "PM", false)),
+    ("en_NZ", clock12("AM", "PM", false)),
+    ("en_PH", clock12("AM", "PM", false)),
+    ("en_US", clock12("AM", "PM", false)),
+    ("es", clock24(":", ",")),
+    ("es_MX", clock12("a. m.", "p. m.", false)),
+    ("es_US", clock12("a. m.", "p. m.", false)),
+    ("fi", clock24(".", ",")),
+    ("fr", clock24(":", ",")),
+    ("hi", clock12("am", "pm", false)),
+    ("it", clock24(":", ",")),
+    ("ja", clock24(":", ".")),
+    ("ko", clock12("오전", "오후", true)),
+    ("nb", clock24(":", ",")),
+    ("nl", clock24(":", ",")),
+    ("pl", clock24(":", ",")),
+    ("pt", clock24(":", ",")),
+    ("ru", clock24(":", ",")),
+    ("sv", clock24(":", ",")),
+    ("tr", clock24(":", ",")),
+    ("zh", clock24(":", ".")),
+    ("zh_HK", clock12("上午", "下午", true)),
+    ("zh_TW", clock12("上午", "下午", true)),
+];
+
+impl Locale {
+    /// The locale of times, from `LC_ALL`, `LC_TIME` or `LANG`, whichever is set first
+    pub fn from_env(context: &Context) -> &'static Locale {
+        let name = ["LC_ALL", "LC_TIME", "LANG"]
+            .iter()
+            .filter_map(|var| context.get_env(var))
+            .find(|value| !value.is_empty());
+        match name {
+            Some(name) => Locale::find(&name),
+            None => &DEFAULT,
+        }
+    }
+
+    /// The locale of a name like `de_DE.UTF-8` or `fi_FI@euro`, falling back to the
+    /// locale of its language
+    pub fn find(name: &str) -> &'static Locale {
+        let name = name.split(|c| ['.', '@'].contains(&c)).next().unwrap_or("");
+        let language = name.split('_').next().unwrap_or("");
+        let locale = LOCALES
+            .iter()
+            .find(|(locale, _)| *locale == name)
+            .or_else(|| LOCALES.iter().find(|(locale, _)| *locale == language));
+        match locale {
+            Some((_, locale)) => locale,
+            None => &DEFAULT,
+        }
+    }
+
+    /// Write a time of day, with hours, minutes and seconds
+    pub fn format_time<T: Timelike>(&self, time: &T) -> String {
+        let sep = self.time_separator;
+        if !self.hour12 {
+            return format!(
+                "{:02}{sep}{:02}{sep}{:02}",
+                time.hour(),
+                time.minute(),
+                time.second(),
+                sep = sep
+            );
+        }
+
+        let (is_pm, hour) = time.hour12();
+        let marker = if is_pm { self.pm } else { self.am };
+        let clock = format!(
+            "{:02}{sep}{:02}{sep}{:02}",
+            hour,
+            time.minute(),
+            time.second(),
+            sep = sep
+        );
+        if self.am_pm_first {
+            format!("{} {}", marker, clock)
+        } else {
+            format!("{} {}", clock, marker)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveTime;
+
+    #[test]
+    fn find_locales() {
+        assert_eq!(Locale::find("en_US.UTF-8"), &clock12("AM", "PM", false));
+        assert_eq!(Locale::find("en_GB.UTF-8"), &clock24(":", "."));
+        assert_eq!(Locale::find("fi_FI@euro"), &clock24(".", ","));
+        assert_eq!(Locale::find("C"), &DEFAULT);
+        assert_eq!(Locale::find(""), &DEFAULT);
+    }
+
+    #[test]
+    fn format_times() {
+        let time = NaiveTime::from_hms(15, 36, 47);
+        assert_eq!(Locale::find("en_US").format_time(&time), "03:36:47 PM");
+        assert_eq!(Locale::find("de_DE").format_time(&time), "15:36:47");
+        assert_eq!(Locale::find("da_DK").format_time(&time), "15.36.47");
+        assert_eq!(Locale::find("ko_KR").format_time(&time), "오후 03:36:47");
+        assert_eq!(
+            Locale::find("es_MX").format_time(&NaiveTime::from_hms(0, 5, 0)),
+            "12:05:00 a. m."
+        );
+    }
+}
@@ -1,5 +1,11 @@
+#[cfg(feature = "battery")]
+pub mod battery;
+pub mod condition;
 pub mod directory;
 pub mod java_version_parser;
+pub mod locale;
+pub mod redact;
+pub mod wasm;
 
 #[cfg(test)]
 pub mod test;
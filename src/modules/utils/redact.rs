@@ -0,0 +1,48 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::configs::redact::Redact;
+
+/// The number of graphemes kept by `Redact::Truncate`
+const TRUNCATED_LENGTH: usize = 4;
+
+const MASK: &str = "****";
+
+/// Hide a value the way the module is configured to, without giving away its length
+pub fn redact(value: &str, redact: Redact) -> String {
+    match redact {
+        Redact::None => value.to_owned(),
+        Redact::Hash => format!("{:08x}", fnv1a(value.as_bytes())),
+        Redact::Truncate => {
+            let mut graphemes = value.graphemes(true);
+            let kept: String = graphemes.by_ref().take(TRUNCATED_LENGTH).collect();
+            match graphemes.next() {
+                Some(_) => format!("{}…", kept),
+                None => kept,
+            }
+        }
+        Redact::Mask => MASK.to_owned(),
+    }
+}
+
+/// The 32-bit FNV-1a hash, which unlike the hasher of the standard library doesn't
+/// change between releases, so that a value is always shown with the same hash
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ u32::from(*byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redact_values() {
+        assert_eq!(redact("secret-token", Redact::None), "secret-token");
+        assert_eq!(redact("secret-token", Redact::Mask), "****");
+        assert_eq!(redact("secret-token", Redact::Truncate), "secr…");
+        assert_eq!(redact("abc", Redact::Truncate), "abc");
+        assert_eq!(redact("", Redact::Hash), "811c9dc5");
+        assert_eq!(redact("a", Redact::Hash), "e40c292c");
+    }
+}
@@ -1,5 +1,4 @@
-use crate::config::StarshipConfig;
-use crate::context::{Context, Shell};
+use crate::test::ModuleRenderer;
 use std::path::Path;
 
 /// Render a specific starship module by name
@@ -8,9 +7,10 @@ pub fn render_module(
     path: &Path,
     config: Option<toml::Value>,
 ) -> Option<String> {
-    let mut context = Context::new_with_dir(clap::ArgMatches::default(), path);
-    context.config = StarshipConfig { config };
-    context.shell = Shell::Unknown;
-
-    crate::print::get_module(module_name, context)
+    let renderer = ModuleRenderer::new(module_name).path(path);
+    match config {
+        Some(config) => renderer.config(config),
+        None => renderer,
+    }
+    .collect()
 }
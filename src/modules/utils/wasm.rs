@@ -0,0 +1,370 @@
+//! Runs WebAssembly plugins in a sandbox. A plugin can only compute its output from its
+//! input, unless it's given capabilities: it can't open files, start processes or use
+//! the network by itself, and it's stopped once it runs too long or uses too much
+//! memory.
+//!
+//! A plugin exports its `memory`, an `alloc(len: i32) -> i32` function that reserves
+//! `len` bytes of it for the input, and a `render(ptr: i32, len: i32) -> i64` function
+//! that is passed the input and returns where its output is in memory, as the pointer
+//! in the upper 32 bits and the length in the lower ones. It may import these functions
+//! from the `starship` module:
+//!
+//! - `log(ptr: i32, len: i32)` logs a message at the debug level
+//! - `read_file(path_ptr: i32, path_len: i32, buf_ptr: i32, buf_len: i32) -> i32` reads
+//!   a file into a buffer, as far as it fits, and returns the length of the file. With
+//!   the `read_files` capability, it reads files inside the current directory, otherwise
+//!   or if the file can't be read, it returns -1.
+
+use std::convert::TryFrom;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use wasmi::{
+    Caller, Config, Engine, Extern, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder,
+    TrapCode,
+};
+
+/// The fuel a plugin is given, which is spent about one unit per instruction, so that it
+/// takes no more than a fraction of a second
+const MAX_FUEL: u64 = 100_000_000;
+
+/// The memory a plugin may use, in bytes
+const MAX_MEMORY: usize = 16 * 1024 * 1024;
+
+/// What a plugin may do besides computing its output
+#[derive(Debug, Default)]
+pub struct Capabilities {
+    /// The directory the plugin may read files in, if any
+    pub read_dir: Option<PathBuf>,
+}
+
+struct State {
+    name: String,
+    capabilities: Capabilities,
+    limits: StoreLimits,
+}
+
+/// Runs the `render` function of a plugin with an input, returning its output
+pub fn run(
+    name: &str,
+    wasm: &[u8],
+    input: &[u8],
+    capabilities: Capabilities,
+) -> Result<Vec<u8>, String> {
+    let mut config = Config::default();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config);
+    let module = Module::new(&engine, wasm).map_err(|error| error.to_string())?;
+
+    let state = State {
+        name: name.to_owned(),
+        capabilities,
+        limits: StoreLimitsBuilder::new()
+            .memory_size(MAX_MEMORY)
+            .instances(1)
+            .build(),
+    };
+    let mut store = Store::new(&engine, state);
+    store.limiter(|state| &mut state.limits);
+    store
+        .set_fuel(MAX_FUEL)
+        .map_err(|error| error.to_string())?;
+
+    let mut linker = <Linker<State>>::new(&engine);
+    linker
+        .func_wrap("starship", "log", log)
+        .and_then(|linker| linker.func_wrap("starship", "read_file", read_file))
+        .map_err(|error| error.to_string())?;
+    let instance = linker
+        .instantiate_and_start(&mut store, &module)
+        .map_err(|error| error.to_string())?;
+
+    let memory = instance
+        .get_memory(&store, "memory")
+        .ok_or_else(|| String::from("expected an exported `memory`"))?;
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&store, "alloc")
+        .map_err(|error| format!("expected an exported `alloc`: {}", error))?;
+    let render = instance
+        .get_typed_func::<(i32, i32), i64>(&store, "render")
+        .map_err(|error| format!("expected an exported `render`: {}", error))?;
+
+    let input_len = i32::try_from(input.len()).map_err(|error| error.to_string())?;
+    let input_ptr = alloc.call(&mut store, input_len).map_err(trap_message)?;
+    memory
+        .write(&mut store, input_ptr as u32 as usize, input)
+        .map_err(|error| error.to_string())?;
+    let output = render
+        .call(&mut store, (input_ptr, input_len))
+        .map_err(trap_message)?;
+
+    let output = memory_slice(memory.data(&store), (output >> 32) as i32, output as i32)
+        .ok_or_else(|| String::from("the output is outside of the memory of the plugin"))?;
+    Ok(output.to_vec())
+}
+
+/// The bytes at `ptr..ptr + len` of the memory of a plugin, if they're all inside it.
+/// The pointer and the length come from the plugin, so nothing is allocated for them
+/// before they're checked.
+fn memory_slice(data: &[u8], ptr: i32, len: i32) -> Option<&[u8]> {
+    let start = ptr as u32 as usize;
+    let end = start.checked_add(len as u32 as usize)?;
+    data.get(start..end)
+}
+
+fn trap_message(error: wasmi::Error) -> String {
+    match error.as_trap_code() {
+        Some(TrapCode::OutOfFuel) => String::from("the plugin ran for too long"),
+        _ => error.to_string(),
+    }
+}
+
+fn log(caller: Caller<'_, State>, ptr: i32, len: i32) {
+    let memory = match memory(&caller) {
+        Some(memory) => memory,
+        None => return,
+    };
+    if let Some(message) = memory_slice(memory.data(&caller), ptr, len) {
+        log::debug!(
+            "The plugin {} logged: {}",
+            caller.data().name,
+            String::from_utf8_lossy(message)
+        );
+    }
+}
+
+fn read_file(
+    mut caller: Caller<'_, State>,
+    path_ptr: i32,
+    path_len: i32,
+    buf_ptr: i32,
+    buf_len: i32,
+) -> i32 {
+    let memory = match memory(&caller) {
+        Some(memory) => memory,
+        None => return -1,
+    };
+    let data = memory.data(&caller);
+    let path = match memory_slice(data, path_ptr, path_len) {
+        Some(path) => String::from_utf8_lossy(path).into_owned(),
+        None => return -1,
+    };
+    // The file is read only as far as it fits in the buffer, which is in the memory
+    let buf_len = match memory_slice(data, buf_ptr, buf_len) {
+        Some(buffer) => buffer.len(),
+        None => return -1,
+    };
+    let contents = match caller.data().capabilities.read_dir.as_ref() {
+        Some(dir) => read_inside(dir, Path::new(&path), buf_len),
+        None => {
+            log::warn!(
+                "The plugin {} isn't allowed to read {:?}, as `read_files` isn't set",
+                caller.data().name,
+                path
+            );
+            None
+        }
+    };
+    let (contents, file_len) = match contents {
+        Some(contents) => contents,
+        None => return -1,
+    };
+
+    if memory
+        .write(&mut caller, buf_ptr as u32 as usize, &contents)
+        .is_err()
+    {
+        return -1;
+    }
+    i32::try_from(file_len).unwrap_or(-1)
+}
+
+fn memory(caller: &Caller<'_, State>) -> Option<Memory> {
+    caller.get_export("memory").and_then(Extern::into_memory)
+}
+
+/// Reads up to `max_len` bytes of a file, along with its length, unless it's outside a
+/// directory, also by a symlink or `..`
+fn read_inside(dir: &Path, path: &Path, max_len: usize) -> Option<(Vec<u8>, u64)> {
+    let dir = fs::canonicalize(dir).ok()?;
+    let path = fs::canonicalize(dir.join(path)).ok()?;
+    if !path.starts_with(&dir) {
+        log::warn!("A plugin isn't allowed to read {:?}", path);
+        return None;
+    }
+    let file = File::open(path).ok()?;
+    let file_len = file.metadata().ok()?.len();
+    let mut contents = Vec::new();
+    file.take(max_len as u64).read_to_end(&mut contents).ok()?;
+    Some((contents, file_len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A plugin that prints its input, and the first 64 bytes of `file.txt` after it
+    const ECHO_PLUGIN: &str = r#"
+        (module
+            (import "starship" "read_file" (func $read_file (param i32 i32 i32 i32) (result i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 0) "file.txt")
+            (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+            (func (export "render") (param $ptr i32) (param $len i32) (result i64)
+                (local $read i32)
+                (local.set $read
+                    (call $read_file
+                        (i32.const 0) (i32.const 8)
+                        (i32.add (local.get $ptr) (local.get $len)) (i32.const 64)))
+                (if (i32.gt_s (local.get $read) (i32.const 64))
+                    (then (local.set $read (i32.const 64))))
+                (if (i32.gt_s (local.get $read) (i32.const 0))
+                    (then (local.set $len (i32.add (local.get $len) (local.get $read)))))
+                (i64.or
+                    (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+                    (i64.extend_i32_u (local.get $len))))
+        )
+    "#;
+
+    #[test]
+    fn run_plugin() {
+        let wasm = wat::parse_str(ECHO_PLUGIN).unwrap();
+        let output = run("echo", &wasm, b"input", Capabilities::default());
+        assert_eq!(output, Ok(b"input".to_vec()));
+    }
+
+    #[test]
+    fn read_files_with_capability() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("file.txt"), " and file")?;
+        let wasm = wat::parse_str(ECHO_PLUGIN).unwrap();
+
+        let capabilities = Capabilities {
+            read_dir: Some(dir.path().to_path_buf()),
+        };
+        let output = run("echo", &wasm, b"input", capabilities);
+        assert_eq!(output, Ok(b"input and file".to_vec()));
+
+        let output = run("echo", &wasm, b"input", Capabilities::default());
+        assert_eq!(output, Ok(b"input".to_vec()));
+        dir.close()
+    }
+
+    #[test]
+    fn read_files_only_inside_dir() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let inside = dir.path().join("inside");
+        fs::create_dir(&inside)?;
+        fs::write(inside.join("file.txt"), "a")?;
+        fs::write(dir.path().join("secret.txt"), "b")?;
+
+        assert_eq!(
+            read_inside(&inside, Path::new("file.txt"), 64),
+            Some((b"a".to_vec(), 1))
+        );
+        assert_eq!(read_inside(&inside, Path::new("../secret.txt"), 64), None);
+        assert_eq!(
+            read_inside(&inside, &dir.path().join("secret.txt"), 64),
+            None
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn read_only_what_fits() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("file.txt"), "abcdef")?;
+        assert_eq!(
+            read_inside(dir.path(), Path::new("file.txt"), 2),
+            Some((b"ab".to_vec(), 6))
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn refuse_lengths_outside_memory() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("file.txt"), "file")?;
+        // Logs and reads with lengths of nearly 2GiB, which don't fit in one page of
+        // memory, and outputs nothing if reading failed, or else nearly 4GiB
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "starship" "log" (func $log (param i32 i32)))
+                (import "starship" "read_file"
+                    (func $read_file (param i32 i32 i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (data (i32.const 0) "file.txt")
+                (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+                (func (export "render") (param i32 i32) (result i64)
+                    (call $log (i32.const 0) (i32.const 0x7fffffff))
+                    (if (result i64)
+                        (i32.eq
+                            (call $read_file
+                                (i32.const 0) (i32.const 8)
+                                (i32.const 16) (i32.const 0x7fffffff))
+                            (i32.const -1))
+                        (then (i64.const 0))
+                        (else (i64.const 0xffffffff))))
+            )
+            "#,
+        )
+        .unwrap();
+        let capabilities = Capabilities {
+            read_dir: Some(dir.path().to_path_buf()),
+        };
+        assert_eq!(run("huge", &wasm, b"", capabilities), Ok(Vec::new()));
+
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (memory (export "memory") 1)
+                (func (export "alloc") (param i32) (result i32) (i32.const 0))
+                (func (export "render") (param i32 i32) (result i64)
+                    (i64.const 0x00000010ffffffff))
+            )
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            run("huge", &wasm, b"", Capabilities::default()),
+            Err(String::from(
+                "the output is outside of the memory of the plugin"
+            ))
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn stop_endless_plugin() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (memory (export "memory") 1)
+                (func (export "alloc") (param i32) (result i32) (i32.const 0))
+                (func (export "render") (param i32 i32) (result i64)
+                    (loop $forever (br $forever))
+                    (i64.const 0))
+            )
+            "#,
+        )
+        .unwrap();
+        let output = run("endless", &wasm, b"", Capabilities::default());
+        assert_eq!(output, Err(String::from("the plugin ran for too long")));
+    }
+
+    #[test]
+    fn refuse_unknown_imports() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "wasi_snapshot_preview1" "fd_write"
+                    (func (param i32 i32 i32 i32) (result i32)))
+                (memory (export "memory") 1)
+            )
+            "#,
+        )
+        .unwrap();
+        assert!(run("wasi", &wasm, b"", Capabilities::default()).is_err());
+    }
+}
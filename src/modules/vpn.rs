@@ -0,0 +1,157 @@
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::vpn::VpnConfig;
+
+/// Creates a module showing the connected VPN
+///
+/// Will display the VPN symbol and interface name if any of the following criteria are met:
+///     - `vpn.detect_command` is set and exits successfully with a non-empty output.
+///       The first line of the output is used as the interface name.
+///     - `vpn.detect_command` isn't set and a network interface whose name starts with
+///       one of `vpn.interface_prefixes` is up (e.g. `tun0` or `wg0`)
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("vpn");
+    let config: VpnConfig = VpnConfig::try_load(module.config);
+
+    let interface = match config.detect_command {
+        Some(command) => detect_with_command(context, command)?,
+        None => find_vpn_interface(&list_up_interfaces(), &config.interface_prefixes)?,
+    };
+
+    module.set_style(config.style);
+    module.create_segment("symbol", &config.symbol);
+
+    if config.show_interface {
+        module.create_segment("interface", &config.interface.with_value(&interface));
+    }
+
+    Some(module)
+}
+
+fn detect_with_command(context: &Context, command: &str) -> Option<String> {
+    let mut words = command.split_whitespace();
+    let cmd = words.next()?;
+    let args: Vec<&str> = words.collect();
+
+    let output = context.exec_cmd("vpn", cmd, &args)?;
+    output
+        .stdout
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(String::from)
+}
+
+/// List the names of the network interfaces on the system that are up
+#[cfg(target_os = "linux")]
+fn list_up_interfaces() -> Vec<String> {
+    read_up_interfaces(std::path::Path::new("/sys/class/net"))
+}
+
+/// List the names of the network interfaces on the system that are up
+///
+/// Interfaces can only be listed on Linux, elsewhere `vpn.detect_command` has to be used.
+#[cfg(not(target_os = "linux"))]
+fn list_up_interfaces() -> Vec<String> {
+    Vec::new()
+}
+
+/// List the interfaces in a directory like `/sys/class/net` whose `operstate` is `up`,
+/// or `unknown`, which tunnels that are up usually report as they have no carrier
+#[cfg(target_os = "linux")]
+fn read_up_interfaces(net_dir: &std::path::Path) -> Vec<String> {
+    match std::fs::read_dir(net_dir) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .filter(|entry| {
+                let operstate = std::fs::read_to_string(entry.path().join("operstate"));
+                matches!(
+                    operstate.as_deref().map(str::trim),
+                    Ok("up") | Ok("unknown")
+                )
+            })
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect(),
+        Err(e) => {
+            log::debug!("Unable to list network interfaces: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn find_vpn_interface(interfaces: &[String], prefixes: &[&str]) -> Option<String> {
+    let mut matching = interfaces
+        .iter()
+        .filter(|name| prefixes.iter().any(|prefix| name.starts_with(prefix)))
+        .collect::<Vec<&String>>();
+    // The order of the directory listing isn't stable, so sort to always pick the same one
+    matching.sort();
+    matching.first().map(|name| (*name).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interfaces(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| (*name).to_string()).collect()
+    }
+
+    #[test]
+    fn no_vpn_interface() {
+        let interfaces = interfaces(&["lo", "eth0", "wlan0", "docker0"]);
+        assert_eq!(find_vpn_interface(&interfaces, &["tun", "wg"]), None);
+    }
+
+    #[test]
+    fn wireguard_interface() {
+        let interfaces = interfaces(&["lo", "wg0", "eth0"]);
+        assert_eq!(
+            find_vpn_interface(&interfaces, &["tun", "wg"]),
+            Some("wg0".to_string())
+        );
+    }
+
+    #[test]
+    fn first_interface_in_sorted_order() {
+        let interfaces = interfaces(&["tun1", "lo", "tun0"]);
+        assert_eq!(
+            find_vpn_interface(&interfaces, &["tun"]),
+            Some("tun0".to_string())
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn down_interface_not_listed() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        for (name, operstate) in &[("tun0", "down"), ("wg0", "unknown"), ("eth0", "up")] {
+            std::fs::create_dir(dir.path().join(name))?;
+            std::fs::write(
+                dir.path().join(name).join("operstate"),
+                format!("{}\n", operstate),
+            )?;
+        }
+
+        let mut interfaces = read_up_interfaces(dir.path());
+        interfaces.sort();
+        assert_eq!(interfaces, vec!["eth0", "wg0"]);
+        assert_eq!(
+            find_vpn_interface(&interfaces, &["tun", "wg"]),
+            Some("wg0".to_string())
+        );
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn detect_command_output() {
+        let context = &Context::new_with_dir(clap::ArgMatches::default(), ".");
+        assert_eq!(
+            detect_with_command(context, "echo corp-vpn"),
+            Some("corp-vpn".to_string())
+        );
+        assert_eq!(detect_with_command(context, "false"), None);
+        assert_eq!(detect_with_command(context, "true"), None);
+    }
+}
@@ -1,29 +1,26 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::zig::ZigConfig;
-use crate::utils;
 
 /// Creates a module with the current Zig version
 ///
 /// Will display the Zig version if any of the following criteria are met:
 ///     - The current directory contains a file with extension `.zig`
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let is_zig_project = context
+    let reason = context
         .try_begin_scan()?
         .set_extensions(&["zig"])
-        .is_match();
+        .find_match()?;
 
-    if !is_zig_project {
-        return None;
-    }
-
-    let zig_version_output = utils::exec_cmd("zig", &["version"])?
+    let zig_version_output = context
+        .exec_cmd("zig", "zig", &["version"])?
         .stdout
         .trim()
         .to_string();
     let zig_version = format!("v{}", zig_version_output);
 
     let mut module = context.new_module("zig");
+    module.set_reason(reason);
     let config = ZigConfig::try_load(module.config);
 
     module.set_style(config.style);
@@ -0,0 +1,222 @@
+use ansi_term::{Color, Style};
+
+use crate::config::parse_color_string;
+use crate::module::Module;
+use crate::segment::Segment;
+
+/// Returns the segments of a module, painted on the background color set with the
+/// module's `bg` option. The foreground is replaced with black or white, whichever
+/// is more readable on that background.
+///
+/// Modules without a valid `bg` option are returned as-is.
+pub fn module_to_segments(module: &Module) -> Vec<Segment> {
+    let mut segments = module.to_segments();
+    let background = match get_module_background(module) {
+        Some(background) => background,
+        None => return segments,
+    };
+    let foreground = contrasting_color(background);
+
+    for segment in &mut segments {
+        let style = segment.style.unwrap_or_default();
+        segment.set_style(style.fg(foreground).on(background));
+    }
+    segments
+}
+
+fn get_module_background(module: &Module) -> Option<Color> {
+    let bg = module.config?.as_table()?.get("bg")?.as_str()?;
+    let color = parse_color_string(&bg.to_lowercase());
+    if color.is_none() {
+        log::warn!(
+            "Invalid background color \"{}\" for module \"{}\"",
+            bg,
+            module.get_name()
+        );
+    }
+    color
+}
+
+/// Inserts a separator wherever the background color changes between two
+/// consecutive segments, drawn in the previous background on top of the next one.
+pub fn insert_separators(segments: Vec<Segment>, separator: &str) -> Vec<Segment> {
+    let mut result = Vec::with_capacity(segments.len());
+    let mut previous_background = None;
+
+    for segment in segments {
+        if segment.value.is_empty() {
+            result.push(segment);
+            continue;
+        }
+
+        let background = segment.style.and_then(|style| style.background);
+        if background != previous_background {
+            if let Some(previous) = previous_background {
+                result.push(separator_segment(separator, previous, background));
+            }
+        }
+        previous_background = if segment.value.ends_with('\n') {
+            None
+        } else {
+            background
+        };
+        result.push(segment);
+    }
+
+    if let Some(previous) = previous_background {
+        result.push(separator_segment(separator, previous, None));
+    }
+    result
+}
+
+fn separator_segment(separator: &str, previous: Color, next: Option<Color>) -> Segment {
+    let style = match next {
+        Some(next) => Style::new().fg(previous).on(next),
+        None => Style::new().fg(previous),
+    };
+
+    let mut segment = Segment::new("_separator");
+    segment.set_style(style);
+    segment.set_value(separator);
+    segment
+}
+
+/// Picks black or white, depending on the perceived brightness of `background`
+fn contrasting_color(background: Color) -> Color {
+    let (r, g, b) = color_to_rgb(background);
+    let luminance = 299 * u32::from(r) + 587 * u32::from(g) + 114 * u32::from(b);
+
+    if luminance > 128_000 {
+        Color::Black
+    } else {
+        Color::White
+    }
+}
+
+/// Approximates the RGB value of a color, using the xterm defaults for the
+/// standard and 256-color palettes
+fn color_to_rgb(color: Color) -> (u8, u8, u8) {
+    const STANDARD_COLORS: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (0, 0, 238),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+    const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    let index = match color {
+        Color::RGB(r, g, b) => return (r, g, b),
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Purple => 5,
+        Color::Cyan => 6,
+        Color::White => 7,
+        Color::Fixed(index) => index,
+    };
+
+    match index {
+        0..=15 => STANDARD_COLORS[usize::from(index)],
+        16..=231 => {
+            let index = usize::from(index - 16);
+            (
+                CUBE_LEVELS[index / 36],
+                CUBE_LEVELS[(index / 6) % 6],
+                CUBE_LEVELS[index % 6],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (index - 232);
+            (level, level, level)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(value: &str, style: Option<Style>) -> Segment {
+        let mut segment = Segment::new("test");
+        segment.set_value(value);
+        segment.style = style;
+        segment
+    }
+
+    fn render(segments: &[Segment]) -> Vec<(String, Option<Style>)> {
+        segments
+            .iter()
+            .map(|segment| (segment.value.clone(), segment.style))
+            .collect()
+    }
+
+    #[test]
+    fn separators_between_backgrounds() {
+        let blue = Style::new().on(Color::Blue);
+        let red = Style::new().on(Color::Red);
+        let segments = vec![
+            segment("a", Some(blue)),
+            segment("b", Some(blue)),
+            segment("", None),
+            segment("c", Some(red)),
+        ];
+
+        let expected = vec![
+            ("a".to_owned(), Some(blue)),
+            ("b".to_owned(), Some(blue)),
+            ("".to_owned(), None),
+            (
+                ">".to_owned(),
+                Some(Style::new().fg(Color::Blue).on(Color::Red)),
+            ),
+            ("c".to_owned(), Some(red)),
+            (">".to_owned(), Some(Style::new().fg(Color::Red))),
+        ];
+        assert_eq!(render(&insert_separators(segments, ">")), expected);
+    }
+
+    #[test]
+    fn separators_around_unpainted_segments() {
+        let blue = Style::new().on(Color::Blue);
+        let segments = vec![
+            segment("a", None),
+            segment("b", Some(blue)),
+            segment("\n", None),
+            segment("c", Some(blue)),
+        ];
+
+        let expected = vec![
+            ("a".to_owned(), None),
+            ("b".to_owned(), Some(blue)),
+            (">".to_owned(), Some(Style::new().fg(Color::Blue))),
+            ("\n".to_owned(), None),
+            ("c".to_owned(), Some(blue)),
+            (">".to_owned(), Some(Style::new().fg(Color::Blue))),
+        ];
+        assert_eq!(render(&insert_separators(segments, ">")), expected);
+    }
+
+    #[test]
+    fn contrasting_foreground() {
+        assert_eq!(contrasting_color(Color::Black), Color::White);
+        assert_eq!(contrasting_color(Color::Blue), Color::White);
+        assert_eq!(contrasting_color(Color::Yellow), Color::Black);
+        assert_eq!(contrasting_color(Color::Fixed(231)), Color::Black);
+        assert_eq!(contrasting_color(Color::Fixed(236)), Color::White);
+        assert_eq!(contrasting_color(Color::RGB(163, 190, 140)), Color::Black);
+    }
+}
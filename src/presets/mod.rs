@@ -0,0 +1,62 @@
+/// Complete configurations to start from, as `(name, config)` entries. Every preset
+/// is a TOML file in this directory, embedded in the binary.
+pub const PRESETS: &[(&str, &str)] = &[
+    ("minimal", include_str!("minimal.toml")),
+    ("nerd-font-symbols", include_str!("nerd-font-symbols.toml")),
+    ("plain-text", include_str!("plain-text.toml")),
+    ("powerline", include_str!("powerline.toml")),
+];
+
+/// Get the config of a preset by its name
+pub fn get_preset(name: &str) -> Option<&'static str> {
+    PRESETS
+        .iter()
+        .find(|(preset_name, _)| *preset_name == name)
+        .map(|(_, config)| *config)
+}
+
+/// Prints the config of a preset, or the list of presets if no name is given
+pub fn print_preset(name: Option<&str>) {
+    match name {
+        Some(name) => match get_preset(name) {
+            Some(config) => print!("{}", config),
+            None => {
+                eprintln!(
+                    "Error: Unknown preset {}. Use starship preset to list out all presets.",
+                    name
+                );
+                std::process::exit(1);
+            }
+        },
+        None => {
+            println!("Available presets");
+            println!("-----------------");
+            for (name, _) in PRESETS {
+                println!("{}", name);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StarshipConfig;
+
+    #[test]
+    fn presets_are_valid_configs() {
+        for (name, config) in PRESETS {
+            let config = config
+                .parse::<toml::Value>()
+                .unwrap_or_else(|error| panic!("can't parse preset {}: {}", name, error));
+            let warnings = StarshipConfig::new(Some(config))
+            .check();
+            assert!(
+                warnings.is_empty(),
+                "invalid preset {}: {:?}",
+                name,
+                warnings
+            );
+        }
+    }
+}
@@ -1,26 +1,258 @@
-use ansi_term::ANSIStrings;
+use ansi_term::{ANSIString, ANSIStrings};
 use clap::ArgMatches;
 use rayon::prelude::*;
+use regex::Regex;
+use serde_json::json;
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::env;
 use std::fmt::{self, Debug, Write as FmtWrite};
+use std::fs;
+use std::hash::{Hash, Hasher};
 use std::io::{self, Write};
-use unicode_width::UnicodeWidthChar;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::process;
+use std::time::{Duration, Instant};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
+use crate::cache;
+use crate::config::{self, parse_style_string, style_to_string, SegmentConfig};
+use crate::configs;
 use crate::context::{Context, Shell};
+use crate::formatter::StringFormatter;
 use crate::module::Module;
-use crate::module::ALL_MODULES;
+use crate::module::{module_group, ALL_MODULES};
 use crate::modules;
+use crate::modules::utils::condition;
+use crate::modules::FILL_SEGMENT;
+use crate::powerline;
+use crate::profile;
+use crate::segment::Segment;
+use crate::utils::{file_url, wrap_colorseq_for_shell, wrap_seq_for_shell};
 
 pub fn prompt(args: ArgMatches) {
-    let context = Context::new(args);
+    // Profiles are about the time it takes to render the prompt, which the cache skips
+    let use_cache = !profile::is_enabled();
+    let prompt = panic::catch_unwind(AssertUnwindSafe(|| {
+        render(args.clone(), Context::new(args.clone()), use_cache)
+    }))
+    .unwrap_or_else(|_| fallback_prompt(args));
+
     let stdout = io::stdout();
     let mut handle = stdout.lock();
-    write!(handle, "{}", get_prompt(context)).unwrap();
+    write!(handle, "{}", prompt).unwrap();
+}
+
+/// The prompt shown if rendering the prompt panicked, which is the directory and the
+/// character, or else `> ` if those panic too. No right prompt is shown.
+fn fallback_prompt(args: ArgMatches) -> String {
+    if args.is_present("right") {
+        return String::new();
+    }
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        render_format(&Context::new(args), "$directory$character")
+    }))
+    .ok()
+    .flatten()
+    .unwrap_or_else(|| String::from("> "))
+}
+
+/// Render the prompt selected by the arguments of `starship prompt`, with the environment
+/// and directory of the shell a session renders it for
+pub fn render_prompt(args: ArgMatches, env: HashMap<String, String>, cwd: PathBuf) -> String {
+    let context = Context::new_with_env(args.clone(), env, cwd);
+    render(args, context, false)
+}
+
+/// Render the prompt selected by the arguments of `starship prompt`, where the left
+/// prompt may come from the cache if `use_cache` is set. The prompt is then rendered
+/// again in the background with the arguments of this process, which a session
+/// doesn't share with the prompts it renders.
+fn render(args: ArgMatches, context: Context, use_cache: bool) -> String {
+    let _palette = context.config.use_palette();
+    let right = args.is_present("right");
+    let continuation = args.is_present("continuation");
+    let transient = args.is_present("transient");
+    let json = args.value_of("format") == Some("json");
+    let modules = args.value_of("modules").map(modules_format);
+    let force_color = args.is_present("force_color");
+    let color = color_enabled(&context, force_color, is_stdout_tty());
+    config::set_color(color);
+    config::set_truecolor(truecolor_enabled(&context));
+    if let Some(format) = modules {
+        get_modules_prompt(&context, &format, json)
+    } else if json {
+        get_prompt_json(&context, right, continuation, transient)
+    } else if transient {
+        get_transient_prompt(context, right)
+    } else if right {
+        get_right_prompt(context)
+    } else if continuation {
+        get_continuation_prompt(context)
+    } else if use_cache {
+        // The prompt rendered in the background isn't shown in a terminal, which would
+        // turn its styles off unless they are forced
+        get_cached_prompt(context, color && !force_color)
+    } else {
+        get_prompt(context)
+    }
+}
+
+/// Set in the environment of the starship that renders a cached prompt again
+const PROMPT_REFRESH_VAR: &str = "STARSHIP_PROMPT_REFRESH";
+
+/// Show the prompt last rendered for the same directory, commit, arguments and
+/// configuration, if it's younger than `prompt_cache_ttl`, and render it again in the
+/// background for the next prompt. Otherwise the prompt is rendered and cached.
+fn get_cached_prompt(context: Context, force_refresh_color: bool) -> String {
+    let ttl = Duration::from_secs(context.config.get_root_config().prompt_cache_ttl);
+    let dir = match cache::cache_dir().filter(|_| ttl > Duration::from_secs(0)) {
+        Some(dir) => dir,
+        None => return get_prompt(context),
+    };
+    let key = prompt_cache_key(&context);
+
+    if context.get_env(PROMPT_REFRESH_VAR).is_none() {
+        if let Some(prompt) = cache::get_prompt(&dir, &key, ttl) {
+            log::trace!("Using the cached prompt");
+            refresh_prompt(force_refresh_color);
+            return prompt;
+        }
+    }
+
+    let prompt = get_prompt(context);
+    cache::set_prompt(&dir, &key, &prompt);
+    prompt
+}
+
+/// Environment variables left out of the key of a cached prompt, as no module shows them
+/// and they change even when the prompt doesn't. The configuration is part of the key
+/// itself, wherever it was read from.
+const PROMPT_CACHE_IGNORED_ENV: &[&str] = &[PROMPT_REFRESH_VAR, "OLDPWD", "STARSHIP_CONFIG"];
+
+/// Identifies a prompt in the cache. The duration of the last command is left out, as
+/// it's different every time, so a cached prompt may show the one of an earlier command.
+/// The environment is part of it, as are the versions of the kubeconfig files, which
+/// modules read without them being in the directory or the repository.
+fn prompt_cache_key(context: &Context) -> String {
+    let mut properties: Vec<_> = context
+        .properties
+        .iter()
+        .filter(|(name, _)| **name != "cmd_duration")
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect();
+    properties.sort();
+
+    let mut hasher = DefaultHasher::new();
+    if let Some(config) = &context.config.config {
+        config.to_string().hash(&mut hasher);
+    }
+
+    let mut env = context.get_env_vars();
+    env.retain(|(key, _)| !PROMPT_CACHE_IGNORED_ENV.contains(&key.as_str()));
+    env.sort();
+    env.hash(&mut hasher);
+
+    for path in modules::kubernetes::config_paths(context) {
+        let metadata = fs::metadata(&path).ok();
+        let modified = metadata
+            .as_ref()
+            .and_then(|metadata| metadata.modified().ok());
+        (path, modified, metadata.map(|metadata| metadata.len())).hash(&mut hasher);
+    }
+
+    let head = context
+        .get_repo()
+        .ok()
+        .and_then(|repo| repo.head.as_deref());
+    format!(
+        "{}\n{}\n{}\n{:016x}",
+        context.current_dir.to_string_lossy(),
+        head.unwrap_or_default(),
+        properties.join("\n"),
+        hasher.finish()
+    )
+}
+
+/// Start starship again with the arguments of this process, to render the prompt and
+/// store it in the cache without waiting for it
+fn refresh_prompt(force_color: bool) {
+    let mut command = match env::current_exe() {
+        Ok(exe) => process::Command::new(exe),
+        Err(error) => {
+            log::debug!("Unable to refresh the cached prompt: {}", error);
+            return;
+        }
+    };
+    command
+        .args(env::args_os().skip(1))
+        .env(PROMPT_REFRESH_VAR, "1")
+        .stdin(process::Stdio::null())
+        .stdout(process::Stdio::null())
+        .stderr(process::Stdio::null());
+    if force_color {
+        command.arg("--force-color");
+    }
+    if let Err(error) = command.spawn() {
+        log::debug!("Unable to refresh the cached prompt: {}", error);
+    }
+}
+
+/// Whether the prompt is styled. Setting `NO_COLOR`, a dumb terminal, or output that
+/// isn't a terminal while starship wasn't started by a shell turn the styles off,
+/// unless colors are forced with `--force-color` or `force_color`.
+fn color_enabled(context: &Context, force_color: bool, is_tty: bool) -> bool {
+    if force_color || context.config.get_root_config().force_color {
+        return true;
+    }
+    let no_color = matches!(context.get_env("NO_COLOR"), Some(value) if !value.is_empty());
+    let dumb_terminal = context.get_env("TERM").as_deref() == Some("dumb");
+    let redirected = !is_tty && context.shell == Shell::Unknown;
+    !(no_color || dumb_terminal || redirected)
+}
+
+/// Whether RGB colors are shown as they are, which they are if `truecolor` is turned on,
+/// or else if the terminal advertises support for them through `COLORTERM`
+fn truecolor_enabled(context: &Context) -> bool {
+    context
+        .config
+        .get_root_config()
+        .truecolor
+        .unwrap_or_else(|| {
+            matches!(
+                context.get_env("COLORTERM").as_deref(),
+                Some("truecolor") | Some("24bit")
+            )
+        })
+}
+
+fn is_stdout_tty() -> bool {
+    atty::is(atty::Stream::Stdout)
 }
 
 pub fn get_prompt(context: Context) -> String {
+    let _palette = context.config.use_palette();
     let config = context.config.get_root_config();
     let mut buf = String::new();
 
+    // Mark the start of the prompt for terminals supporting semantic prompts
+    buf.push_str(&semantic_prompt_marker("A", context.shell));
+
+    if config.report_cwd {
+        buf.push_str(&cwd_report(&context));
+    }
+
+    if config.iterm2_integration {
+        buf.push_str(&iterm2_marks(&context));
+    }
+
+    if let Some(title) = get_window_title(&context, config.window_title) {
+        let title = format!("\x1b]0;{}\x07", title);
+        buf.push_str(&wrap_seq_for_shell(title, context.shell, '\x1b', '\x07'));
+    }
+
     // Write a new line before the prompt
     if config.add_newline {
         writeln!(buf).unwrap();
@@ -32,39 +264,568 @@ pub fn get_prompt(context: Context) -> String {
         buf.push_str("\x1b[J"); // An ASCII control code to clear screen
     }
 
-    let modules = compute_modules(&context);
+    let format = get_prompt_format(&context);
+    match render_format(&context, &format) {
+        Some(prompt) => buf.push_str(&prompt),
+        None => buf.push('>'),
+    }
 
-    let mut print_without_prefix = true;
-    let printable = modules.iter();
+    // Mark the end of the prompt, which is where the user starts typing the command
+    buf.push_str(&semantic_prompt_marker("B", context.shell));
 
-    for module in printable {
-        // Skip printing the prefix of a module after the line_break
-        if print_without_prefix {
-            let module_without_prefix = module.to_string_without_prefix(context.shell.clone());
-            write!(buf, "{}", module_without_prefix).unwrap()
-        } else {
-            let module = module.ansi_strings_for_shell(context.shell.clone());
-            write!(buf, "{}", ANSIStrings(&module)).unwrap();
+    buf
+}
+
+/// Renders the prompt selected like for `get_prompt` and the others as a JSON array of
+/// its segments, with their text, style and module, for other programs to display.
+/// Unlike the prompt itself, it contains no escape sequences or new line before it.
+fn get_prompt_json(context: &Context, right: bool, continuation: bool, transient: bool) -> String {
+    let config = context.config.get_root_config();
+    let format = match (transient, right, continuation) {
+        (true, true, _) => Cow::Borrowed(config.transient_right_format),
+        (true, false, _) => Cow::Borrowed(config.transient_format),
+        (false, true, _) => Cow::Borrowed(config.right_format),
+        (false, false, true) => Cow::Borrowed(config.continuation_format),
+        (false, false, false) => get_prompt_format(context),
+    };
+    segments_to_json(context, &format)
+}
+
+/// Renders only the modules of a format of `--modules`, without the decoration of a
+/// prompt like the new line before it, for editors and status lines to display
+fn get_modules_prompt(context: &Context, format: &str, json: bool) -> String {
+    if json {
+        segments_to_json(context, format)
+    } else {
+        render_format(context, format).unwrap_or_default()
+    }
+}
+
+/// The format rendering the modules of a comma-separated list, one after another
+fn modules_format(modules: &str) -> String {
+    modules
+        .split(',')
+        .map(str::trim)
+        .filter(|module| !module.is_empty())
+        .map(|module| format!("${{{}}}", module))
+        .collect()
+}
+
+/// The segments of a format string as a JSON array, leaving out the empty ones
+fn segments_to_json(context: &Context, format: &str) -> String {
+    let segments = render_segments(context, format)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|segment| !segment.value.is_empty())
+        .map(|segment| {
+            json!({
+                "text": segment.value,
+                "style": segment.style.map(style_to_string).unwrap_or_default(),
+                "module": segment.module,
+            })
+        })
+        .collect::<Vec<serde_json::Value>>();
+    serde_json::Value::Array(segments).to_string()
+}
+
+/// Returns an OSC 133 (FinalTerm) escape sequence, which lets terminals tell the prompt,
+/// the command and its output apart. The markers around the command output are printed
+/// by the init scripts, so nothing is returned if starship wasn't started from one.
+fn semantic_prompt_marker(marker: &str, shell: Shell) -> String {
+    if let Shell::Unknown = shell {
+        return String::new();
+    }
+    wrap_seq_for_shell(format!("\x1b]133;{}\x07", marker), shell, '\x1b', '\x07')
+}
+
+/// Returns an OSC 7 escape sequence reporting the current directory to the terminal,
+/// which lets it open new tabs and splits in the same directory.
+fn cwd_report(context: &Context) -> String {
+    let report = format!("\x1b]7;{}\x07", file_url(&context.current_dir));
+    wrap_seq_for_shell(report, context.shell, '\x1b', '\x07')
+}
+
+/// Returns the escape sequences of iTerm2's shell integration, which report the user,
+/// the host and the current directory. The command marks are the OSC 133 sequences,
+/// which iTerm2 understands as well.
+fn iterm2_marks(context: &Context) -> String {
+    let shell = match context.shell {
+        Shell::Bash => "bash",
+        Shell::Elvish => "elvish",
+        Shell::Fish => "fish",
+        Shell::Zsh => "zsh",
+        _ => "unknown",
+    };
+    let user = context.get_env("USER").unwrap_or_default();
+    let hostname = gethostname::gethostname();
+    let marks = format!(
+        "\x1b]1337;ShellIntegrationVersion=5;shell={}\x07\
+         \x1b]1337;RemoteHost={}@{}\x07\
+         \x1b]1337;CurrentDir={}\x07",
+        shell,
+        user,
+        hostname.to_string_lossy(),
+        context.current_dir.to_string_lossy(),
+    );
+    wrap_seq_for_shell(marks, context.shell, '\x1b', '\x07')
+}
+
+/// Renders `window_title` as plain text, to be set as the title of the terminal.
+/// Returns `None` if no title is configured.
+fn get_window_title(context: &Context, format: &str) -> Option<String> {
+    if format.is_empty() {
+        return None;
+    }
+    let formatter = match StringFormatter::new(format) {
+        Ok(formatter) => formatter,
+        Err(error) => {
+            log::error!("Error parsing `window_title`:\n{}", error);
+            return None;
         }
+    };
+    let module_list = formatter.get_variables();
+    let formatter = formatter.map_variables_to_segments(|variable| {
+        let segments = handle_module(variable, context, &module_list)
+            .iter()
+            .flat_map(Module::to_segments)
+            .collect::<Vec<Segment>>();
+        Some(segments)
+    });
+
+    // Titles can't contain line breaks or escape sequences
+    let title = skip_prefixes_at_line_start(formatter.parse(None))
+        .iter()
+        .filter(|segment| segment._name != FILL_SEGMENT)
+        .flat_map(|segment| segment.value.chars())
+        .map(|c| if c == '\n' { ' ' } else { c })
+        .filter(|c| !c.is_control())
+        .collect::<String>();
+    Some(title.trim().to_owned())
+}
+
+/// Renders the right prompt from `right_format`, which is empty unless configured.
+pub fn get_right_prompt(context: Context) -> String {
+    let config = context.config.get_root_config();
+    render_format(&context, config.right_format).unwrap_or_default()
+}
 
-        print_without_prefix = module.get_name() == "line_break"
+/// Renders the prompt that replaces the previous prompt once a command has been accepted,
+/// from `transient_format` or `transient_right_format`. Both are empty unless configured,
+/// which leaves previous prompts as they are.
+pub fn get_transient_prompt(context: Context, right: bool) -> String {
+    let config = context.config.get_root_config();
+    let format = if right {
+        config.transient_right_format
+    } else {
+        config.transient_format
+    };
+    render_format(&context, format).unwrap_or_default()
+}
+
+/// Renders the prompt shown while a command spans multiple lines from `continuation_format`.
+pub fn get_continuation_prompt(context: Context) -> String {
+    let config = context.config.get_root_config();
+    render_format(&context, config.continuation_format).unwrap_or_else(|| String::from("> "))
+}
+
+/// Renders a format string with the output of the modules it references,
+/// or returns `None` if the format string is invalid.
+fn render_format(context: &Context, format: &str) -> Option<String> {
+    let _palette = context.config.use_palette();
+    let segments = render_segments(context, format)?;
+    Some(segments_to_string(&segments, context.shell))
+}
+
+/// Returns the segments of a format string filled in with the modules it references,
+/// fit to the terminal, or `None` if the format string is invalid.
+fn render_segments(context: &Context, format: &str) -> Option<Vec<Segment>> {
+    let config = context.config.get_root_config();
+    let module_list = match StringFormatter::new(format) {
+        Ok(formatter) => formatter.get_variables(),
+        Err(error) => {
+            log::error!("Error parsing format `{}`:\n{}", format, error);
+            return None;
+        }
+    };
+
+    // Modules are only computed once, even if the prompt is rendered again to fit the terminal
+    let modules = module_list
+        .par_iter()
+        .map(|variable| (variable, handle_module(variable, context, &module_list)))
+        .collect::<Vec<(&String, Vec<Module>)>>();
+    let mut priorities = HashMap::new();
+    let mut module_segments = HashMap::new();
+    for (variable, modules) in &modules {
+        for module in modules {
+            if let Some(priority) = get_module_priority(module) {
+                priorities.insert(module.get_name().as_str(), priority);
+            }
+        }
+        let segments = modules
+            .iter()
+            .flat_map(|module| {
+                if config.powerline {
+                    powerline::module_to_segments(module)
+                } else {
+                    module.to_segments()
+                }
+            })
+            .collect::<Vec<Segment>>();
+        module_segments.insert(variable.as_str(), segments);
     }
 
-    buf
+    let terminal_width = context.get_terminal_width();
+    let mut omitted = OmittedModules::default();
+    let segments = loop {
+        let mut formatter = StringFormatter::new(format).ok()?;
+        formatter = formatter.map_variables_to_segments(|variable| {
+            let segments = module_segments
+                .get(variable)?
+                .iter()
+                .filter(|segment| !omitted.is_omitted(segment))
+                .cloned()
+                .collect::<Vec<Segment>>();
+
+            if segments.is_empty() {
+                None
+            } else {
+                Some(segments)
+            }
+        });
+
+        let mut segments = skip_prefixes_at_line_start(formatter.parse(None));
+        if config.powerline {
+            segments = powerline::insert_separators(segments, config.powerline_separator);
+        }
+        if !omitted.omit_next(&segments, &priorities, terminal_width) {
+            break segments;
+        }
+    };
+
+    Some(expand_fills(segments, terminal_width))
+}
+
+/// Paints the segments, wrapping those with a hyperlink in OSC 8 escape sequences.
+/// Consecutive segments with the same hyperlink share a single link.
+fn segments_to_string(segments: &[Segment], shell: Shell) -> String {
+    let mut result = String::new();
+    let mut start = 0;
+
+    while start < segments.len() {
+        let hyperlink = &segments[start].hyperlink;
+        let end = start
+            + segments[start..]
+                .iter()
+                .take_while(|segment| &segment.hyperlink == hyperlink)
+                .count();
+        let ansi_strings = segments[start..end]
+            .iter()
+            .map(Segment::ansi_string)
+            .collect::<Vec<ANSIString>>();
+        let painted = wrap_colorseq_for_shell(ANSIStrings(&ansi_strings).to_string(), shell);
+
+        match hyperlink {
+            Some(url) => {
+                let link_start = format!("\x1b]8;;{}\x07", url);
+                let link_end = String::from("\x1b]8;;\x07");
+                result.push_str(&wrap_seq_for_shell(link_start, shell, '\x1b', '\x07'));
+                result.push_str(&painted);
+                result.push_str(&wrap_seq_for_shell(link_end, shell, '\x1b', '\x07'));
+            }
+            None => result.push_str(&painted),
+        }
+        start = end;
+    }
+    result
+}
+
+/// Returns the `priority` option of a module. Only modules with a priority are
+/// shortened or left out when the prompt doesn't fit the terminal.
+fn get_module_priority(module: &Module) -> Option<i64> {
+    module.config?.as_table()?.get("priority")?.as_integer()
+}
+
+/// The modules that are shortened or left out for the prompt to fit the terminal
+#[derive(Default)]
+struct OmittedModules {
+    /// Modules that are shown without their prefix
+    shortened: HashSet<String>,
+    /// Modules that aren't shown at all
+    dropped: HashSet<String>,
+}
+
+impl OmittedModules {
+    fn is_omitted(&self, segment: &Segment) -> bool {
+        match &segment.module {
+            Some(module) => {
+                self.dropped.contains(module)
+                    || (segment._name == "_prefix" && self.shortened.contains(module))
+            }
+            None => false,
+        }
+    }
+
+    /// Shortens or drops the module with the lowest priority on the first line that
+    /// is wider than the terminal. A module is shortened by removing its prefix, and
+    /// only dropped if it has no prefix left.
+    ///
+    /// Returns false if every line fits, or if there is nothing left to omit.
+    fn omit_next(
+        &mut self,
+        segments: &[Segment],
+        priorities: &HashMap<&str, i64>,
+        terminal_width: usize,
+    ) -> bool {
+        let mut line_width = 0;
+        let mut line_modules: Vec<&str> = Vec::new();
+        let mut prefixed_modules: HashSet<&str> = HashSet::new();
+
+        let mut lines = Vec::new();
+        for segment in segments {
+            if let Some(module) = &segment.module {
+                if !line_modules.contains(&module.as_str()) {
+                    line_modules.push(module);
+                }
+                if segment._name == "_prefix" && !segment.value.is_empty() {
+                    prefixed_modules.insert(module);
+                }
+            }
+            if segment._name == FILL_SEGMENT {
+                continue;
+            }
+
+            let value = &segment.value;
+            match (value.find('\n'), value.rfind('\n')) {
+                (Some(first), Some(last)) => {
+                    line_width += UnicodeWidthStr::width(&value[..first]);
+                    lines.push((line_width, std::mem::take(&mut line_modules)));
+                    line_width = UnicodeWidthStr::width(&value[last + 1..]);
+                }
+                _ => line_width += UnicodeWidthStr::width(value.as_str()),
+            }
+        }
+        lines.push((line_width, line_modules));
+
+        for (width, modules) in lines {
+            if width <= terminal_width {
+                continue;
+            }
+
+            // On equal priorities, modules further to the right are omitted first
+            let candidate = modules
+                .iter()
+                .rev()
+                .filter_map(|module| Some((*module, *priorities.get(module)?)))
+                .min_by_key(|(_, priority)| *priority);
+            if let Some((module, _)) = candidate {
+                if prefixed_modules.contains(module) {
+                    self.shortened.insert(module.to_owned());
+                } else {
+                    self.dropped.insert(module.to_owned());
+                }
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Returns the format of the prompt, joining its lines if it's given as an array.
+fn get_prompt_format<'a>(context: &'a Context) -> Cow<'a, str> {
+    let config = context.config.get_root_config();
+    let format_config = context.config.get_module_config("format");
+
+    // Multi-line prompts can be configured with one format string per line
+    if let Some(lines) = format_config.and_then(toml::Value::as_array) {
+        match lines
+            .iter()
+            .map(toml::Value::as_str)
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(lines) => return Cow::Owned(lines.join("\n")),
+            None => log::warn!("Every line of `format` has to be a string"),
+        }
+    }
+
+    Cow::Borrowed(config.format)
+}
+
+/// Removes the prefix of every module that doesn't follow any other output on
+/// its line, i.e. the first module of the prompt and the first module after a line break.
+fn skip_prefixes_at_line_start(segments: Vec<Segment>) -> Vec<Segment> {
+    let mut at_line_start = true;
+
+    segments
+        .into_iter()
+        .filter(|segment| {
+            if segment._name == "_prefix" && at_line_start {
+                return false;
+            }
+            if !segment.value.is_empty() {
+                at_line_start = segment.value.ends_with('\n');
+            }
+            true
+        })
+        .collect()
+}
+
+/// Expands the segments of the `fill` module, so that each line takes up the full
+/// width of the terminal. Multiple fills on the same line share the remaining space.
+fn expand_fills(mut segments: Vec<Segment>, terminal_width: usize) -> Vec<Segment> {
+    let mut line_width = 0;
+    let mut fills = Vec::new();
+
+    for index in 0..segments.len() {
+        if segments[index]._name == FILL_SEGMENT {
+            fills.push(index);
+            continue;
+        }
+
+        let value = &segments[index].value;
+        match (value.find('\n'), value.rfind('\n')) {
+            (Some(first), Some(last)) => {
+                line_width += UnicodeWidthStr::width(&value[..first]);
+                let next_line_width = UnicodeWidthStr::width(&value[last + 1..]);
+                fill_line(
+                    &mut segments,
+                    &fills,
+                    terminal_width.saturating_sub(line_width),
+                );
+                fills.clear();
+                line_width = next_line_width;
+            }
+            _ => line_width += UnicodeWidthStr::width(value.as_str()),
+        }
+    }
+    fill_line(
+        &mut segments,
+        &fills,
+        terminal_width.saturating_sub(line_width),
+    );
+
+    segments
+}
+
+fn fill_line(segments: &mut [Segment], fills: &[usize], remaining_width: usize) {
+    if fills.is_empty() {
+        return;
+    }
+
+    let width = remaining_width / fills.len();
+    for (i, index) in fills.iter().enumerate() {
+        // The first fills take up the columns that can't be shared evenly
+        let width = if i < remaining_width % fills.len() {
+            width + 1
+        } else {
+            width
+        };
+        let value = repeat_to_width(&segments[*index].value, width);
+        segments[*index].set_value(value);
+    }
+}
+
+/// Repeats `symbol` for as long as it fits in `width` columns, padding the rest with spaces
+fn repeat_to_width(symbol: &str, width: usize) -> String {
+    let mut result = String::new();
+    let mut result_width = 0;
+
+    let symbol_width = UnicodeWidthStr::width(symbol);
+    if symbol_width > 0 {
+        while result_width + symbol_width <= width {
+            result.push_str(symbol);
+            result_width += symbol_width;
+        }
+    }
+    result.push_str(&" ".repeat(width - result_width));
+    result
 }
 
 pub fn module(module_name: &str, args: ArgMatches) {
+    let force_color = args.is_present("force_color");
     let context = Context::new(args);
+    config::set_color(color_enabled(&context, force_color, is_stdout_tty()));
+    config::set_truecolor(truecolor_enabled(&context));
     let module = get_module(module_name, context).unwrap_or_default();
     print!("{}", module);
 }
 
 pub fn get_module(module_name: &str, context: Context) -> Option<String> {
-    modules::handle(module_name, &context).map(|m| m.to_string())
+    let _palette = context.config.use_palette();
+    if let Some(custom_module) = module_name.strip_prefix("custom.") {
+        if context
+            .config
+            .get_custom_module_config(custom_module)
+            .is_none()
+        {
+            eprintln!("Error: Unknown custom module {}. Use starship module --list to list out all supported modules.", module_name);
+            return None;
+        }
+        return modules::custom::module(custom_module, &context)
+            .or_else(|| timeout_placeholder(module_name, &context))
+            .map(|m| finish_module(m, &context).to_string());
+    }
+    // Custom modules check their own `when`
+    if !condition::is_met(module_name, &context) {
+        return None;
+    }
+    if let Some(name) = module_name.strip_prefix("plugin.") {
+        let (_, path) = match modules::plugin::discover(&context)
+            .into_iter()
+            .find(|(plugin, _)| plugin == name)
+        {
+            Some(plugin) => plugin,
+            None => {
+                eprintln!("Error: Unknown plugin {}. Use starship module --list to list out all supported modules.", module_name);
+                return None;
+            }
+        };
+        return modules::plugin::module(name, &path, &context)
+            .or_else(|| timeout_placeholder(module_name, &context))
+            .map(|m| finish_module(m, &context).to_string());
+    }
+    if let Some(name) = module_name.strip_prefix("env_var.") {
+        return modules::env_var::instance(name, &context)
+            .map(|m| finish_module(m, &context).to_string());
+    }
+    modules::handle(module_name, &context)
+        .or_else(|| timeout_placeholder(module_name, &context))
+        .map(|m| finish_module(m, &context).to_string())
+}
+
+/// Prints the names of all modules, including the custom modules in the configuration
+pub fn list_modules(args: ArgMatches) {
+    let context = Context::new(args);
+
+    println!("Supported modules list");
+    println!("----------------------");
+    for module in get_module_names(&context) {
+        println!("{}", module);
+    }
+}
+
+/// The names of all modules, followed by the registered modules that aren't built in,
+/// the custom modules in the configuration and the plugins that were found
+pub fn get_module_names(context: &Context) -> Vec<String> {
+    let mut names: Vec<String> = ALL_MODULES.iter().map(|name| (*name).to_owned()).collect();
+    names.extend(modules::registered_names());
+    if let Some(custom_modules) = context.config.get_custom_modules() {
+        names.extend(
+            custom_modules
+                .keys()
+                .map(|custom_module| format!("custom.{}", custom_module)),
+        );
+    }
+    names.extend(
+        modules::plugin::discover(context)
+            .into_iter()
+            .map(|(name, _)| format!("plugin.{}", name)),
+    );
+    names
 }
 
 pub fn explain(args: ArgMatches) {
     let context = Context::new(args);
+    let _palette = context.config.use_palette();
 
     struct ModuleInfo {
         value: String,
@@ -74,7 +835,8 @@ pub fn explain(args: ArgMatches) {
 
     let dont_print = vec!["line_break", "character"];
 
-    let modules = compute_modules(&context)
+    let module_list = get_prompt_module_list(&context);
+    let modules = compute_modules(&context, &module_list)
         .into_iter()
         .filter(|module| !dont_print.contains(&module.get_name().as_str()))
         .map(|module| {
@@ -83,7 +845,12 @@ pub fn explain(args: ArgMatches) {
             ModuleInfo {
                 value: ansi_term::ANSIStrings(&ansi_strings[1..ansi_strings.len() - 1]).to_string(),
                 value_len: value.chars().count() + count_wide_chars(&value),
-                desc: module.get_description().to_owned(),
+                desc: match module.get_reason() {
+                    Some(reason) => {
+                        format!("{} (shown because {})", module.get_description(), reason)
+                    }
+                    None => module.get_description().to_owned(),
+                },
             }
         })
         .collect::<Vec<ModuleInfo>>();
@@ -131,12 +898,322 @@ pub fn explain(args: ArgMatches) {
     }
 }
 
-fn compute_modules<'a>(context: &'a Context) -> Vec<Module<'a>> {
-    enum Mod<'a> {
-        Builtin(&'a str),
-        Custom(&'a str),
+/// Renders the modules of the prompt once and prints how long each of them took,
+/// slowest first.
+pub fn timings(args: ArgMatches) {
+    let context = Context::new(args);
+    let _palette = context.config.use_palette();
+
+    struct ModuleTiming {
+        name: String,
+        value: String,
+        duration: Duration,
+    }
+
+    let module_list = get_prompt_module_list(&context);
+    let start = Instant::now();
+    let mut timings = module_list
+        .par_iter()
+        .map(|variable| {
+            let start = Instant::now();
+            let modules = handle_module(variable, &context, &module_list);
+            let duration = start.elapsed();
+            let value = modules
+                .iter()
+                .map(|module| module.get_segments().join(""))
+                .collect::<String>();
+            ModuleTiming {
+                name: variable.to_owned(),
+                value: value.replace('\n', " ").trim().to_owned(),
+                duration,
+            }
+        })
+        .collect::<Vec<ModuleTiming>>();
+    let total = start.elapsed();
+    timings.sort_by_key(|timing| std::cmp::Reverse(timing.duration));
+
+    let name_width = timings
+        .iter()
+        .map(|timing| timing.name.width())
+        .max()
+        .unwrap_or(0);
+    let durations = timings
+        .iter()
+        .map(|timing| format_timing(timing.duration))
+        .collect::<Vec<String>>();
+    let duration_width = durations.iter().map(String::len).max().unwrap_or(0);
+
+    println!("\n Here are the timings of the modules in your prompt:");
+    for (timing, duration) in timings.iter().zip(&durations) {
+        println!(
+            " {:name_width$}  -  {:>duration_width$}  -  \"{}\"",
+            timing.name,
+            duration,
+            timing.value,
+            name_width = name_width,
+            duration_width = duration_width,
+        );
+    }
+    println!("\n Total: {}", format_timing(total));
+}
+
+/// Formats a duration in milliseconds, or as `<1ms` for faster modules
+fn format_timing(duration: Duration) -> String {
+    match duration.as_millis() {
+        0 => String::from("<1ms"),
+        millis => format!("{}ms", millis),
+    }
+}
+
+/// Renders the modules of the prompt, or a single one, a number of times and prints the
+/// latencies of each of them, slowest first. The latencies can be saved to a file, and
+/// compared with the ones saved earlier.
+pub fn benchmark(args: ArgMatches) {
+    let runs = match args.value_of("runs").unwrap_or("10").parse::<u32>() {
+        Ok(runs) if runs > 0 => runs,
+        _ => {
+            eprintln!("Error: The number of runs has to be a positive number");
+            std::process::exit(1);
+        }
+    };
+    let baseline = args.value_of_os("baseline").map(|path| {
+        let baseline = fs::read_to_string(path)
+            .map_err(|error| error.to_string())
+            .and_then(|baseline| {
+                serde_json::from_str::<serde_json::Value>(&baseline).map_err(|e| e.to_string())
+            });
+        baseline.unwrap_or_else(|error| {
+            eprintln!(
+                "Error: Unable to read the baseline {}: {}",
+                path.to_string_lossy(),
+                error
+            );
+            std::process::exit(1);
+        })
+    });
+
+    let module = args.value_of("module").map(str::to_owned);
+    if let Some(module) = &module {
+        let context = Context::new(args.clone());
+        if !get_module_names(&context).contains(module) {
+            eprintln!("Error: Unknown module {}. Use starship module --list to list out all supported modules.", module);
+            std::process::exit(1);
+        }
     }
 
+    let mut durations: Vec<(String, Vec<Duration>)> = Vec::new();
+    let mut totals = Vec::new();
+    for _ in 0..runs {
+        // Every run starts from scratch, like a prompt does
+        let context = Context::new(args.clone());
+        let module_list = match &module {
+            Some(module) => vec![module.clone()],
+            None => get_prompt_module_list(&context),
+        };
+        let start = Instant::now();
+        let timings = module_list
+            .par_iter()
+            .map(|variable| {
+                let start = Instant::now();
+                handle_module(variable, &context, &module_list);
+                (variable, start.elapsed())
+            })
+            .collect::<Vec<(&String, Duration)>>();
+        totals.push(start.elapsed());
+
+        for (name, duration) in timings {
+            match durations.iter_mut().find(|(module, _)| module == name) {
+                Some((_, module_durations)) => module_durations.push(duration),
+                None => durations.push((name.clone(), vec![duration])),
+            }
+        }
+    }
+
+    let mut latencies = durations
+        .iter()
+        .map(|(name, durations)| (name.as_str(), Latencies::new(durations)))
+        .collect::<Vec<_>>();
+    latencies.sort_by_key(|(_, latencies)| std::cmp::Reverse(latencies.mean));
+    let total = Latencies::new(&totals);
+
+    if let Some(path) = args.value_of_os("save") {
+        let modules: serde_json::Map<String, serde_json::Value> = latencies
+            .iter()
+            .map(|(name, latencies)| ((*name).to_owned(), latencies.to_json()))
+            .collect();
+        let saved = json!({ "runs": runs, "modules": modules, "total": total.to_json() });
+        if let Err(error) = fs::write(path, format!("{:#}\n", saved)) {
+            eprintln!(
+                "Error: Unable to save the latencies to {}: {}",
+                path.to_string_lossy(),
+                error
+            );
+            std::process::exit(1);
+        }
+    }
+
+    let mut rows = vec![vec![
+        String::from("Module"),
+        String::from("Min"),
+        String::from("Mean"),
+        String::from("P95"),
+    ]];
+    if baseline.is_some() {
+        rows[0].push(String::from("Baseline"));
+    }
+    let all_latencies = latencies
+        .iter()
+        .map(|(name, latencies)| {
+            let saved = baseline
+                .as_ref()
+                .map(|baseline| &baseline["modules"][*name]);
+            (*name, latencies, saved)
+        })
+        // The total is the one of the single module
+        .chain(
+            Some((
+                "Total",
+                &total,
+                baseline.as_ref().map(|baseline| &baseline["total"]),
+            ))
+            .filter(|_| module.is_none()),
+        );
+    for (name, latencies, saved) in all_latencies {
+        let mut row = vec![
+            name.to_owned(),
+            format_latency(latencies.min),
+            format_latency(latencies.mean),
+            format_latency(latencies.p95),
+        ];
+        if let Some(saved) = saved {
+            let change = saved["mean"].as_f64().map(|saved| {
+                format_latency_change(latencies.mean, Duration::from_secs_f64(saved / 1000.0))
+            });
+            row.push(change.unwrap_or_else(|| String::from("new")));
+        }
+        rows.push(row);
+    }
+
+    let widths = (0..rows[0].len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].width())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<usize>>();
+    match &module {
+        Some(module) => println!(
+            "\n Here are the latencies of {} over {} runs:\n",
+            module, runs
+        ),
+        None => println!(
+            "\n Here are the latencies of the modules in your prompt over {} runs:\n",
+            runs
+        ),
+    }
+    for row in rows {
+        let cells = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(column, (cell, width))| match column {
+                0 => format!("{:width$}", cell, width = width),
+                _ => format!("{:>width$}", cell, width = width),
+            })
+            .collect::<Vec<String>>();
+        println!(" {}", cells.join("  "));
+    }
+}
+
+/// The latencies of a module over the runs of a benchmark
+#[derive(Debug, PartialEq)]
+struct Latencies {
+    min: Duration,
+    mean: Duration,
+    p95: Duration,
+}
+
+impl Latencies {
+    /// Summarizes the durations of the runs, of which there is at least one
+    fn new(durations: &[Duration]) -> Self {
+        let mut durations = durations.to_vec();
+        durations.sort();
+        let total: Duration = durations.iter().sum();
+        // The smallest duration that at least 95% of the runs didn't exceed
+        let rank = (durations.len() * 95).div_ceil(100);
+        Latencies {
+            min: durations[0],
+            mean: total / durations.len() as u32,
+            p95: durations[rank - 1],
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let millis = |duration: Duration| duration.as_micros() as f64 / 1000.0;
+        json!({
+            "min": millis(self.min),
+            "mean": millis(self.mean),
+            "p95": millis(self.p95),
+        })
+    }
+}
+
+/// Formats a latency in milliseconds, to a tenth of a millisecond
+fn format_latency(duration: Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// Formats how much slower or faster a latency is than the one of the baseline, also
+/// relatively unless the baseline is too fast for it to mean anything
+fn format_latency_change(latency: Duration, baseline: Duration) -> String {
+    let baseline = baseline.as_secs_f64() * 1000.0;
+    let change = latency.as_secs_f64() * 1000.0 - baseline;
+    if baseline < 1.0 {
+        format!("{:+.1}ms", change)
+    } else {
+        format!("{:+.1}ms ({:+.0}%)", change, change / baseline * 100.0)
+    }
+}
+
+/// Returns the variables referenced by the prompt and the right prompt, without duplicates
+fn get_prompt_module_list(context: &Context) -> Vec<String> {
+    let format = get_prompt_format(context);
+    let right_format = context.config.get_root_config().right_format;
+    let mut module_list = Vec::new();
+    for format in &[format.as_ref(), right_format] {
+        match StringFormatter::new(format) {
+            Ok(formatter) => {
+                for module in formatter.get_variables() {
+                    if !module_list.contains(&module) {
+                        module_list.push(module);
+                    }
+                }
+            }
+            Err(error) => log::error!("Error parsing format `{}`:\n{}", format, error),
+        }
+    }
+    module_list
+}
+
+fn compute_modules<'a>(context: &'a Context, modules: &'a [String]) -> Vec<Module<'a>> {
+    modules
+        .par_iter()
+        .map(|module| handle_module(module, context, modules))
+        .flatten()
+        .collect::<Vec<Module<'a>>>()
+}
+
+/// Computes the module(s) a variable of the prompt format refers to.
+///
+/// `$custom` expands to all custom modules that aren't explicitly part of the format, and
+/// the variable of a group, like `$vcs`, to the modules of the group that aren't either.
+/// `$all` expands to the modules of the default format that aren't part of the format.
+fn handle_module<'a>(
+    module: &'a str,
+    context: &'a Context,
+    module_list: &[String],
+) -> Vec<Module<'a>> {
     struct DebugCustomModules<'tmp>(&'tmp toml::value::Table);
 
     impl Debug for DebugCustomModules<'_> {
@@ -145,72 +1222,283 @@ fn compute_modules<'a>(context: &'a Context) -> Vec<Module<'a>> {
         }
     }
 
-    let mut prompt_order: Vec<Mod> = Vec::new();
+    let _span = profile::span("module", module);
+    // Modules are computed on the threads of rayon, which parse their styles too
+    let _palette = context.config.use_palette();
+    let mut modules: Vec<Option<Module>> = Vec::new();
 
-    // Write out a custom prompt order
-    let config_prompt_order = context.config.get_root_config().prompt_order;
+    if context.skip_async {
+        let config = context.config.get_root_config();
+        if config.async_modules.contains(&module) && !context.is_module_disabled_in_config(module) {
+            // Rendered by a later invocation in the background instead
+            return placeholder_module(
+                module,
+                context,
+                "rendered asynchronously",
+                config.async_placeholder,
+            )
+            .into_iter()
+            .collect();
+        }
+    }
 
-    for module in &config_prompt_order {
-        if ALL_MODULES.contains(module) {
-            // Write out a module if it isn't disabled
-            if !context.is_module_disabled_in_config(*module) {
-                prompt_order.push(Mod::Builtin(module));
-            }
-        } else if *module == "custom" {
-            // Write out all custom modules, except for those that are explicitly set
-            if let Some(custom_modules) = context.config.get_custom_modules() {
-                for (custom_module, config) in custom_modules {
-                    if should_add_implicit_custom_module(
-                        custom_module,
-                        config,
-                        &config_prompt_order,
-                    ) {
-                        prompt_order.push(Mod::Custom(custom_module));
-                    }
+    if modules::is_registered(module) {
+        // Write out a module if it isn't disabled, and its `when` condition holds
+        if !context.is_module_disabled_in_config(module) && condition::is_met(module, context) {
+            modules.push(
+                modules::handle(module, context).or_else(|| timeout_placeholder(module, context)),
+            );
+            // Followed by the variables of its own tables that aren't explicitly set
+            if module == "env_var" {
+                if let Some(config) = context.config.get_module_config("env_var") {
+                    let instances = configs::env_var::instances(config)
+                        .into_iter()
+                        .filter(|(name, _)| {
+                            let explicit_name = format!("env_var.{}", name);
+                            !module_list.contains(&explicit_name)
+                                && condition::is_met(&explicit_name, context)
+                        })
+                        .map(|(name, _)| modules::env_var::instance(name, context));
+                    modules.extend(instances);
                 }
             }
-        } else if module.starts_with("custom.") {
-            // Write out a custom module if it isn't disabled (and it exists...)
-            match context.is_custom_module_disabled_in_config(&module[7..]) {
-                Some(true) => (), // Module is disabled, we don't add it to the prompt
-                Some(false) => prompt_order.push(Mod::Custom(&module[7..])),
-                None => match context.config.get_custom_modules() {
-                    Some(modules) => log::debug!(
-                        "prompt_order contains custom module \"{}\", but no configuration was provided. Configuration for the following modules were provided: {:?}",
-                        module,
-                        DebugCustomModules(modules),
-                    ),
-                    None => log::debug!(
-                        "prompt_order contains custom module \"{}\", but no configuration was provided.",
-                        module,
-                    ),
-                },
+        }
+    } else if let Some(name) = module.strip_prefix("env_var.") {
+        if !context.is_module_disabled_in_config("env_var") && condition::is_met(module, context) {
+            modules.push(modules::env_var::instance(name, context));
+        }
+    } else if module == "plugin" {
+        // Write out all plugins, except for those that are explicitly set
+        let plugins = modules::plugin::discover(context)
+            .into_par_iter()
+            .filter(|(name, _)| {
+                let explicit_name = format!("plugin.{}", name);
+                !module_list.contains(&explicit_name) && condition::is_met(&explicit_name, context)
+            })
+            .map(|(name, path)| {
+                let _palette = context.config.use_palette();
+                modules::plugin::module(&name, &path, context)
+                    .or_else(|| timeout_placeholder(&format!("plugin.{}", name), context))
+            })
+            .collect::<Vec<Option<Module<'a>>>>();
+        modules.extend(plugins);
+    } else if let Some(name) = module.strip_prefix("plugin.") {
+        let plugin = modules::plugin::discover(context)
+            .into_iter()
+            .find(|(plugin, _)| plugin == name);
+        match plugin {
+            Some(_) if !condition::is_met(module, context) => (),
+            Some((_, path)) => modules.push(
+                modules::plugin::module(name, &path, context)
+                    .or_else(|| timeout_placeholder(module, context)),
+            ),
+            None => log::debug!("format contains plugin \"{}\", but it wasn't found", name),
+        }
+    } else if module == "custom" {
+        // Write out all custom modules, except for those that are explicitly set.
+        // They usually run a command each, so they are computed in parallel too.
+        if let Some(custom_modules) = context.config.get_custom_modules() {
+            let custom_modules = custom_modules
+                .iter()
+                .filter(|(custom_module, config)| {
+                    should_add_implicit_custom_module(custom_module, config, module_list)
+                })
+                .map(|(custom_module, _)| custom_module.as_str())
+                .collect::<Vec<&str>>()
+                .into_par_iter()
+                .map(|custom_module| {
+                    let _palette = context.config.use_palette();
+                    modules::custom::module(custom_module, context).or_else(|| {
+                        timeout_placeholder(&format!("custom.{}", custom_module), context)
+                    })
+                })
+                .collect::<Vec<Option<Module<'a>>>>();
+            modules.extend(custom_modules);
+        }
+    } else if let Some(custom_module) = module.strip_prefix("custom.") {
+        // Write out a custom module if it isn't disabled (and it exists...)
+        match context.is_custom_module_disabled_in_config(custom_module) {
+            Some(true) => (), // Module is disabled, we don't add it to the prompt
+            Some(false) => modules.push(
+                modules::custom::module(custom_module, context)
+                    .or_else(|| timeout_placeholder(module, context)),
+            ),
+            None => match context.config.get_custom_modules() {
+                Some(modules) => log::debug!(
+                    "format contains custom module \"{}\", but no configuration was provided. Configuration for the following modules were provided: {:?}",
+                    module,
+                    DebugCustomModules(modules),
+                ),
+                None => log::debug!(
+                    "format contains custom module \"{}\", but no configuration was provided.",
+                    module,
+                ),
+            },
+        }
+    } else if let Some(group) = module_group(module) {
+        // Write out the modules of the group, except for those that are explicitly set.
+        // Each of them is finished already.
+        return group
+            .par_iter()
+            .filter(|member| !module_list.iter().any(|name| name == *member))
+            .map(|member| handle_module(member, context, module_list))
+            .flatten()
+            .collect();
+    } else if module == "all" {
+        // Write out the modules of the default format, which is nothing but variables,
+        // that are mentioned nowhere else, in the order of the default format
+        let default_list: Vec<&str> = configs::PROMPT_FORMAT
+            .split('$')
+            .filter(|name| !name.is_empty())
+            .collect();
+        return default_list
+            .par_iter()
+            .filter(|name| !is_mentioned(name, module_list))
+            .map(|name| handle_module(name, context, module_list))
+            .flatten()
+            .collect();
+    } else {
+        log::debug!(
+            "Expected format to contain value from {:?}. Instead received {}",
+            ALL_MODULES,
+            module,
+        );
+    }
+
+    modules
+        .into_iter()
+        .flatten()
+        .map(|module| finish_module(module, context))
+        .collect()
+}
+
+/// Whether a module is part of the format, by itself or as part of a group
+fn is_mentioned(name: &str, module_list: &[String]) -> bool {
+    module_list.iter().any(|variable| {
+        variable == name
+            || module_group(variable)
+                .filter(|group| group.contains(&name))
+                .is_some()
+    })
+}
+
+/// Applies the options every module accepts to a rendered module
+fn finish_module<'a>(module: Module<'a>, context: &Context) -> Module<'a> {
+    with_max_width(with_style_rules(module, context))
+}
+
+/// Restyles a module with the first of its `style_rules` whose pattern matches what it
+/// shows, not counting its prefix, suffix and symbols
+fn with_style_rules<'a>(mut module: Module<'a>, context: &Context) -> Module<'a> {
+    let rules = module.config.and_then(|config| config.get("style_rules"));
+    let rules = match rules.and_then(toml::Value::as_table) {
+        Some(rules) => rules,
+        None => return module,
+    };
+    let regexes = context
+        .style_rules
+        .get_or_init(|| compile_style_rules(context.config.config.as_ref()));
+    let value = module.get_value_segments().concat();
+    let style = rules.iter().find_map(|(pattern, style)| {
+        if !regexes.get(pattern)?.is_match(&value) {
+            return None;
+        }
+        let parsed = style.as_str().and_then(parse_style_string);
+        if parsed.is_none() {
+            log::warn!("Invalid style {} in `style_rules`", style);
+        }
+        parsed
+    });
+    if let Some(style) = style {
+        module.set_segments_style(style);
+    }
+    module
+}
+
+/// The regular expressions of the `style_rules` of every module in a config, by their
+/// pattern, so that each is compiled once. Invalid patterns are left out.
+fn compile_style_rules(config: Option<&toml::Value>) -> HashMap<String, Regex> {
+    fn collect<'v>(table: &'v toml::value::Table, patterns: &mut Vec<&'v str>) {
+        for (key, value) in table {
+            match (key.as_str(), value) {
+                ("style_rules", toml::Value::Table(rules)) => {
+                    patterns.extend(rules.keys().map(String::as_str))
+                }
+                // Custom modules and plugins are tables inside of `custom` and `plugin`
+                (_, toml::Value::Table(table)) => collect(table, patterns),
+                _ => {}
             }
-        } else {
-            log::debug!(
-                "Expected prompt_order to contain value from {:?}. Instead received {}",
-                ALL_MODULES,
-                module,
-            );
         }
     }
 
-    prompt_order
-        .par_iter()
-        .map(|module| match module {
-            Mod::Builtin(builtin) => modules::handle(builtin, context),
-            Mod::Custom(custom) => modules::custom::module(custom, context),
-        }) // Compute segments
-        .flatten() // Remove segments set to `None`
-        .collect::<Vec<Module<'a>>>()
+    let mut patterns = Vec::new();
+    if let Some(table) = config.and_then(toml::Value::as_table) {
+        collect(table, &mut patterns);
+    }
+    patterns
+        .into_iter()
+        .filter_map(|pattern| match Regex::new(pattern) {
+            Ok(regex) => Some((pattern.to_owned(), regex)),
+            Err(error) => {
+                log::warn!("Invalid pattern {:?} in `style_rules`: {}", pattern, error);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Shortens a module to its `max_width` option, ending with `max_width_symbol`
+fn with_max_width(mut module: Module) -> Module {
+    let config = match module.config.and_then(toml::Value::as_table) {
+        Some(config) => config,
+        None => return module,
+    };
+    let max_width = match config.get("max_width").and_then(toml::Value::as_integer) {
+        Some(max_width) if max_width > 0 => max_width as usize,
+        _ => return module,
+    };
+    let symbol = config
+        .get("max_width_symbol")
+        .and_then(toml::Value::as_str)
+        .unwrap_or("…");
+    module.truncate(max_width, symbol);
+    module
+}
+
+/// Stands in for a module that had a command killed for running longer than its timeout,
+/// if `command_timeout_placeholder` is set
+fn timeout_placeholder<'a>(name: &str, context: &'a Context) -> Option<Module<'a>> {
+    if !context.has_timed_out(name) {
+        return None;
+    }
+    let placeholder = context.config.get_root_config().command_timeout_placeholder;
+    placeholder_module(name, context, "a command timed out", placeholder)
+}
+
+/// A module showing only `placeholder`, unless it's empty
+fn placeholder_module<'a>(
+    name: &str,
+    context: &'a Context,
+    reason: &str,
+    placeholder: &'a str,
+) -> Option<Module<'a>> {
+    if placeholder.is_empty() {
+        return None;
+    }
+
+    let mut module = context.new_module(name);
+    module.set_reason(String::from(reason));
+    module.get_prefix().set_value("");
+    module.create_segment("placeholder", &SegmentConfig::new(placeholder));
+    Some(module)
 }
 
 fn should_add_implicit_custom_module(
     custom_module: &str,
     config: &toml::Value,
-    config_prompt_order: &[&str],
+    module_list: &[String],
 ) -> bool {
-    let is_explicitly_specified = config_prompt_order.iter().any(|x| {
+    let is_explicitly_specified = module_list.iter().any(|x| {
         x.len() == 7 + custom_module.len() && &x[..7] == "custom." && &x[7..] == custom_module
     });
 
@@ -231,3 +1519,178 @@ fn should_add_implicit_custom_module(
 fn count_wide_chars(value: &str) -> usize {
     value.chars().filter(|c| c.width().unwrap_or(0) > 1).count()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(env: &[(&str, &str)], shell: Shell) -> Context<'static> {
+        let mut context = Context::new_with_dir(ArgMatches::default(), std::env::temp_dir());
+        context.config = config::StarshipConfig::new(None);
+        context.shell = shell;
+        context.env = Some(
+            env.iter()
+                .map(|(key, value)| ((*key).to_owned(), (*value).to_owned()))
+                .collect::<HashMap<String, String>>(),
+        );
+        context
+    }
+
+    #[test]
+    fn color_enabled_in_terminal() {
+        assert!(color_enabled(&context(&[], Shell::Unknown), false, true));
+        assert!(color_enabled(
+            &context(&[("NO_COLOR", "")], Shell::Bash),
+            false,
+            false
+        ));
+    }
+
+    #[test]
+    fn color_disabled_by_environment() {
+        assert!(!color_enabled(
+            &context(&[("NO_COLOR", "1")], Shell::Zsh),
+            false,
+            true
+        ));
+        assert!(!color_enabled(
+            &context(&[("TERM", "dumb")], Shell::Zsh),
+            false,
+            true
+        ));
+    }
+
+    #[test]
+    fn prompt_cache_key_of_env_and_kubeconfig() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let kubeconfig = dir.path().join("config");
+        fs::write(&kubeconfig, "current-context: a")?;
+        let kubeconfig = kubeconfig.to_string_lossy();
+
+        let key = prompt_cache_key(&context(&[("KUBECONFIG", &kubeconfig)], Shell::Zsh));
+        assert_eq!(
+            key,
+            prompt_cache_key(&context(
+                &[("KUBECONFIG", &kubeconfig), ("OLDPWD", "/tmp")],
+                Shell::Zsh
+            ))
+        );
+        assert_ne!(
+            key,
+            prompt_cache_key(&context(
+                &[("KUBECONFIG", &kubeconfig), ("AWS_PROFILE", "prod")],
+                Shell::Zsh
+            ))
+        );
+
+        fs::write(dir.path().join("config"), "current-context: other")?;
+        assert_ne!(
+            key,
+            prompt_cache_key(&context(&[("KUBECONFIG", &kubeconfig)], Shell::Zsh))
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn color_disabled_when_redirected() {
+        assert!(!color_enabled(&context(&[], Shell::Unknown), false, false));
+    }
+
+    #[test]
+    fn color_forced() {
+        let no_color = context(&[("NO_COLOR", "1"), ("TERM", "dumb")], Shell::Unknown);
+        assert!(color_enabled(&no_color, true, false));
+
+        let mut forced = context(&[("NO_COLOR", "1")], Shell::Unknown);
+        forced.config = config::StarshipConfig::new(Some(toml::toml! { force_color = true }));
+        assert!(color_enabled(&forced, false, false));
+    }
+
+    #[test]
+    fn style_rules_of_every_module() {
+        let config = toml::toml! {
+            [git_branch.style_rules]
+            "^(main|master)$" = "bold red"
+            "(" = "bold"
+
+            [custom.deploy.style_rules]
+            "prod" = "underline"
+        };
+        let regexes = compile_style_rules(Some(&config));
+
+        let mut patterns: Vec<&str> = regexes.keys().map(String::as_str).collect();
+        patterns.sort_unstable();
+        assert_eq!(patterns, ["^(main|master)$", "prod"]);
+        assert!(regexes["^(main|master)$"].is_match("main"));
+    }
+
+    #[test]
+    fn truecolor_from_colorterm_or_config() {
+        assert!(truecolor_enabled(&context(
+            &[("COLORTERM", "truecolor")],
+            Shell::Zsh
+        )));
+        assert!(truecolor_enabled(&context(
+            &[("COLORTERM", "24bit")],
+            Shell::Zsh
+        )));
+        assert!(!truecolor_enabled(&context(&[], Shell::Zsh)));
+
+        let mut turned_off = context(&[("COLORTERM", "truecolor")], Shell::Zsh);
+        turned_off.config = config::StarshipConfig::new(Some(toml::toml! { truecolor = false }));
+        assert!(!truecolor_enabled(&turned_off));
+
+        let mut turned_on = context(&[], Shell::Zsh);
+        turned_on.config = config::StarshipConfig::new(Some(toml::toml! { truecolor = true }));
+        assert!(truecolor_enabled(&turned_on));
+    }
+
+    #[test]
+    fn iterm2_marks_of_user() {
+        let marks = iterm2_marks(&context(&[("USER", "astronaut")], Shell::Unknown));
+        assert!(marks.contains("RemoteHost=astronaut@"));
+    }
+
+    #[test]
+    fn summarize_latencies() {
+        let durations: Vec<Duration> = (1..=20).rev().map(Duration::from_millis).collect();
+        let expected = Latencies {
+            min: Duration::from_millis(1),
+            mean: Duration::from_micros(10_500),
+            p95: Duration::from_millis(19),
+        };
+        assert_eq!(Latencies::new(&durations), expected);
+    }
+
+    #[test]
+    fn summarize_latencies_of_few_runs() {
+        let single = Latencies::new(&[Duration::from_millis(3)]);
+        assert_eq!(single.p95, Duration::from_millis(3));
+
+        // With fewer than 20 runs, the slowest one is the 95th percentile
+        let two = Latencies::new(&[Duration::from_millis(4), Duration::from_millis(2)]);
+        let expected = Latencies {
+            min: Duration::from_millis(2),
+            mean: Duration::from_millis(3),
+            p95: Duration::from_millis(4),
+        };
+        assert_eq!(two, expected);
+    }
+
+    #[test]
+    fn format_latency_changes() {
+        let baseline = Duration::from_millis(20);
+        assert_eq!(
+            format_latency_change(Duration::from_millis(25), baseline),
+            "+5.0ms (+25%)"
+        );
+        assert_eq!(
+            format_latency_change(Duration::from_millis(15), baseline),
+            "-5.0ms (-25%)"
+        );
+        assert_eq!(
+            format_latency_change(Duration::from_millis(1), Duration::from_micros(100)),
+            "+0.9ms"
+        );
+    }
+}
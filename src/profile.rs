@@ -0,0 +1,100 @@
+use once_cell::sync::{Lazy, OnceCell};
+use serde_json::json;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The file the profile is written to, if profiling is enabled
+static PROFILE_PATH: OnceCell<PathBuf> = OnceCell::new();
+
+/// The time profiling was enabled, which the times of the spans are relative to
+static START: Lazy<Instant> = Lazy::new(Instant::now);
+
+/// The spans that ended, as events of the Chrome trace format
+static EVENTS: Lazy<Mutex<Vec<serde_json::Value>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+static NEXT_THREAD_ID: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// Numbers the threads spans happen on, in the order they first record one
+    static THREAD_ID: usize = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Enable profiling if `path` is given, or `$STARSHIP_PROFILE` is set. The profile is
+/// written to `path`, or the path in `$STARSHIP_PROFILE`, or else to a file in the
+/// temporary directory if `$STARSHIP_PROFILE` is `1`.
+pub fn enable(path: Option<&str>) {
+    let path = match (path, env::var_os("STARSHIP_PROFILE")) {
+        (Some(path), _) => PathBuf::from(path),
+        (None, Some(value)) if value == "1" => {
+            env::temp_dir().join(format!("starship-{}.trace.json", std::process::id()))
+        }
+        (None, Some(value)) if !value.is_empty() => PathBuf::from(value),
+        _ => return,
+    };
+    Lazy::force(&START);
+    PROFILE_PATH.set(path).ok();
+}
+
+/// Whether spans are being recorded
+pub fn is_enabled() -> bool {
+    PROFILE_PATH.get().is_some()
+}
+
+/// A span of time starship spent on something, recorded once it's dropped
+pub struct Span {
+    category: &'static str,
+    name: String,
+    start: Instant,
+}
+
+/// Start a span, named after what is being done, in a category like `module` or `command`.
+/// Without profiling, nothing is recorded.
+pub fn span<T: Into<String>>(category: &'static str, name: T) -> Option<Span> {
+    PROFILE_PATH.get()?;
+    Some(Span {
+        category,
+        name: name.into(),
+        start: Instant::now(),
+    })
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let event = json!({
+            "name": self.name,
+            "cat": self.category,
+            "ph": "X",
+            "ts": micros(self.start.duration_since(*START)),
+            "dur": micros(self.start.elapsed()),
+            "pid": std::process::id(),
+            "tid": THREAD_ID.with(|id| *id),
+        });
+        EVENTS.lock().unwrap().push(event);
+    }
+}
+
+/// Write the recorded spans to the profile, as a trace that can be opened with
+/// `chrome://tracing` or https://ui.perfetto.dev
+pub fn write() {
+    let path = match PROFILE_PATH.get() {
+        Some(path) => path,
+        None => return,
+    };
+    let events = std::mem::take(&mut *EVENTS.lock().unwrap());
+    let trace = json!({ "traceEvents": events, "displayTimeUnit": "ms" });
+    if let Err(error) = fs::write(path, trace.to_string()) {
+        log::warn!(
+            "Unable to write the profile to {}: {}",
+            path.to_string_lossy(),
+            error
+        );
+    }
+}
+
+fn micros(duration: Duration) -> u64 {
+    duration.as_micros() as u64
+}
@@ -1,3 +1,4 @@
+use crate::config::display_style;
 use ansi_term::{ANSIString, Style};
 use std::fmt;
 
@@ -14,6 +15,12 @@ pub struct Segment {
 
     /// The string value of the current segment.
     pub value: String,
+
+    /// The name of the module the segment belongs to, if any.
+    pub module: Option<String>,
+
+    /// The URL the segment links to, if the terminal supports hyperlinks.
+    pub hyperlink: Option<String>,
 }
 
 impl Segment {
@@ -23,9 +30,17 @@ impl Segment {
             _name: name.to_string(),
             style: None,
             value: "".to_string(),
+            module: None,
+            hyperlink: None,
         }
     }
 
+    /// Whether the segment is the symbol of its module, like `symbol` or `error_symbol`,
+    /// rather than something it shows.
+    pub fn is_symbol(&self) -> bool {
+        self._name == "symbol" || self._name.ends_with("_symbol")
+    }
+
     /// Sets the style of the segment.
     ///
     /// Accepts either `Color` or `Style`.
@@ -59,7 +74,7 @@ impl Segment {
     // Returns the ANSIString of the segment value, not including its prefix and suffix
     pub fn ansi_string(&self) -> ANSIString {
         match self.style {
-            Some(style) => style.paint(&self.value),
+            Some(style) => display_style(style).paint(&self.value),
             None => ANSIString::from(&self.value),
         }
     }
@@ -0,0 +1,212 @@
+#[cfg(unix)]
+mod unix {
+    use crate::cache;
+    use crate::config::StarshipConfig;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::env;
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::net::Shutdown;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::panic::{self, AssertUnwindSafe};
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    /// The socket of the session, from `$STARSHIP_SESSION` or else `session.sock` in the
+    /// cache directory
+    fn socket_path() -> Option<PathBuf> {
+        match env::var_os("STARSHIP_SESSION") {
+            Some(path) if !path.is_empty() => Some(PathBuf::from(path)),
+            _ => cache::cache_dir().map(|dir| dir.join("session.sock")),
+        }
+    }
+
+    /// The arguments, directory and environment of this invocation of starship, which
+    /// the session renders the prompt with
+    fn request() -> serde_json::Value {
+        let args: Vec<String> = env::args_os()
+            .skip(1)
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        let env: serde_json::Map<String, serde_json::Value> = env::vars_os()
+            .map(|(key, value)| {
+                (
+                    key.to_string_lossy().into_owned(),
+                    json!(value.to_string_lossy()),
+                )
+            })
+            .collect();
+        let cwd = env::current_dir()
+            .map(|dir| dir.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        json!({ "args": args, "env": env, "cwd": cwd })
+    }
+
+    /// Get the prompt rendered by the session, if `$STARSHIP_SESSION` is set and the
+    /// session answers
+    pub fn render() -> Option<String> {
+        env::var_os("STARSHIP_SESSION").filter(|path| !path.is_empty())?;
+        let response = send(&request())?;
+        Some(response["prompt"].as_str()?.to_owned())
+    }
+
+    /// Stop a running session
+    pub fn stop() {
+        if send(&json!({ "stop": true })).is_none() {
+            eprintln!("Error: No starship session is running");
+            std::process::exit(1);
+        }
+    }
+
+    /// How long a request may take on top of the `command_timeout` of the commands run
+    /// for the prompt, before the client renders the prompt itself
+    const RENDER_TIME: Duration = Duration::from_millis(500);
+
+    /// How long the session waits for a client to send its request
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+    fn send(request: &serde_json::Value) -> Option<serde_json::Value> {
+        let mut stream = UnixStream::connect(socket_path()?).ok()?;
+        let command_timeout = StarshipConfig::initialize()
+            .get_root_config()
+            .command_timeout;
+        let timeout = Duration::from_millis(command_timeout) + RENDER_TIME;
+        stream.set_read_timeout(Some(timeout)).ok()?;
+        stream.write_all(request.to_string().as_bytes()).ok()?;
+        stream.shutdown(Shutdown::Write).ok()?;
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).ok()?;
+        serde_json::from_str(&response).ok()
+    }
+
+    /// Serve prompts on the socket of the session until it's stopped. Every request is
+    /// rendered by `render` from the arguments it was invoked with, its environment and
+    /// its directory, which a prompt is rendered with instead of those of the session.
+    /// Requests are handled one after the other.
+    pub fn start(render: impl Fn(Vec<String>, HashMap<String, String>, PathBuf) -> Option<String>) {
+        let path = match socket_path() {
+            Some(path) => path,
+            None => {
+                eprintln!("Error: Unable to find a path for the socket, set $STARSHIP_SESSION");
+                std::process::exit(1);
+            }
+        };
+        if UnixStream::connect(&path).is_ok() {
+            eprintln!(
+                "Error: A starship session is already running on {}",
+                path.to_string_lossy()
+            );
+            std::process::exit(1);
+        }
+        // A socket that can't be connected to is left over from a session that died
+        fs::remove_file(&path).ok();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).ok();
+        }
+        let listener = match UnixListener::bind(&path) {
+            Ok(listener) => listener,
+            Err(error) => {
+                eprintln!(
+                    "Error: Unable to listen on {}: {}",
+                    path.to_string_lossy(),
+                    error
+                );
+                std::process::exit(1);
+            }
+        };
+        log::debug!("Starship session listening on {}", path.to_string_lossy());
+
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(error) => {
+                    log::warn!("Unable to accept a connection: {}", error);
+                    continue;
+                }
+            };
+            // A client that never finishes its request would keep the others waiting
+            if let Err(error) = stream.set_read_timeout(Some(REQUEST_TIMEOUT)) {
+                log::warn!("Unable to set a timeout for a request: {}", error);
+                continue;
+            }
+            let mut request = String::new();
+            if let Err(error) = stream.read_to_string(&mut request) {
+                log::warn!("Unable to read a request: {}", error);
+                continue;
+            }
+            let request: serde_json::Value = match serde_json::from_str(&request) {
+                Ok(request) => request,
+                Err(error) => {
+                    log::warn!("Unable to parse a request: {}", error);
+                    continue;
+                }
+            };
+            if request["stop"].as_bool() == Some(true) {
+                stream.write_all(b"{}").ok();
+                break;
+            }
+
+            let env = request_env(&request);
+            let cwd = PathBuf::from(request["cwd"].as_str().unwrap_or_default());
+            let args = request["args"]
+                .as_array()
+                .map(|args| {
+                    args.iter()
+                        .filter_map(|arg| Some(arg.as_str()?.to_owned()))
+                        .collect()
+                })
+                .unwrap_or_default();
+            // Without an answer the client renders the prompt itself, so a module that
+            // panics doesn't take down the session
+            if let Ok(Some(prompt)) =
+                panic::catch_unwind(AssertUnwindSafe(|| render(args, env, cwd)))
+            {
+                let response = json!({ "prompt": prompt });
+                if let Err(error) = stream.write_all(response.to_string().as_bytes()) {
+                    log::warn!("Unable to answer a request: {}", error);
+                }
+            }
+        }
+
+        fs::remove_file(&path).ok();
+    }
+
+    /// The environment of a request
+    fn request_env(request: &serde_json::Value) -> HashMap<String, String> {
+        request["env"]
+            .as_object()
+            .map(|env| {
+                env.iter()
+                    .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_owned())))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(unix)]
+pub use unix::{render, start, stop};
+
+#[cfg(not(unix))]
+pub fn render() -> Option<String> {
+    None
+}
+
+#[cfg(not(unix))]
+pub fn start(
+    _render: impl Fn(
+        Vec<String>,
+        std::collections::HashMap<String, String>,
+        std::path::PathBuf,
+    ) -> Option<String>,
+) {
+    eprintln!("Error: starship session is only supported on Unix");
+    std::process::exit(1);
+}
+
+#[cfg(not(unix))]
+pub fn stop() {
+    start(|_, _, _| None)
+}
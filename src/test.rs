@@ -0,0 +1,173 @@
+//! Render a single module in a controlled environment, to test what it prints.
+//!
+//! ```
+//! use starship::test::ModuleRenderer;
+//! use starship::CommandOutput;
+//!
+//! let dir = tempfile::tempdir().unwrap();
+//! std::fs::write(dir.path().join("elm.json"), "{}").unwrap();
+//! let actual = ModuleRenderer::new("elm")
+//!     .path(dir.path())
+//!     .cmd("elm --version", Some(CommandOutput::stdout("0.19.1")))
+//!     .collect();
+//! assert!(actual.unwrap().contains("v0.19.1"));
+//! ```
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::config::StarshipConfig;
+use crate::context::{Context, Shell};
+use crate::print;
+use crate::utils::CommandOutput;
+
+/// Renders a module the way `starship module` would, with a given directory,
+/// configuration, environment, command outputs and terminal width.
+///
+/// Nothing is taken from the machine the tests run on: the environment starts out
+/// empty, the configuration is the default one, and the directory is the system's
+/// temporary directory unless they are set.
+pub struct ModuleRenderer {
+    name: String,
+    path: PathBuf,
+    config: Option<toml::Value>,
+    env: HashMap<String, String>,
+    cmd_outputs: HashMap<String, Option<CommandOutput>>,
+    width: Option<usize>,
+    shell: Shell,
+}
+
+impl ModuleRenderer {
+    /// A renderer for a module, by the name `starship module` takes, e.g. `custom.test`
+    pub fn new(name: &str) -> Self {
+        ModuleRenderer {
+            name: name.to_owned(),
+            path: std::env::temp_dir(),
+            config: None,
+            env: HashMap::new(),
+            cmd_outputs: HashMap::new(),
+            width: None,
+            shell: Shell::Unknown,
+        }
+    }
+
+    /// Render the module in a directory
+    pub fn path<T: Into<PathBuf>>(mut self, path: T) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Render the module with a configuration, as it would be read from `starship.toml`
+    pub fn config(mut self, config: toml::Value) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Set an environment variable
+    pub fn env<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.env.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set the output of a command line, e.g. `node --version`, or `None` for a
+    /// command that fails. Commands with no output set are run as usual.
+    pub fn cmd(mut self, cmd: &str, output: Option<CommandOutput>) -> Self {
+        self.cmd_outputs.insert(cmd.to_owned(), output);
+        self
+    }
+
+    /// Set the width of the terminal, as if passed with `--terminal-width`
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Set the shell the prompt is rendered for
+    pub fn shell(mut self, shell: Shell) -> Self {
+        self.shell = shell;
+        self
+    }
+
+    /// Render the module, or `None` if it isn't shown
+    pub fn collect(self) -> Option<String> {
+        let mut context = Context::new_with_dir(clap::ArgMatches::default(), &self.path);
+        context.config = StarshipConfig::new(self.config);
+        context.shell = self.shell;
+        context.env = Some(self.env);
+        context.cmd_outputs = self.cmd_outputs;
+        if let Some(width) = self.width {
+            context
+                .properties
+                .insert("terminal_width", width.to_string());
+        }
+
+        print::get_module(&self.name, context)
+    }
+}
+
+impl CommandOutput {
+    /// The output of a command that only printed to stdout
+    pub fn stdout<T: Into<String>>(stdout: T) -> Self {
+        CommandOutput {
+            stdout: stdout.into(),
+            stderr: String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ansi_term::Color;
+
+    #[test]
+    fn render_with_env() {
+        let actual = ModuleRenderer::new("aws")
+            .env("AWS_REGION", "ap-northeast-2")
+            .collect();
+        let expected = Some(format!(
+            "on {} ",
+            Color::Yellow.bold().paint("☁️  ap-northeast-2")
+        ));
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn render_without_process_env() {
+        std::env::set_var("STARSHIP_TEST_HARNESS_VAR", "leaked");
+        let actual = ModuleRenderer::new("env_var")
+            .config(toml::toml! {
+                [env_var]
+                variable = "STARSHIP_TEST_HARNESS_VAR"
+            })
+            .collect();
+        assert_eq!(None, actual);
+    }
+
+    #[test]
+    fn render_with_cmd_output() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::File::create(dir.path().join("elm.json"))?.sync_all()?;
+        let actual = ModuleRenderer::new("elm")
+            .path(dir.path())
+            .cmd("elm --version", Some(CommandOutput::stdout("0.18.0")))
+            .width(40)
+            .shell(Shell::Bash)
+            .collect();
+        let expected = Some(format!("via {} ", Color::Cyan.bold().paint("🌳 v0.18.0")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn render_with_failing_cmd() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::File::create(dir.path().join("elm.json"))?.sync_all()?;
+        let actual = ModuleRenderer::new("elm")
+            .path(dir.path())
+            .cmd("elm --version", None)
+            .collect();
+        assert_eq!(None, actual);
+        dir.close()
+    }
+}
@@ -1,7 +1,15 @@
-use std::fs::File;
+use path_slash::PathExt;
+use process_control::{ChildExt, Output, Timeout};
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsStr;
+use std::fs::{self, File};
 use std::io::{Read, Result};
-use std::path::Path;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 use crate::context::Shell;
 
@@ -14,7 +22,30 @@ pub fn read_file<P: AsRef<Path>>(file_name: P) -> Result<String> {
     Ok(data)
 }
 
-#[derive(Debug)]
+/// An environment variable from `env`, or else from the environment of the process when
+/// there's no `env`, like a session has for each prompt it renders
+pub fn get_env(env: Option<&HashMap<String, String>>, key: &str) -> Option<String> {
+    match env {
+        Some(env) => env.get(key).cloned(),
+        None => env::var_os(key).map(|value| value.to_string_lossy().into_owned()),
+    }
+}
+
+/// The value of a key in a section of an INI file, like gcloud's configurations or hgrc
+pub fn get_ini_value(contents: &str, section: &str, key: &str) -> Option<String> {
+    let header = format!("[{}]", section);
+    contents
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| *line != header)
+        .skip(1)
+        .take_while(|line| !line.starts_with('['))
+        .filter_map(|line| line.split_once('='))
+        .find(|(name, _)| name.trim() == key)
+        .map(|(_, value)| value.trim().to_owned())
+}
+
+#[derive(Clone, Debug)]
 pub struct CommandOutput {
     pub stdout: String,
     pub stderr: String,
@@ -26,64 +57,127 @@ impl PartialEq for CommandOutput {
     }
 }
 
-/// Execute a command and return the output on stdout and stderr if sucessful
+/// Why a command didn't produce an output
+#[derive(Debug, PartialEq)]
+pub enum ExecError {
+    /// The command couldn't be run, or it exited with a non-zero status
+    Failed,
+    /// The command was killed because it ran longer than its timeout
+    TimedOut,
+}
+
+/// Execute a command and return the output on stdout and stderr if sucessful.
+/// The command is killed if it runs longer than `timeout`.
 #[cfg(not(test))]
-pub fn exec_cmd(cmd: &str, args: &[&str]) -> Option<CommandOutput> {
-    internal_exec_cmd(&cmd, &args)
+#[allow(dead_code)] // Only called by `bug-report`, which isn't part of the library
+pub fn exec_cmd(
+    cmd: &str,
+    args: &[&str],
+    timeout: Option<Duration>,
+) -> std::result::Result<CommandOutput, ExecError> {
+    internal_exec_cmd(cmd, args, timeout)
+}
+
+/// Execute a command like `exec_cmd`, with its executable found by `policy`, which
+/// refuses to run it if it's inside the current repository
+#[cfg(not(test))]
+pub fn exec_cmd_with_policy(
+    cmd: &str,
+    args: &[&str],
+    timeout: Option<Duration>,
+    policy: &ExecPolicy,
+) -> std::result::Result<CommandOutput, ExecError> {
+    let program = policy.resolve(cmd).ok_or(ExecError::Failed)?;
+    exec_command(policy.command(&program), args, timeout)
+}
+
+/// Mocks a command like `exec_cmd`, unless `policy` finds its executable and refuses to
+/// run it. The mocked commands don't need to be installed.
+#[cfg(test)]
+pub fn exec_cmd_with_policy(
+    cmd: &str,
+    args: &[&str],
+    timeout: Option<Duration>,
+    policy: &ExecPolicy,
+) -> std::result::Result<CommandOutput, ExecError> {
+    if policy.find(cmd).is_some() && policy.resolve(cmd).is_none() {
+        return Err(ExecError::Failed);
+    }
+    exec_cmd(cmd, args, timeout)
 }
 
 #[cfg(test)]
-pub fn exec_cmd(cmd: &str, args: &[&str]) -> Option<CommandOutput> {
+pub fn exec_cmd(
+    cmd: &str,
+    args: &[&str],
+    timeout: Option<Duration>,
+) -> std::result::Result<CommandOutput, ExecError> {
     let command = match args.len() {
         0 => String::from(cmd),
         _ => format!("{} {}", cmd, args.join(" ")),
     };
     match command.as_str() {
-        "crystal --version" => Some(CommandOutput {
+        "ansible --version" => Ok(CommandOutput {
+            stdout: String::from("ansible [core 2.15.4]\n  config file = None"),
+            stderr: String::default(),
+        }),
+        "bun --version" => Ok(CommandOutput {
+            stdout: String::from("1.0.3"),
+            stderr: String::default(),
+        }),
+        "crystal --version" => Ok(CommandOutput {
             stdout: String::from("Crystal 0.32.1 (2019-12-18)"),
             stderr: String::default(),
         }),
-        "dummy_command" => Some(CommandOutput {
+        "dummy_command" => Ok(CommandOutput {
             stdout: String::from("stdout ok!"),
             stderr: String::from("stderr ok!"),
         }),
-        "elm --version" => Some(CommandOutput {
+        "elm --version" => Ok(CommandOutput {
             stdout: String::from("0.19.1"),
             stderr: String::default(),
         }),
-        "go version" => Some(CommandOutput {
+        "go version" => Ok(CommandOutput {
             stdout: String::from("go version go1.12.1 linux/amd64"),
             stderr: String::default(),
         }),
-        "julia --version" => Some(CommandOutput {
+        "julia --version" => Ok(CommandOutput {
             stdout: String::from("julia version 1.4.0"),
             stderr: String::default(),
         }),
-        "node --version" => Some(CommandOutput {
+        "node --version" => Ok(CommandOutput {
             stdout: String::from("v12.0.0"),
             stderr: String::default(),
         }),
-        "ocaml -vnum" => Some(CommandOutput {
+        "ocaml -vnum" => Ok(CommandOutput {
             stdout: String::from("4.10.0"),
             stderr: String::default(),
         }),
         "php -r echo PHP_MAJOR_VERSION.'.'.PHP_MINOR_VERSION.'.'.PHP_RELEASE_VERSION;" => {
-            Some(CommandOutput {
+            Ok(CommandOutput {
                 stdout: String::from("7.3.8"),
                 stderr: String::default(),
             })
         }
-        "ruby -v" => Some(CommandOutput {
+        "ruby -v" => Ok(CommandOutput {
             stdout: String::from("ruby 2.5.1p57 (2018-03-29 revision 63029) [x86_64-linux-gnu]"),
             stderr: String::default(),
         }),
+        "solc --version" => Ok(CommandOutput {
+            stdout: String::from(
+                "\
+solc, the solidity compiler commandline interface
+Version: 0.8.21+commit.d9974bed.Linux.g++",
+            ),
+            stderr: String::default(),
+        }),
         "stack --no-install-ghc --lock-file read-only ghc -- --numeric-version" => {
-            Some(CommandOutput {
+            Ok(CommandOutput {
                 stdout: String::from("8.6.5"),
                 stderr: String::default(),
             })
         }
-        "elixir --version" => Some(CommandOutput {
+        "elixir --version" => Ok(CommandOutput {
             stdout: String::from(
                 "\
 Erlang/OTP 22 [erts-10.6.4] [source] [64-bit] [smp:8:8] [ds:8:8:10] [async-threads:1] [hipe]
@@ -92,19 +186,41 @@ Elixir 1.10 (compiled with Erlang/OTP 22)",
             ),
             stderr: String::default(),
         }),
-        "zig version" => Some(CommandOutput {
+        "tex --version" => Ok(CommandOutput {
+            stdout: String::from("TeX 3.141592653 (TeX Live 2023/Debian)\nkpathsea version 6.3.5"),
+            stderr: String::default(),
+        }),
+        "typst --version" => Ok(CommandOutput {
+            stdout: String::from("typst 0.10.0 (70ca0d25)"),
+            stderr: String::default(),
+        }),
+        "zig version" => Ok(CommandOutput {
             stdout: String::from("0.6.0"),
             stderr: String::default(),
         }),
-        s if s.starts_with("erl") => Some(CommandOutput {
+        s if s.starts_with("erl") => Ok(CommandOutput {
             stdout: String::from("22.1.3"),
             stderr: String::default(),
         }),
         // If we don't have a mocked command fall back to executing the command
-        _ => internal_exec_cmd(&cmd, &args),
+        _ => internal_exec_cmd(cmd, args, timeout),
     }
 }
 
+/// Returns the `file://` URL of a path on the local host, as used by terminal escape sequences
+pub fn file_url(path: &Path) -> String {
+    let hostname = gethostname::gethostname();
+    let path = path
+        .to_slash_lossy()
+        .split('/')
+        .map(urlencoding::encode)
+        .collect::<Vec<String>>()
+        .join("/");
+    let separator = if path.starts_with('/') { "" } else { "/" };
+
+    format!("file://{}{}{}", hostname.to_string_lossy(), separator, path)
+}
+
 /// Wraps ANSI color escape sequences in the shell-appropriate wrappers.
 pub fn wrap_colorseq_for_shell(ansi: String, shell: Shell) -> String {
     const ESCAPE_BEGIN: char = '\u{1b}';
@@ -136,14 +252,14 @@ pub fn wrap_seq_for_shell(
                 escaped = true;
                 match shell {
                     Shell::Bash => format!("{}{}", BASH_BEG, escape_begin),
-                    Shell::Zsh => format!("{}{}", ZSH_BEG, escape_begin),
+                    Shell::Zsh | Shell::Tcsh => format!("{}{}", ZSH_BEG, escape_begin),
                     _ => x.to_string(),
                 }
             } else if x == escape_end && escaped {
                 escaped = false;
                 match shell {
                     Shell::Bash => format!("{}{}", escape_end, BASH_END),
-                    Shell::Zsh => format!("{}{}", escape_end, ZSH_END),
+                    Shell::Zsh | Shell::Tcsh => format!("{}{}", escape_end, ZSH_END),
                     _ => x.to_string(),
                 }
             } else {
@@ -154,29 +270,225 @@ pub fn wrap_seq_for_shell(
     final_string
 }
 
-fn internal_exec_cmd(cmd: &str, args: &[&str]) -> Option<CommandOutput> {
+/// The number of columns a grapheme takes up in the terminal. Emoji made up of several
+/// characters, like ZWJ sequences, flags and emoji with a variation selector, take up
+/// two columns like any other emoji.
+pub fn grapheme_width(grapheme: &str) -> usize {
+    if grapheme.contains('\u{fe0f}') {
+        return 2;
+    }
+    UnicodeWidthStr::width(grapheme).min(2)
+}
+
+/// The number of columns a string takes up in the terminal
+pub fn display_width(text: &str) -> usize {
+    text.graphemes(true).map(grapheme_width).sum()
+}
+
+/// The longest start of a string that fits in `width` columns, cut between graphemes
+pub fn truncate_to_width(text: &str, width: usize) -> &str {
+    let mut remaining = width;
+    for (index, grapheme) in text.grapheme_indices(true) {
+        let grapheme_width = grapheme_width(grapheme);
+        if grapheme_width > remaining {
+            return &text[..index];
+        }
+        remaining -= grapheme_width;
+    }
+    text
+}
+
+fn internal_exec_cmd<T: AsRef<OsStr>>(
+    cmd: T,
+    args: &[&str],
+    timeout: Option<Duration>,
+) -> std::result::Result<CommandOutput, ExecError> {
+    exec_command(Command::new(cmd), args, timeout)
+}
+
+fn exec_command(
+    mut command: Command,
+    args: &[&str],
+    timeout: Option<Duration>,
+) -> std::result::Result<CommandOutput, ExecError> {
+    let cmd = command.get_program().to_owned();
     log::trace!("Executing command {:?} with args {:?}", cmd, args);
-    match Command::new(cmd).args(args).output() {
-        Ok(output) => {
-            let stdout_string = String::from_utf8(output.stdout).unwrap();
-            let stderr_string = String::from_utf8(output.stderr).unwrap();
+    let child = command
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let output = match child {
+        Ok(child) => wait_with_timeout(child, timeout)?,
+        Err(error) => {
+            log::trace!("Executing command {:?} failed by: {:?}", cmd, error);
+            return Err(ExecError::Failed);
+        }
+    };
 
-            log::trace!("stdout: {:?}", stdout_string);
-            log::trace!("stderr: {:?}", stderr_string);
-            log::trace!("exit code: \"{:?}\"", output.status.code());
+    let stdout_string = String::from_utf8_lossy(&output.stdout).into_owned();
+    let stderr_string = String::from_utf8_lossy(&output.stderr).into_owned();
 
-            if !output.status.success() {
-                return None;
-            }
+    log::trace!("stdout: {:?}", stdout_string);
+    log::trace!("stderr: {:?}", stderr_string);
+    log::trace!("exit code: \"{:?}\"", output.status.code());
 
-            Some(CommandOutput {
-                stdout: stdout_string,
-                stderr: stderr_string,
-            })
+    if !output.status.success() {
+        return Err(ExecError::Failed);
+    }
+
+    Ok(CommandOutput {
+        stdout: stdout_string,
+        stderr: stderr_string,
+    })
+}
+
+/// Where the executables of the commands run by modules may be found. A repository can
+/// contain executables named like the tools modules run, e.g. a `git` or `node` that
+/// would run as soon as the repository is entered, so those inside it are never run.
+#[derive(Debug, Default)]
+pub struct ExecPolicy {
+    /// The directory executables are refused in, the current repository unless it's
+    /// trusted, or in strict mode the current directory outside of repositories
+    pub untrusted_dir: Option<PathBuf>,
+    /// Whether directories of `PATH` that are relative, and so depend on the current
+    /// directory, or are inside `untrusted_dir`, are skipped instead of searched
+    pub strict: bool,
+    /// The directories executables are searched in, the `PATH` of the context
+    pub path: Option<String>,
+    /// The directory commands run in, the current directory of the context
+    pub dir: Option<PathBuf>,
+    /// The environment of commands, if the context has its own instead of the one of
+    /// the process
+    pub env: Option<HashMap<String, String>>,
+}
+
+impl ExecPolicy {
+    /// A command for an executable, which runs in the directory and environment of the
+    /// policy
+    pub fn command<T: AsRef<OsStr>>(&self, program: T) -> Command {
+        let mut command = Command::new(program);
+        if let Some(dir) = &self.dir {
+            command.current_dir(dir);
         }
+        if let Some(env) = &self.env {
+            command.env_clear().envs(env);
+        }
+        command
+    }
+
+    /// The executable a command runs, searched for in `PATH`, or `None` if it isn't found
+    /// or is inside the untrusted directory
+    pub fn resolve(&self, cmd: &str) -> Option<PathBuf> {
+        self.find(cmd).filter(|program| self.allows(program))
+    }
+
+    /// The executable a command runs, whether or not it may be run
+    fn find(&self, cmd: &str) -> Option<PathBuf> {
+        if cmd.contains(std::path::is_separator) {
+            // Run relative to the directory of the policy, not searched for in `PATH`
+            return self.absolute(PathBuf::from(cmd));
+        }
+        let path = self.path.as_ref()?;
+        env::split_paths(path)
+            .filter(|dir| !self.strict || !(dir.is_relative() || self.is_untrusted(dir)))
+            .filter_map(|dir| self.absolute(dir))
+            .find_map(|dir| find_executable(&dir, cmd))
+    }
+
+    /// A path relative to the directory commands run in, or else the current directory
+    fn absolute(&self, path: PathBuf) -> Option<PathBuf> {
+        if path.is_absolute() {
+            return Some(path);
+        }
+        match &self.dir {
+            Some(dir) => Some(dir.join(path)),
+            None => Some(env::current_dir().ok()?.join(path)),
+        }
+    }
+
+    /// Whether an executable may be run, which it may unless it's inside the untrusted
+    /// directory
+    pub fn allows(&self, program: &Path) -> bool {
+        if self.is_untrusted(program) {
+            log::warn!(
+                "Refusing to run {:?}, which is inside the current repository",
+                program
+            );
+            return false;
+        }
+        true
+    }
+
+    fn is_untrusted(&self, path: &Path) -> bool {
+        let untrusted_dir = match &self.untrusted_dir {
+            Some(dir) => dir,
+            None => return false,
+        };
+        // Both the path and where its symlinks lead are checked, since a link in the
+        // repository to e.g. a shell would run it with arguments the repository chose
+        let canonical_dir = fs::canonicalize(untrusted_dir).ok();
+        let canonical_path = fs::canonicalize(path).ok();
+        let dirs = [Some(untrusted_dir.as_path()), canonical_dir.as_deref()];
+        [Some(path), canonical_path.as_deref()]
+            .iter()
+            .flatten()
+            .any(|path| dirs.iter().flatten().any(|dir| path.starts_with(dir)))
+    }
+}
+
+/// The executable file for a command in a directory, trying the extensions of
+/// `PATHEXT` on Windows
+fn find_executable(dir: &Path, cmd: &str) -> Option<PathBuf> {
+    let program = dir.join(cmd);
+    if is_executable(&program) {
+        return Some(program);
+    }
+    if cfg!(windows) {
+        let extensions =
+            env::var("PATHEXT").unwrap_or_else(|_| String::from(".COM;.EXE;.BAT;.CMD"));
+        return extensions
+            .split(';')
+            .filter(|extension| !extension.is_empty())
+            .map(|extension| dir.join(format!("{}{}", cmd, extension)))
+            .find(|program| is_executable(program));
+    }
+    None
+}
+
+/// Whether a path is a file that can be executed
+#[cfg(unix)]
+pub fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    match fs::metadata(path) {
+        Ok(metadata) => metadata.is_file() && metadata.permissions().mode() & 0o111 != 0,
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(unix))]
+pub fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Wait for a command to exit and collect its output. The command is killed once it
+/// runs longer than `timeout`.
+pub fn wait_with_timeout(
+    child: Child,
+    timeout: Option<Duration>,
+) -> std::result::Result<Output, ExecError> {
+    let output = match timeout {
+        Some(timeout) => child.with_output_timeout(timeout).terminating().wait(),
+        None => child.wait_with_output().map(|output| Some(output.into())),
+    };
+    match output {
+        Ok(Some(output)) => Ok(output),
+        Ok(None) => Err(ExecError::TimedOut),
         Err(error) => {
-            log::trace!("Executing command {:?} failed by: {:?}", cmd, error);
-            None
+            log::trace!("Waiting for the command failed by: {:?}", error);
+            Err(ExecError::Failed)
         }
     }
 }
@@ -186,10 +498,75 @@ fn internal_exec_cmd(cmd: &str, args: &[&str]) -> Option<CommandOutput> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn refuse_untrusted_executables() -> Result<()> {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let dir = tempfile::tempdir()?;
+        let program = dir.path().join("git");
+        fs::write(&program, "#!/bin/sh\n")?;
+        fs::set_permissions(&program, fs::Permissions::from_mode(0o755))?;
+        let outside = tempfile::tempdir()?;
+        let link = outside.path().join("git");
+        symlink(&program, &link)?;
+
+        let policy = ExecPolicy {
+            untrusted_dir: Some(dir.path().to_path_buf()),
+            ..ExecPolicy::default()
+        };
+        assert_eq!(policy.resolve(&program.to_string_lossy()), None);
+        assert_eq!(policy.resolve(&link.to_string_lossy()), None);
+        assert_eq!(
+            ExecPolicy::default().resolve(&program.to_string_lossy()),
+            Some(program)
+        );
+        outside.close()?;
+        dir.close()
+    }
+
+    #[test]
+    fn resolve_relative_to_dir_of_policy() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir()?;
+        fs::create_dir(dir.path().join("bin"))?;
+        let program = dir.path().join("bin").join("tool");
+        fs::write(&program, "#!/bin/sh\n")?;
+        fs::set_permissions(&program, fs::Permissions::from_mode(0o755))?;
+
+        let policy = ExecPolicy {
+            path: Some(String::from("bin")),
+            dir: Some(dir.path().to_path_buf()),
+            ..ExecPolicy::default()
+        };
+        assert_eq!(policy.resolve("tool"), Some(program.clone()));
+        assert_eq!(policy.resolve("bin/tool"), Some(program));
+        dir.close()
+    }
+
+    #[test]
+    fn display_width_of_wide_text() {
+        assert_eq!(display_width("main"), 4);
+        assert_eq!(display_width("功能分支"), 8);
+        assert_eq!(display_width("👨‍👩‍👧-fix"), 6);
+        assert_eq!(display_width("e\u{301}toile"), 6);
+        assert_eq!(display_width("☁️"), 2);
+    }
+
+    #[test]
+    fn truncate_wide_text_to_width() {
+        assert_eq!(truncate_to_width("main", 10), "main");
+        assert_eq!(truncate_to_width("功能分支", 4), "功能");
+        assert_eq!(truncate_to_width("功能分支", 5), "功能");
+        assert_eq!(truncate_to_width("👨‍👩‍👧👨‍👩‍👧", 3), "👨‍👩‍👧");
+        assert_eq!(truncate_to_width("e\u{301}toile", 2), "e\u{301}t");
+        assert_eq!(truncate_to_width("功能", 1), "");
+    }
+
     #[test]
     fn exec_mocked_command() {
-        let result = exec_cmd("dummy_command", &[]);
-        let expected = Some(CommandOutput {
+        let result = exec_cmd("dummy_command", &[], None);
+        let expected = Ok(CommandOutput {
             stdout: String::from("stdout ok!"),
             stderr: String::from("stderr ok!"),
         });
@@ -197,10 +574,36 @@ mod tests {
         assert_eq!(result, expected)
     }
 
+    #[test]
+    #[cfg(not(windows))]
+    fn exec_in_dir_and_env_of_policy() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut env = HashMap::new();
+        env.insert(String::from("GREETING"), String::from("hello"));
+        let policy = ExecPolicy {
+            dir: Some(fs::canonicalize(dir.path())?),
+            env: Some(env),
+            ..ExecPolicy::default()
+        };
+
+        let script = "pwd; echo $GREETING; echo ${HOME:-unset}";
+        let result = exec_command(policy.command("/bin/sh"), &["-c", script], None);
+        let expected = Ok(CommandOutput {
+            stdout: format!(
+                "{}\nhello\nunset\n",
+                fs::canonicalize(dir.path())?.display()
+            ),
+            stderr: String::from(""),
+        });
+
+        assert_eq!(result, expected);
+        dir.close()
+    }
+
     #[test]
     fn exec_no_output() {
-        let result = internal_exec_cmd("true", &[]);
-        let expected = Some(CommandOutput {
+        let result = internal_exec_cmd("true", &[], None);
+        let expected = Ok(CommandOutput {
             stdout: String::from(""),
             stderr: String::from(""),
         });
@@ -210,8 +613,8 @@ mod tests {
 
     #[test]
     fn exec_with_output_stdout() {
-        let result = internal_exec_cmd("/bin/sh", &["-c", "echo hello"]);
-        let expected = Some(CommandOutput {
+        let result = internal_exec_cmd("/bin/sh", &["-c", "echo hello"], None);
+        let expected = Ok(CommandOutput {
             stdout: String::from("hello\n"),
             stderr: String::from(""),
         });
@@ -221,8 +624,8 @@ mod tests {
 
     #[test]
     fn exec_with_output_stderr() {
-        let result = internal_exec_cmd("/bin/sh", &["-c", "echo hello >&2"]);
-        let expected = Some(CommandOutput {
+        let result = internal_exec_cmd("/bin/sh", &["-c", "echo hello >&2"], None);
+        let expected = Ok(CommandOutput {
             stdout: String::from(""),
             stderr: String::from("hello\n"),
         });
@@ -232,8 +635,8 @@ mod tests {
 
     #[test]
     fn exec_with_output_both() {
-        let result = internal_exec_cmd("/bin/sh", &["-c", "echo hello; echo world >&2"]);
-        let expected = Some(CommandOutput {
+        let result = internal_exec_cmd("/bin/sh", &["-c", "echo hello; echo world >&2"], None);
+        let expected = Ok(CommandOutput {
             stdout: String::from("hello\n"),
             stderr: String::from("world\n"),
         });
@@ -243,8 +646,16 @@ mod tests {
 
     #[test]
     fn exec_with_non_zero_exit_code() {
-        let result = internal_exec_cmd("false", &[]);
-        let expected = None;
+        let result = internal_exec_cmd("false", &[], None);
+        let expected = Err(ExecError::Failed);
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn exec_with_timeout() {
+        let result = internal_exec_cmd("sleep", &["5"], Some(Duration::from_millis(50)));
+        let expected = Err(ExecError::TimedOut);
 
         assert_eq!(result, expected)
     }
@@ -286,4 +697,32 @@ mod tests {
         assert_eq!(&bresult4, "herpaderp");
         assert_eq!(&bresult5, "");
     }
+
+    #[test]
+    fn parse_ini_values() {
+        let input = "\
+[core]
+account = astronaut@example.com
+project=starship-123456
+disable_usage_reporting = True
+
+[compute]
+region = europe-west1
+zone = europe-west1-b
+";
+        assert_eq!(
+            get_ini_value(input, "core", "account"),
+            Some(String::from("astronaut@example.com"))
+        );
+        assert_eq!(
+            get_ini_value(input, "core", "project"),
+            Some(String::from("starship-123456"))
+        );
+        assert_eq!(
+            get_ini_value(input, "compute", "zone"),
+            Some(String::from("europe-west1-b"))
+        );
+        assert_eq!(get_ini_value(input, "compute", "account"), None);
+        assert_eq!(get_ini_value(input, "auth", "account"), None);
+    }
 }
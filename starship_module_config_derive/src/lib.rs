@@ -17,11 +17,17 @@ fn impl_module_config(dinput: DeriveInput) -> proc_macro::TokenStream {
 
     let mut from_config = quote! {};
     let mut load_config = quote! {};
+    let mut to_config = quote! {};
+    let mut check_config = quote! {};
+    let mut schema = quote! {};
 
     if let syn::Data::Struct(data) = dinput.data {
         if let syn::Fields::Named(fields_named) = data.fields {
             let mut load_tokens = quote! {};
             let mut from_tokens = quote! {};
+            let mut to_tokens = quote! {};
+            let mut check_tokens = quote! {};
+            let mut schema_tokens = quote! {};
 
             for field in fields_named.named.iter() {
                 let ident = field.ident.as_ref().unwrap();
@@ -35,6 +41,20 @@ fn impl_module_config(dinput: DeriveInput) -> proc_macro::TokenStream {
                 let new_from_tokens = quote! {
                     #ident: config.get(stringify!(#ident)).and_then(<#ty>::from_config)?,
                 };
+                let new_to_tokens = quote! {
+                    if let Some(value) = self.#ident.to_config() {
+                        table.insert(stringify!(#ident).to_owned(), value);
+                    }
+                };
+                let new_schema_tokens = quote! {
+                    properties.insert(
+                        stringify!(#ident).to_owned(),
+                        <#ty as ModuleConfig<'a>>::schema(),
+                    );
+                };
+                let new_check_tokens = quote! {
+                    stringify!(#ident) => self.#ident.check_config(value),
+                };
 
                 load_tokens = quote! {
                     #load_tokens
@@ -43,7 +63,19 @@ fn impl_module_config(dinput: DeriveInput) -> proc_macro::TokenStream {
                 from_tokens = quote! {
                     #from_tokens
                     #new_from_tokens
-                }
+                };
+                to_tokens = quote! {
+                    #to_tokens
+                    #new_to_tokens
+                };
+                check_tokens = quote! {
+                    #check_tokens
+                    #new_check_tokens
+                };
+                schema_tokens = quote! {
+                    #schema_tokens
+                    #new_schema_tokens
+                };
             }
 
             load_config = quote! {
@@ -64,6 +96,44 @@ fn impl_module_config(dinput: DeriveInput) -> proc_macro::TokenStream {
                     })
                 }
             };
+            to_config = quote! {
+                fn to_config(&self) -> Option<toml::Value> {
+                    let mut table = toml::value::Table::new();
+                    #to_tokens
+                    Some(toml::Value::Table(table))
+                }
+            };
+            schema = quote! {
+                fn schema() -> serde_json::Value {
+                    let mut properties = serde_json::Map::new();
+                    #schema_tokens
+                    serde_json::json!({
+                        "type": "object",
+                        "properties": properties,
+                        "additionalProperties": false,
+                    })
+                }
+            };
+            check_config = quote! {
+                fn check_config(&self, config: &'a toml::Value) -> Vec<crate::config::ConfigWarning> {
+                    let config = match config.as_table() {
+                        Some(config) => config,
+                        None => {
+                            return vec![crate::config::ConfigWarning::expected("a table", config)]
+                        }
+                    };
+
+                    let mut warnings = Vec::new();
+                    for (key, value) in config.iter() {
+                        let key_warnings = match key.as_str() {
+                            #check_tokens
+                            _ => vec![crate::config::ConfigWarning::new(String::from("unknown option"))],
+                        };
+                        warnings.extend(key_warnings.into_iter().map(|warning| warning.in_table(key)));
+                    }
+                    warnings
+                }
+            };
         }
     }
 
@@ -71,6 +141,9 @@ fn impl_module_config(dinput: DeriveInput) -> proc_macro::TokenStream {
         impl<'a> ModuleConfig<'a> for #struct_ident #ty_generics #where_clause {
             #from_config
             #load_config
+            #to_config
+            #check_config
+            #schema
         }
     })
 }
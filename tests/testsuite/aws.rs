@@ -1,7 +1,7 @@
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{self, Write};
 
-use ansi_term::Color;
+use ansi_term::{ANSIStrings, Color};
 
 use crate::common::{self, TestCommand};
 
@@ -93,6 +93,25 @@ fn profile_and_region_set() -> io::Result<()> {
     Ok(())
 }
 
+#[test]
+fn profile_and_region_set_with_redact() -> io::Result<()> {
+    let output = common::render_module("aws")
+        .env("AWS_PROFILE", "123456789012-admin")
+        .env("AWS_REGION", "ap-northeast-2")
+        .use_config(toml::toml! {
+            [aws]
+            redact = "truncate"
+        })
+        .output()?;
+    let expected = format!(
+        "on {} ",
+        Color::Yellow.bold().paint("☁️  1234…(ap-northeast-2)")
+    );
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
 #[test]
 fn default_profile_set() -> io::Result<()> {
     let dir = tempfile::tempdir()?;
@@ -260,3 +279,65 @@ fn region_not_set_with_display_region() -> io::Result<()> {
     assert_eq!(expected, actual);
     Ok(())
 }
+
+#[test]
+fn expired_aws_vault_credentials() -> io::Result<()> {
+    let output = common::render_module("aws")
+        .env("AWS_VAULT", "astronauts")
+        .env("AWS_REGION", "ap-northeast-2")
+        .env("AWS_SESSION_EXPIRATION", "2020-06-17T10:18:53Z")
+        .output()?;
+    let expected = format!(
+        "on {} ",
+        ANSIStrings(&[
+            Color::Yellow.bold().paint("☁️  astronauts(ap-northeast-2)"),
+            Color::Red.bold().paint(" (expired)"),
+        ])
+    );
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn expired_sso_token() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let config_path = dir.path().join("config");
+    fs::write(
+        &config_path,
+        "[profile astronauts]
+region = us-east-2
+sso_start_url = https://astronauts.awsapps.com/start
+",
+    )?;
+    let cache = dir.path().join(".aws/sso/cache");
+    fs::create_dir_all(&cache)?;
+    fs::write(
+        cache.join("0123456789abcdef.json"),
+        r#"{"startUrl": "https://other.awsapps.com/start", "expiresAt": "3020-06-17T10:18:53Z"}"#,
+    )?;
+    fs::write(
+        cache.join("fedcba9876543210.json"),
+        r#"{"startUrl": "https://astronauts.awsapps.com/start", "region": "us-east-2", "expiresAt": "2020-06-17T10:18:53UTC"}"#,
+    )?;
+
+    let output = common::render_module("aws")
+        .env("HOME", dir.path())
+        .env("AWS_CONFIG_FILE", config_path.to_string_lossy().as_ref())
+        .env("AWS_PROFILE", "astronauts")
+        .use_config(toml::toml! {
+            [aws]
+            expiration_style = "bold purple"
+        })
+        .output()?;
+    let expected = format!(
+        "on {} ",
+        ANSIStrings(&[
+            Color::Yellow.bold().paint("☁️  astronauts(us-east-2)"),
+            Color::Purple.bold().paint(" (expired)"),
+        ])
+    );
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(expected, actual);
+    dir.close()
+}
@@ -111,6 +111,20 @@ fn char_module_zsh_keymap() -> io::Result<()> {
     Ok(())
 }
 
+#[test]
+fn char_module_shell_flag() -> io::Result<()> {
+    // --shell takes precedence over STARSHIP_SHELL
+    let output = common::render_module("character")
+        .env("STARSHIP_SHELL", "bash")
+        .arg("--shell=zsh")
+        .arg("--keymap=vicmd")
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert!(actual.contains("❮"));
+
+    Ok(())
+}
+
 #[test]
 fn char_module_fish_keymap() -> io::Result<()> {
     let expected_vicmd = "❮";
@@ -148,3 +162,37 @@ fn char_module_fish_keymap() -> io::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn char_module_shell_symbols() -> io::Result<()> {
+    let config = toml::toml! {
+        [character]
+        success_symbol = { value = "➜", style = "bold purple" }
+        [character.shells.powershell]
+        success_symbol = "❯❯"
+    };
+
+    let output = common::render_module("character")
+        .use_config(config.clone())
+        .arg("--shell=powershell")
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(format!("{} ", Color::Green.bold().paint("❯❯")), actual);
+
+    let output = common::render_module("character")
+        .use_config(config.clone())
+        .arg("--shell=bash")
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(format!("{} ", Color::Purple.bold().paint("➜")), actual);
+
+    // The default symbols of other shells are kept
+    let output = common::render_module("character")
+        .use_config(config)
+        .arg("--shell=nu")
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(format!("{} ", Color::Green.bold().paint(">")), actual);
+
+    Ok(())
+}
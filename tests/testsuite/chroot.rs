@@ -0,0 +1,40 @@
+use ansi_term::Color;
+use std::io;
+
+use crate::common;
+
+#[test]
+fn not_in_chroot() -> io::Result<()> {
+    let output = common::render_module("chroot").output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = "";
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn debian_chroot_set() -> io::Result<()> {
+    let output = common::render_module("chroot")
+        .env("debian_chroot", "buster-arm64")
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("in {} ", Color::Purple.bold().paint("🔒 buster-arm64"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn schroot_session() -> io::Result<()> {
+    let output = common::render_module("chroot")
+        .env("SCHROOT_CHROOT_NAME", "sid-amd64-sbuild")
+        .env("SCHROOT_SESSION_ID", "sid-amd64-sbuild-0123abcd")
+        .env("debian_chroot", "sid")
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("in {} ", Color::Purple.bold().paint("🔒 sid-amd64-sbuild"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
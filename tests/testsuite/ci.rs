@@ -0,0 +1,77 @@
+use ansi_term::Color;
+use std::io;
+
+use crate::common;
+use crate::common::TestCommand;
+
+#[test]
+fn not_in_ci() -> io::Result<()> {
+    let output = common::render_module("ci").output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = "";
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn generic_ci() -> io::Result<()> {
+    let output = common::render_module("ci").env("CI", "true").output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("on {} ", Color::Red.bold().paint("⚙️  CI"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn ci_set_to_false() -> io::Result<()> {
+    let output = common::render_module("ci").env("CI", "false").output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = "";
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn github_actions_takes_precedence() -> io::Result<()> {
+    let output = common::render_module("ci")
+        .env("CI", "true")
+        .env("GITHUB_ACTIONS", "true")
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("on {} ", Color::Red.bold().paint("🐙 GitHub Actions"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn gitlab_ci() -> io::Result<()> {
+    let output = common::render_module("ci")
+        .env("GITLAB_CI", "true")
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("on {} ", Color::Red.bold().paint("🦊 GitLab CI"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn buildkite_without_provider_name() -> io::Result<()> {
+    let output = common::render_module("ci")
+        .env("BUILDKITE", "true")
+        .use_config(toml::toml! {
+            [ci]
+            show_provider = false
+            buildkite_symbol = "BK"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("on {} ", Color::Red.bold().paint("BK"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
@@ -27,6 +27,18 @@ fn config_blank_duration_5s() -> io::Result<()> {
     Ok(())
 }
 
+#[test]
+fn config_blank_fractional_duration_5s() -> io::Result<()> {
+    let output = common::render_module("cmd_duration")
+        .arg("--cmd-duration=5000.25")
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("took {} ", Color::Yellow.bold().paint("5s"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
 #[test]
 fn config_5s_duration_3s() -> io::Result<()> {
     let output = common::render_module("cmd_duration")
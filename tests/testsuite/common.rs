@@ -21,6 +21,48 @@ pub fn render_prompt() -> process::Command {
 
     command
         .arg("prompt")
+        // The output is captured, which would otherwise turn off its styles
+        .arg("--force-color")
+        .env_clear()
+        .env("PATH", env!("PATH")) // Provide the $PATH variable so that external programs are runnable
+        .env("STARSHIP_CONFIG", EMPTY_CONFIG.as_os_str());
+
+    command
+}
+
+/// Print the configuration starship is using
+pub fn print_config() -> process::Command {
+    let mut command = process::Command::new(EXE_PATH);
+
+    command
+        .arg("print-config")
+        .env_clear()
+        .env("PATH", env!("PATH")) // Provide the $PATH variable so that external programs are runnable
+        .env("STARSHIP_CONFIG", EMPTY_CONFIG.as_os_str());
+
+    command
+}
+
+/// Get or set a key of the starship configuration
+pub fn config() -> process::Command {
+    let mut command = process::Command::new(EXE_PATH);
+
+    command
+        .arg("config")
+        .env_clear()
+        .env("PATH", env!("PATH")) // Provide the $PATH variable so that external programs are runnable
+        .env("STARSHIP_CONFIG", EMPTY_CONFIG.as_os_str());
+
+    command
+}
+
+/// Toggle a boolean option of a module in the starship configuration
+pub fn toggle(module_name: &str) -> process::Command {
+    let mut command = process::Command::new(EXE_PATH);
+
+    command
+        .arg("toggle")
+        .arg(module_name)
         .env_clear()
         .env("PATH", env!("PATH")) // Provide the $PATH variable so that external programs are runnable
         .env("STARSHIP_CONFIG", EMPTY_CONFIG.as_os_str());
@@ -36,6 +78,8 @@ pub fn render_module(module_name: &str) -> process::Command {
     command
         .arg("module")
         .arg(module_name)
+        // The output is captured, which would otherwise turn off its styles
+        .arg("--force-color")
         .env_clear()
         .env("PATH", env!("PATH")) // Provide the $PATH variable so that external programs are runnable
         .env("STARSHIP_CONFIG", EMPTY_CONFIG.as_os_str());
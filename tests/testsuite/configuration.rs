@@ -1,4 +1,5 @@
-use ansi_term::Color;
+use ansi_term::{ANSIStrings, Color, Style};
+use std::fs;
 use std::io;
 
 use crate::common::{self, TestCommand};
@@ -39,3 +40,1336 @@ fn add_newline_configuration() -> io::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn format_configuration() -> io::Result<()> {
+    let output = common::render_prompt()
+        .env("CONDA_DEFAULT_ENV", "astronauts")
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "$conda|$line_break$conda$conda[>](bold red) "
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    // The prefix of a module is skipped at the start of each line
+    let conda = Color::Green.bold().paint("C astronauts");
+    let expected = format!(
+        "{} |\n{} via {} {} ",
+        conda,
+        conda,
+        conda,
+        Color::Red.bold().paint(">")
+    );
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn format_with_custom_modules() -> io::Result<()> {
+    let output = common::render_prompt()
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "${custom.b}$custom"
+
+            [custom.a]
+            command = "echo a"
+            when = "true"
+            style = "red"
+            prefix = ""
+            suffix = ""
+
+            [custom.b]
+            command = "echo b"
+            when = "true"
+            style = "blue"
+            prefix = ""
+            suffix = ""
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("{}{}", Color::Blue.paint("b"), Color::Red.paint("a"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn format_with_module_groups() -> io::Result<()> {
+    let output = common::render_module("aws")
+        .env("AWS_REGION", "ap-northeast-2")
+        .output()?;
+    let aws = String::from_utf8(output.stdout).unwrap();
+
+    let output = common::render_prompt()
+        .env("AWS_REGION", "ap-northeast-2")
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "> $cloud"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(format!("> {}", aws), actual);
+
+    let output = common::render_prompt()
+        .env("AWS_REGION", "ap-northeast-2")
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "> $aws|$cloud"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(format!("> {}|", aws), actual);
+    Ok(())
+}
+
+#[test]
+fn format_with_all_modules() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let output = common::render_prompt()
+        .arg("--path")
+        .arg(dir.path())
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "${custom.b}$all"
+
+            [custom.a]
+            command = "echo a"
+            when = "true"
+            style = "red"
+            prefix = ""
+            suffix = ""
+
+            [custom.b]
+            command = "echo b"
+            when = "true"
+            style = "blue"
+            prefix = ""
+            suffix = ""
+
+            [directory]
+            disabled = true
+
+            [battery]
+            disabled = true
+
+            [line_break]
+            disabled = true
+
+            [character]
+            disabled = true
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("{}{}", Color::Blue.paint("b"), Color::Red.paint("a"));
+    assert_eq!(expected, actual);
+    dir.close()
+}
+
+#[test]
+fn render_custom_module() -> io::Result<()> {
+    let output = common::render_module("custom.a")
+        .use_config(toml::toml! {
+            [custom.a]
+            command = "echo a"
+            when = "true"
+            style = "red"
+            prefix = ""
+            suffix = ""
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(Color::Red.paint("a").to_string(), actual);
+    Ok(())
+}
+
+#[test]
+fn skip_async_modules() -> io::Result<()> {
+    let config = toml::toml! {
+        add_newline = false
+        format = "${custom.a}${custom.b}"
+        async_modules = ["custom.b"]
+
+        [custom.a]
+        command = "echo a"
+        when = "true"
+        style = "red"
+        prefix = ""
+        suffix = ""
+
+        [custom.b]
+        command = "echo b"
+        when = "true"
+        style = "blue"
+        prefix = ""
+        suffix = ""
+    };
+
+    let output = common::render_prompt()
+        .arg("--skip-async")
+        .use_config(config.clone())
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("{}… ", Color::Red.paint("a"));
+    assert_eq!(expected, actual);
+
+    let output = common::render_prompt().use_config(config).output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("{}{}", Color::Red.paint("a"), Color::Blue.paint("b"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn timed_out_command_shows_placeholder() -> io::Result<()> {
+    let output = common::render_module("custom.a")
+        .use_config(toml::toml! {
+            command_timeout_placeholder = "…"
+
+            [custom.a]
+            command = "sleep 5; echo a"
+            when = "true"
+            timeout = 50
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    assert!(actual.contains('…'));
+    assert!(!actual.contains('a'));
+    Ok(())
+}
+
+#[test]
+fn list_custom_modules() -> io::Result<()> {
+    let output = common::render_module("--list")
+        .use_config(toml::toml! {
+            [custom.a]
+            command = "echo a"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    assert!(actual.lines().any(|line| line == "directory"));
+    assert!(actual.lines().any(|line| line == "custom.a"));
+    Ok(())
+}
+
+#[test]
+fn prompt_order_is_used_without_format() -> io::Result<()> {
+    let output = common::render_prompt()
+        .env("CONDA_DEFAULT_ENV", "astronauts")
+        .use_config(toml::toml! {
+            add_newline = false
+            prompt_order = ["conda", "line_break"]
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("{} \n", Color::Green.bold().paint("C astronauts"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn invalid_format_prints_fallback() -> io::Result<()> {
+    let output = common::render_prompt()
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "[$conda"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(">", actual);
+    Ok(())
+}
+
+#[test]
+fn format_with_conditional_groups() -> io::Result<()> {
+    let output = common::render_prompt()
+        .env("CONDA_DEFAULT_ENV", "astronauts")
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "(<$conda>)(<$nix_shell>)"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("<via {} >", Color::Green.bold().paint("C astronauts"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn truecolor_style_is_kept_with_truecolor_support() -> io::Result<()> {
+    let output = common::render_module("conda")
+        .env("CONDA_DEFAULT_ENV", "astronauts")
+        .env("COLORTERM", "truecolor")
+        .use_config(toml::toml! {
+            [conda]
+            style = "#ff8800"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("via {} ", Color::RGB(255, 136, 0).paint("C astronauts"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn truecolor_style_is_downgraded_without_truecolor_support() -> io::Result<()> {
+    let output = common::render_module("conda")
+        .env("CONDA_DEFAULT_ENV", "astronauts")
+        .use_config(toml::toml! {
+            [conda]
+            style = "#ff8800"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("via {} ", Color::Fixed(208).paint("C astronauts"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn palette_colors_in_style_strings() -> io::Result<()> {
+    let output = common::render_module("conda")
+        .env("CONDA_DEFAULT_ENV", "astronauts")
+        .env("COLORTERM", "truecolor")
+        .use_config(toml::toml! {
+            palette = "mytheme"
+
+            [palettes.mytheme]
+            primary = "#a3be8c"
+
+            [conda]
+            style = "bold primary"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!(
+        "via {} ",
+        Color::RGB(163, 190, 140).bold().paint("C astronauts")
+    );
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn powerline_separators_between_module_backgrounds() -> io::Result<()> {
+    let output = common::render_prompt()
+        .env("CONDA_DEFAULT_ENV", "astronauts")
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "$conda$character"
+            powerline = true
+            powerline_separator = ">"
+
+            [conda]
+            bg = "blue"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = ANSIStrings(&[
+        Color::White.bold().on(Color::Blue).paint("C astronauts"),
+        Color::White.on(Color::Blue).paint(" "),
+        Color::Blue.paint(">"),
+        Color::Green.bold().paint("❯"),
+        Style::new().paint(" "),
+    ])
+    .to_string();
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn ascii_symbol_set() -> io::Result<()> {
+    let output = common::render_module("character")
+        .use_config(toml::toml! {
+            symbol_set = "ascii"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("{} ", Color::Green.bold().paint(">"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn right_prompt_uses_right_format() -> io::Result<()> {
+    let output = common::render_prompt()
+        .arg("--right")
+        .env("CONDA_DEFAULT_ENV", "astronauts")
+        .use_config(toml::toml! {
+            right_format = "$conda"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("{} ", Color::Green.bold().paint("C astronauts"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn right_prompt_is_empty_by_default() -> io::Result<()> {
+    let output = common::render_prompt().arg("--right").output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!("", actual);
+    Ok(())
+}
+
+#[test]
+fn continuation_prompt_default() -> io::Result<()> {
+    let output = common::render_prompt().arg("--continuation").output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("{} ", Color::Fixed(8).paint("∙"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn continuation_prompt_configuration() -> io::Result<()> {
+    let output = common::render_prompt()
+        .arg("--continuation")
+        .use_config(toml::toml! {
+            add_newline = true
+            continuation_format = "[...](bold blue) "
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("{} ", Color::Blue.bold().paint("..."));
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn transient_prompt_is_empty_by_default() -> io::Result<()> {
+    let output = common::render_prompt().arg("--transient").output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!("", actual);
+    Ok(())
+}
+
+#[test]
+fn transient_prompt_configuration() -> io::Result<()> {
+    let config = toml::toml! {
+        transient_format = "[❯](green) "
+        transient_right_format = "$conda"
+    };
+
+    let output = common::render_prompt()
+        .arg("--transient")
+        .env("CONDA_DEFAULT_ENV", "astronauts")
+        .use_config(config.clone())
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("{} ", Color::Green.paint("❯"));
+    assert_eq!(expected, actual);
+
+    let output = common::render_prompt()
+        .arg("--transient")
+        .arg("--right")
+        .env("CONDA_DEFAULT_ENV", "astronauts")
+        .use_config(config)
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("{} ", Color::Green.bold().paint("C astronauts"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn narrow_terminal_shortens_low_priority_modules() -> io::Result<()> {
+    let output = common::render_prompt()
+        .arg("--terminal-width=20")
+        .env("CONDA_DEFAULT_ENV", "astronauts")
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "[abcdef](red)$conda"
+
+            [conda]
+            priority = 1
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = ANSIStrings(&[
+        Color::Red.paint("abcdef"),
+        Color::Green.bold().paint("C astronauts"),
+        Style::new().paint(" "),
+    ])
+    .to_string();
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn narrow_terminal_drops_low_priority_modules() -> io::Result<()> {
+    let output = common::render_prompt()
+        .arg("--terminal-width=10")
+        .env("CONDA_DEFAULT_ENV", "astronauts")
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "[abcdef](red)($conda)[!](blue)"
+
+            [conda]
+            priority = 1
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = ANSIStrings(&[Color::Red.paint("abcdef"), Color::Blue.paint("!")]).to_string();
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn narrow_terminal_keeps_modules_without_priority() -> io::Result<()> {
+    let output = common::render_prompt()
+        .arg("--terminal-width=10")
+        .env("CONDA_DEFAULT_ENV", "astronauts")
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "[abcdef](red)$conda"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!(
+        "{}via {} ",
+        Color::Red.paint("abcdef"),
+        Color::Green.bold().paint("C astronauts")
+    );
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn format_with_multiple_lines() -> io::Result<()> {
+    let output = common::render_prompt()
+        .env("CONDA_DEFAULT_ENV", "astronauts")
+        .use_config(toml::toml! {
+            add_newline = false
+            format = ["$conda", "[>](red) "]
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!(
+        "{} \n{} ",
+        Color::Green.bold().paint("C astronauts"),
+        Color::Red.paint(">")
+    );
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn format_with_escaped_newline() -> io::Result<()> {
+    let output = common::render_prompt()
+        .use_config(toml::toml! {
+            add_newline = false
+            format = r"one\ntwo"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!("one\ntwo", actual);
+    Ok(())
+}
+
+#[test]
+fn prompt_has_semantic_prompt_markers() -> io::Result<()> {
+    let output = common::render_prompt()
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "prompt"
+        })
+        .env("STARSHIP_SHELL", "bash")
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(
+        "\\[\u{1b}]133;A\u{7}\\]prompt\\[\u{1b}]133;B\u{7}\\]",
+        actual
+    );
+    Ok(())
+}
+
+#[test]
+fn right_prompt_has_no_semantic_prompt_markers() -> io::Result<()> {
+    let output = common::render_prompt()
+        .arg("--right")
+        .use_config(toml::toml! {
+            right_format = "right"
+        })
+        .env("STARSHIP_SHELL", "zsh")
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!("right", actual);
+    Ok(())
+}
+
+#[test]
+fn report_cwd() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("a b");
+    std::fs::create_dir(&path)?;
+    let output = common::render_prompt()
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "prompt"
+            report_cwd = true
+        })
+        .arg("--path")
+        .arg(&path)
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let hostname = gethostname::gethostname();
+    assert!(actual.starts_with(&format!("\u{1b}]7;file://{}/", hostname.to_string_lossy())));
+    assert!(actual.ends_with("/a%20b\u{7}prompt"));
+    dir.close()
+}
+
+#[test]
+fn window_title() -> io::Result<()> {
+    let output = common::render_prompt()
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "prompt"
+            window_title = "[title](red) $character"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    assert!(actual.starts_with("\u{1b}]0;title ❯\u{7}"));
+    assert!(actual.ends_with("prompt"));
+    Ok(())
+}
+
+#[test]
+fn iterm2_integration() -> io::Result<()> {
+    let output = common::render_prompt()
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "prompt"
+            iterm2_integration = true
+        })
+        .env("USER", "astronaut")
+        .arg("--path=/tmp")
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let hostname = gethostname::gethostname();
+    let expected = format!(
+        "\u{1b}]1337;ShellIntegrationVersion=5;shell=unknown\u{7}\
+         \u{1b}]1337;RemoteHost=astronaut@{}\u{7}\
+         \u{1b}]1337;CurrentDir=/tmp\u{7}prompt",
+        hostname.to_string_lossy()
+    );
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn print_config_with_defaults() -> io::Result<()> {
+    let output = common::print_config()
+        .arg("directory")
+        .use_config(toml::toml! {
+            [directory]
+            truncation_length = 5
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let config = actual.parse::<toml::Value>().unwrap();
+
+    assert_eq!(
+        config["directory"]["truncation_length"].as_integer(),
+        Some(5)
+    );
+    assert_eq!(config["directory"]["prefix"].as_str(), Some("in "));
+    assert_eq!(config["directory"]["style"].as_str(), Some("bold fg:cyan"));
+    Ok(())
+}
+
+#[test]
+fn print_config_of_everything() -> io::Result<()> {
+    let output = common::print_config()
+        .use_config(toml::toml! {
+            add_newline = false
+
+            [custom.a]
+            command = "echo a"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let config = actual.parse::<toml::Value>().unwrap();
+
+    assert_eq!(config["add_newline"].as_bool(), Some(false));
+    assert_eq!(config["character"]["success_symbol"].as_str(), Some("❯"));
+    assert_eq!(config["custom"]["a"]["command"].as_str(), Some("echo a"));
+    Ok(())
+}
+
+#[test]
+fn config_get_value() -> io::Result<()> {
+    let output = common::config()
+        .arg("directory.truncation_length")
+        .use_config(toml::toml! {
+            [directory]
+            truncation_length = 5
+        })
+        .output()?;
+    assert_eq!("5\n", String::from_utf8(output.stdout).unwrap());
+
+    let output = common::config().arg("directory.prefix").output()?;
+    assert_eq!("in \n", String::from_utf8(output.stdout).unwrap());
+
+    let output = common::config().arg("directory.unknown").output()?;
+    assert!(!output.status.success());
+    Ok(())
+}
+
+#[test]
+fn config_set_value_keeps_comments() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let config_path = dir.path().join("starship.toml");
+    std::fs::write(
+        &config_path,
+        "# My prompt\nadd_newline = false\n\n[directory] # Paths\ntruncation_length = 3\n",
+    )?;
+
+    for (key, value) in &[
+        ("directory.truncation_length", "5"),
+        ("git_branch.symbol", "git "),
+        ("git_status.disabled", "true"),
+    ] {
+        let output = common::config()
+            .arg(key)
+            .arg(value)
+            .env("STARSHIP_CONFIG", &config_path)
+            .output()?;
+        assert!(output.status.success());
+    }
+
+    let expected = "\
+# My prompt
+add_newline = false
+
+[directory] # Paths
+truncation_length = 5
+
+[git_branch]
+symbol = \"git \"
+
+[git_status]
+disabled = true
+";
+    assert_eq!(expected, std::fs::read_to_string(&config_path)?);
+    dir.close()
+}
+
+#[test]
+fn migrate_config_file() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let config_path = dir.path().join("starship.toml");
+    std::fs::write(
+        &config_path,
+        "# My prompt\nadd_newline = false\nprompt_order = [\"directory\", \"character\"]\n\n[directory] # Paths\ntruncation_length = 3\n",
+    )?;
+
+    let output = common::config()
+        .arg("migrate")
+        .env("STARSHIP_CONFIG", &config_path)
+        .output()?;
+    assert!(output.status.success());
+
+    let expected = "\
+# My prompt
+add_newline = false
+format = \"${directory}${character}\"
+
+[directory] # Paths
+truncation_length = 3
+";
+    assert_eq!(expected, std::fs::read_to_string(&config_path)?);
+    dir.close()
+}
+
+#[test]
+fn toggle_module() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let config_path = dir.path().join("starship.toml");
+    std::fs::write(&config_path, "[directory]\ntruncation_length = 5\n")?;
+
+    let output = common::toggle("directory")
+        .env("STARSHIP_CONFIG", &config_path)
+        .output()?;
+    assert!(output.status.success());
+    let expected = "[directory]\ntruncation_length = 5\ndisabled = true\n";
+    assert_eq!(expected, std::fs::read_to_string(&config_path)?);
+
+    let output = common::toggle("directory")
+        .env("STARSHIP_CONFIG", &config_path)
+        .output()?;
+    assert!(output.status.success());
+    let expected = "[directory]\ntruncation_length = 5\ndisabled = false\n";
+    assert_eq!(expected, std::fs::read_to_string(&config_path)?);
+
+    let output = common::toggle("directory")
+        .arg("truncation_length")
+        .env("STARSHIP_CONFIG", &config_path)
+        .output()?;
+    assert!(!output.status.success());
+    dir.close()
+}
+
+#[test]
+fn config_check() -> io::Result<()> {
+    let output = common::config()
+        .arg("--check")
+        .use_config(toml::toml! {
+            add_newline = false
+
+            [directory]
+            truncation_lenght = 5
+            bg = "blue"
+
+            [git_branch]
+            truncation_length = -1
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = "\
+directory.truncation_lenght: unknown option
+git_branch.truncation_length: should be a positive value, found -1
+";
+    assert_eq!(expected, actual);
+    assert!(!output.status.success());
+
+    let output = common::config().arg("--check").output()?;
+    assert!(output.status.success());
+    Ok(())
+}
+
+#[test]
+fn config_schema() -> io::Result<()> {
+    let output = common::config().arg("--schema").output()?;
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert_eq!(schema["properties"]["add_newline"]["type"], "boolean");
+    let directory = &schema["properties"]["directory"]["properties"];
+    assert_eq!(directory["truncation_length"]["default"], 3);
+    assert_eq!(directory["prefix"]["type"], "string");
+    let custom = &schema["properties"]["custom"]["additionalProperties"]["properties"];
+    assert_eq!(custom["command"]["type"], "string");
+    Ok(())
+}
+
+#[test]
+fn profile_writes_trace() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let trace_path = dir.path().join("starship.trace.json");
+    common::render_prompt()
+        .arg("--profile")
+        .arg(&trace_path)
+        .output()?;
+    let trace = std::fs::read_to_string(&trace_path)?;
+
+    assert!(trace.contains(r#""traceEvents":["#));
+    assert!(trace.contains(r#""cat":"module""#));
+    dir.close()
+}
+
+#[test]
+fn env_overrides_config() -> io::Result<()> {
+    let output = common::render_prompt()
+        .env("STARSHIP_CONFIG_ADD_NEWLINE", "false")
+        .env("STARSHIP_CONFIG_FORMAT", "${custom.a}")
+        .env("STARSHIP_CONFIG_CUSTOM_A_COMMAND", "echo b")
+        .use_config(toml::toml! {
+            [custom.a]
+            command = "echo a"
+            when = "true"
+            style = "red"
+            prefix = ""
+            suffix = ""
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("{}", Color::Red.paint("b"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+fn create_project_repo() -> io::Result<tempfile::TempDir> {
+    let repo_dir = tempfile::tempdir()?;
+    std::process::Command::new("git")
+        .arg("init")
+        .arg("--quiet")
+        .current_dir(repo_dir.path())
+        .output()?;
+    std::fs::write(
+        repo_dir.path().join(".starship.toml"),
+        "[custom.a]\ncommand = \"echo b\"\n",
+    )?;
+    Ok(repo_dir)
+}
+
+fn project_config() -> toml::Value {
+    toml::toml! {
+        add_newline = false
+        format = "${custom.a}"
+
+        [custom.a]
+        command = "echo a"
+        when = "true"
+        style = "red"
+        prefix = ""
+        suffix = ""
+    }
+}
+
+#[test]
+fn trusted_project_config() -> io::Result<()> {
+    let repo_dir = create_project_repo()?;
+
+    let output = common::render_prompt()
+        .arg("--path")
+        .arg(repo_dir.path())
+        .env("STARSHIP_TRUST_PROJECTS", "1")
+        .use_config(project_config())
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("{}", Color::Red.paint("b"));
+    assert_eq!(expected, actual);
+    repo_dir.close()
+}
+
+#[test]
+fn untrusted_project_config() -> io::Result<()> {
+    let repo_dir = create_project_repo()?;
+
+    let output = common::render_prompt()
+        .arg("--path")
+        .arg(repo_dir.path())
+        .use_config(project_config())
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("{}", Color::Red.paint("a"));
+    assert_eq!(expected, actual);
+    repo_dir.close()
+}
+
+#[test]
+fn project_config_palette() -> io::Result<()> {
+    let repo_dir = create_project_repo()?;
+    std::fs::write(
+        repo_dir.path().join(".starship.toml"),
+        "[palettes.theme]\nprimary = \"blue\"\n",
+    )?;
+
+    let output = common::render_prompt()
+        .arg("--path")
+        .arg(repo_dir.path())
+        .env("STARSHIP_TRUST_PROJECTS", "1")
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "${custom.a}"
+            palette = "theme"
+
+            [palettes.theme]
+            primary = "red"
+
+            [custom.a]
+            command = "echo a"
+            when = "true"
+            style = "primary"
+            prefix = ""
+            suffix = ""
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("{}", Color::Blue.paint("a"));
+    assert_eq!(expected, actual);
+    repo_dir.close()
+}
+
+/// A directory with a Crystal file and a `bin/crystal` that prints a version
+#[cfg(unix)]
+fn create_crystal_dir() -> io::Result<tempfile::TempDir> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir()?;
+    std::fs::create_dir(dir.path().join("bin"))?;
+    let crystal = dir.path().join("bin/crystal");
+    std::fs::write(&crystal, "#!/bin/sh\necho 'Crystal 0.35.1 (2020-06-19)'\n")?;
+    std::fs::set_permissions(&crystal, std::fs::Permissions::from_mode(0o755))?;
+    std::fs::File::create(dir.path().join("main.cr"))?.sync_all()?;
+    Ok(dir)
+}
+
+#[test]
+#[cfg(unix)]
+fn commands_inside_repository() -> io::Result<()> {
+    let repo_dir = create_crystal_dir()?;
+    std::process::Command::new("git")
+        .arg("init")
+        .arg("--quiet")
+        .current_dir(repo_dir.path())
+        .output()?;
+    let path = repo_dir.path().join("bin");
+
+    let output = common::render_module("crystal")
+        .arg("--path")
+        .arg(repo_dir.path())
+        .env("PATH", &path)
+        .output()?;
+    assert_eq!("", String::from_utf8(output.stdout).unwrap());
+
+    let output = common::render_module("crystal")
+        .arg("--path")
+        .arg(repo_dir.path())
+        .env("PATH", &path)
+        .env("STARSHIP_TRUST_PROJECTS", "1")
+        .output()?;
+    let expected = format!("via {} ", Color::Red.bold().paint("🔮 v0.35.1"));
+    assert_eq!(expected, String::from_utf8(output.stdout).unwrap());
+    repo_dir.close()
+}
+
+#[test]
+#[cfg(unix)]
+fn strict_commands() -> io::Result<()> {
+    let dir = create_crystal_dir()?;
+
+    let output = common::render_module("crystal")
+        .arg("--path")
+        .arg(dir.path())
+        .current_dir(dir.path())
+        .env("PATH", "bin")
+        .output()?;
+    let expected = format!("via {} ", Color::Red.bold().paint("🔮 v0.35.1"));
+    assert_eq!(expected, String::from_utf8(output.stdout).unwrap());
+
+    let output = common::render_module("crystal")
+        .arg("--path")
+        .arg(dir.path())
+        .current_dir(dir.path())
+        .env("PATH", "bin")
+        .use_config(toml::toml! {
+            strict_commands = true
+        })
+        .output()?;
+    assert_eq!("", String::from_utf8(output.stdout).unwrap());
+    dir.close()
+}
+
+#[test]
+fn profiles_overlay_config() -> io::Result<()> {
+    let config = toml::toml! {
+        add_newline = false
+        format = "${custom.a}"
+
+        [custom.a]
+        command = "echo a"
+        when = "true"
+        style = "red"
+        prefix = ""
+        suffix = ""
+
+        [profiles.minimal]
+        format = "${custom.a}>"
+
+        [profiles.minimal.custom.a]
+        command = "echo b"
+    };
+
+    let output = common::render_prompt()
+        .use_config(config.clone())
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("{}", Color::Red.paint("a"));
+    assert_eq!(expected, actual);
+
+    let expected = format!("{}>", Color::Red.paint("b"));
+    let output = common::render_prompt()
+        .arg("--profile-name=minimal")
+        .use_config(config.clone())
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(expected, actual);
+
+    let output = common::render_prompt()
+        .env("STARSHIP_PROFILE_NAME", "minimal")
+        .use_config(config)
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn when_condition_configuration() -> io::Result<()> {
+    let config = toml::toml! {
+        add_newline = false
+        format = "$conda"
+
+        [conda.when]
+        env = "STARSHIP_WHEN=infra"
+        command = "true"
+    };
+
+    let output = common::render_prompt()
+        .env("CONDA_DEFAULT_ENV", "astronauts")
+        .env("STARSHIP_WHEN", "infra")
+        .use_config(config.clone())
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("{} ", Color::Green.bold().paint("C astronauts"));
+    assert_eq!(expected, actual);
+
+    let output = common::render_prompt()
+        .env("CONDA_DEFAULT_ENV", "astronauts")
+        .env("STARSHIP_WHEN", "docs")
+        .use_config(config)
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!("", actual);
+    Ok(())
+}
+
+#[test]
+fn max_width_configuration() -> io::Result<()> {
+    let output = common::render_module("conda")
+        .env("CONDA_DEFAULT_ENV", "astronauts")
+        .use_config(toml::toml! {
+            [conda]
+            max_width = 6
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("via {} ", Color::Green.bold().paint("C ast…"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn style_rules_configuration() -> io::Result<()> {
+    let config = toml::toml! {
+        [conda.style_rules]
+        "^production$" = "bold red"
+        "prod" = "underline"
+    };
+
+    let output = common::render_module("conda")
+        .env("CONDA_DEFAULT_ENV", "production")
+        .use_config(config.clone())
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("via {} ", Color::Red.bold().paint("C production"));
+    assert_eq!(expected, actual);
+
+    let output = common::render_module("conda")
+        .env("CONDA_DEFAULT_ENV", "astronauts")
+        .use_config(config)
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("via {} ", Color::Green.bold().paint("C astronauts"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn json_prompt_output() -> io::Result<()> {
+    let output = common::render_prompt()
+        .arg("--format=json")
+        .env("CONDA_DEFAULT_ENV", "astronauts")
+        .use_config(toml::toml! {
+            format = "$conda>"
+        })
+        .output()?;
+    let actual: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let expected = serde_json::json!([
+        { "text": "C ", "style": "bold fg:green", "module": "conda" },
+        { "text": "astronauts", "style": "bold fg:green", "module": "conda" },
+        { "text": " ", "style": "", "module": "conda" },
+        { "text": ">", "style": "", "module": null },
+    ]);
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn cached_prompt() -> io::Result<()> {
+    let cache = tempfile::tempdir()?;
+    let value = cache.path().join("value");
+    let mut config = toml::toml! {
+        add_newline = false
+        prompt_cache_ttl = 60
+        format = "$custom"
+
+        [custom.value]
+        when = "true"
+        style = "red"
+        prefix = ""
+        suffix = ""
+    };
+    config["custom"]["value"].as_table_mut().unwrap().insert(
+        String::from("command"),
+        toml::Value::from(format!("cat {}", value.display())),
+    );
+    let render = |mode: &str| -> io::Result<String> {
+        let output = common::render_prompt()
+            .env("STARSHIP_CACHE", cache.path())
+            .env("MODE", mode)
+            .use_config(config.clone())
+            .output()?;
+        Ok(String::from_utf8(output.stdout).unwrap())
+    };
+
+    fs::write(&value, "a")?;
+    assert_eq!(Color::Red.paint("a").to_string(), render("1")?);
+    // The prompt is shown from the cache, and rendered again in the background
+    fs::write(&value, "b")?;
+    assert_eq!(Color::Red.paint("a").to_string(), render("1")?);
+    let mut actual = String::new();
+    for _ in 0..50 {
+        actual = render("1")?;
+        if actual == Color::Red.paint("b").to_string() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    assert_eq!(Color::Red.paint("b").to_string(), actual);
+
+    // Another environment renders the prompt anew
+    fs::write(&value, "c")?;
+    assert_eq!(Color::Red.paint("c").to_string(), render("2")?);
+    cache.close()
+}
+
+#[test]
+fn modules_prompt_output() -> io::Result<()> {
+    let output = common::render_prompt()
+        .arg("--modules=conda, aws")
+        .env("CONDA_DEFAULT_ENV", "astronauts")
+        .env("AWS_REGION", "eu-west-1")
+        .use_config(toml::toml! {
+            format = "$all"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!(
+        "{} on {} ",
+        Color::Green.bold().paint("C astronauts"),
+        Color::Yellow.bold().paint("☁️  eu-west-1")
+    );
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn plugin_module() -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir()?;
+    let plugin = dir.path().join("starship-plugin-echo");
+    std::fs::write(
+        &plugin,
+        "#!/bin/sh\n\
+         grep -q '\"greeting\":\"hi\"' || exit 1\n\
+         echo '{\"segments\": [{\"text\": \"hi\", \"style\": \"bold red\"}]}'\n",
+    )?;
+    std::fs::set_permissions(&plugin, std::fs::Permissions::from_mode(0o755))?;
+    let path = std::env::join_paths(vec![
+        dir.path().to_path_buf(),
+        "/bin".into(),
+        "/usr/bin".into(),
+    ])
+    .unwrap();
+
+    let output = common::render_prompt()
+        .env("PATH", &path)
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "$plugin>"
+
+            [plugin.echo]
+            greeting = "hi"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("{} >", Color::Red.bold().paint("hi"));
+    assert_eq!(expected, actual);
+
+    let output = common::render_module("plugin.echo")
+        .env("PATH", &path)
+        .output()?;
+    assert_eq!("", String::from_utf8(output.stdout).unwrap());
+    dir.close()
+}
+
+/// A WebAssembly plugin that outputs the given JSON
+fn wasm_plugin(output: &str) -> Vec<u8> {
+    wat::parse_str(format!(
+        r#"
+        (module
+            (memory (export "memory") 1)
+            (data (i32.const 0) "{}")
+            (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+            (func (export "render") (param i32 i32) (result i64) (i64.const {}))
+        )
+        "#,
+        output.replace('"', "\\\""),
+        output.len()
+    ))
+    .unwrap()
+}
+
+#[test]
+#[cfg(unix)]
+fn plugins_directory() -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir()?;
+    std::fs::write(
+        dir.path().join("hello.wasm"),
+        wasm_plugin(r#"{"segments": [{"text": "hello"}]}"#),
+    )?;
+    // Only WebAssembly modules are plugins, as they run in a sandbox
+    let script = dir.path().join("script");
+    std::fs::write(
+        &script,
+        "#!/bin/sh\necho '{\"segments\": [{\"text\": \"script\"}]}'\n",
+    )?;
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))?;
+
+    let output = common::render_module("plugin.hello")
+        .env("STARSHIP_PLUGINS", dir.path())
+        .output()?;
+    assert_eq!("via hello ", String::from_utf8(output.stdout).unwrap());
+
+    let output = common::render_module("plugin.script")
+        .env("STARSHIP_PLUGINS", dir.path())
+        .output()?;
+    assert_eq!("", String::from_utf8(output.stdout).unwrap());
+    dir.close()
+}
@@ -0,0 +1,114 @@
+use ansi_term::Color;
+use std::fs;
+use std::io;
+
+use crate::common;
+use crate::common::TestCommand;
+
+const PROFILES: &str = "\
+jaffle_shop:
+  target: dev
+  outputs:
+    dev:
+      type: postgres
+      host: localhost
+    prod:
+      type: snowflake
+      account: abc123
+";
+
+#[test]
+fn folder_without_dbt_project() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    fs::write(dir.path().join("profiles.yml"), PROFILES)?;
+
+    let output = common::render_module("dbt")
+        .arg("--path")
+        .arg(dir.path())
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = "";
+    assert_eq!(expected, actual);
+    dir.close()
+}
+
+#[test]
+fn target_of_profile() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    fs::write(
+        dir.path().join("dbt_project.yml"),
+        "name: jaffle_shop\nprofile: 'jaffle_shop'\n",
+    )?;
+    fs::write(dir.path().join("profiles.yml"), PROFILES)?;
+    let models = dir.path().join("models");
+    fs::create_dir(&models)?;
+
+    let output = common::render_module("dbt")
+        .arg("--path")
+        .arg(&models)
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!(
+        "via {} ",
+        Color::Fixed(208).bold().paint("🟧 jaffle_shop (dev)")
+    );
+    assert_eq!(expected, actual);
+    dir.close()
+}
+
+#[test]
+fn target_from_env() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let profiles_dir = tempfile::tempdir()?;
+    fs::write(dir.path().join("dbt_project.yml"), "profile: jaffle_shop\n")?;
+    fs::write(profiles_dir.path().join("profiles.yml"), PROFILES)?;
+
+    let output = common::render_module("dbt")
+        .arg("--path")
+        .arg(dir.path())
+        .env("DBT_PROFILES_DIR", profiles_dir.path())
+        .env("DBT_TARGET", "prod")
+        .use_config(toml::toml! {
+            [dbt]
+            format = "$symbol$profile \\($target on $type\\)"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!(
+        "via {} ",
+        Color::Fixed(208)
+            .bold()
+            .paint("🟧 jaffle_shop (prod on snowflake)")
+    );
+    assert_eq!(expected, actual);
+    profiles_dir.close()?;
+    dir.close()
+}
+
+#[test]
+fn target_from_jinja_env_var() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    fs::write(dir.path().join("dbt_project.yml"), "profile: jaffle_shop\n")?;
+    let profiles = PROFILES.replace(
+        "target: dev",
+        "target: \"{{ env_var('JAFFLE_TARGET', 'dev') }}\"",
+    );
+    fs::write(dir.path().join("profiles.yml"), profiles)?;
+
+    let render = |target: Option<&str>| -> io::Result<String> {
+        let mut command = common::render_module("dbt");
+        command.arg("--path").arg(dir.path());
+        if let Some(target) = target {
+            command.env("JAFFLE_TARGET", target);
+        }
+        Ok(String::from_utf8(command.output()?.stdout).unwrap())
+    };
+
+    let paint = |text| format!("via {} ", Color::Fixed(208).bold().paint(text));
+    assert_eq!(paint("🟧 jaffle_shop (dev)"), render(None)?);
+    assert_eq!(paint("🟧 jaffle_shop (prod)"), render(Some("prod"))?);
+    dir.close()
+}
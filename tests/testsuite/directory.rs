@@ -1,4 +1,4 @@
-use ansi_term::Color;
+use ansi_term::{Color, Style};
 use dirs::home_dir;
 use git2::Repository;
 use std::fs;
@@ -87,6 +87,10 @@ fn fish_directory_in_home() -> io::Result<()> {
 fn root_directory() -> io::Result<()> {
     let output = common::render_module("directory")
         .arg("--path=/")
+        .use_config(toml::toml! {
+            [directory]
+            check_read_only = false
+        })
         .output()?;
     let actual = String::from_utf8(output.stdout).unwrap();
 
@@ -102,6 +106,7 @@ fn test_prefix() -> io::Result<()> {
         .use_config(toml::toml! {
             [directory]
             prefix = "sample "
+            check_read_only = false
         })
         .output()?;
     let actual = String::from_utf8(output.stdout).unwrap();
@@ -111,11 +116,186 @@ fn test_prefix() -> io::Result<()> {
     Ok(())
 }
 
+#[test]
+#[cfg(unix)]
+fn non_utf8_directory() -> io::Result<()> {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let tmp_dir = TempDir::new()?;
+    let dir = tmp_dir.path().join(OsStr::from_bytes(b"caf\xe9"));
+    fs::create_dir(&dir)?;
+
+    let output = common::render_module("directory")
+        .arg("--path")
+        .arg(&dir)
+        .use_config(toml::toml! {
+            [directory]
+            truncation_length = 1
+            check_read_only = false
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("in {} ", Color::Cyan.bold().paint("caf\u{fffd}"));
+    assert_eq!(expected, actual);
+    tmp_dir.close()
+}
+
+#[test]
+#[cfg(unix)]
+fn logical_and_physical_path() -> io::Result<()> {
+    let tmp_dir = TempDir::new()?;
+    let physical = tmp_dir.path().join("big");
+    fs::create_dir(&physical)?;
+    let logical = tmp_dir.path().join("work");
+    std::os::unix::fs::symlink(&physical, &logical)?;
+
+    let output = common::render_module("directory")
+        .arg("--logical-path")
+        .arg(&logical)
+        .use_config(toml::toml! {
+            [directory]
+            truncation_length = 1
+            check_read_only = false
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("in {} ", Color::Cyan.bold().paint("work"));
+    assert_eq!(expected, actual);
+
+    let output = common::render_module("directory")
+        .arg("--path")
+        .arg(&logical)
+        .arg("--logical-path")
+        .arg(&logical)
+        .use_config(toml::toml! {
+            [directory]
+            truncation_length = 1
+            check_read_only = false
+            use_logical_path = false
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("in {} ", Color::Cyan.bold().paint("big"));
+    assert_eq!(expected, actual);
+    tmp_dir.close()
+}
+
+#[test]
+fn directory_in_hg_repo() -> io::Result<()> {
+    let tmp_dir = TempDir::new()?;
+    let repo_dir = tmp_dir.path().join("rocket").join("controls");
+    let dir = repo_dir.join("src").join("meters");
+    fs::create_dir_all(repo_dir.join(".hg"))?;
+    fs::create_dir_all(&dir)?;
+
+    let output = common::render_module("directory")
+        .arg("--path")
+        .arg(&dir)
+        .use_config(toml::toml! {
+            [directory]
+            truncation_length = 5
+            check_read_only = false
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("in {} ", Color::Cyan.bold().paint("controls/src/meters"));
+    assert_eq!(expected, actual);
+    tmp_dir.close()
+}
+
+#[test]
+fn directory_with_repo_root_style() -> io::Result<()> {
+    let tmp_dir = TempDir::new()?;
+    let repo_dir = tmp_dir.path().join("rocket").join("controls");
+    let dir = repo_dir.join("src").join("meters");
+    fs::create_dir_all(repo_dir.join(".hg"))?;
+    fs::create_dir_all(&dir)?;
+
+    let output = common::render_module("directory")
+        .arg("--path")
+        .arg(&dir)
+        .use_config(toml::toml! {
+            [directory]
+            style = "dimmed"
+            repo_root_style = "bold blue"
+            check_read_only = false
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!(
+        "in {}{} ",
+        Color::Blue.bold().paint("controls"),
+        Style::new().dimmed().paint("/src/meters")
+    );
+    assert_eq!(expected, actual);
+    tmp_dir.close()
+}
+
+#[test]
+#[cfg(not(target_os = "windows"))]
+fn directory_with_home_symbol() -> io::Result<()> {
+    let tmp_dir = TempDir::new()?;
+    let dir = tmp_dir.path().join("starship");
+    fs::create_dir_all(&dir)?;
+
+    let output = common::render_module("directory")
+        .use_config(toml::toml! {
+            [directory]
+            home_symbol = "🏠"
+            check_read_only = false
+        })
+        .env("HOME", tmp_dir.path())
+        .arg("--path")
+        .arg(&dir)
+        .arg("--logical-path")
+        .arg(&dir)
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("in {} ", Color::Cyan.bold().paint("🏠/starship"));
+    assert_eq!(expected, actual);
+    tmp_dir.close()
+}
+
+#[test]
+#[cfg(unix)]
+fn directory_in_symlinked_git_repo() -> io::Result<()> {
+    let tmp_dir = TempDir::new()?;
+    let repo_dir = tmp_dir.path().join("big").join("rocket-controls");
+    fs::create_dir_all(repo_dir.join("src"))?;
+    std::process::Command::new("git")
+        .arg("init")
+        .arg("--quiet")
+        .current_dir(&repo_dir)
+        .output()?;
+    let work = tmp_dir.path().join("work");
+    std::os::unix::fs::symlink(tmp_dir.path().join("big"), &work)?;
+
+    let output = common::render_module("directory")
+        .arg("--path")
+        .arg(work.join("rocket-controls").join("src"))
+        .use_config(toml::toml! {
+            [directory]
+            truncation_length = 5
+            check_read_only = false
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("in {} ", Color::Cyan.bold().paint("rocket-controls/src"));
+    assert_eq!(expected, actual);
+    tmp_dir.close()
+}
+
 #[test]
 #[cfg(not(target_os = "windows"))]
 fn directory_in_root() -> io::Result<()> {
     let output = common::render_module("directory")
         .arg("--path=/etc")
+        .use_config(toml::toml! {
+            [directory]
+            check_read_only = false
+        })
         .output()?;
     let actual = String::from_utf8(output.stdout).unwrap();
 
@@ -129,6 +309,10 @@ fn directory_in_root() -> io::Result<()> {
 fn directory_in_root() -> io::Result<()> {
     let output = common::render_module("directory")
         .arg("--path=C:\\")
+        .use_config(toml::toml! {
+            [directory]
+            check_read_only = false
+        })
         .output()?;
     let actual = String::from_utf8(output.stdout).unwrap();
 
@@ -137,6 +321,55 @@ fn directory_in_root() -> io::Result<()> {
     Ok(())
 }
 
+#[test]
+fn substituted_directory() -> io::Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let storage = tmp_dir.path().join("mnt/storage/projects");
+    let dir = storage.join("rocket/src");
+    fs::create_dir_all(&dir)?;
+
+    let config = format!(
+        "[directory.substitutions]\n{:?} = \"📦\"",
+        storage.to_string_lossy()
+    );
+    let output = common::render_module("directory")
+        .use_config(toml::from_str(&config).unwrap())
+        .arg("--path")
+        .arg(&dir)
+        .arg("--logical-path")
+        .arg(&dir)
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("in {} ", Color::Cyan.bold().paint("📦/rocket/src"));
+    assert_eq!(expected, actual);
+    tmp_dir.close()
+}
+
+#[test]
+fn fish_style_substituted_directory() -> io::Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let storage = tmp_dir.path().join("mnt/storage/projects");
+    let dir = storage.join("rocket/engine/src");
+    fs::create_dir_all(&dir)?;
+
+    let config = format!(
+        "[directory]\ntruncation_length = 1\nfish_style_pwd_dir_length = 2\n\
+         [directory.substitutions]\n{:?} = \"📦\"",
+        storage.to_string_lossy()
+    );
+    let output = common::render_module("directory")
+        .use_config(toml::from_str(&config).unwrap())
+        .arg("--path")
+        .arg(&dir)
+        .arg("--logical-path")
+        .arg(&dir)
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = format!("in {} ", Color::Cyan.bold().paint("📦/ro/en/src"));
+    assert_eq!(expected, actual);
+    tmp_dir.close()
+}
+
 #[test]
 #[ignore]
 fn truncated_directory_in_root() -> io::Result<()> {
@@ -467,3 +700,82 @@ fn git_repo_in_home_directory_truncate_to_repo_true() -> io::Result<()> {
     assert_eq!(expected, actual);
     tmp_dir.close()
 }
+
+#[test]
+#[cfg(not(target_os = "windows"))]
+fn read_only_directory() -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    // root is allowed to write anywhere, so the lock would never be shown
+    if nix::unistd::geteuid().is_root() {
+        return Ok(());
+    }
+
+    let tmp_dir = TempDir::new()?;
+    let dir = tmp_dir.path().join("locked");
+    fs::create_dir(&dir)?;
+    fs::set_permissions(&dir, fs::Permissions::from_mode(0o555))?;
+
+    let output = common::render_module("directory")
+        .arg("--path")
+        .arg(&dir)
+        .use_config(toml::toml! {
+            [directory]
+            truncation_length = 1
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    fs::set_permissions(&dir, fs::Permissions::from_mode(0o755))?;
+
+    let expected = format!(
+        "in {}{} ",
+        Color::Cyan.bold().paint("locked"),
+        Color::Red.paint("🔒")
+    );
+    assert_eq!(expected, actual);
+    tmp_dir.close()
+}
+
+#[test]
+fn writable_directory() -> io::Result<()> {
+    let tmp_dir = TempDir::new()?;
+    let dir = tmp_dir.path().join("writable");
+    fs::create_dir(&dir)?;
+
+    let output = common::render_module("directory")
+        .arg("--path")
+        .arg(&dir)
+        .use_config(toml::toml! {
+            [directory]
+            truncation_length = 1
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("in {} ", Color::Cyan.bold().paint("writable"));
+    assert_eq!(expected, actual);
+    tmp_dir.close()
+}
+
+#[test]
+fn hyperlinked_directory() -> io::Result<()> {
+    let output = common::render_prompt()
+        .arg("--path=/")
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "$directory"
+            [directory]
+            hyperlink = true
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let hostname = gethostname::gethostname();
+    let expected = format!(
+        "\u{1b}]8;;file://{}/\u{7}{}\u{1b}]8;;\u{7} ",
+        hostname.to_string_lossy(),
+        Color::Cyan.bold().paint("/")
+    );
+    assert_eq!(expected, actual);
+    Ok(())
+}
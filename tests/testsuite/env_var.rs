@@ -135,6 +135,89 @@ fn suffix() -> io::Result<()> {
     Ok(())
 }
 
+#[test]
+fn redact() -> io::Result<()> {
+    let output = common::render_module("env_var")
+        .env_clear()
+        .use_config(toml::toml! {
+            [env_var]
+            variable = "TEST_VAR"
+            redact = "mask"
+        })
+        .env("TEST_VAR", TEST_VAR_VALUE)
+        .output()?;
+    let expected = format!("with {} ", style().paint("****"));
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn instances() -> io::Result<()> {
+    let config = toml::toml! {
+        add_newline = false
+        format = "${env_var}${env_var.TENANT}"
+
+        [env_var.ENVIRONMENT]
+        style = "red"
+
+        [env_var.TENANT]
+        variable = "TEST_TENANT"
+        default = "none"
+        style = "blue"
+    };
+
+    let output = common::render_prompt()
+        .use_config(config.clone())
+        .env("ENVIRONMENT", "staging")
+        .output()?;
+    let expected = format!(
+        "{} with {} ",
+        Color::Red.paint("staging"),
+        Color::Blue.paint("none")
+    );
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(expected, actual);
+
+    let output = common::render_module("env_var.TENANT")
+        .use_config(config)
+        .env("TEST_TENANT", TEST_VAR_VALUE)
+        .output()?;
+    let expected = format!("with {} ", Color::Blue.paint(TEST_VAR_VALUE));
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn when_expression() -> io::Result<()> {
+    let config = toml::toml! {
+        [env_var]
+        variable = "TEST_VAR"
+        when.expression = "$STAGE == 'prod'"
+    };
+
+    let output = common::render_module("env_var")
+        .env_clear()
+        .use_config(config.clone())
+        .env("TEST_VAR", TEST_VAR_VALUE)
+        .env("STAGE", "prod")
+        .output()?;
+    let expected = format!("with {} ", style().paint(TEST_VAR_VALUE));
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(expected, actual);
+
+    let output = common::render_module("env_var")
+        .env_clear()
+        .use_config(config)
+        .env("TEST_VAR", TEST_VAR_VALUE)
+        .env("STAGE", "dev")
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!("", actual);
+    Ok(())
+}
+
 fn style() -> Style {
     // default style
     Color::Black.bold().dimmed()
@@ -0,0 +1,70 @@
+use ansi_term::{Color, Style};
+use std::io;
+
+use crate::common::{self, TestCommand};
+
+#[test]
+fn fill_to_terminal_width() -> io::Result<()> {
+    let output = common::render_prompt()
+        .arg("--terminal-width=10")
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "ab$fill[c](red)"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!(
+        "ab{}{}",
+        Color::Black.bold().paint("......."),
+        Color::Red.paint("c")
+    );
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn fill_on_each_line() -> io::Result<()> {
+    let output = common::render_prompt()
+        .arg("--terminal-width=6")
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "a${fill}b$line_break$fill[c](red)$fill"
+
+            [fill]
+            symbol = "-"
+            style = "blue"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!(
+        "a{}b\n{}{}{}",
+        Color::Blue.paint("----"),
+        Color::Blue.paint("---"),
+        Color::Red.paint("c"),
+        Color::Blue.paint("--")
+    );
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn fill_with_wide_symbol() -> io::Result<()> {
+    let output = common::render_prompt()
+        .arg("--terminal-width=5")
+        .use_config(toml::toml! {
+            add_newline = false
+            format = "$fill"
+
+            [fill]
+            symbol = "🚀"
+            style = ""
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = Style::new().paint("🚀🚀 ").to_string();
+    assert_eq!(expected, actual);
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use ansi_term::{Color, Style};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::common;
+use crate::common::TestCommand;
+
+fn write_configuration(dir: &Path, name: &str, contents: &str) -> io::Result<()> {
+    let configurations = dir.join("configurations");
+    fs::create_dir_all(&configurations)?;
+    fs::write(configurations.join(format!("config_{}", name)), contents)
+}
+
+#[test]
+fn no_configuration() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let output = common::render_module("gcloud")
+        .env("CLOUDSDK_CONFIG", dir.path())
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = "";
+    assert_eq!(expected, actual);
+    dir.close()
+}
+
+#[test]
+fn active_configuration() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    write_configuration(
+        dir.path(),
+        "default",
+        "[core]\naccount = other@example.com\n",
+    )?;
+    write_configuration(
+        dir.path(),
+        "astronauts",
+        "[core]\naccount = astronaut@example.com\nproject = starship-123456\n",
+    )?;
+    fs::write(dir.path().join("active_config"), "astronauts")?;
+
+    let output = common::render_module("gcloud")
+        .env("CLOUDSDK_CONFIG", dir.path())
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!(
+        "on {} ",
+        Color::Blue
+            .bold()
+            .paint("☁️ astronaut@example.com(starship-123456)")
+    );
+    assert_eq!(expected, actual);
+    dir.close()
+}
+
+#[test]
+fn env_overrides_and_aliases() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    write_configuration(
+        dir.path(),
+        "default",
+        "[core]\naccount = astronaut@example.com\nproject = starship-123456\n\n[compute]\nregion = europe-west1\n",
+    )?;
+
+    let output = common::render_module("gcloud")
+        .env("CLOUDSDK_CONFIG", dir.path())
+        .env("CLOUDSDK_CORE_PROJECT", "starship-build-654321")
+        .env("CLOUDSDK_COMPUTE_ZONE", "us-central1-a")
+        .use_config(toml::toml! {
+            [gcloud]
+            format = "$symbol$project [$region/$zone](dimmed)"
+            [gcloud.project_aliases]
+            starship-build-654321 = "build"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!(
+        "on {}{} ",
+        Color::Blue.bold().paint("☁️ build "),
+        Style::new().dimmed().paint("europe-west1/us-central1-a")
+    );
+    assert_eq!(expected, actual);
+    dir.close()
+}
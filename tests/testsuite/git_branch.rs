@@ -95,7 +95,48 @@ fn test_hindi_truncation2() -> io::Result<()> {
 
 #[test]
 fn test_japanese_truncation() -> io::Result<()> {
-    test_truncate_length("がんばってね", 4, "がんばっ", "…")
+    test_truncate_length("がんばってね", 4, "がん", "…")
+}
+
+#[test]
+fn test_japanese_truncation_between_columns() -> io::Result<()> {
+    test_truncate_length("がんばってね", 5, "がん", "…")
+}
+
+#[test]
+fn test_emoji_zwj_truncation() -> io::Result<()> {
+    test_truncate_length("👩‍🚀-launch", 3, "👩‍🚀-", "…")
+}
+
+#[test]
+fn test_combining_mark_truncation() -> io::Result<()> {
+    test_truncate_length("e\u{301}toile", 2, "e\u{301}t", "…")
+}
+
+#[test]
+#[cfg(unix)]
+fn test_non_utf8_branch_name() -> io::Result<()> {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let repo_dir = common::create_fixture_repo()?;
+
+    Command::new("git")
+        .arg("checkout")
+        .arg("-b")
+        .arg(OsStr::from_bytes(b"caf\xe9"))
+        .current_dir(repo_dir.as_path())
+        .output()?;
+
+    let output = common::render_module("git_branch")
+        .arg("--path")
+        .arg(&repo_dir)
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("on {} ", Color::Purple.bold().paint("\u{e0a0} caf\u{fffd}"),);
+    assert_eq!(expected, actual);
+    remove_dir_all(repo_dir)
 }
 
 #[test]
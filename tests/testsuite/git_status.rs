@@ -546,6 +546,98 @@ fn suffix() -> io::Result<()> {
     remove_dir_all(repo_dir)
 }
 
+#[test]
+#[ignore]
+fn shows_too_large() -> io::Result<()> {
+    let repo_dir = common::create_fixture_repo()?;
+
+    create_untracked(&repo_dir)?;
+
+    let output = common::render_module("git_status")
+        .arg("--path")
+        .arg(&repo_dir)
+        .use_config(toml::toml! {
+            [git_status]
+            max_files = 1
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = Color::Red.bold().paint(format!("[{}] ", "…")).to_string();
+
+    assert_eq!(expected, actual);
+
+    remove_dir_all(repo_dir)
+}
+
+#[test]
+#[ignore]
+fn shows_too_large_after_timeout() -> io::Result<()> {
+    let repo_dir = common::create_fixture_repo()?;
+
+    create_untracked(&repo_dir)?;
+
+    let output = common::render_module("git_status")
+        .arg("--path")
+        .arg(&repo_dir)
+        .use_config(toml::toml! {
+            [git_status]
+            timeout = 0
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = Color::Red.bold().paint(format!("[{}] ", "…")).to_string();
+
+    assert_eq!(expected, actual);
+
+    remove_dir_all(repo_dir)
+}
+
+#[test]
+#[ignore]
+fn shows_status_without_timeout() -> io::Result<()> {
+    let repo_dir = common::create_fixture_repo()?;
+
+    create_untracked(&repo_dir)?;
+
+    let output = common::render_module("git_status")
+        .arg("--path")
+        .arg(&repo_dir)
+        .use_config(toml::toml! {
+            command_timeout = 0
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = Color::Red.bold().paint(format!("[{}] ", "?")).to_string();
+
+    assert_eq!(expected, actual);
+
+    remove_dir_all(repo_dir)
+}
+
+#[test]
+#[ignore]
+fn shows_status_in_unlimited_repo() -> io::Result<()> {
+    let repo_dir = common::create_fixture_repo()?;
+
+    create_untracked(&repo_dir)?;
+
+    let config = format!(
+        "[git_status]\nmax_files = 1\nunlimited_repos = [{:?}]",
+        repo_dir.to_string_lossy()
+    );
+    let output = common::render_module("git_status")
+        .arg("--path")
+        .arg(&repo_dir)
+        .use_config(toml::from_str(&config).unwrap())
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = Color::Red.bold().paint(format!("[{}] ", "?")).to_string();
+
+    assert_eq!(expected, actual);
+
+    remove_dir_all(repo_dir)
+}
+
 fn ahead(repo_dir: &PathBuf) -> io::Result<()> {
     File::create(repo_dir.join("readme.md"))?.sync_all()?;
 
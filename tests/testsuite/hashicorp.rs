@@ -0,0 +1,49 @@
+use ansi_term::Color;
+use std::io;
+
+use crate::common;
+use crate::common::TestCommand;
+
+#[test]
+fn no_address_set() -> io::Result<()> {
+    let output = common::render_module("hashicorp").output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = "";
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn nomad_and_consul_addresses() -> io::Result<()> {
+    let output = common::render_module("hashicorp")
+        .env("NOMAD_ADDR", "https://nomad.prod.example.com:4646")
+        .env("CONSUL_HTTP_ADDR", "127.0.0.1:8500")
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!(
+        "on {} ",
+        Color::Green
+            .bold()
+            .paint("Ⓝ nomad.prod.example.com:4646 Ⓒ 127.0.0.1:8500")
+    );
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn aliased_address() -> io::Result<()> {
+    let output = common::render_module("hashicorp")
+        .env("CONSUL_HTTP_ADDR", "https://consul.staging.example.com")
+        .use_config(toml::toml! {
+            [hashicorp.address_aliases]
+            "consul.staging.example.com" = "staging"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("on {} ", Color::Green.bold().paint("Ⓒ staging"));
+    assert_eq!(expected, actual);
+    Ok(())
+}
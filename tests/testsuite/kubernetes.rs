@@ -0,0 +1,112 @@
+use ansi_term::Color;
+use std::fs;
+use std::io;
+
+use crate::common;
+use crate::common::TestCommand;
+
+const EKS_CONTEXT: &str = "arn:aws:eks:us-west-2:123456789012:cluster/prod";
+
+fn write_kubeconfig(dir: &tempfile::TempDir) -> io::Result<String> {
+    let kubeconfig = dir.path().join("config");
+    fs::write(
+        &kubeconfig,
+        format!(
+            "\
+apiVersion: v1
+clusters: []
+contexts:
+- context:
+    cluster: prod.us-west-2.eksctl.io
+    user: admin@prod
+    namespace: web
+  name: {ctx}
+current-context: {ctx}
+kind: Config
+preferences: {{}}
+users: []
+",
+            ctx = EKS_CONTEXT
+        ),
+    )?;
+    Ok(kubeconfig.to_string_lossy().into_owned())
+}
+
+#[test]
+fn context_and_namespace() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let output = common::render_module("kubernetes")
+        .env("KUBECONFIG", write_kubeconfig(&dir)?)
+        .use_config(toml::toml! {
+            [kubernetes]
+            disabled = false
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!(
+        "on {} ",
+        Color::Cyan.bold().paint(format!("☸ {} (web)", EKS_CONTEXT))
+    );
+    assert_eq!(expected, actual);
+    dir.close()
+}
+
+#[test]
+fn context_renamed_and_restyled() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let config = format!(
+        "[kubernetes]\ndisabled = false\n[kubernetes.contexts.\"{}\"]\nalias = \"prod\"\nstyle = \"bold red\"",
+        EKS_CONTEXT
+    );
+    let output = common::render_module("kubernetes")
+        .env("KUBECONFIG", write_kubeconfig(&dir)?)
+        .use_config(toml::from_str(&config).unwrap())
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("on {} ", Color::Red.bold().paint("☸ prod (web)"));
+    assert_eq!(expected, actual);
+    dir.close()
+}
+
+#[test]
+fn context_hidden() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let config = format!(
+        "[kubernetes]\ndisabled = false\n[kubernetes.contexts.\"{}\"]\ndisabled = true",
+        EKS_CONTEXT
+    );
+    let output = common::render_module("kubernetes")
+        .env("KUBECONFIG", write_kubeconfig(&dir)?)
+        .use_config(toml::from_str(&config).unwrap())
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = "";
+    assert_eq!(expected, actual);
+    dir.close()
+}
+
+#[test]
+fn format_with_user_and_cluster() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let output = common::render_module("kubernetes")
+        .env("KUBECONFIG", write_kubeconfig(&dir)?)
+        .use_config(toml::toml! {
+            [kubernetes]
+            disabled = false
+            format = "$symbol$user@$cluster"
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!(
+        "on {} ",
+        Color::Cyan
+            .bold()
+            .paint("☸ admin@prod@prod.us-west-2.eksctl.io")
+    );
+    assert_eq!(expected, actual);
+    dir.close()
+}
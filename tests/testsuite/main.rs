@@ -1,22 +1,30 @@
 mod aws;
 mod character;
+mod chroot;
+mod ci;
 mod cmd_duration;
 mod common;
 mod conda;
 mod configuration;
+mod dbt;
 mod directory;
 mod dotnet;
 mod env_var;
+mod fill;
+mod gcloud;
 mod git_branch;
 mod git_commit;
 mod git_state;
 mod git_status;
+mod hashicorp;
 mod hg_branch;
 mod hostname;
 mod jobs;
+mod kubernetes;
 mod line_break;
 mod modules;
 mod nix_shell;
+mod proxy;
 mod python;
 mod singularity;
 mod terraform;
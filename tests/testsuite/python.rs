@@ -190,6 +190,99 @@ fn with_active_venv() -> io::Result<()> {
     dir.close()
 }
 
+#[test]
+fn with_virtual_env_pyvenv_cfg() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    File::create(dir.path().join("main.py"))?.sync_all()?;
+    let venv_dir = tempfile::tempdir()?;
+    let venv = venv_dir.path().join("my_venv");
+    std::fs::create_dir(&venv)?;
+    std::fs::write(
+        venv.join("pyvenv.cfg"),
+        "home = /usr/bin\nversion = 3.8.2\n",
+    )?;
+
+    let output = common::render_module("python")
+        .env("VIRTUAL_ENV", &venv)
+        .arg("--path")
+        .arg(dir.path())
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("via {} ", Color::Yellow.bold().paint("🐍 v3.8.2 (my_venv)"));
+    assert_eq!(expected, actual);
+    venv_dir.close()?;
+    dir.close()
+}
+
+#[test]
+fn with_virtual_env_prompt() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    File::create(dir.path().join("main.py"))?.sync_all()?;
+    let venv_dir = tempfile::tempdir()?;
+    let venv = venv_dir.path().join(".venv");
+    std::fs::create_dir(&venv)?;
+    std::fs::write(
+        venv.join("pyvenv.cfg"),
+        "home = /usr/bin\nversion = 3.9.0\nprompt = 'starship-docs'\n",
+    )?;
+
+    let output = common::render_module("python")
+        .env("VIRTUAL_ENV", &venv)
+        .arg("--path")
+        .arg(dir.path())
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!(
+        "via {} ",
+        Color::Yellow.bold().paint("🐍 v3.9.0 (starship-docs)")
+    );
+    assert_eq!(expected, actual);
+    venv_dir.close()?;
+    dir.close()
+}
+
+#[test]
+fn with_virtual_env_aliases() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    File::create(dir.path().join("main.py"))?.sync_all()?;
+    let venv_dir = tempfile::tempdir()?;
+    let venv = venv_dir.path().join(".venv");
+    std::fs::create_dir(&venv)?;
+    std::fs::write(
+        venv.join("pyvenv.cfg"),
+        "home = /usr/bin\nversion = 3.9.0\nprompt = 'unused'\n",
+    )?;
+    let other_venv = venv_dir.path().join("env");
+    std::fs::create_dir(&other_venv)?;
+    std::fs::write(
+        other_venv.join("pyvenv.cfg"),
+        "home = /usr/bin\nversion = 3.8.2\n",
+    )?;
+
+    let config = format!(
+        "[python.virtualenv_aliases]\n\"{}\" = \"api\"\nenv = \"scratch\"\n",
+        venv.to_string_lossy()
+    );
+    let render = |venv: &std::path::Path| -> io::Result<String> {
+        let output = common::render_module("python")
+            .env("VIRTUAL_ENV", venv)
+            .arg("--path")
+            .arg(dir.path())
+            .use_config(toml::from_str(&config).unwrap())
+            .output()?;
+        Ok(String::from_utf8(output.stdout).unwrap())
+    };
+
+    let expected = format!("via {} ", Color::Yellow.bold().paint("🐍 v3.9.0 (api)"));
+    assert_eq!(expected, render(&venv)?);
+    let expected = format!("via {} ", Color::Yellow.bold().paint("🐍 v3.8.2 (scratch)"));
+    assert_eq!(expected, render(&other_venv)?);
+    venv_dir.close()?;
+    dir.close()
+}
+
 #[test]
 fn disabled_scan_for_pyfiles_and_folder_with_ignored_py_file() -> io::Result<()> {
     let dir = tempfile::tempdir()?;
@@ -25,3 +25,18 @@ fn env_set() -> io::Result<()> {
     assert_eq!(expected, actual);
     Ok(())
 }
+
+#[test]
+fn apptainer_env_set() -> io::Result<()> {
+    let output = common::render_module("singularity")
+        .env_clear()
+        .env("APPTAINER_NAME", "rocky.sif")
+        .env("SINGULARITY_NAME", "")
+        .output()?;
+
+    let expected = format!("{} ", Color::Blue.bold().dimmed().paint("[rocky.sif]"));
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(expected, actual);
+    Ok(())
+}
@@ -133,6 +133,7 @@ fn folder_with_dotterraform_with_version_no_environment() -> io::Result<()> {
         .use_config(toml::toml! {
             [terraform]
             show_version = true
+            exec = true
         })
         .output()?;
     let actual = String::from_utf8(output.stdout).unwrap();
@@ -161,6 +162,7 @@ fn folder_with_dotterraform_with_version_with_environment() -> io::Result<()> {
         .use_config(toml::toml! {
             [terraform]
             show_version = true
+            exec = true
         })
         .output()?;
     let actual = String::from_utf8(output.stdout).unwrap();
@@ -172,3 +174,48 @@ fn folder_with_dotterraform_with_version_with_environment() -> io::Result<()> {
     assert_eq!(expected, actual);
     Ok(())
 }
+
+#[test]
+fn folder_with_tfstate_workspaces() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    fs::create_dir_all(dir.path().join("terraform.tfstate.d/development"))?;
+
+    let output = common::render_module("terraform")
+        .arg("--path")
+        .arg(dir.path())
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!("via {} ", Color::Fixed(105).bold().paint("💠 default"));
+    assert_eq!(expected, actual);
+    dir.close()
+}
+
+#[test]
+fn folder_with_required_version() -> io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    fs::write(
+        dir.path().join("versions.tf"),
+        "terraform {\n  required_version = \">= 0.12\"\n}\n",
+    )?;
+    let tf_dir = dir.path().join(".terraform");
+    fs::create_dir(&tf_dir)?;
+    fs::write(tf_dir.join("environment"), "development")?;
+
+    let output = common::render_module("terraform")
+        .arg("--path")
+        .arg(dir.path())
+        .use_config(toml::toml! {
+            [terraform]
+            show_version = true
+        })
+        .output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!(
+        "via {} ",
+        Color::Fixed(105).bold().paint("💠 >= 0.12 development")
+    );
+    assert_eq!(expected, actual);
+    dir.close()
+}